@@ -1,49 +1,227 @@
 use anyhow::{anyhow, Result};
+use futures::StreamExt;
 use glib::{clone, MainContext, Priority};
 use gtk4::prelude::*;
 use gtk4::{
     ApplicationWindow, Box, Button, CellRendererText, CheckButton, ComboBoxText, Dialog,
-    DialogFlags, Entry, Grid, Label, ListBox, ListBoxRow, Notebook, Paned, ResponseType,
-    ScrolledWindow, TextView, TreeIter, TreePath, TreeSelection, TreeStore, TreeView,
-    TreeViewColumn, Window,
+    DialogFlags, Entry, Grid, Label, ListBox, ListStore, Notebook, Paned, ResponseType,
+    ScrolledWindow, TreeIter, TreeModel, TreeModelSort, TreeSelection, TreeStore, TreeView,
+    TreeViewColumn,
 };
 use log::{debug, error, info, warn};
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use tokio::runtime::Runtime;
 
-use crate::remote_host::{AuthType, RemoteHost};
-use crate::service_manager::{ServiceInfo, ServiceManager, ServiceStatus};
+use crate::remote_host::{
+    conflicting_host_names, merge_hosts, parse_port, AuthType, CommandHistory, ConnectionEventType,
+    ConnectionLog, HostCapabilities, HostConnectionState, HostsConfig, RemoteHost,
+    DEFAULT_SSH_PORT,
+};
+use crate::service_manager::{
+    drop_in_path, editable_drop_in_path, security_fix_directives, service_unit_content,
+    strip_unit_file_sources, unit_file_name, ExecSteps, LogOptions, RemoteServiceManager,
+    ResourceTimeSeries, ServiceAction, ServiceInfo, ServiceManager, ServiceSpec, ServiceStatus,
+    UnitScope,
+};
+use crate::ui::components::{
+    attach_context_menu, attach_tree_view_context_menu, create_connection_status_bar,
+    create_empty_state_widget, create_host_list_item, create_service_filter_box,
+    format_status_cell, sort_pinned_first, status_filter_choice, unit_scope_choice,
+    update_mask_buttons, ContextMenuBuilder, LoadingOverlay, NotificationBar, NotificationKind,
+    ProgressNotificationBar, ServiceActionHistory, ServiceDetailsPanel, ServiceListFilter,
+};
 use crate::ui::dialogs::*;
+use crate::ui::shortcuts::build_shortcuts_window;
+use crate::utils::audit::AuditLog;
+use crate::utils::config::{config_file, save_json, Config, RefreshConfig};
+use crate::utils::dbus_notifier::{send_notification, NotificationUrgency};
+use crate::utils::diagnostics::{bundle_file_name, strip_credentials, write_bundle};
+use crate::utils::file_watcher::{watch_directory, WatcherHandle};
+use crate::utils::format::{
+    format_bytes, format_cpu_time, format_systemd_timestamp, parse_systemd_timestamp,
+};
+use crate::utils::import::{export_hosts_yaml, parse_ansible_inventory};
+use crate::utils::keyring;
+use crate::utils::known_hosts::{HostKeyStatus, KnownHosts};
+use crate::utils::preferences::PreferencesConfig;
+use crate::utils::ssh::{
+    agent_available, connect, ControlMaster, HostConnection, SshTunnel, TunnelHandle,
+    UntrustedHostKey,
+};
+use crate::utils::terminal::open_terminal_for_service;
 use crate::utils::theme::ThemeManager;
+use crate::utils::window_state::{column_key, orientation_key, ColumnSort, WindowState};
+
+/// Keyboard shortcuts for `app.*` actions
+const ACTION_ACCELERATORS: &[(&str, &[&str])] = &[
+    ("app.refresh", &["F5"]),
+    ("app.logs", &["F4", "<Ctrl>l"]),
+    ("app.start", &["F2", "<Ctrl><Shift>s"]),
+    ("app.stop", &["F3", "<Ctrl><Shift>x"]),
+    ("app.restart", &["<Ctrl>r", "<Ctrl><Shift>r"]),
+    ("win.next-tab", &["<Ctrl>Tab"]),
+    ("win.focus-search", &["<Ctrl>f"]),
+    ("win.add-host", &["<Ctrl>n"]),
+    ("win.focus-host-search", &["<Ctrl><Shift>f"]),
+    ("win.show-help-overlay", &["<Ctrl>question", "F1"]),
+    ("win.preferences", &["<Ctrl>comma"]),
+];
+
+/// Drop-in the fixes from the security dialog are added to
+const SECURITY_DROP_IN: &str = "50-security-hardening";
+
+/// Name of the file the saved hosts are kept in, in the app's config directory
+const HOSTS_FILE_NAME: &str = "hosts.json";
+
+/// How many of a service's recent actions are listed below its details
+const RECENT_ACTIONS_SHOWN: usize = 5;
+
+/// How often the resource graph samples a service's usage
+const RESOURCE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// How often the details panel re-checks the selected service's status
+const STATUS_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// How long column changes settle before the window state is saved, so
+/// dragging a column edge doesn't write the file on every step
+const COLUMN_STATE_SAVE_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// How long listing the local services may take before it is abandoned
+const SERVICE_LIST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// How long fetching a remote host's copy of a unit file may take
+const REMOTE_UNIT_FETCH_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Number of journal lines included in an exported diagnostics bundle
+const DIAGNOSTICS_LOG_LINES: u32 = 100;
+
+/// Number of journal lines first shown for a remote service, kept lower
+/// than for local ones since they come over SSH
+const REMOTE_LOG_LINES: u32 = 200;
+
+/// How often a remote service's journal is polled while following it
+const REMOTE_LOG_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Directories holding unit files that administrators commonly edit by hand
+const UNIT_FILE_DIRECTORIES: &[&str] = &["/etc/systemd/system", "/lib/systemd/system"];
 
 pub struct SystemdPilotApp {
     window: ApplicationWindow,
     notebook: Notebook,
     remote_hosts: Rc<RefCell<HashMap<String, RemoteHost>>>,
-    active_connections: Arc<Mutex<HashMap<String, ssh2::Session>>>,
+    active_connections: Arc<Mutex<HashMap<String, HostConnection>>>,
+    /// Connection state of each host, shown in the hosts list
+    connection_status: Arc<Mutex<HashMap<String, HostConnectionState>>>,
+    connection_logs: RefCell<HashMap<String, ConnectionLog>>,
+    command_histories: RefCell<HashMap<String, CommandHistory>>,
     service_manager: Arc<ServiceManager>,
     theme_manager: Rc<ThemeManager>,
     runtime: Arc<Runtime>,
+    config: Rc<RefCell<Config>>,
+    preferences: Rc<RefCell<PreferencesConfig>>,
+    audit_log: Rc<RefCell<AuditLog>>,
 
     // UI Components
     local_services_list: TreeView,
+    // Optional resource columns of the local services list
+    memory_column: TreeViewColumn,
+    cpu_column: TreeViewColumn,
+    local_filter_box: Box,
+    local_search_entry: Entry,
+    status_filter: ComboBoxText,
+    /// Picks whether the local lists show system units or the user's own
+    unit_scope_combo: ComboBoxText,
+    local_services_filter: ServiceListFilter,
+    /// Spinner shown above the local services while they are listed
+    local_loading: LoadingOverlay,
     remote_services_list: TreeView,
     hosts_listbox: ListBox,
+    host_search_entry: gtk4::SearchEntry,
+    /// Shown over the hosts list when no host matches the search
+    hosts_no_match: Box,
     show_inactive_button: CheckButton,
+    group_by_slice_button: CheckButton,
+    timers_list: TreeView,
+    show_inactive_timers_button: CheckButton,
+    /// Spinner shown above the timers while they are listed
+    timers_loading: LoadingOverlay,
+    sockets_list: TreeView,
+    show_inactive_sockets_button: CheckButton,
+    /// Spinner shown above the sockets while they are listed
+    sockets_loading: LoadingOverlay,
+    unit_files_info_bar: gtk4::InfoBar,
+    details_panel: ServiceDetailsPanel,
+    action_history: ServiceActionHistory,
+    progress_bar: ProgressNotificationBar,
+    /// Reports the outcome of operations without a dialog
+    notification_bar: NotificationBar,
+    shutdown_info_bar: gtk4::InfoBar,
+    status_bar: Box,
+    status_label: Label,
+    status_refresh_button: Button,
+    auto_refresh_button: gtk4::ToggleButton,
+    refresh_interval_spin: gtk4::SpinButton,
+
+    // Unit file watchers, kept alive for the lifetime of the app
+    file_watchers: RefCell<Vec<WatcherHandle>>,
+
+    // Keeps the details panel status current for the selected service
+    status_watch: RefCell<Option<tokio::task::JoinHandle<()>>>,
+
+    // Active SSH port forwards
+    port_forwards: RefCell<Vec<TunnelHandle>>,
 
     // Tree stores
     local_services_store: TreeStore,
     remote_services_store: TreeStore,
+    timers_store: ListStore,
+    sockets_store: ListStore,
+
+    // Service to select once the local services are loaded, set from the
+    // command line
+    pending_selection: Rc<RefCell<Option<String>>>,
+
+    // Split panes whose divider positions are saved in the window state
+    panes: RefCell<Vec<Paned>>,
+
+    // Timer driving the auto-refresh, while it's enabled
+    auto_refresh_source: RefCell<Option<glib::SourceId>>,
+
+    // Timers refreshing the hosts that have their own auto-refresh, by host
+    host_refresh_sources: RefCell<HashMap<String, glib::SourceId>>,
+
+    // Pending save of the window state after a column was resized or sorted
+    column_state_save: RefCell<Option<glib::SourceId>>,
+
+    // Passwords to save in the keyring once they get their host connected
+    passwords_to_remember: RefCell<HashMap<String, String>>,
+
+    // Hosts being connected to with a password from the keyring
+    keyring_password_hosts: RefCell<HashSet<String>>,
 }
 
 impl SystemdPilotApp {
     pub fn new(window: &ApplicationWindow) -> Self {
         let runtime = Arc::new(Runtime::new().expect("Failed to create Tokio runtime"));
 
+        let mut config = Config::load().unwrap_or_else(|e| {
+            warn!("Failed to load config, using defaults: {}", e);
+            Config::default()
+        });
+        let preferences = PreferencesConfig::load(&config).unwrap_or_else(|e| {
+            warn!("Failed to load preferences, using defaults: {}", e);
+            PreferencesConfig::default()
+        });
+        preferences.apply_to(&mut config);
+        config.merge_from_env();
+
         let theme_manager = Rc::new(ThemeManager::new());
+        if let Some(dark_mode) = config.dark_mode {
+            theme_manager.set_dark_mode(dark_mode);
+        }
         let service_manager = Arc::new(ServiceManager::new(runtime.clone()));
 
         // Create tree stores
@@ -51,8 +229,27 @@ impl SystemdPilotApp {
             glib::Type::STRING, // Service name
             glib::Type::STRING, // Status
             glib::Type::STRING, // Description
+            glib::Type::STRING, // Tooltip markup
+            glib::Type::BOOL,   // Pinned
+            glib::Type::U64,    // Memory in bytes
+            glib::Type::U64,    // CPU time in nanoseconds
+            glib::Type::BOOL,   // Enabled
         ]);
 
+        let local_services_filter = ServiceListFilter::new(&local_services_store);
+        let (
+            local_filter_box,
+            local_search_entry,
+            show_inactive_button,
+            unit_scope_combo,
+            status_filter,
+        ) = create_service_filter_box();
+        show_inactive_button.set_active(preferences.show_inactive_default);
+        let show_inactive_timers_button = CheckButton::with_label("Show inactive");
+        show_inactive_timers_button.set_active(preferences.show_inactive_default);
+        let show_inactive_sockets_button = CheckButton::with_label("Show inactive");
+        show_inactive_sockets_button.set_active(preferences.show_inactive_default);
+
         let remote_services_store = TreeStore::new(&[
             glib::Type::STRING, // Host
             glib::Type::STRING, // Service name
@@ -60,44 +257,358 @@ impl SystemdPilotApp {
             glib::Type::STRING, // Description
         ]);
 
+        let timers_store = ListStore::new(&[
+            glib::Type::STRING, // Timer name
+            glib::Type::STRING, // Status
+            glib::Type::STRING, // Next trigger
+            glib::Type::STRING, // Last trigger
+            glib::Type::STRING, // Description
+            glib::Type::I64,    // Next trigger (epoch microseconds, for sorting)
+            glib::Type::I64,    // Last trigger (epoch microseconds, for sorting)
+            glib::Type::STRING, // Exact trigger times (tooltip)
+        ]);
+
+        let sockets_store = ListStore::new(&[
+            glib::Type::STRING, // Socket name
+            glib::Type::STRING, // Status
+            glib::Type::STRING, // Listen addresses
+            glib::Type::STRING, // Description
+        ]);
+
+        let unit_files_info_bar = gtk4::InfoBar::new();
+        unit_files_info_bar.set_message_type(gtk4::MessageType::Info);
+        unit_files_info_bar.add_child(&Label::new(Some("Unit files changed. Reload?")));
+        unit_files_info_bar.add_button("Reload", ResponseType::Accept);
+        unit_files_info_bar.set_show_close_button(true);
+        unit_files_info_bar.set_revealed(false);
+
+        let shutdown_info_bar = gtk4::InfoBar::new();
+        shutdown_info_bar.set_message_type(gtk4::MessageType::Warning);
+        shutdown_info_bar.add_child(&Label::new(Some("Operations in progress—please wait")));
+        shutdown_info_bar.set_revealed(false);
+
+        let (status_bar, status_label, status_refresh_button) = create_connection_status_bar();
+
+        let auto_refresh_button = gtk4::ToggleButton::new();
+        let refresh_interval_spin = gtk4::SpinButton::with_range(
+            RefreshConfig::MIN_INTERVAL_SECS as f64,
+            RefreshConfig::MAX_INTERVAL_SECS as f64,
+            5.0,
+        );
+
         Self {
             window: window.clone(),
             notebook: Notebook::new(),
             remote_hosts: Rc::new(RefCell::new(HashMap::new())),
             active_connections: Arc::new(Mutex::new(HashMap::new())),
+            connection_status: Arc::new(Mutex::new(HashMap::new())),
+            connection_logs: RefCell::new(HashMap::new()),
+            command_histories: RefCell::new(HashMap::new()),
             service_manager,
             theme_manager,
             runtime,
+            config: Rc::new(RefCell::new(config)),
+            preferences: Rc::new(RefCell::new(preferences)),
+            audit_log: Rc::new(RefCell::new(AuditLog::load().unwrap_or_else(|e| {
+                warn!("Failed to load the audit log: {}", e);
+                AuditLog::default()
+            }))),
             local_services_list: TreeView::new(),
+            memory_column: TreeViewColumn::new(),
+            cpu_column: TreeViewColumn::new(),
+            local_filter_box,
+            local_search_entry,
+            status_filter,
+            unit_scope_combo,
+            local_services_filter,
+            local_loading: LoadingOverlay::new("Loading services…"),
             remote_services_list: TreeView::new(),
             hosts_listbox: ListBox::new(),
-            show_inactive_button: CheckButton::with_label("Show inactive services"),
+            host_search_entry: gtk4::SearchEntry::new(),
+            hosts_no_match: create_empty_state_widget(
+                "No hosts found",
+                "Try a different search term",
+            ),
+            show_inactive_button,
+            group_by_slice_button: CheckButton::with_label("Group by slice"),
+            timers_list: TreeView::new(),
+            show_inactive_timers_button,
+            timers_loading: LoadingOverlay::new("Loading timers…"),
+            sockets_list: TreeView::new(),
+            show_inactive_sockets_button,
+            sockets_loading: LoadingOverlay::new("Loading sockets…"),
+            unit_files_info_bar,
+            details_panel: ServiceDetailsPanel::new(),
+            action_history: ServiceActionHistory::new(),
+            progress_bar: ProgressNotificationBar::new(),
+            notification_bar: NotificationBar::new(),
+            shutdown_info_bar,
+            status_bar,
+            status_label,
+            status_refresh_button,
+            auto_refresh_button,
+            refresh_interval_spin,
+            file_watchers: RefCell::new(Vec::new()),
+            status_watch: RefCell::new(None),
+            port_forwards: RefCell::new(Vec::new()),
             local_services_store,
             remote_services_store,
+            timers_store,
+            sockets_store,
+            pending_selection: Rc::new(RefCell::new(None)),
+            panes: RefCell::new(Vec::new()),
+            auto_refresh_source: RefCell::new(None),
+            host_refresh_sources: RefCell::new(HashMap::new()),
+            column_state_save: RefCell::new(None),
+            passwords_to_remember: RefCell::new(HashMap::new()),
+            keyring_password_hosts: RefCell::new(HashSet::new()),
         }
     }
 
-    pub fn setup_ui(&self) {
+    pub fn setup_ui(self: &Rc<Self>) {
         let main_box = Box::new(gtk4::Orientation::Vertical, 0);
 
+        // Actions used by the buttons and menus below
+        self.setup_application_actions();
+
         // Setup header bar
         self.setup_header_bar();
 
         // Setup notebook with tabs
         self.setup_notebook();
 
+        main_box.append(&self.shutdown_info_bar);
+        main_box.append(self.progress_bar.widget());
+        main_box.append(&self.unit_files_info_bar);
         main_box.append(&self.notebook);
+        main_box.append(&self.status_bar);
+
+        // Notifications slide up over the bottom of the window
+        let overlay = gtk4::Overlay::new();
+        overlay.set_child(Some(&main_box));
+        overlay.add_overlay(self.notification_bar.widget());
 
-        self.window.set_child(Some(&main_box));
+        self.window.set_child(Some(&overlay));
 
         // Apply theme
         self.theme_manager.apply_theme(&self.window);
 
         // Setup signal handlers
         self.setup_signal_handlers();
+
+        // Offer a reload when unit files are edited outside the app
+        self.watch_service_file_changes();
+
+        self.restart_auto_refresh();
+    }
+
+    /// Refreshes the service lists periodically when the config asks for it,
+    /// replacing any timer set up for an earlier setting
+    fn restart_auto_refresh(self: &Rc<Self>) {
+        if let Some(source) = self.auto_refresh_source.take() {
+            source.remove();
+        }
+
+        let refresh = self.config.borrow().refresh;
+        if !refresh.enabled {
+            return;
+        }
+
+        let app = Rc::downgrade(self);
+        let source =
+            glib::timeout_add_seconds_local(refresh.interval(), move || match app.upgrade() {
+                Some(app) => {
+                    app.refresh_all_services();
+                    glib::ControlFlow::Continue
+                }
+                None => glib::ControlFlow::Break,
+            });
+        self.auto_refresh_source.replace(Some(source));
+    }
+
+    /// Applies a change to the auto-refresh settings and saves it in the
+    /// preferences. The change is made to the preferences separately, so an
+    /// interval set from the environment isn't saved along with it.
+    fn update_refresh_config(self: &Rc<Self>, update: impl Fn(&mut RefreshConfig)) {
+        update(&mut self.config.borrow_mut().refresh);
+        {
+            let mut preferences = self.preferences.borrow_mut();
+            let mut refresh = preferences.refresh();
+            update(&mut refresh);
+            preferences.set_refresh(refresh);
+            if let Err(e) = preferences.save() {
+                warn!("Failed to save preferences: {}", e);
+            }
+        }
+        self.restart_auto_refresh();
+    }
+
+    /// Applies preferences saved from the Preferences dialog
+    fn apply_preferences(self: &Rc<Self>, preferences: &PreferencesConfig) {
+        if let Err(e) = preferences.save() {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Preferences",
+                &format!("Failed to save preferences: {}", e),
+            );
+        }
+
+        // The header controls save the refresh settings as they change
+        let refresh = preferences.refresh();
+        self.refresh_interval_spin
+            .set_value(refresh.interval() as f64);
+        self.auto_refresh_button.set_active(refresh.enabled);
+
+        self.theme_manager.set_dark_mode(
+            preferences
+                .dark_mode_override
+                .unwrap_or_else(ThemeManager::detect_system_theme),
+        );
+        self.theme_manager.apply_theme(&self.window);
+    }
+
+    pub fn watch_service_file_changes(self: &Rc<Self>) {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+
+        for directory in UNIT_FILE_DIRECTORIES {
+            let sender = sender.clone();
+            match watch_directory(std::path::Path::new(directory), move |path| {
+                let _ = sender.send(path);
+            }) {
+                Ok(handle) => self.file_watchers.borrow_mut().push(handle),
+                Err(e) => warn!("Failed to watch {}: {}", directory, e),
+            }
+        }
+
+        let info_bar = self.unit_files_info_bar.clone();
+        glib::MainContext::default().spawn_local(async move {
+            while let Some(path) = receiver.recv().await {
+                debug!("Unit file changed: {}", path.display());
+                info_bar.set_revealed(true);
+            }
+        });
+
+        let app = Rc::downgrade(self);
+        self.unit_files_info_bar
+            .connect_response(move |info_bar, response| {
+                info_bar.set_revealed(false);
+
+                if response != ResponseType::Accept {
+                    return;
+                }
+
+                if let Some(app) = app.upgrade() {
+                    app.daemon_reload();
+                }
+            });
+    }
+
+    /// Registers the service operations and other global commands as `app.*`
+    /// actions, so they can have keyboard shortcuts, appear in the shell's
+    /// app menu and be invoked with `gdbus call`
+    fn setup_application_actions(self: &Rc<Self>) {
+        let Some(application) = self.window.application() else {
+            warn!("Window has no application, so actions were not registered");
+            return;
+        };
+
+        for action in [
+            ServiceAction::Start,
+            ServiceAction::Stop,
+            ServiceAction::Restart,
+            ServiceAction::Enable,
+            ServiceAction::Disable,
+            ServiceAction::Reload,
+            ServiceAction::Mask,
+            ServiceAction::Unmask,
+        ] {
+            let simple_action = gio::SimpleAction::new(action.command(), None);
+            let app = Rc::downgrade(self);
+            simple_action.connect_activate(move |_, _| {
+                if let Some(app) = app.upgrade() {
+                    app.confirm_service_command(action);
+                }
+            });
+            application.add_action(&simple_action);
+        }
+
+        let logs_action = gio::SimpleAction::new("logs", None);
+        let app = Rc::downgrade(self);
+        logs_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_service_logs();
+            }
+        });
+        application.add_action(&logs_action);
+
+        let refresh_action = gio::SimpleAction::new("refresh", None);
+        let app = Rc::downgrade(self);
+        refresh_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_all_services();
+            }
+        });
+        application.add_action(&refresh_action);
+
+        let about_action = gio::SimpleAction::new("about", None);
+        let window = self.window.clone();
+        about_action.connect_activate(move |_, _| show_about_dialog(window.upcast_ref()));
+        application.add_action(&about_action);
+
+        self.setup_window_actions();
+
+        for (action, accels) in ACTION_ACCELERATORS {
+            application.set_accels_for_action(action, accels);
+        }
+
+        // Also provides the win.show-help-overlay action
+        self.window
+            .set_help_overlay(Some(&build_shortcuts_window(&application)));
+    }
+
+    /// Registers `win.*` actions for moving around the window, so they can
+    /// have keyboard shortcuts
+    fn setup_window_actions(self: &Rc<Self>) {
+        let next_tab_action = gio::SimpleAction::new("next-tab", None);
+        let notebook = self.notebook.clone();
+        next_tab_action.connect_activate(move |_, _| {
+            let pages = notebook.n_pages();
+            if pages > 0 {
+                notebook.set_current_page(Some((notebook.current_page().unwrap_or(0) + 1) % pages));
+            }
+        });
+        self.window.add_action(&next_tab_action);
+
+        let focus_search_action = gio::SimpleAction::new("focus-search", None);
+        let app = Rc::downgrade(self);
+        focus_search_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.notebook.set_current_page(Some(0));
+                app.local_search_entry.grab_focus();
+            }
+        });
+        self.window.add_action(&focus_search_action);
+
+        let focus_host_search_action = gio::SimpleAction::new("focus-host-search", None);
+        let app = Rc::downgrade(self);
+        focus_host_search_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.notebook.set_current_page(Some(1));
+                app.host_search_entry.grab_focus();
+            }
+        });
+        self.window.add_action(&focus_host_search_action);
+
+        let add_host_action = gio::SimpleAction::new("add-host", None);
+        let window = self.window.clone();
+        let remote_hosts = self.remote_hosts.clone();
+        add_host_action.connect_activate(move |_, _| {
+            show_add_host_dialog(&window, &remote_hosts);
+        });
+        self.window.add_action(&add_host_action);
     }
 
-    fn setup_header_bar(&self) {
+    fn setup_header_bar(self: &Rc<Self>) {
         let header_bar = gtk4::HeaderBar::new();
         let title = Label::new(Some("systemd Pilot"));
         header_bar.set_title_widget(Some(&title));
@@ -116,21 +627,209 @@ impl SystemdPilotApp {
 
         header_bar.pack_end(&theme_button);
 
+        // Add help menu
+        let help_menu = gio::Menu::new();
+        help_menu.append(Some("Export Diagnostics…"), Some("win.export-diagnostics"));
+        help_menu.append(Some("Keyboard Shortcuts"), Some("win.show-help-overlay"));
+        help_menu.append(Some("About systemd Pilot"), Some("app.about"));
+        let help_button = gtk4::MenuButton::new();
+        help_button.set_label("?");
+        help_button.set_tooltip_text(Some("Help"));
+        help_button.set_menu_model(Some(&help_menu));
+
+        let export_diagnostics_action = gio::SimpleAction::new("export-diagnostics", None);
+        let app = Rc::downgrade(self);
+        export_diagnostics_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.export_diagnostics();
+            }
+        });
+        self.window.add_action(&export_diagnostics_action);
+
+        header_bar.pack_end(&help_button);
+
+        let daemon_reload_button = Button::with_label("↺ Daemon Reload");
+        daemon_reload_button.set_tooltip_text(Some(
+            "Reload systemd's configuration after editing unit files",
+        ));
+        let app = Rc::downgrade(self);
+        daemon_reload_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.daemon_reload();
+            }
+        });
+
+        // Add advanced menu
+        let advanced_menu = gio::Menu::new();
+        advanced_menu.append(Some("Preferences…"), Some("win.preferences"));
+        advanced_menu.append(Some("Audit Log"), Some("win.audit-log"));
+        let generators_section = gio::Menu::new();
+        generators_section.append(
+            Some("Environment Generators…"),
+            Some("win.environment-generators"),
+        );
+        advanced_menu.append_section(None, &generators_section);
+        let advanced_button = gtk4::MenuButton::new();
+        advanced_button.set_label("⚙");
+        advanced_button.set_tooltip_text(Some("Advanced"));
+        advanced_button.set_menu_model(Some(&advanced_menu));
+
+        let preferences_action = gio::SimpleAction::new("preferences", None);
+        let app = Rc::downgrade(self);
+        preferences_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                let weak_app = Rc::downgrade(&app);
+                let clear_app = weak_app.clone();
+                show_preferences_dialog(
+                    app.window.upcast_ref(),
+                    app.preferences.clone(),
+                    move |preferences| {
+                        if let Some(app) = weak_app.upgrade() {
+                            app.apply_preferences(preferences);
+                        }
+                    },
+                    move || {
+                        if let Some(app) = clear_app.upgrade() {
+                            app.clear_saved_passwords();
+                        }
+                    },
+                );
+            }
+        });
+        self.window.add_action(&preferences_action);
+
+        let audit_log_action = gio::SimpleAction::new("audit-log", None);
+        let app = Rc::downgrade(self);
+        audit_log_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                show_audit_log_dialog(app.window.upcast_ref(), app.audit_log.clone());
+            }
+        });
+        self.window.add_action(&audit_log_action);
+
+        let generators_action = gio::SimpleAction::new("environment-generators", None);
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        generators_action.connect_activate(move |_, _| {
+            let service_manager = service_manager.clone();
+            let window = window.clone();
+            spawn_with_callback(
+                &runtime,
+                async move { service_manager.list_environment_generators().await },
+                move |result| match result {
+                    Ok(generators) => {
+                        show_environment_generators_dialog(window.upcast_ref(), &generators)
+                    }
+                    Err(e) => show_error_dialog(
+                        window.upcast_ref(),
+                        "Environment Generators",
+                        &format!("Failed to list environment generators: {}", e),
+                    ),
+                },
+            );
+        });
+        self.window.add_action(&generators_action);
+
+        header_bar.pack_end(&advanced_button);
+
+        // Add startup analysis menu
+        let analyze_menu = gio::Menu::new();
+        analyze_menu.append(Some("Startup Timeline…"), Some("win.boot-timeline"));
+        analyze_menu.append(Some("Slowest Units…"), Some("win.analyze-blame"));
+        let analyze_button = gtk4::MenuButton::new();
+        analyze_button.set_label("⏱ Analyze");
+        analyze_button.set_tooltip_text(Some("Startup analysis"));
+        analyze_button.set_menu_model(Some(&analyze_menu));
+
+        let boot_timeline_action = gio::SimpleAction::new("boot-timeline", None);
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        boot_timeline_action.connect_activate(move |_, _| {
+            let service_manager = service_manager.clone();
+            let window = window.clone();
+            spawn_with_callback(
+                &runtime,
+                async move {
+                    let summary = service_manager.get_boot_time_summary().await?;
+                    let svg = service_manager.get_boot_plot_svg().await?;
+                    Ok::<_, anyhow::Error>((summary, svg))
+                },
+                move |result| match result {
+                    Ok((summary, svg)) => {
+                        show_boot_performance_dialog(window.upcast_ref(), &summary, &svg)
+                    }
+                    Err(e) => show_error_dialog(
+                        window.upcast_ref(),
+                        "Startup Analysis",
+                        &format!("Failed to analyze boot: {}", e),
+                    ),
+                },
+            );
+        });
+        self.window.add_action(&boot_timeline_action);
+
+        let blame_action = gio::SimpleAction::new("analyze-blame", None);
+        let app = Rc::downgrade(self);
+        blame_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.show_analyze_blame();
+            }
+        });
+        self.window.add_action(&blame_action);
+
+        header_bar.pack_end(&analyze_button);
+
         // Add refresh button
         let refresh_button = Button::with_label("🔄");
         refresh_button.set_tooltip_text(Some("Refresh services"));
 
-        // TODO: Connect refresh button after refactoring to handle callbacks properly
-        // refresh_button.connect_clicked(move |_| {
-        //     // Need to refactor to properly handle self reference
-        // });
+        refresh_button.connect_clicked(|button| activate_action(button, "app.refresh"));
 
         header_bar.pack_start(&refresh_button);
+        header_bar.pack_start(&daemon_reload_button);
+
+        // Add auto-refresh toggle and interval
+        let refresh = self.config.borrow().refresh;
+        let auto_refresh_button = self.auto_refresh_button.clone();
+        auto_refresh_button.set_label(auto_refresh_label(refresh.enabled));
+        auto_refresh_button.set_active(refresh.enabled);
+        auto_refresh_button.set_tooltip_text(Some("Refresh the service lists periodically"));
+        let interval_spin = self.refresh_interval_spin.clone();
+        interval_spin.set_tooltip_text(Some("Seconds between refreshes"));
+        interval_spin.set_value(refresh.interval() as f64);
+        interval_spin.set_sensitive(refresh.enabled);
+
+        let app = Rc::downgrade(self);
+        auto_refresh_button.connect_toggled(clone!(
+            #[weak]
+            interval_spin,
+            move |button| {
+                let enabled = button.is_active();
+                button.set_label(auto_refresh_label(enabled));
+                interval_spin.set_sensitive(enabled);
+                if let Some(app) = app.upgrade() {
+                    app.update_refresh_config(|refresh| refresh.enabled = enabled);
+                }
+            }
+        ));
+
+        let app = Rc::downgrade(self);
+        interval_spin.connect_value_changed(move |spin| {
+            if let Some(app) = app.upgrade() {
+                let interval_secs = spin.value_as_int() as u32;
+                app.update_refresh_config(|refresh| refresh.interval_secs = interval_secs);
+            }
+        });
+
+        header_bar.pack_start(&auto_refresh_button);
+        header_bar.pack_start(&interval_spin);
 
         self.window.set_titlebar(Some(&header_bar));
     }
 
-    fn setup_notebook(&self) {
+    fn setup_notebook(self: &Rc<Self>) {
         // Local services tab
         let local_page = self.create_local_page();
         self.notebook
@@ -141,11 +840,21 @@ impl SystemdPilotApp {
         self.notebook
             .append_page(&remote_page, Some(&Label::new(Some("Remote"))));
 
+        // Local timers tab
+        let timers_page = self.create_timers_page();
+        self.notebook
+            .append_page(&timers_page, Some(&Label::new(Some("Timers"))));
+
+        // Local sockets tab
+        let sockets_page = self.create_sockets_page();
+        self.notebook
+            .append_page(&sockets_page, Some(&Label::new(Some("Sockets"))));
+
         self.notebook.set_tab_pos(gtk4::PositionType::Top);
         self.notebook.set_scrollable(true);
     }
 
-    fn create_local_page(&self) -> Box {
+    fn create_local_page(self: &Rc<Self>) -> Box {
         let main_box = Box::new(gtk4::Orientation::Vertical, 6);
         main_box.set_margin_start(12);
         main_box.set_margin_end(12);
@@ -160,44 +869,332 @@ impl SystemdPilotApp {
         let restart_button = Button::with_label("🔄 Restart");
         let enable_button = Button::with_label("✓ Enable");
         let disable_button = Button::with_label("✗ Disable");
+        let reload_button = Button::with_label("↻ Reload");
+        reload_button.set_tooltip_text(Some("Reload the service configuration"));
+        let mask_button = Button::with_label("🚫 Mask");
+        mask_button.set_tooltip_text(Some("Prevent the service from being started"));
+        mask_button.set_sensitive(false);
+        let unmask_button = Button::with_label("↩ Unmask");
+        unmask_button.set_tooltip_text(Some("Allow the service to be started again"));
+        unmask_button.set_sensitive(false);
         let logs_button = Button::with_label("📋 Logs");
+        let dependencies_button = Button::with_label("🔗 Dependencies");
+        dependencies_button.set_tooltip_text(Some("Show what the service depends on"));
+        let audit_button = Button::with_label("👤 Audit");
+        audit_button.set_tooltip_text(Some("Show who started or stopped the service"));
+        let environment_button = Button::with_label("🌐 Environment");
+        environment_button.set_tooltip_text(Some("Show the service's environment variables"));
+        let unit_file_button = Button::with_label("📄 View Unit File");
+        unit_file_button.set_tooltip_text(Some("Show the service's unit file"));
+        let drop_ins_button = Button::with_label("🧩 Drop-ins");
+        drop_ins_button.set_tooltip_text(Some("List and add files overriding the unit file"));
+        let security_button = Button::with_label("🛡 Security");
+        security_button.set_tooltip_text(Some("Show how well the service is sandboxed"));
 
         button_box.append(&start_button);
         button_box.append(&stop_button);
         button_box.append(&restart_button);
         button_box.append(&enable_button);
         button_box.append(&disable_button);
+        button_box.append(&reload_button);
+        button_box.append(&mask_button);
+        button_box.append(&unmask_button);
         button_box.append(&logs_button);
+        button_box.append(&dependencies_button);
+        button_box.append(&audit_button);
+        button_box.append(&environment_button);
+        button_box.append(&unit_file_button);
+        button_box.append(&drop_ins_button);
+        button_box.append(&security_button);
+
+        let app = Rc::downgrade(self);
+        drop_ins_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_drop_ins();
+            }
+        });
+        let app = Rc::downgrade(self);
+        security_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_security();
+            }
+        });
+
+        // New unit menu
+        let new_menu = gio::Menu::new();
+        new_menu.append(Some("New Service…"), Some("new.service"));
+        new_menu.append(Some("Triggered by File Change…"), Some("new.path-unit"));
+        new_menu.append(Some("New Timer…"), Some("new.timer"));
+        let new_button = gtk4::MenuButton::new();
+        new_button.set_label("＋ New");
+        new_button.set_tooltip_text(Some("Create a new unit"));
+        new_button.set_menu_model(Some(&new_menu));
+
+        let new_actions = gio::SimpleActionGroup::new();
+        let service_action = gio::SimpleAction::new("service", None);
+        let app = Rc::downgrade(self);
+        service_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.create_service();
+            }
+        });
+        new_actions.add_action(&service_action);
+        let path_unit_action = gio::SimpleAction::new("path-unit", None);
+        let app = Rc::downgrade(self);
+        path_unit_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.create_path_unit();
+            }
+        });
+        new_actions.add_action(&path_unit_action);
+        let timer_action = gio::SimpleAction::new("timer", None);
+        let app = Rc::downgrade(self);
+        timer_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.create_timer_unit();
+            }
+        });
+        new_actions.add_action(&timer_action);
+        button_box.insert_action_group("new", Some(&new_actions));
+        button_box.append(&new_button);
+
+        // Slice grouping toggle
+        button_box.append(&self.group_by_slice_button);
+
+        // Optional columns of the services list
+        let columns_menu = gio::Menu::new();
+        columns_menu.append(Some("Memory"), Some("columns.memory"));
+        columns_menu.append(Some("CPU"), Some("columns.cpu"));
+        let columns_button = gtk4::MenuButton::new();
+        columns_button.set_label("Columns");
+        columns_button.set_tooltip_text(Some("Choose the columns shown"));
+        columns_button.set_menu_model(Some(&columns_menu));
+
+        let column_actions = gio::SimpleActionGroup::new();
+        for (name, column) in [("memory", &self.memory_column), ("cpu", &self.cpu_column)] {
+            let action = gio::SimpleAction::new_stateful(name, None, &false.to_variant());
+            let column = column.clone();
+            let app = Rc::downgrade(self);
+            action.connect_activate(move |action, _| {
+                let shown = !action
+                    .state()
+                    .and_then(|state| state.get::<bool>())
+                    .unwrap_or_default();
+                action.set_state(&shown.to_variant());
+                column.set_visible(shown);
+                // The usage is only fetched while a resource column is shown
+                if let Some(app) = app.upgrade().filter(|_| shown) {
+                    app.refresh_local_services();
+                }
+            });
+            column_actions.add_action(&action);
+        }
+        button_box.insert_action_group("columns", Some(&column_actions));
+        button_box.append(&columns_button);
 
-        // Show inactive services toggle
-        button_box.append(&self.show_inactive_button);
+        let select_all_button = Button::with_label("Select All");
+        select_all_button.set_tooltip_text(Some("Select every listed service"));
+        let selection = self.local_services_list.selection();
+        select_all_button.connect_clicked(move |_| selection.select_all());
+        button_box.append(&select_all_button);
 
         main_box.append(&button_box);
+        self.local_loading.add_control(&button_box);
+
+        // Search, show inactive and status filters
+        main_box.append(&self.local_filter_box);
 
         // Services list
         self.setup_local_services_list();
         let scrolled = ScrolledWindow::new();
         scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
         scrolled.set_child(Some(&self.local_services_list));
+        self.local_loading.set_child(&scrolled);
 
-        scrolled.set_vexpand(true);
-        main_box.append(&scrolled);
+        // Details panel for the selected service
+        let details_scrolled = ScrolledWindow::new();
+        details_scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+        let details_box = Box::new(gtk4::Orientation::Vertical, 0);
+        details_box.append(self.details_panel.widget());
+        details_box.append(self.action_history.widget());
+        details_scrolled.set_child(Some(&details_box));
+        details_scrolled.set_size_request(300, -1);
+
+        let paned = Paned::new(gtk4::Orientation::Horizontal);
+        paned.set_start_child(Some(self.local_loading.widget()));
+        paned.set_end_child(Some(&details_scrolled));
+        paned.set_resize_end_child(false);
+
+        paned.set_vexpand(true);
+        main_box.append(&paned);
+        self.panes.borrow_mut().push(paned.clone());
+
+        // Offer Mask or Unmask depending on the selected services
+        self.local_services_list.selection().connect_changed(clone!(
+            #[weak]
+            mask_button,
+            #[weak]
+            unmask_button,
+            move |selection| update_mask_buttons(
+                &mask_button,
+                &unmask_button,
+                &get_all_selected_service_statuses(selection)
+            )
+        ));
 
         // Setup local service control signals
         self.setup_local_service_signals(
-            &start_button,
-            &stop_button,
-            &restart_button,
-            &enable_button,
-            &disable_button,
+            &[
+                (&start_button, ServiceAction::Start),
+                (&stop_button, ServiceAction::Stop),
+                (&restart_button, ServiceAction::Restart),
+                (&enable_button, ServiceAction::Enable),
+                (&disable_button, ServiceAction::Disable),
+                (&reload_button, ServiceAction::Reload),
+                (&mask_button, ServiceAction::Mask),
+                (&unmask_button, ServiceAction::Unmask),
+            ],
             &logs_button,
+            &dependencies_button,
+            &audit_button,
+            &environment_button,
+            &unit_file_button,
         );
 
         main_box
     }
 
-    fn create_remote_page(&self) -> Box {
+    fn create_timers_page(self: &Rc<Self>) -> Box {
+        let main_box = Box::new(gtk4::Orientation::Vertical, 6);
+        main_box.set_margin_start(12);
+        main_box.set_margin_end(12);
+        main_box.set_margin_top(12);
+        main_box.set_margin_bottom(12);
+
+        // Control buttons
+        let button_box = Box::new(gtk4::Orientation::Horizontal, 6);
+
+        let start_button = Button::with_label("▶ Start");
+        let stop_button = Button::with_label("⏹ Stop");
+        let enable_button = Button::with_label("✓ Enable");
+        let disable_button = Button::with_label("✗ Disable");
+        let new_button = Button::with_label("＋ New Timer…");
+        new_button.set_tooltip_text(Some("Create a timer that starts a service"));
+
+        button_box.append(&start_button);
+        button_box.append(&stop_button);
+        button_box.append(&enable_button);
+        button_box.append(&disable_button);
+        button_box.append(&new_button);
+        button_box.append(&self.show_inactive_timers_button);
+
+        main_box.append(&button_box);
+        self.timers_loading.add_control(&button_box);
+
+        // Timers list
+        self.setup_timers_list();
+        let scrolled = ScrolledWindow::new();
+        scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+        scrolled.set_child(Some(&self.timers_list));
+        scrolled.set_vexpand(true);
+        self.timers_loading.set_child(&scrolled);
+        main_box.append(self.timers_loading.widget());
+
+        // Setup timer control signals
+        for (button, action) in [
+            (&start_button, ServiceAction::Start),
+            (&stop_button, ServiceAction::Stop),
+            (&enable_button, ServiceAction::Enable),
+            (&disable_button, ServiceAction::Disable),
+        ] {
+            let app = Rc::downgrade(self);
+            button.connect_clicked(move |_| {
+                if let Some(app) = app.upgrade() {
+                    app.confirm_unit_command(&app.timers_list, action, |app, _| {
+                        app.refresh_timers()
+                    });
+                }
+            });
+        }
+
+        let app = Rc::downgrade(self);
+        new_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.create_timer_unit();
+            }
+        });
+
+        let app = Rc::downgrade(self);
+        self.show_inactive_timers_button.connect_toggled(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_timers();
+            }
+        });
+
+        main_box
+    }
+
+    fn create_sockets_page(self: &Rc<Self>) -> Box {
+        let main_box = Box::new(gtk4::Orientation::Vertical, 6);
+        main_box.set_margin_start(12);
+        main_box.set_margin_end(12);
+        main_box.set_margin_top(12);
+        main_box.set_margin_bottom(12);
+
+        // Control buttons
+        let button_box = Box::new(gtk4::Orientation::Horizontal, 6);
+
+        let start_button = Button::with_label("▶ Start");
+        let stop_button = Button::with_label("⏹ Stop");
+        let enable_button = Button::with_label("✓ Enable");
+        let disable_button = Button::with_label("✗ Disable");
+
+        button_box.append(&start_button);
+        button_box.append(&stop_button);
+        button_box.append(&enable_button);
+        button_box.append(&disable_button);
+        button_box.append(&self.show_inactive_sockets_button);
+
+        main_box.append(&button_box);
+        self.sockets_loading.add_control(&button_box);
+
+        // Sockets list
+        self.setup_sockets_list();
+        let scrolled = ScrolledWindow::new();
+        scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+        scrolled.set_child(Some(&self.sockets_list));
+        scrolled.set_vexpand(true);
+        self.sockets_loading.set_child(&scrolled);
+        main_box.append(self.sockets_loading.widget());
+
+        // Setup socket control signals
+        for (button, action) in [
+            (&start_button, ServiceAction::Start),
+            (&stop_button, ServiceAction::Stop),
+            (&enable_button, ServiceAction::Enable),
+            (&disable_button, ServiceAction::Disable),
+        ] {
+            let app = Rc::downgrade(self);
+            button.connect_clicked(move |_| {
+                if let Some(app) = app.upgrade() {
+                    app.confirm_unit_command(&app.sockets_list, action, Self::refresh_socket_rows);
+                }
+            });
+        }
+
+        let app = Rc::downgrade(self);
+        self.show_inactive_sockets_button.connect_toggled(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_sockets();
+            }
+        });
+
+        main_box
+    }
+
+    fn create_remote_page(self: &Rc<Self>) -> Box {
         let paned = Paned::new(gtk4::Orientation::Horizontal);
+        self.panes.borrow_mut().push(paned.clone());
 
         // Left panel - hosts
         let hosts_box = Box::new(gtk4::Orientation::Vertical, 6);
@@ -210,16 +1207,122 @@ impl SystemdPilotApp {
         hosts_label.set_markup("<b>Remote Hosts</b>");
         hosts_box.append(&hosts_label);
 
-        let add_host_button = Button::with_label("+ Add Host");
-        hosts_box.append(&add_host_button);
-
+        // Hosts are filtered by name, address and user as the search changes
+        self.host_search_entry
+            .set_placeholder_text(Some("Search hosts..."));
+        let app = Rc::downgrade(self);
+        self.hosts_listbox.set_filter_func(move |row| {
+            app.upgrade()
+                .is_none_or(|app| app.host_matches_search(&row.widget_name()))
+        });
+        let app = Rc::downgrade(self);
+        self.host_search_entry.connect_search_changed(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.hosts_listbox.invalidate_filter();
+                app.update_hosts_no_match();
+            }
+        });
+        hosts_box.append(&self.host_search_entry);
+
+        let add_host_button = Button::with_label("+ Add Host");
+        add_host_button.set_hexpand(true);
+
+        let import_menu = gio::Menu::new();
+        import_menu.append(
+            Some("Import from Ansible Inventory…"),
+            Some("hosts.import-ansible"),
+        );
+        import_menu.append(
+            Some("Import from SSH Config…"),
+            Some("hosts.import-ssh-config"),
+        );
+        import_menu.append(
+            Some("Export as Ansible Inventory…"),
+            Some("hosts.export-ansible"),
+        );
+        let json_section = gio::Menu::new();
+        json_section.append(Some("Import Hosts…"), Some("hosts.import-json"));
+        json_section.append(Some("Export Hosts…"), Some("hosts.export-json"));
+        import_menu.append_section(None, &json_section);
+        let import_button = gtk4::MenuButton::new();
+        import_button.set_label("Inventory");
+        import_button.set_menu_model(Some(&import_menu));
+
+        let host_actions = gio::SimpleActionGroup::new();
+        let import_ansible_action = gio::SimpleAction::new("import-ansible", None);
+        let app = Rc::downgrade(self);
+        import_ansible_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.import_ansible_inventory();
+            }
+        });
+        host_actions.add_action(&import_ansible_action);
+        let import_ssh_config_action = gio::SimpleAction::new("import-ssh-config", None);
+        let app = Rc::downgrade(self);
+        import_ssh_config_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.import_ssh_config();
+            }
+        });
+        host_actions.add_action(&import_ssh_config_action);
+        let export_ansible_action = gio::SimpleAction::new("export-ansible", None);
+        let app = Rc::downgrade(self);
+        export_ansible_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.export_ansible_inventory();
+            }
+        });
+        host_actions.add_action(&export_ansible_action);
+        let import_json_action = gio::SimpleAction::new("import-json", None);
+        let app = Rc::downgrade(self);
+        import_json_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.import_hosts_json();
+            }
+        });
+        host_actions.add_action(&import_json_action);
+        let export_json_action = gio::SimpleAction::new("export-json", None);
+        let app = Rc::downgrade(self);
+        export_json_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.export_hosts_json();
+            }
+        });
+        host_actions.add_action(&export_json_action);
+        hosts_box.insert_action_group("hosts", Some(&host_actions));
+
+        let host_button_box = Box::new(gtk4::Orientation::Horizontal, 6);
+        host_button_box.append(&add_host_button);
+        host_button_box.append(&import_button);
+
+        let connection_log_button = Button::with_label("📜");
+        connection_log_button.set_tooltip_text(Some("Connection Log"));
+        let app = Rc::downgrade(self);
+        connection_log_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                let events: Vec<_> = app
+                    .connection_logs
+                    .borrow()
+                    .values()
+                    .flat_map(|log| log.entries.iter().cloned())
+                    .collect();
+                show_remote_host_connection_log(app.window.upcast_ref(), &events);
+            }
+        });
+        host_button_box.append(&connection_log_button);
+        hosts_box.append(&host_button_box);
+
         let scrolled_hosts = ScrolledWindow::new();
         scrolled_hosts.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
         scrolled_hosts.set_child(Some(&self.hosts_listbox));
         scrolled_hosts.set_size_request(250, -1);
 
         scrolled_hosts.set_vexpand(true);
-        hosts_box.append(&scrolled_hosts);
+        self.hosts_no_match.set_visible(false);
+        let hosts_overlay = gtk4::Overlay::new();
+        hosts_overlay.set_child(Some(&scrolled_hosts));
+        hosts_overlay.add_overlay(&self.hosts_no_match);
+        hosts_box.append(&hosts_overlay);
 
         paned.set_start_child(Some(&hosts_box));
 
@@ -238,14 +1341,26 @@ impl SystemdPilotApp {
         let remote_restart_button = Button::with_label("🔄 Restart");
         let remote_enable_button = Button::with_label("✓ Enable");
         let remote_disable_button = Button::with_label("✗ Disable");
+        let remote_reload_button = Button::with_label("⟳ Reload");
+        remote_reload_button.set_tooltip_text(Some("Reload the service's configuration"));
         let remote_logs_button = Button::with_label("📋 Logs");
+        let remote_unit_file_button = Button::with_label("📄 Unit File");
+        remote_unit_file_button.set_tooltip_text(Some("View the service's unit file on the host"));
 
         remote_button_box.append(&remote_start_button);
         remote_button_box.append(&remote_stop_button);
         remote_button_box.append(&remote_restart_button);
         remote_button_box.append(&remote_enable_button);
         remote_button_box.append(&remote_disable_button);
+        remote_button_box.append(&remote_reload_button);
         remote_button_box.append(&remote_logs_button);
+        remote_button_box.append(&remote_unit_file_button);
+
+        let remote_select_all_button = Button::with_label("Select All");
+        remote_select_all_button.set_tooltip_text(Some("Select every listed service"));
+        let selection = self.remote_services_list.selection();
+        remote_select_all_button.connect_clicked(move |_| selection.select_all());
+        remote_button_box.append(&remote_select_all_button);
 
         services_box.append(&remote_button_box);
 
@@ -260,15 +1375,50 @@ impl SystemdPilotApp {
 
         paned.set_end_child(Some(&services_box));
 
+        // Disable the actions the selected service's host can't perform
+        let sudo_buttons = [
+            remote_start_button.clone(),
+            remote_stop_button.clone(),
+            remote_restart_button.clone(),
+            remote_enable_button.clone(),
+            remote_disable_button.clone(),
+            remote_reload_button.clone(),
+        ];
+        let logs_button = remote_logs_button.clone();
+        let app = Rc::downgrade(self);
+        self.remote_services_list
+            .selection()
+            .connect_changed(move |selection| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                let (host_name, capabilities) = get_selected_remote_service(selection)
+                    .and_then(|(host_name, _)| {
+                        let capabilities = app
+                            .remote_hosts
+                            .borrow()
+                            .get(&host_name)?
+                            .capabilities
+                            .clone();
+                        Some((host_name, capabilities))
+                    })
+                    .unwrap_or_default();
+                apply_host_capabilities(&host_name, &capabilities, &sudo_buttons, &logs_button);
+            });
+
         // Setup remote host signals
         self.setup_remote_host_signals(&add_host_button);
         self.setup_remote_service_signals(
-            &remote_start_button,
-            &remote_stop_button,
-            &remote_restart_button,
-            &remote_enable_button,
-            &remote_disable_button,
+            &[
+                (&remote_start_button, ServiceAction::Start),
+                (&remote_stop_button, ServiceAction::Stop),
+                (&remote_restart_button, ServiceAction::Restart),
+                (&remote_enable_button, ServiceAction::Enable),
+                (&remote_disable_button, ServiceAction::Disable),
+                (&remote_reload_button, ServiceAction::Reload),
+            ],
             &remote_logs_button,
+            &remote_unit_file_button,
         );
 
         {
@@ -279,8 +1429,32 @@ impl SystemdPilotApp {
     }
 
     fn setup_local_services_list(&self) {
+        // Rows are filtered by name and status, then sorted by column
+        let filter = self.local_services_filter.clone();
+        self.local_search_entry
+            .connect_changed(move |entry| filter.set_search_text(&entry.text()));
+        let filter = self.local_services_filter.clone();
+        self.status_filter
+            .connect_changed(move |combo| filter.set_status_filter(status_filter_choice(combo)));
+        let sort_model = TreeModelSort::with_model(self.local_services_filter.model());
+        sort_pinned_first(&sort_model, 4, &[0, 1]);
+        self.local_services_list.set_model(Some(&sort_model));
         self.local_services_list
-            .set_model(Some(&self.local_services_store));
+            .selection()
+            .set_mode(gtk4::SelectionMode::Multiple);
+
+        // Pinned column
+        let pinned_column = TreeViewColumn::new();
+        pinned_column.set_title("Pinned");
+
+        let pinned_renderer = CellRendererText::new();
+        pinned_column.pack_start(&pinned_renderer, false);
+        pinned_column.set_cell_data_func(&pinned_renderer, |_, cell, model, iter| {
+            let pinned = model.get::<bool>(iter, 4);
+            cell.set_property("text", if pinned { "📌" } else { "" });
+        });
+
+        self.local_services_list.append_column(&pinned_column);
 
         // Service name column
         let name_column = TreeViewColumn::new();
@@ -302,7 +1476,10 @@ impl SystemdPilotApp {
 
         let status_renderer = CellRendererText::new();
         status_column.pack_start(&status_renderer, true);
-        status_column.add_attribute(&status_renderer, "text", 1);
+        let theme_manager = self.theme_manager.clone();
+        status_column.set_cell_data_func(&status_renderer, move |_, cell, model, iter| {
+            format_status_cell(cell, model, iter, 1, Some(7), &theme_manager)
+        });
 
         self.local_services_list.append_column(&status_column);
 
@@ -316,11 +1493,104 @@ impl SystemdPilotApp {
         desc_column.add_attribute(&desc_renderer, "text", 2);
 
         self.local_services_list.append_column(&desc_column);
+
+        // Resource columns, hidden until chosen from the Columns menu
+        for (column, title, column_id, format) in [
+            (
+                &self.memory_column,
+                "Memory",
+                5,
+                format_bytes as fn(u64) -> String,
+            ),
+            (&self.cpu_column, "CPU", 6, format_cpu_time),
+        ] {
+            column.set_title(title);
+            column.set_resizable(true);
+            column.set_sort_column_id(column_id);
+            column.set_visible(false);
+
+            let renderer = CellRendererText::new();
+            column.pack_start(&renderer, true);
+            column.set_cell_data_func(&renderer, move |_, cell, model, iter| {
+                // 0 until the usage is fetched, and for units using nothing
+                let value = model.get::<u64>(iter, column_id);
+                let text = if value == 0 {
+                    String::new()
+                } else {
+                    format(value)
+                };
+                cell.set_property("text", text);
+            });
+
+            self.local_services_list.append_column(column);
+        }
+
+        // Explains statuses such as an unmet condition
+        self.local_services_list.set_tooltip_column(3);
+    }
+
+    fn setup_timers_list(&self) {
+        let sort_model = TreeModelSort::with_model(&self.timers_store);
+        self.timers_list.set_model(Some(&sort_model));
+        self.timers_list
+            .selection()
+            .set_mode(gtk4::SelectionMode::Multiple);
+
+        // The trigger columns show relative times, so they sort by the
+        // hidden timestamp columns instead
+        for (column_id, sort_column_id, title) in [
+            (0, 0, "Timer"),
+            (1, 1, "Status"),
+            (2, 5, "Next Trigger"),
+            (3, 6, "Last Trigger"),
+            (4, 4, "Description"),
+        ] {
+            let column = TreeViewColumn::new();
+            column.set_title(title);
+            column.set_resizable(true);
+            column.set_sort_column_id(sort_column_id);
+
+            let renderer = CellRendererText::new();
+            column.pack_start(&renderer, true);
+            column.add_attribute(&renderer, "text", column_id);
+
+            self.timers_list.append_column(&column);
+        }
+        self.timers_list.set_tooltip_column(7);
+    }
+
+    fn setup_sockets_list(&self) {
+        let sort_model = TreeModelSort::with_model(&self.sockets_store);
+        self.sockets_list.set_model(Some(&sort_model));
+        self.sockets_list
+            .selection()
+            .set_mode(gtk4::SelectionMode::Multiple);
+
+        for (column_id, title) in [
+            (0, "Socket"),
+            (1, "Status"),
+            (2, "Listen"),
+            (3, "Description"),
+        ] {
+            let column = TreeViewColumn::new();
+            column.set_title(title);
+            column.set_resizable(true);
+            column.set_sort_column_id(column_id);
+
+            let renderer = CellRendererText::new();
+            column.pack_start(&renderer, true);
+            column.add_attribute(&renderer, "text", column_id);
+
+            self.sockets_list.append_column(&column);
+        }
     }
 
     fn setup_remote_services_list(&self) {
         self.remote_services_list
             .set_model(Some(&self.remote_services_store));
+        self.remote_services_list
+            .selection()
+            .set_mode(gtk4::SelectionMode::Multiple);
 
         // Host column
         let host_column = TreeViewColumn::new();
@@ -354,7 +1624,10 @@ impl SystemdPilotApp {
 
         let status_renderer = CellRendererText::new();
         status_column.pack_start(&status_renderer, true);
-        status_column.add_attribute(&status_renderer, "text", 2);
+        let theme_manager = self.theme_manager.clone();
+        status_column.set_cell_data_func(&status_renderer, move |_, cell, model, iter| {
+            format_status_cell(cell, model, iter, 2, None, &theme_manager)
+        });
 
         self.remote_services_list.append_column(&status_column);
 
@@ -370,318 +1643,4300 @@ impl SystemdPilotApp {
         self.remote_services_list.append_column(&desc_column);
     }
 
-    fn setup_signal_handlers(&self) {
-        // Show inactive services toggle
-        let service_manager = self.service_manager.clone();
-        let local_store = self.local_services_store.clone();
+    fn setup_signal_handlers(self: &Rc<Self>) {
+        // Remember column widths and sort order across restarts
+        self.watch_column_state();
 
-        self.show_inactive_button.connect_toggled(move |button| {
-            let show_inactive = button.is_active();
-            // Refresh local services with new filter
-            // This would need to be implemented in service_manager
+        // Relist the local services with or without inactive ones
+        let app = Rc::downgrade(self);
+        self.show_inactive_button.connect_toggled(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_local_services();
+            }
         });
-    }
-
-    fn setup_local_service_signals(
-        &self,
-        start_btn: &Button,
-        stop_btn: &Button,
-        restart_btn: &Button,
-        enable_btn: &Button,
-        disable_btn: &Button,
-        logs_btn: &Button,
-    ) {
-        let selection = self.local_services_list.selection();
 
-        // Start service
-        let service_manager = self.service_manager.clone();
-        let tree_selection = selection.clone();
-        start_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                // Implement start service logic
-                info!("Starting local service: {}", service_name);
+        // Relist every local unit from the system or the user's manager
+        let app = Rc::downgrade(self);
+        self.unit_scope_combo.connect_changed(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_local_services();
+                app.refresh_timers();
+                app.refresh_sockets();
             }
         });
 
-        // Stop service
-        let service_manager = self.service_manager.clone();
-        let tree_selection = selection.clone();
-        stop_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                info!("Stopping local service: {}", service_name);
+        // Hold the window open until running operations finish, so the
+        // Tokio runtime isn't dropped in the middle of one
+        let app = Rc::downgrade(self);
+        let closing = Rc::new(std::cell::Cell::new(false));
+        self.window.connect_close_request(move |window| {
+            let Some(app) = app.upgrade() else {
+                return glib::Propagation::Proceed;
+            };
+            if !app.progress_bar.is_busy() {
+                app.save_window_state();
+                return glib::Propagation::Proceed;
+            }
+
+            app.shutdown_info_bar.set_revealed(true);
+            if !closing.replace(true) {
+                info!("Waiting for running operations before closing");
+                let mut idle = app.progress_bar.notify_when_idle();
+                let window = window.clone();
+                MainContext::default().spawn_local(async move {
+                    idle.recv().await;
+                    window.close();
+                });
             }
+            glib::Propagation::Stop
         });
 
-        // Restart service
-        let service_manager = self.service_manager.clone();
-        let tree_selection = selection.clone();
-        restart_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                info!("Restarting local service: {}", service_name);
+        // Status bar refresh
+        let app = Rc::downgrade(self);
+        self.status_refresh_button.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_all_services();
             }
         });
 
-        // Enable service
-        let service_manager = self.service_manager.clone();
-        let tree_selection = selection.clone();
-        enable_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                info!("Enabling local service: {}", service_name);
+        // Resource usage graph for the selected service
+        let app = Rc::downgrade(self);
+        self.details_panel.connect_graph_clicked(move || {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_service_graph();
             }
         });
 
-        // Disable service
-        let service_manager = self.service_manager.clone();
-        let tree_selection = selection.clone();
-        disable_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                info!("Disabling local service: {}", service_name);
+        // Connect to a host when its row is activated
+        let app = Rc::downgrade(self);
+        self.hosts_listbox.connect_row_activated(move |_, row| {
+            if let Some(app) = app.upgrade() {
+                app.connect_to_host(&row.widget_name());
             }
         });
 
-        // Show logs
-        let window = self.window.clone();
-        let tree_selection = selection.clone();
-        logs_btn.connect_clicked(move |_| {
-            if let Some(service_name) = get_selected_service_name(&tree_selection) {
-                show_service_logs_dialog(&window, &service_name, None);
+        // Regroup the local services list by slice
+        let app = Rc::downgrade(self);
+        self.group_by_slice_button.connect_toggled(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.refresh_local_services();
             }
         });
-    }
 
-    fn setup_remote_host_signals(&self, add_host_btn: &Button) {
-        let window = self.window.clone();
-        let remote_hosts = self.remote_hosts.clone();
+        // Right-click menus
+        self.setup_context_menus();
 
-        add_host_btn.connect_clicked(move |_| {
-            show_add_host_dialog(&window, &remote_hosts);
-        });
+        // Hosts can be added by dropping ssh:// or sftp:// URIs
+        self.setup_drag_and_drop_for_hosts();
+
+        // Keep the details panel in sync with the selection
+        let app = Rc::downgrade(self);
+        self.local_services_list
+            .selection()
+            .connect_changed(move |_| {
+                if let Some(app) = app.upgrade() {
+                    app.show_selected_service_details();
+                }
+            });
     }
 
-    fn setup_remote_service_signals(
-        &self,
-        start_btn: &Button,
-        stop_btn: &Button,
-        restart_btn: &Button,
-        enable_btn: &Button,
-        disable_btn: &Button,
-        logs_btn: &Button,
-    ) {
-        let selection = self.remote_services_list.selection();
+    fn setup_context_menus(self: &Rc<Self>) {
+        // Local services
+        let local_actions = gio::SimpleActionGroup::new();
+        let open_terminal_action = gio::SimpleAction::new("open-terminal", None);
+        let selection = self.local_services_list.selection();
+        let window = self.window.clone();
+        open_terminal_action.connect_activate(move |_, _| {
+            let Some(service_name) = get_selected_service_name(&selection) else {
+                return;
+            };
+            if let Err(e) = open_terminal_for_service(&service_name) {
+                show_error_dialog(
+                    window.upcast_ref(),
+                    "Open in Terminal",
+                    &format!("Failed to open a terminal: {}", e),
+                );
+            }
+        });
+        local_actions.add_action(&open_terminal_action);
 
-        // Similar to local service signals but for remote services
-        // Implementation would handle remote SSH connections
-    }
+        // Service operations, which are only enabled when they apply to a
+        // selected service
+        let service_actions: Vec<(gio::SimpleAction, ServiceAction)> = [
+            ServiceAction::Start,
+            ServiceAction::Stop,
+            ServiceAction::Restart,
+            ServiceAction::Enable,
+            ServiceAction::Disable,
+            ServiceAction::Mask,
+            ServiceAction::Unmask,
+        ]
+        .into_iter()
+        .map(|action| {
+            let simple_action = gio::SimpleAction::new(action.command(), None);
+            let app = Rc::downgrade(self);
+            simple_action.connect_activate(move |_, _| {
+                if let Some(app) = app.upgrade() {
+                    app.confirm_service_command(action);
+                }
+            });
+            local_actions.add_action(&simple_action);
+            (simple_action, action)
+        })
+        .collect();
 
-    pub fn load_saved_hosts(&self) {
-        // Load saved remote hosts from configuration
-        if let Ok(hosts) = self.load_hosts_from_config() {
-            let mut remote_hosts = self.remote_hosts.borrow_mut();
-            *remote_hosts = hosts;
-            self.refresh_hosts_list();
-        }
-    }
+        let view_unit_file_action = gio::SimpleAction::new("view-unit-file", None);
+        let app = Rc::downgrade(self);
+        view_unit_file_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_unit_file();
+            }
+        });
+        local_actions.add_action(&view_unit_file_action);
 
-    fn load_hosts_from_config(&self) -> Result<HashMap<String, RemoteHost>> {
-        let config_dir =
-            dirs::config_dir().ok_or_else(|| anyhow!("Could not find config directory"))?;
-        let config_file = config_dir.join("systemd-pilot").join("hosts.json");
+        // Copies full unit names, ready to paste into a terminal
+        let copy_name_action = gio::SimpleAction::new("copy-name", None);
+        let app = Rc::downgrade(self);
+        copy_name_action.connect_activate(move |_, _| {
+            let Some(app) = app.upgrade() else {
+                return;
+            };
+            let tree_view = &app.local_services_list;
+            let unit_names: Vec<String> = get_all_selected_service_names(&tree_view.selection())
+                .iter()
+                .map(|name| unit_file_name(name))
+                .collect();
+            let message = match unit_names.as_slice() {
+                [] => return,
+                [unit_name] => format!("Copied '{}' to clipboard", unit_name),
+                _ => format!("Copied {} service names to clipboard", unit_names.len()),
+            };
+            tree_view.clipboard().set_text(&unit_names.join("\n"));
+            app.show_notification(&message, NotificationKind::Success);
+        });
+        local_actions.add_action(&copy_name_action);
 
-        if !config_file.exists() {
-            return Ok(HashMap::new());
+        let pin_action = gio::SimpleAction::new("pin", None);
+        let unpin_action = gio::SimpleAction::new("unpin", None);
+        for (action, pinned) in [(&pin_action, true), (&unpin_action, false)] {
+            let app = Rc::downgrade(self);
+            action.connect_activate(move |_, _| {
+                if let Some(app) = app.upgrade() {
+                    let service_names =
+                        get_all_selected_service_names(&app.local_services_list.selection());
+                    app.set_services_pinned(&service_names, pinned);
+                }
+            });
+            local_actions.add_action(action);
         }
 
-        let content = std::fs::read_to_string(&config_file)?;
-        let hosts: HashMap<String, RemoteHost> = serde_json::from_str(&content)?;
-        Ok(hosts)
-    }
+        // Grey out what doesn't apply to the selected services
+        let app = Rc::downgrade(self);
+        self.local_services_list
+            .selection()
+            .connect_changed(move |selection| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                let statuses = get_all_selected_service_statuses(selection);
+                for (simple_action, action) in &service_actions {
+                    simple_action
+                        .set_enabled(statuses.iter().any(|status| action.applies_to(status)));
+                }
 
-    pub fn save_hosts(&self) -> Result<()> {
-        let config_dir =
-            dirs::config_dir().ok_or_else(|| anyhow!("Could not find config directory"))?;
-        let app_config_dir = config_dir.join("systemd-pilot");
-        std::fs::create_dir_all(&app_config_dir)?;
+                let config = app.config.borrow();
+                let (pinned, unpinned): (Vec<String>, Vec<String>) =
+                    get_all_selected_service_names(selection)
+                        .into_iter()
+                        .partition(|name| config.pinned_services.contains(name));
+                pin_action.set_enabled(!unpinned.is_empty());
+                unpin_action.set_enabled(!pinned.is_empty());
+            });
 
-        let config_file = app_config_dir.join("hosts.json");
-        let hosts = self.remote_hosts.borrow();
-        let content = serde_json::to_string_pretty(&*hosts)?;
-        std::fs::write(&config_file, content)?;
+        self.local_services_list
+            .insert_action_group("local", Some(&local_actions));
 
-        Ok(())
-    }
+        // Like Delete in the hosts list, Ctrl+C only applies while the
+        // services list has focus
+        let shortcuts = gtk4::ShortcutController::new();
+        shortcuts.add_shortcut(gtk4::Shortcut::new(
+            gtk4::ShortcutTrigger::parse_string("<Control>c"),
+            Some(gtk4::NamedAction::new("local.copy-name")),
+        ));
+        self.local_services_list.add_controller(shortcuts);
 
-    fn refresh_hosts_list(&self) {
-        // Clear existing hosts in UI
-        // Clear existing items
-        while let Some(child) = self.hosts_listbox.first_child() {
-            self.hosts_listbox.remove(&child);
-        }
+        let local_menu = ContextMenuBuilder::new()
+            .add_item("Start", "local.start")
+            .add_item("Stop", "local.stop")
+            .add_item("Restart", "local.restart")
+            .add_separator()
+            .add_item("Enable", "local.enable")
+            .add_item("Disable", "local.disable")
+            .add_item("Mask", "local.mask")
+            .add_item("Unmask", "local.unmask")
+            .add_separator()
+            .add_item("View Unit File", "local.view-unit-file")
+            .add_item("View Logs", "app.logs")
+            .add_item("Copy Name", "local.copy-name")
+            .add_item("Open in Terminal", "local.open-terminal")
+            .add_separator()
+            .add_item("Pin", "local.pin")
+            .add_item("Unpin", "local.unpin")
+            .add_submenu(
+                "More…",
+                ContextMenuBuilder::new().add_item("Reload", "app.reload"),
+            )
+            .build();
+        attach_tree_view_context_menu(&self.local_services_list, &local_menu);
 
-        // Add hosts to UI
-        let hosts = self.remote_hosts.borrow();
-        for (name, host) in hosts.iter() {
-            let row = ListBoxRow::new();
-            let label = Label::new(Some(&format!("{}@{}", host.username, host.hostname)));
-            label.set_markup(&format!(
-                "<b>{}</b>\n{}@{}",
-                name, host.username, host.hostname
-            ));
-            row.set_child(Some(&label));
-            self.hosts_listbox.append(&row);
-        }
+        // Remote services
+        let remote_actions = gio::SimpleActionGroup::new();
+        let forward_port_action = gio::SimpleAction::new("forward-port", None);
+        let app = Rc::downgrade(self);
+        forward_port_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                app.forward_port_for_selected_host();
+            }
+        });
+        remote_actions.add_action(&forward_port_action);
+        self.remote_services_list
+            .insert_action_group("remote", Some(&remote_actions));
 
-        self.hosts_listbox.show();
-    }
+        let remote_menu = ContextMenuBuilder::new()
+            .add_item("Forward Port…", "remote.forward-port")
+            .build();
+        attach_tree_view_context_menu(&self.remote_services_list, &remote_menu);
 
-    fn refresh_all_services(&self) {
-        self.refresh_local_services();
-        self.refresh_remote_services();
-    }
+        // Hosts
+        let host_actions = gio::SimpleActionGroup::new();
+        let connect_action = gio::SimpleAction::new("connect", None);
+        let app = Rc::downgrade(self);
+        connect_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.connect_to_host(&row.widget_name());
+                }
+            }
+        });
+        host_actions.add_action(&connect_action);
 
-    fn refresh_local_services(&self) {
-        let runtime = self.runtime.clone();
-        let service_manager = self.service_manager.clone();
-        let store = self.local_services_store.clone();
-        let show_inactive = self.show_inactive_button.is_active();
+        let disconnect_action = gio::SimpleAction::new("disconnect", None);
+        let app = Rc::downgrade(self);
+        disconnect_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.disconnect_from_host(&row.widget_name());
+                }
+            }
+        });
+        host_actions.add_action(&disconnect_action);
 
-        let (sender, receiver) = std::sync::mpsc::channel();
+        let run_command_action = gio::SimpleAction::new("run-command", None);
+        let app = Rc::downgrade(self);
+        run_command_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.run_remote_command(&row.widget_name());
+                }
+            }
+        });
+        host_actions.add_action(&run_command_action);
 
-        runtime.spawn(async move {
-            match service_manager.list_local_services(show_inactive).await {
-                Ok(services) => {
-                    sender.send(services).expect("Failed to send services");
+        let daemon_reload_action = gio::SimpleAction::new("daemon-reload", None);
+        let app = Rc::downgrade(self);
+        daemon_reload_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.remote_daemon_reload(&row.widget_name());
                 }
-                Err(e) => {
-                    error!("Failed to list services: {}", e);
+            }
+        });
+        host_actions.add_action(&daemon_reload_action);
+
+        let edit_action = gio::SimpleAction::new("edit", None);
+        let app = Rc::downgrade(self);
+        edit_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.edit_host(&row.widget_name());
                 }
             }
         });
+        host_actions.add_action(&edit_action);
 
-        glib::idle_add_local(move || match receiver.try_recv() {
-            Ok(services) => {
-                store.clear();
-                for service in services {
-                    store.insert_with_values(
-                        None,
-                        None,
-                        &[
-                            (0, &service.name),
-                            (1, &service.status.to_string()),
-                            (2, &service.enabled.to_string()),
-                            (3, &service.description.as_deref().unwrap_or("")),
-                        ],
-                    );
+        let remove_action = gio::SimpleAction::new("remove", None);
+        let app = Rc::downgrade(self);
+        remove_action.connect_activate(move |_, _| {
+            if let Some(app) = app.upgrade() {
+                if let Some(row) = app.hosts_listbox.selected_row() {
+                    app.remove_host(&row.widget_name());
                 }
-                glib::ControlFlow::Break
             }
-            Err(std::sync::mpsc::TryRecvError::Empty) => glib::ControlFlow::Continue,
-            Err(std::sync::mpsc::TryRecvError::Disconnected) => glib::ControlFlow::Break,
         });
-    }
+        host_actions.add_action(&remove_action);
+        self.hosts_listbox
+            .insert_action_group("host", Some(&host_actions));
 
-    fn refresh_remote_services(&self) {
-        // Similar to local services but for remote hosts
-        // Would iterate through active connections and refresh each
+        // Delete only removes hosts while the list has focus, so text
+        // entries keep the key
+        let shortcuts = gtk4::ShortcutController::new();
+        shortcuts.add_shortcut(gtk4::Shortcut::new(
+            gtk4::ShortcutTrigger::parse_string("Delete"),
+            Some(gtk4::NamedAction::new("host.remove")),
+        ));
+        self.hosts_listbox.add_controller(shortcuts);
+
+        let hosts_menu = ContextMenuBuilder::new()
+            .add_item("Connect", "host.connect")
+            .add_item("Disconnect", "host.disconnect")
+            .add_separator()
+            .add_item("Run Command…", "host.run-command")
+            .add_item("Daemon Reload", "host.daemon-reload")
+            .add_separator()
+            .add_item("Edit Host…", "host.edit")
+            .add_item("Remove Host", "host.remove")
+            .build();
+        let listbox = self.hosts_listbox.clone();
+        attach_context_menu(&self.hosts_listbox, &hosts_menu, move |_, y| match listbox
+            .row_at_y(y as i32)
+        {
+            Some(row) => {
+                listbox.select_row(Some(&row));
+                true
+            }
+            None => false,
+        });
     }
-}
 
-fn get_selected_service_name(selection: &TreeSelection) -> Option<String> {
-    if let Some((model, iter)) = selection.selected() {
-        model.get_value(&iter, 0).get::<String>().ok()
-    } else {
-        None
+    /// Prompts for and creates a `.path` unit that starts a service when a
+    /// file changes
+    fn create_path_unit(self: &Rc<Self>) {
+        let services = self.local_service_names();
+        let services: Vec<&str> = services.iter().map(String::as_str).collect();
+        let app = Rc::downgrade(self);
+        show_create_path_unit_dialog(
+            self.window.upcast_ref(),
+            &services,
+            move |name, path, service| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+
+                let service_manager = app.service_manager.clone();
+                let runtime = app.runtime.clone();
+                let progress_bar = app.progress_bar.clone();
+                spawn_tracked(
+                    &runtime,
+                    &progress_bar,
+                    &format!("Creating {}.path…", name),
+                    async move {
+                        service_manager
+                            .create_path_unit(&name, &path, &service)
+                            .await?;
+                        let unit_name = format!("{}.path", name);
+                        Ok::<_, anyhow::Error>((
+                            service_manager
+                                .verify_unit(&unit_name, UnitScope::System)
+                                .await,
+                            name,
+                        ))
+                    },
+                    move |result| match result {
+                        Ok((problems, name)) => {
+                            show_verify_results(
+                                app.window.upcast_ref(),
+                                &format!("{}.path", name),
+                                problems,
+                                false,
+                            );
+                            app.refresh_local_services();
+                            app.show_notification(
+                                &format!("{}.path is enabled and watching for changes", name),
+                                NotificationKind::Success,
+                            );
+                        }
+                        Err(e) => show_error_dialog(
+                            app.window.upcast_ref(),
+                            "Create Path Unit",
+                            &format!("Failed to create path unit: {}", e),
+                        ),
+                    },
+                );
+            },
+        );
     }
-}
 
-fn show_service_logs_dialog(parent: &ApplicationWindow, service_name: &str, host: Option<&str>) {
-    let dialog = Dialog::with_buttons(
-        Some(&format!("Logs for {}", service_name)),
-        Some(parent),
-        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
-        &[("Close", ResponseType::Close)],
-    );
+    /// Creates a service from the new service wizard, asking first when a
+    /// unit of the same name already exists
+    fn create_service(self: &Rc<Self>) {
+        let app = Rc::downgrade(self);
+        show_create_service_wizard(self.window.upcast_ref(), move |spec| {
+            let Some(app) = app.upgrade() else {
+                return;
+            };
 
-    dialog.set_default_size(800, 600);
+            let service_manager = app.service_manager.clone();
+            let unit_name = spec.unit_name();
+            let runtime = app.runtime.clone();
+            spawn_with_callback(
+                &runtime,
+                async move {
+                    service_manager
+                        .check_service_exists(&unit_name, UnitScope::System)
+                        .await
+                },
+                move |result| match result {
+                    Ok(true) => {
+                        let message = format!(
+                            "A unit named {} already exists. Replace it with the new service?",
+                            spec.unit_name()
+                        );
+                        let weak = Rc::downgrade(&app);
+                        show_confirmation_dialog(
+                            app.window.upcast_ref(),
+                            "Replace Unit",
+                            &message,
+                            move |confirmed| {
+                                if let (true, Some(app)) = (confirmed, weak.upgrade()) {
+                                    app.write_new_service(spec.clone());
+                                }
+                            },
+                        );
+                    }
+                    Ok(false) => app.write_new_service(spec),
+                    Err(e) => show_error_dialog(
+                        app.window.upcast_ref(),
+                        "New Service",
+                        &format!("Failed to check for an existing unit: {}", e),
+                    ),
+                },
+            );
+        });
+    }
 
-    let scrolled = ScrolledWindow::new();
-    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    /// Writes the unit file for a wizard's service and shows what
+    /// `systemd-analyze verify` makes of it
+    fn write_new_service(self: &Rc<Self>, spec: ServiceSpec) {
+        let service_manager = self.service_manager.clone();
+        let app = self.clone();
+        spawn_tracked(
+            &self.runtime,
+            &self.progress_bar,
+            &format!("Creating {}…", spec.unit_name()),
+            async move {
+                let unit_name = spec.unit_name();
+                service_manager
+                    .create_service_file(
+                        &unit_name,
+                        &service_unit_content(&spec),
+                        UnitScope::System,
+                    )
+                    .await?;
+                Ok::<_, anyhow::Error>((
+                    service_manager
+                        .verify_unit(&unit_name, UnitScope::System)
+                        .await,
+                    unit_name,
+                ))
+            },
+            move |result| match result {
+                Ok((problems, unit_name)) => {
+                    show_verify_results(app.window.upcast_ref(), &unit_name, problems, false);
+                    app.refresh_local_services();
+                    app.status_label.set_text(&format!("Created {}", unit_name));
+                }
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "New Service",
+                    &format!("Failed to create service: {}", e),
+                ),
+            },
+        );
+    }
 
-    let text_view = TextView::new();
-    text_view.set_editable(false);
-    text_view.set_cursor_visible(false);
+    /// Prompts for and creates a `.timer` unit activating a service
+    fn create_timer_unit(self: &Rc<Self>) {
+        let service_manager = self.service_manager.clone();
+        let runtime = self.runtime.clone();
+        let validate =
+            move |expression: String, done: std::boxed::Box<dyn FnOnce(Result<String>)>| {
+                let service_manager = service_manager.clone();
+                spawn_with_callback(
+                    &runtime,
+                    async move {
+                        service_manager
+                            .validate_calendar_expression(&expression)
+                            .await
+                    },
+                    done,
+                );
+            };
 
-    // Set monospace font
-    let font_desc = pango::FontDescription::from_string("monospace");
-    text_view.set_monospace(true);
+        let services = self.local_service_names();
+        let services: Vec<&str> = services.iter().map(String::as_str).collect();
+        let app = Rc::downgrade(self);
+        show_create_timer_dialog(
+            self.window.upcast_ref(),
+            &services,
+            validate,
+            move |spec, enable| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
 
-    scrolled.set_child(Some(&text_view));
+                let service_manager = app.service_manager.clone();
+                let runtime = app.runtime.clone();
+                let progress_bar = app.progress_bar.clone();
+                spawn_tracked(
+                    &runtime,
+                    &progress_bar,
+                    &format!("Creating {}…", spec.unit_name()),
+                    async move {
+                        service_manager.create_timer_unit(&spec, enable).await?;
+                        let unit_name = spec.unit_name();
+                        Ok::<_, anyhow::Error>((
+                            service_manager
+                                .verify_unit(&unit_name, UnitScope::System)
+                                .await,
+                            unit_name,
+                        ))
+                    },
+                    move |result| match result {
+                        Ok((problems, unit_name)) => {
+                            show_verify_results(
+                                app.window.upcast_ref(),
+                                &unit_name,
+                                problems,
+                                false,
+                            );
+                            app.refresh_local_services();
+                            app.refresh_timers();
+                            app.status_label.set_text(&format!("Created {}", unit_name));
+                        }
+                        Err(e) => show_error_dialog(
+                            app.window.upcast_ref(),
+                            "New Timer",
+                            &format!("Failed to create timer: {}", e),
+                        ),
+                    },
+                );
+            },
+        );
+    }
 
-    let content_area = dialog.content_area();
-    content_area.append(&scrolled);
+    /// Accepts `ssh://` and `sftp://` URIs dropped on the hosts list, e.g.
+    /// bookmarks dragged from a file manager, and offers to add them as hosts
+    fn setup_drag_and_drop_for_hosts(self: &Rc<Self>) {
+        const URI_MIME_TYPES: [&str; 2] = ["text/uri-list", "text/x-uri"];
 
-    dialog.show();
-    dialog.close();
-}
+        let drop_target = gtk4::DropTargetAsync::new(
+            Some(gdk4::ContentFormats::new(&URI_MIME_TYPES)),
+            gdk4::DragAction::COPY,
+        );
 
-fn show_add_host_dialog(
-    parent: &ApplicationWindow,
-    remote_hosts: &Rc<RefCell<HashMap<String, RemoteHost>>>,
-) {
-    let dialog = Dialog::with_buttons(
-        Some("Add Remote Host"),
-        Some(parent),
-        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
-        &[("Cancel", ResponseType::Cancel), ("Add", ResponseType::Ok)],
-    );
+        let app = Rc::downgrade(self);
+        drop_target.connect_drop(move |_, drop, _, _| {
+            let Some(app) = app.upgrade() else {
+                return false;
+            };
 
-    let grid = Grid::new();
-    grid.set_row_spacing(6);
-    grid.set_column_spacing(12);
-    grid.set_margin_start(12);
-    grid.set_margin_end(12);
-    grid.set_margin_top(12);
-    grid.set_margin_bottom(12);
+            let drop = drop.clone();
+            MainContext::default().spawn_local(async move {
+                let text = async {
+                    let (stream, _) = drop.read_future(&URI_MIME_TYPES, Priority::DEFAULT).await?;
+                    let buffer = gio::MemoryOutputStream::new_resizable();
+                    buffer
+                        .splice_future(
+                            &stream,
+                            gio::OutputStreamSpliceFlags::CLOSE_SOURCE
+                                | gio::OutputStreamSpliceFlags::CLOSE_TARGET,
+                            Priority::DEFAULT,
+                        )
+                        .await?;
+                    Ok::<_, glib::Error>(
+                        String::from_utf8_lossy(&buffer.steal_as_bytes()).into_owned(),
+                    )
+                }
+                .await;
 
-    // Name field
-    let name_label = Label::new(Some("Name:"));
-    let name_entry = Entry::new();
-    grid.attach(&name_label, 0, 0, 1, 1);
-    grid.attach(&name_entry, 1, 0, 1, 1);
+                let uri = match text {
+                    Ok(text) => text
+                        .lines()
+                        .map(str::trim)
+                        .find(|line| !line.is_empty() && !line.starts_with('#'))
+                        .map(str::to_string),
+                    Err(e) => {
+                        warn!("Failed to read dropped data: {}", e);
+                        None
+                    }
+                };
+                drop.finish(if uri.is_some() {
+                    gdk4::DragAction::COPY
+                } else {
+                    gdk4::DragAction::empty()
+                });
 
-    // Hostname field
-    let hostname_label = Label::new(Some("Hostname:"));
-    let hostname_entry = Entry::new();
-    grid.attach(&hostname_label, 0, 1, 1, 1);
-    grid.attach(&hostname_entry, 1, 1, 1, 1);
+                if let Some(uri) = uri {
+                    app.add_host_from_uri(&uri);
+                }
+            });
 
-    // Username field
-    let username_label = Label::new(Some("Username:"));
-    let username_entry = Entry::new();
-    grid.attach(&username_label, 0, 2, 1, 1);
-    grid.attach(&username_entry, 1, 2, 1, 1);
+            true
+        });
 
-    // Auth type
-    let auth_label = Label::new(Some("Authentication:"));
-    let auth_combo = ComboBoxText::new();
-    auth_combo.append_text("Password");
-    auth_combo.append_text("SSH Key");
-    auth_combo.set_active(Some(0));
-    grid.attach(&auth_label, 0, 3, 1, 1);
-    grid.attach(&auth_combo, 1, 3, 1, 1);
+        self.hosts_listbox.add_controller(drop_target);
+    }
 
-    let content_area = dialog.content_area();
-    content_area.append(&grid);
+    /// Opens the host dialog pre-filled from an SSH URI, adding the host on save
+    fn add_host_from_uri(self: &Rc<Self>, uri: &str) {
+        let mut host = match RemoteHost::from_ssh_uri(uri) {
+            Ok(host) => host,
+            Err(e) => {
+                show_error_dialog(self.window.upcast_ref(), "Cannot Add Host", &e.to_string());
+                return;
+            }
+        };
 
-    let remote_hosts_clone = remote_hosts.clone();
+        // Don't let the new host replace an existing one with the same name
+        let base_name = host.name.clone();
+        let mut suffix = 2;
+        while self.remote_hosts.borrow().contains_key(&host.name) {
+            host.name = format!("{}-{}", base_name, suffix);
+            suffix += 1;
+        }
+
+        let app = Rc::downgrade(self);
+        show_edit_host_dialog(
+            self.window.upcast_ref(),
+            &host,
+            &self.remote_hosts,
+            move |_| {
+                if let Some(app) = app.upgrade() {
+                    if let Err(e) = app.save_hosts() {
+                        error!("Failed to save hosts: {}", e);
+                    }
+                    app.refresh_hosts_list();
+                }
+            },
+        );
+    }
+
+    /// Opens the host dialog for a saved host. Saving restarts the host's
+    /// auto-refresh with its new settings when it is connected.
+    fn edit_host(self: &Rc<Self>, host_name: &str) {
+        let Some(host) = self.remote_hosts.borrow().get(host_name).cloned() else {
+            return;
+        };
+
+        let app = Rc::downgrade(self);
+        let old_name = host_name.to_string();
+        show_edit_host_dialog(
+            self.window.upcast_ref(),
+            &host,
+            &self.remote_hosts,
+            move |new_name| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                if let Err(e) = app.save_hosts() {
+                    error!("Failed to save hosts: {}", e);
+                }
+                app.refresh_hosts_list();
+
+                app.stop_host_auto_refresh(&old_name);
+                let connected = app
+                    .active_connections
+                    .lock()
+                    .unwrap()
+                    .contains_key(new_name);
+                if connected {
+                    app.start_host_auto_refresh(new_name);
+                }
+            },
+        );
+    }
+
+    fn forward_port_for_selected_host(self: &Rc<Self>) {
+        let Some((host_name, _)) =
+            get_selected_remote_service(&self.remote_services_list.selection())
+        else {
+            return;
+        };
+
+        let connection = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(&host_name)
+            .cloned();
+        let Some(connection) = connection else {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Not Connected",
+                &format!("There is no active connection to {}", host_name),
+            );
+            return;
+        };
+        let Some(session) = connection.session().cloned() else {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Port Forwarding",
+                &format!(
+                    "{} is connected through an SSH control socket; port forwarding needs a direct connection",
+                    host_name
+                ),
+            );
+            return;
+        };
+
+        let app = Rc::downgrade(self);
+        let host = host_name.clone();
+        show_forward_port_dialog(
+            self.window.upcast_ref(),
+            &host_name,
+            move |local_port, remote_host, remote_port| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+
+                let tunnel = SshTunnel::new(local_port, remote_host, remote_port, session.clone());
+                let host = host.clone();
+                let runtime = app.runtime.clone();
+                spawn_with_callback(
+                    &runtime,
+                    async move { tunnel.establish().await },
+                    move |result| match result {
+                        Ok(handle) => {
+                            show_info_dialog(
+                                app.window.upcast_ref(),
+                                "Port Forwarded",
+                                &format!(
+                                    "Forwarding 127.0.0.1:{} to {}:{} via {}",
+                                    handle.local_port, handle.remote_host, handle.remote_port, host
+                                ),
+                            );
+                            app.port_forwards.borrow_mut().push(handle);
+                        }
+                        Err(e) => show_error_dialog(
+                            app.window.upcast_ref(),
+                            "Port Forwarding",
+                            &format!("Failed to forward port: {}", e),
+                        ),
+                    },
+                );
+            },
+        );
+    }
+
+    fn show_selected_service_details(self: &Rc<Self>) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        self.watch_selected_service_status(&service_name);
+        self.show_recent_actions(&service_name);
+
+        let service_manager = self.service_manager.clone();
+        let name = service_name.clone();
+        let scope = self.unit_scope();
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                (
+                    service_manager.get_service_status(&name, scope).await,
+                    service_manager.get_service_unit_path(&name, scope).await,
+                    service_manager.get_resource_limits(&name, scope).await,
+                    service_manager.get_service_triggers(&name, scope).await,
+                    service_manager.get_service_exec_steps(&name, scope).await,
+                )
+            },
+            move |(status, paths, limits, triggers, exec_steps)| {
+                // The selection may have moved on while the query was running
+                let selected = get_selected_service_name(&app.local_services_list.selection());
+                if selected.as_deref() != Some(service_name.as_str()) {
+                    return;
+                }
+
+                match status {
+                    Ok(service) => {
+                        app.details_panel.update(&service);
+                        if service.status == ServiceStatus::Failed {
+                            app.show_failure_reason(&service_name);
+                        }
+                    }
+                    Err(e) => warn!("Failed to get status of {}: {}", service_name, e),
+                }
+
+                match paths {
+                    Ok(paths) => {
+                        let window = app.window.clone();
+                        let exec_steps = exec_steps
+                            .map_err(|e| {
+                                warn!("Failed to get exec steps of {}: {}", service_name, e)
+                            })
+                            .ok();
+                        app.details_panel.set_unit_files(&paths, move |path| {
+                            show_unit_file(&window, &path, exec_steps.as_ref())
+                        });
+                    }
+                    Err(e) => warn!("Failed to get unit files of {}: {}", service_name, e),
+                }
+
+                match limits {
+                    Ok(limits) => app.details_panel.set_resource_limits(&limits),
+                    Err(e) => warn!("Failed to get resource limits of {}: {}", service_name, e),
+                }
+
+                match triggers {
+                    Ok(triggers) => app.details_panel.set_triggers(&triggers),
+                    Err(e) => warn!("Failed to get triggers of {}: {}", service_name, e),
+                }
+            },
+        );
+    }
+
+    /// Replaces any previous status watch with one for `service_name`, pushing
+    /// status changes into the details panel while it stays selected. A
+    /// desktop notification reports the service failing while the window
+    /// is in the background.
+    fn watch_selected_service_status(self: &Rc<Self>, service_name: &str) {
+        if let Some(previous) = self.status_watch.borrow_mut().take() {
+            previous.abort();
+        }
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let service_manager = self.service_manager.clone();
+        let name = service_name.to_string();
+        let scope = self.unit_scope();
+        let watch = self.runtime.spawn(async move {
+            let statuses = match service_manager
+                .watch_service_status(&name, STATUS_WATCH_INTERVAL, scope)
+                .await
+            {
+                Ok(statuses) => statuses,
+                Err(e) => {
+                    debug!("Not watching status of {}: {}", name, e);
+                    return;
+                }
+            };
+            futures::pin_mut!(statuses);
+            while let Some(status) = statuses.next().await {
+                if tx.send(status).is_err() {
+                    break;
+                }
+            }
+        });
+        *self.status_watch.borrow_mut() = Some(watch);
+
+        let app = Rc::downgrade(self);
+        let service_name = service_name.to_string();
+        MainContext::default().spawn_local(async move {
+            let mut last_status = None;
+            while let Some(status) = rx.recv().await {
+                let Some(app) = app.upgrade() else {
+                    break;
+                };
+                let selected = get_selected_service_name(&app.local_services_list.selection());
+                if selected.as_deref() != Some(service_name.as_str()) {
+                    continue;
+                }
+
+                app.details_panel.set_status(&status);
+                let newly_failed = status == ServiceStatus::Failed
+                    && last_status.is_some_and(|last| last != ServiceStatus::Failed);
+                if let (true, false, Some(application)) = (
+                    newly_failed,
+                    app.window.is_active(),
+                    app.window.application(),
+                ) {
+                    send_notification(
+                        &application,
+                        &format!("{} failed", service_name),
+                        "The service you were watching has entered the failed state",
+                        NotificationUrgency::Urgent,
+                    );
+                }
+                last_status = Some(status);
+            }
+        });
+    }
+
+    /// Fills the details panel's "Last Failure" section from the journal
+    fn show_failure_reason(self: &Rc<Self>, service_name: &str) {
+        let service_manager = self.service_manager.clone();
+        let name = service_name.to_string();
+        let service_name = service_name.to_string();
+        let scope = self.unit_scope();
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move { service_manager.get_failure_reason(&name, scope).await },
+            move |result| {
+                let selected = get_selected_service_name(&app.local_services_list.selection());
+                if selected.as_deref() != Some(service_name.as_str()) {
+                    return;
+                }
+
+                match result {
+                    Ok(reason) => app.details_panel.set_failure_reason(Some(&reason)),
+                    Err(e) => warn!("Failed to get failure reason of {}: {}", service_name, e),
+                }
+            },
+        );
+    }
+
+    /// Opens the resource graph for the selected service and samples its
+    /// usage until the dialog is closed
+    fn show_selected_service_graph(&self) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        let series = Rc::new(RefCell::new(ResourceTimeSeries::new()));
+        let dialog =
+            show_service_graph_dialog(self.window.upcast_ref(), &service_name, series.clone());
+
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        let scope = self.unit_scope();
+        let poll = move || {
+            let service_manager = service_manager.clone();
+            let name = service_name.clone();
+            let series = series.clone();
+            spawn_with_callback(
+                &runtime,
+                async move { service_manager.get_resource_usage(&name, scope).await },
+                move |result| match result {
+                    Ok(usage) => series
+                        .borrow_mut()
+                        .record(std::time::Instant::now(), &usage),
+                    Err(e) => warn!("Failed to sample resource usage: {}", e),
+                },
+            );
+        };
+
+        poll();
+        let weak_dialog = dialog.downgrade();
+        glib::timeout_add_local(RESOURCE_POLL_INTERVAL, move || {
+            if weak_dialog.upgrade().is_none() {
+                return glib::ControlFlow::Break;
+            }
+            poll();
+            glib::ControlFlow::Continue
+        });
+    }
+
+    /// Opens the log viewer for the selected local service
+    fn show_selected_service_logs(self: &Rc<Self>) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        let name = service_name.clone();
+        let log_lines = self.preferences.borrow().log_lines();
+        let scope = self.unit_scope();
+        let reload = {
+            let service_manager = service_manager.clone();
+            let runtime = runtime.clone();
+            let name = service_name.clone();
+            move |options: LogOptions, done: std::boxed::Box<dyn FnOnce(Result<String>)>| {
+                let service_manager = service_manager.clone();
+                let name = name.clone();
+                let options = options.with_default_lines(log_lines);
+                spawn_with_callback(
+                    &runtime,
+                    async move {
+                        service_manager
+                            .get_service_logs(&name, &options, scope)
+                            .await
+                    },
+                    done,
+                );
+            }
+        };
+        let follow = {
+            let service_manager = service_manager.clone();
+            let name = service_name.clone();
+            move |sender| service_manager.stream_service_logs(&name, sender, scope)
+        };
+        spawn_with_callback(
+            &runtime,
+            async move {
+                (
+                    service_manager
+                        .get_service_logs(&name, &LogOptions::lines(log_lines), scope)
+                        .await,
+                    service_manager.list_boots().await,
+                )
+            },
+            move |(logs, boots)| match logs {
+                Ok(logs) => {
+                    let boots = boots.unwrap_or_else(|e| {
+                        warn!("Failed to list boots: {}", e);
+                        Vec::new()
+                    });
+                    show_service_logs_dialog(
+                        window.upcast_ref(),
+                        &service_name,
+                        &logs,
+                        None,
+                        &boots,
+                        reload,
+                        follow,
+                    )
+                }
+                Err(e) => show_error_dialog(
+                    window.upcast_ref(),
+                    "Logs",
+                    &format!("Failed to read logs: {}", e),
+                ),
+            },
+        );
+    }
+
+    fn setup_local_service_signals(
+        self: &Rc<Self>,
+        action_buttons: &[(&Button, ServiceAction)],
+        logs_btn: &Button,
+        dependencies_btn: &Button,
+        audit_btn: &Button,
+        environment_btn: &Button,
+        unit_file_btn: &Button,
+    ) {
+        let selection = self.local_services_list.selection();
+
+        // Service control actions
+        for &(button, action) in action_buttons {
+            let name = format!("app.{}", action.command());
+            button.connect_clicked(move |button| activate_action(button, &name));
+        }
+
+        // Show logs
+        logs_btn.connect_clicked(|button| activate_action(button, "app.logs"));
+
+        // Show dependencies
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        let tree_selection = selection.clone();
+        let unit_scope = self.unit_scope_combo.clone();
+        let app = Rc::downgrade(self);
+        dependencies_btn.connect_clicked(move |_| {
+            if let Some(service_name) = get_selected_service_name(&tree_selection) {
+                let scope = unit_scope_choice(&unit_scope);
+                let service_manager = service_manager.clone();
+                let name = service_name.clone();
+                let window = window.clone();
+                let app = app.clone();
+                spawn_with_callback(
+                    &runtime,
+                    async move {
+                        let (forward, reverse) = tokio::join!(
+                            service_manager.get_service_dependencies(&name, false, scope),
+                            service_manager.get_reverse_dependencies(&name, scope)
+                        );
+                        Ok::<_, anyhow::Error>((forward?, reverse?))
+                    },
+                    move |result| match result {
+                        Ok((forward, reverse)) => show_dependencies_dialog(
+                            window.upcast_ref(),
+                            &service_name,
+                            &forward,
+                            &reverse,
+                            move |unit| {
+                                if let Some(app) = app.upgrade() {
+                                    app.select_local_service(unit);
+                                }
+                            },
+                        ),
+                        Err(e) => show_error_dialog(
+                            window.upcast_ref(),
+                            "Dependencies",
+                            &format!("Failed to list dependencies: {}", e),
+                        ),
+                    },
+                );
+            }
+        });
+
+        // Show audit log
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        let tree_selection = selection.clone();
+        let unit_scope = self.unit_scope_combo.clone();
+        audit_btn.connect_clicked(move |_| {
+            if let Some(service_name) = get_selected_service_name(&tree_selection) {
+                let scope = unit_scope_choice(&unit_scope);
+                let service_manager = service_manager.clone();
+                let name = service_name.clone();
+                let window = window.clone();
+                spawn_with_callback(
+                    &runtime,
+                    async move { service_manager.get_service_audit_log(&name, scope).await },
+                    move |result| match result {
+                        Ok(entries) => {
+                            show_service_audit_dialog(window.upcast_ref(), &service_name, &entries)
+                        }
+                        Err(e) => show_error_dialog(
+                            window.upcast_ref(),
+                            "Audit Log",
+                            &format!("Failed to read audit log: {}", e),
+                        ),
+                    },
+                );
+            }
+        });
+
+        // Show environment variables
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let service_manager = self.service_manager.clone();
+        let tree_selection = selection.clone();
+        let unit_scope = self.unit_scope_combo.clone();
+        environment_btn.connect_clicked(move |_| {
+            if let Some(service_name) = get_selected_service_name(&tree_selection) {
+                let scope = unit_scope_choice(&unit_scope);
+                let service_manager = service_manager.clone();
+                let name = service_name.clone();
+                let window = window.clone();
+                spawn_with_callback(
+                    &runtime,
+                    async move { service_manager.get_service_environment(&name, scope).await },
+                    move |result| match result {
+                        Ok(environment) => show_environment_dialog(
+                            window.upcast_ref(),
+                            &service_name,
+                            &environment,
+                        ),
+                        Err(e) => show_error_dialog(
+                            window.upcast_ref(),
+                            "Environment",
+                            &format!("Failed to read the environment: {}", e),
+                        ),
+                    },
+                );
+            }
+        });
+
+        // View unit file
+        let app = Rc::downgrade(self);
+        unit_file_btn.connect_clicked(move |_| {
+            if let Some(app) = app.upgrade() {
+                app.show_selected_unit_file();
+            }
+        });
+    }
+
+    /// Opens the unit file of the selected local service for viewing and editing
+    fn show_selected_unit_file(&self) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        let service_manager = self.service_manager.clone();
+        let name = service_name.clone();
+        let window = self.window.clone();
+        let save_runtime = self.runtime.clone();
+        let save_manager = service_manager.clone();
+        let save_window = self.window.clone();
+        let save_progress = self.progress_bar.clone();
+        let scope = self.unit_scope();
+        // Writing the file also runs daemon-reload, then the saved file is
+        // checked for problems. The edited copy goes to /etc (or the user's
+        // unit directory), overriding a vendor unit rather than changing it.
+        let save = move |content: String, done: std::boxed::Box<dyn FnOnce(Result<()>)>| {
+            let service_manager = save_manager.clone();
+            let name = name.clone();
+            let window = save_window.clone();
+            let content = strip_unit_file_sources(&content);
+            spawn_tracked(
+                &save_runtime,
+                &save_progress,
+                &format!("Saving {}…", name),
+                async move {
+                    service_manager
+                        .create_service_file(&name, &content, scope)
+                        .await?;
+                    Ok::<_, anyhow::Error>((service_manager.verify_unit(&name, scope).await, name))
+                },
+                move |result| match result {
+                    Ok((problems, name)) => {
+                        done(Ok(()));
+                        show_verify_results(window.upcast_ref(), &name, problems, false);
+                    }
+                    Err(e) => done(Err(e)),
+                },
+            );
+        };
+        let verify_runtime = self.runtime.clone();
+        let verify_manager = service_manager.clone();
+        let name = service_name.clone();
+        let verify = move |done: std::boxed::Box<dyn FnOnce(Result<Vec<String>>)>| {
+            let service_manager = verify_manager.clone();
+            let name = name.clone();
+            spawn_with_callback(
+                &verify_runtime,
+                async move { service_manager.verify_unit(&name, scope).await },
+                done,
+            );
+        };
+        // The selected remote host's copy of the unit is fetched when it is
+        // compared with the local one
+        let remote = self
+            .selected_connected_host()
+            .map(|(host_name, connection)| {
+                let runtime = self.runtime.clone();
+                let name = service_name.clone();
+                let fetch = move |done: std::boxed::Box<dyn FnOnce(Result<String>)>| {
+                    let manager = RemoteServiceManager::new(connection.clone());
+                    let name = name.clone();
+                    spawn_with_callback(
+                        &runtime,
+                        tokio::time::timeout(REMOTE_UNIT_FETCH_TIMEOUT, async move {
+                            manager.get_unit_file_contents(&name).await
+                        }),
+                        move |result| {
+                            done(
+                                result.unwrap_or_else(|_| Err(anyhow!("The host did not respond"))),
+                            )
+                        },
+                    );
+                };
+                (host_name, std::boxed::Box::new(fetch) as RemoteUnitFetch)
+            });
+        let name = service_name.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move { service_manager.get_unit_file_contents(&name, scope).await },
+            move |result| match result {
+                Ok(content) => {
+                    let (remote_host, fetch_remote) = remote.unzip();
+                    show_unit_file_dialog(
+                        window.upcast_ref(),
+                        &service_name,
+                        &content,
+                        UnitFileKind::Unit,
+                        remote_host.as_deref().zip(fetch_remote),
+                        save,
+                        verify,
+                    )
+                }
+                Err(e) => show_error_dialog(
+                    window.upcast_ref(),
+                    "Unit File",
+                    &format!("Failed to read unit file: {}", e),
+                ),
+            },
+        );
+    }
+
+    /// Whether local units are listed from the system or the user's manager
+    fn unit_scope(&self) -> UnitScope {
+        unit_scope_choice(&self.unit_scope_combo)
+    }
+
+    /// The host selected in the hosts list and its connection, if it is
+    /// connected
+    fn selected_connected_host(&self) -> Option<(String, HostConnection)> {
+        let host_name = self.hosts_listbox.selected_row()?.widget_name().to_string();
+        let connection = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(&host_name)
+            .cloned()?;
+        Some((host_name, connection))
+    }
+
+    /// Lists the drop-ins of the selected local service, from which they
+    /// can be viewed, edited and added
+    fn show_selected_drop_ins(self: &Rc<Self>) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        let service_manager = self.service_manager.clone();
+        let name = service_name.clone();
+        let scope = self.unit_scope();
+        let app = Rc::downgrade(self);
+        spawn_with_callback(
+            &self.runtime,
+            async move { service_manager.list_drop_ins(&name, scope).await },
+            move |result| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                let drop_ins = match result {
+                    Ok(drop_ins) => drop_ins,
+                    Err(e) => {
+                        show_error_dialog(
+                            app.window.upcast_ref(),
+                            "Drop-ins",
+                            &format!("Failed to list drop-ins: {}", e),
+                        );
+                        return;
+                    }
+                };
+
+                let view_app = Rc::downgrade(&app);
+                let view_service = service_name.clone();
+                let new_app = Rc::downgrade(&app);
+                let new_service = service_name.clone();
+                show_drop_ins_dialog(
+                    app.window.upcast_ref(),
+                    &service_name,
+                    &drop_ins,
+                    move |path| {
+                        if let Some(app) = view_app.upgrade() {
+                            app.open_drop_in(&view_service, path, UnitFileKind::DropIn, scope);
+                        }
+                    },
+                    move |drop_in_name| {
+                        let Some(app) = new_app.upgrade() else {
+                            return;
+                        };
+                        match drop_in_path(&new_service, &drop_in_name, scope) {
+                            // An existing drop-in of that name is opened instead
+                            Ok(path) if path.exists() => {
+                                app.open_drop_in(&new_service, &path, UnitFileKind::DropIn, scope)
+                            }
+                            Ok(path) => app.edit_drop_in(
+                                &new_service,
+                                &path,
+                                "[Service]\n",
+                                UnitFileKind::NewDropIn,
+                                scope,
+                            ),
+                            Err(e) => show_error_dialog(
+                                app.window.upcast_ref(),
+                                "New Drop-in",
+                                &e.to_string(),
+                            ),
+                        }
+                    },
+                );
+            },
+        );
+    }
+
+    fn show_selected_security(self: &Rc<Self>) {
+        let Some(service_name) = get_selected_service_name(&self.local_services_list.selection())
+        else {
+            return;
+        };
+
+        let service_manager = self.service_manager.clone();
+        let name = service_name.clone();
+        let scope = self.unit_scope();
+        let app = Rc::downgrade(self);
+        spawn_with_callback(
+            &self.runtime,
+            async move { service_manager.get_security_score(&name, scope).await },
+            move |result| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                let (score, rows) = match result {
+                    Ok(report) => report,
+                    Err(e) => {
+                        show_error_dialog(
+                            app.window.upcast_ref(),
+                            "Security",
+                            &format!("Failed to analyze {}: {}", service_name, e),
+                        );
+                        return;
+                    }
+                };
+
+                let fixes = security_fix_directives(&rows);
+                let fix_app = Rc::downgrade(&app);
+                let fix_service = service_name.clone();
+                let fix_directives = fixes.clone();
+                show_security_dialog(
+                    app.window.upcast_ref(),
+                    &service_name,
+                    score,
+                    &rows,
+                    &fixes,
+                    move || {
+                        if let Some(app) = fix_app.upgrade() {
+                            app.add_security_fixes(&fix_service, &fix_directives, scope);
+                        }
+                    },
+                );
+            },
+        );
+    }
+
+    /// Adds `directives` to the service's hardening drop-in, creating it if
+    /// needed, so fixes from earlier runs are kept
+    fn add_security_fixes(
+        self: &Rc<Self>,
+        service_name: &str,
+        directives: &[&str],
+        scope: UnitScope,
+    ) {
+        let path = match drop_in_path(service_name, SECURITY_DROP_IN, scope) {
+            Ok(path) => path,
+            Err(e) => {
+                show_error_dialog(self.window.upcast_ref(), "Security", &e.to_string());
+                return;
+            }
+        };
+        let mut content =
+            std::fs::read_to_string(&path).unwrap_or_else(|_| "[Service]\n".to_string());
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        for directive in directives {
+            content.push_str(directive);
+            content.push('\n');
+        }
+
+        let service_manager = self.service_manager.clone();
+        let app = self.clone();
+        let message = format!(
+            "Added {} hardening setting(s) to {}; restart {} to apply them",
+            directives.len(),
+            path.display(),
+            service_name
+        );
+        spawn_tracked(
+            &self.runtime,
+            &self.progress_bar,
+            &format!("Hardening {}…", service_name),
+            async move { service_manager.write_drop_in(&path, &content, scope).await },
+            move |result| match result {
+                Ok(()) => app.show_notification(&message, NotificationKind::Success),
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "Security",
+                    &format!("Failed to write the drop-in: {}", e),
+                ),
+            },
+        );
+    }
+
+    /// Reads the drop-in at `path` and opens it in the unit file dialog
+    fn open_drop_in(
+        &self,
+        service_name: &str,
+        path: &std::path::Path,
+        kind: UnitFileKind,
+        scope: UnitScope,
+    ) {
+        match std::fs::read_to_string(path) {
+            Ok(content) => self.edit_drop_in(service_name, path, &content, kind, scope),
+            Err(e) => show_error_dialog(
+                self.window.upcast_ref(),
+                "Drop-in",
+                &format!("Failed to read {}: {}", path.display(), e),
+            ),
+        }
+    }
+
+    /// Shows a drop-in of `service_name` in the unit file dialog. Saving
+    /// writes it back to `path`, or to `/etc` for a vendor drop-in, then
+    /// checks the service for problems.
+    fn edit_drop_in(
+        &self,
+        service_name: &str,
+        path: &std::path::Path,
+        content: &str,
+        kind: UnitFileKind,
+        scope: UnitScope,
+    ) {
+        let save_path = match editable_drop_in_path(service_name, path, scope) {
+            Ok(save_path) => save_path,
+            Err(e) => {
+                show_error_dialog(self.window.upcast_ref(), "Drop-in", &e.to_string());
+                return;
+            }
+        };
+        let save_runtime = self.runtime.clone();
+        let save_manager = self.service_manager.clone();
+        let save_window = self.window.clone();
+        let save_progress = self.progress_bar.clone();
+        let name = service_name.to_string();
+        let save = move |content: String, done: std::boxed::Box<dyn FnOnce(Result<()>)>| {
+            let service_manager = save_manager.clone();
+            let path = save_path.clone();
+            let name = name.clone();
+            let window = save_window.clone();
+            spawn_tracked(
+                &save_runtime,
+                &save_progress,
+                &format!("Saving {}…", path.display()),
+                async move {
+                    service_manager
+                        .write_drop_in(&path, &content, scope)
+                        .await?;
+                    Ok::<_, anyhow::Error>((service_manager.verify_unit(&name, scope).await, name))
+                },
+                move |result| match result {
+                    Ok((problems, name)) => {
+                        done(Ok(()));
+                        show_verify_results(window.upcast_ref(), &name, problems, false);
+                    }
+                    Err(e) => done(Err(e)),
+                },
+            );
+        };
+
+        let verify_runtime = self.runtime.clone();
+        let verify_manager = self.service_manager.clone();
+        let name = service_name.to_string();
+        let verify = move |done: std::boxed::Box<dyn FnOnce(Result<Vec<String>>)>| {
+            let service_manager = verify_manager.clone();
+            let name = name.clone();
+            spawn_with_callback(
+                &verify_runtime,
+                async move { service_manager.verify_unit(&name, scope).await },
+                done,
+            );
+        };
+
+        show_unit_file_dialog(
+            self.window.upcast_ref(),
+            &path.display().to_string(),
+            content,
+            kind,
+            None,
+            save,
+            verify,
+        );
+    }
+
+    fn setup_remote_host_signals(&self, add_host_btn: &Button) {
+        let window = self.window.clone();
+        let remote_hosts = self.remote_hosts.clone();
+
+        add_host_btn.connect_clicked(move |_| {
+            show_add_host_dialog(&window, &remote_hosts);
+        });
+    }
+
+    fn setup_remote_service_signals(
+        self: &Rc<Self>,
+        action_buttons: &[(&Button, ServiceAction)],
+        logs_btn: &Button,
+        unit_file_btn: &Button,
+    ) {
+        let selection = self.remote_services_list.selection();
+
+        // Service control actions, run on every selected service
+        for &(button, action) in action_buttons {
+            let app = Rc::downgrade(self);
+            button.connect_clicked(move |_| {
+                if let Some(app) = app.upgrade() {
+                    app.confirm_remote_service_command(action);
+                }
+            });
+        }
+
+        // Show logs
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let active_connections = self.active_connections.clone();
+        let tree_selection = selection.clone();
+        logs_btn.connect_clicked(move |_| {
+            let Some((host_name, service_name)) = get_selected_remote_service(&tree_selection)
+            else {
+                return;
+            };
+
+            let connection = active_connections.lock().unwrap().get(&host_name).cloned();
+            let Some(connection) = connection else {
+                show_error_dialog(
+                    window.upcast_ref(),
+                    "Not Connected",
+                    &format!("There is no active connection to {}", host_name),
+                );
+                return;
+            };
+
+            let reload = {
+                let connection = connection.clone();
+                let runtime = runtime.clone();
+                let name = service_name.clone();
+                move |options: LogOptions, done: std::boxed::Box<dyn FnOnce(Result<String>)>| {
+                    let manager = RemoteServiceManager::new(connection.clone());
+                    let name = name.clone();
+                    let options = options.with_default_lines(REMOTE_LOG_LINES);
+                    spawn_with_callback(
+                        &runtime,
+                        async move {
+                            manager
+                                .get_service_logs(&name, &options, UnitScope::System)
+                                .await
+                        },
+                        done,
+                    );
+                }
+            };
+            let follow = {
+                let connection = connection.clone();
+                let runtime = runtime.clone();
+                let name = service_name.clone();
+                move |sender| {
+                    let manager = RemoteServiceManager::new(connection.clone());
+                    let name = name.clone();
+                    runtime.spawn(async move {
+                        manager
+                            .follow_service_logs(
+                                &name,
+                                sender,
+                                REMOTE_LOG_POLL_INTERVAL,
+                                UnitScope::System,
+                            )
+                            .await
+                    })
+                }
+            };
+
+            let manager = RemoteServiceManager::new(connection);
+            let name = service_name.clone();
+            let window = window.clone();
+            spawn_with_callback(
+                &runtime,
+                async move {
+                    manager
+                        .get_service_logs(
+                            &name,
+                            &LogOptions::lines(REMOTE_LOG_LINES),
+                            UnitScope::System,
+                        )
+                        .await
+                },
+                move |result| match result {
+                    // Earlier boots can only be listed for local services
+                    Ok(logs) => show_service_logs_dialog(
+                        window.upcast_ref(),
+                        &service_name,
+                        &logs,
+                        Some(&host_name),
+                        &[],
+                        reload,
+                        follow,
+                    ),
+                    Err(e) => show_error_dialog(
+                        window.upcast_ref(),
+                        "Logs",
+                        &format!("Failed to read logs from {}: {}", host_name, e),
+                    ),
+                },
+            );
+        });
+
+        // View unit file
+        let window = self.window.clone();
+        let runtime = self.runtime.clone();
+        let active_connections = self.active_connections.clone();
+        let tree_selection = selection.clone();
+        unit_file_btn.connect_clicked(move |_| {
+            let Some((host_name, service_name)) = get_selected_remote_service(&tree_selection)
+            else {
+                return;
+            };
+
+            let connection = active_connections.lock().unwrap().get(&host_name).cloned();
+            let Some(connection) = connection else {
+                show_error_dialog(
+                    window.upcast_ref(),
+                    "Not Connected",
+                    &format!("There is no active connection to {}", host_name),
+                );
+                return;
+            };
+
+            let manager = RemoteServiceManager::new(connection);
+            let name = service_name.clone();
+            let window = window.clone();
+            spawn_with_callback(
+                &runtime,
+                async move { manager.copy_unit_file_from_host(&name).await },
+                move |result| match result {
+                    Ok(files) => {
+                        let content = files
+                            .iter()
+                            .map(|(path, content)| format!("# {}\n{}", path.display(), content))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        show_service_details_dialog(
+                            window.upcast_ref(),
+                            &service_name,
+                            &content,
+                            Some(&host_name),
+                            None,
+                        );
+                    }
+                    Err(e) => show_error_dialog(
+                        window.upcast_ref(),
+                        "Unit File",
+                        &format!("Failed to read unit file from {}: {}", host_name, e),
+                    ),
+                },
+            );
+        });
+    }
+
+    /// Restores the window size and pane dividers saved at the last exit,
+    /// keeping the window within the monitor
+    pub fn restore_window_state(&self) {
+        let state = WindowState::load().unwrap_or_else(|e| {
+            warn!("Failed to load window state: {}", e);
+            WindowState::default()
+        });
+
+        if let Some((width, height)) = state.size_within(monitor_size()) {
+            self.window.set_default_size(width, height);
+        }
+        for paned in self.panes.borrow().iter() {
+            if let Some(position) = state
+                .paned_positions
+                .get(orientation_key(paned.orientation()))
+            {
+                paned.set_position(*position);
+            }
+        }
+
+        for (list, tree_view) in self.service_lists() {
+            for column in tree_view.columns() {
+                let title = column.title();
+                if let Some(width) = state.column_widths.get(&column_key(list, &title)) {
+                    // GTK only keeps a fixed width for fixed-size columns
+                    column.set_sizing(gtk4::TreeViewColumnSizing::Fixed);
+                    column.set_fixed_width(*width);
+                }
+            }
+
+            let Some(sort) = state.sort_columns.get(list) else {
+                continue;
+            };
+            let column = tree_view
+                .columns()
+                .into_iter()
+                .find(|column| column.title() == sort.column.as_str());
+            let sortable = tree_view
+                .model()
+                .and_then(|model| model.dynamic_cast::<gtk4::TreeSortable>().ok());
+            if let (Some(column), Some(sortable)) = (column, sortable) {
+                if column.sort_column_id() >= 0 {
+                    let order = if sort.descending {
+                        gtk4::SortType::Descending
+                    } else {
+                        gtk4::SortType::Ascending
+                    };
+                    sortable.set_sort_column_id(
+                        gtk4::SortColumn::Index(column.sort_column_id() as u32),
+                        order,
+                    );
+                }
+            }
+        }
+    }
+
+    /// The service lists whose columns are saved in the window state, with
+    /// the key each is saved under
+    fn service_lists(&self) -> [(&'static str, &TreeView); 2] {
+        [
+            ("local", &self.local_services_list),
+            ("remote", &self.remote_services_list),
+        ]
+    }
+
+    /// Saves the window state shortly after a service list column is
+    /// resized or sorted
+    fn watch_column_state(self: &Rc<Self>) {
+        for (_, tree_view) in self.service_lists() {
+            for column in tree_view.columns() {
+                let app = Rc::downgrade(self);
+                column.connect_width_notify(move |_| {
+                    if let Some(app) = app.upgrade() {
+                        app.schedule_column_state_save();
+                    }
+                });
+            }
+
+            let sortable = tree_view
+                .model()
+                .and_then(|model| model.dynamic_cast::<gtk4::TreeSortable>().ok());
+            if let Some(sortable) = sortable {
+                let app = Rc::downgrade(self);
+                sortable.connect_sort_column_changed(move |_| {
+                    if let Some(app) = app.upgrade() {
+                        app.schedule_column_state_save();
+                    }
+                });
+            }
+        }
+    }
+
+    fn schedule_column_state_save(self: &Rc<Self>) {
+        if let Some(source) = self.column_state_save.take() {
+            source.remove();
+        }
+
+        let app = Rc::downgrade(self);
+        let source = glib::timeout_add_local_once(COLUMN_STATE_SAVE_DELAY, move || {
+            if let Some(app) = app.upgrade() {
+                app.column_state_save.take();
+                app.save_window_state();
+            }
+        });
+        self.column_state_save.replace(Some(source));
+    }
+
+    fn save_window_state(&self) {
+        let (width, height) = self.window.default_size();
+        let mut state = WindowState {
+            width: Some(width),
+            height: Some(height),
+            ..Default::default()
+        };
+        // Panes of the same orientation share a position; the first wins
+        for paned in self.panes.borrow().iter() {
+            state
+                .paned_positions
+                .entry(orientation_key(paned.orientation()).to_string())
+                .or_insert(paned.position());
+        }
+
+        for (list, tree_view) in self.service_lists() {
+            let columns = tree_view.columns();
+            for column in columns.iter().filter(|column| column.is_resizable()) {
+                if column.width() > 0 {
+                    state
+                        .column_widths
+                        .insert(column_key(list, &column.title()), column.width());
+                }
+            }
+
+            let sort = tree_view
+                .model()
+                .and_then(|model| model.dynamic_cast::<gtk4::TreeSortable>().ok())
+                .and_then(|sortable| sortable.sort_column_id());
+            if let Some((gtk4::SortColumn::Index(id), order)) = sort {
+                if let Some(column) = columns
+                    .iter()
+                    .find(|column| column.sort_column_id() == id as i32)
+                {
+                    state.sort_columns.insert(
+                        list.to_string(),
+                        ColumnSort {
+                            column: column.title().to_string(),
+                            descending: order == gtk4::SortType::Descending,
+                        },
+                    );
+                }
+            }
+        }
+
+        if let Err(e) = state.save() {
+            warn!("Failed to save window state: {}", e);
+        }
+    }
+
+    pub fn load_saved_hosts(self: &Rc<Self>) {
+        // Load saved remote hosts from configuration
+        if let Ok(hosts) = self.load_hosts_from_config() {
+            let mut remote_hosts = self.remote_hosts.borrow_mut();
+            *remote_hosts = hosts;
+            drop(remote_hosts);
+            self.refresh_hosts_list();
+        }
+
+        let default_host = self.config.borrow().default_host.clone();
+        if let Some(host_name) = default_host {
+            if self.remote_hosts.borrow().contains_key(&host_name) {
+                self.connect_to_host(&host_name);
+            } else {
+                warn!("Default host {} is not a saved host", host_name);
+            }
+        }
+    }
+
+    fn load_hosts_from_config(&self) -> Result<HashMap<String, RemoteHost>> {
+        let path = config_file(HOSTS_FILE_NAME)?;
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        Ok(HostsConfig::from_json(&content)?.hosts)
+    }
+
+    pub fn save_hosts(&self) -> Result<()> {
+        save_json(
+            HOSTS_FILE_NAME,
+            &HostsConfig::new(self.remote_hosts.borrow().clone()),
+        )
+    }
+
+    /// Validates the host's settings, then opens an SSH session to it,
+    /// prompting for a password first when needed
+    fn connect_to_host(self: &Rc<Self>, host_name: &str) {
+        let Some(host) = self.remote_hosts.borrow().get(host_name).cloned() else {
+            return;
+        };
+
+        let errors = host.validate();
+        if !errors.is_empty() {
+            show_validation_errors_dialog(self.window.upcast_ref(), host_name, &errors);
+            return;
+        }
+
+        // A live control socket needs no credentials
+        let control_master = ControlMaster::for_host(&host);
+        if host.is_agent_auth() && !agent_available() && control_master.is_none() {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "No SSH Agent",
+                &format!(
+                    "{} authenticates with the SSH agent, but none is running \
+                     (SSH_AUTH_SOCK is not set).",
+                    host_name
+                ),
+            );
+        } else if host.is_password_auth() && control_master.is_none() {
+            self.connect_with_saved_password(host);
+        } else {
+            self.open_host_session(host, None);
+        }
+    }
+
+    /// Connects with the password saved in the keyring for the host, or
+    /// asks for one when none is saved
+    fn connect_with_saved_password(self: &Rc<Self>, host: RemoteHost) {
+        let address = KnownHosts::address(&host.hostname, host.port);
+        let username = host.username.clone();
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                tokio::task::spawn_blocking(move || keyring::get_password(&address, &username))
+                    .await?
+            },
+            move |result| match result {
+                Ok(Some(password)) => {
+                    app.keyring_password_hosts
+                        .borrow_mut()
+                        .insert(host.name.clone());
+                    app.open_host_session(host, Some(password));
+                }
+                Ok(None) => app.prompt_for_password(host),
+                Err(e) => {
+                    warn!("Failed to read the saved password for {}: {}", host.name, e);
+                    app.prompt_for_password(host);
+                }
+            },
+        );
+    }
+
+    fn prompt_for_password(self: &Rc<Self>, host: RemoteHost) {
+        let app = Rc::downgrade(self);
+        show_password_dialog(
+            self.window.upcast_ref(),
+            &host.clone(),
+            move |password, remember| {
+                if let (Some(app), Some(password)) = (app.upgrade(), password) {
+                    if remember {
+                        app.passwords_to_remember
+                            .borrow_mut()
+                            .insert(host.name.clone(), password.clone());
+                    }
+                    app.open_host_session(host, Some(password));
+                }
+            },
+        );
+    }
+
+    /// Saves the password that got `host` connected in the keyring
+    fn remember_password(self: &Rc<Self>, host: &RemoteHost, password: String) {
+        let address = KnownHosts::address(&host.hostname, host.port);
+        let username = host.username.clone();
+        let host_name = host.name.clone();
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    keyring::store_password(&address, &username, &password)
+                })
+                .await?
+            },
+            move |result| {
+                if let Err(e) = result {
+                    app.show_notification(
+                        &format!("Could not save the password for {}: {}", host_name, e),
+                        NotificationKind::Warning,
+                    );
+                }
+            },
+        );
+    }
+
+    /// Removes every SSH password saved in the keyring
+    fn clear_saved_passwords(self: &Rc<Self>) {
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move { tokio::task::spawn_blocking(keyring::clear_passwords).await? },
+            move |result| match result {
+                Ok(cleared) => app.show_notification(
+                    &format!("Cleared {} saved password(s)", cleared),
+                    NotificationKind::Success,
+                ),
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "Saved Passwords",
+                    &format!("Failed to clear saved passwords: {}", e),
+                ),
+            },
+        );
+    }
+
+    /// Checks which tools a newly connected host has and updates the remote
+    /// toolbar to match
+    fn detect_host_capabilities(self: &Rc<Self>, host_name: &str, connection: HostConnection) {
+        let manager = RemoteServiceManager::new(connection);
+        let host_name = host_name.to_string();
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move { manager.detect_capabilities().await },
+            move |result| match result {
+                Ok(capabilities) => {
+                    debug!("Capabilities of {}: {:?}", host_name, capabilities);
+                    if capabilities.systemd_version > 0 {
+                        app.status_label.set_text(&format!(
+                            "Connected to {} (systemd {})",
+                            host_name, capabilities.systemd_version
+                        ));
+                    }
+                    if let Some(host) = app.remote_hosts.borrow_mut().get_mut(&host_name) {
+                        host.capabilities = capabilities;
+                    }
+                    app.remote_services_list
+                        .selection()
+                        .emit_by_name::<()>("changed", &[]);
+                }
+                Err(e) => warn!("Failed to detect capabilities of {}: {}", host_name, e),
+            },
+        );
+    }
+
+    /// Runs `systemctl daemon-reload`, so edited unit files take effect
+    fn daemon_reload(self: &Rc<Self>) {
+        let service_manager = self.service_manager.clone();
+        let scope = self.unit_scope();
+        let app = self.clone();
+        spawn_tracked(
+            &self.runtime,
+            &self.progress_bar,
+            "Reloading systemd…",
+            async move { service_manager.daemon_reload(scope).await },
+            move |result| match result {
+                Ok(()) => {
+                    app.show_notification("Reloaded systemd", NotificationKind::Success);
+                    app.refresh_local_services();
+                }
+                Err(e) => app.show_notification(
+                    &format!("Failed to reload systemd: {}", e),
+                    NotificationKind::Error,
+                ),
+            },
+        );
+    }
+
+    fn remote_daemon_reload(self: &Rc<Self>, host_name: &str) {
+        let connection = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(host_name)
+            .cloned();
+        let Some(connection) = connection else {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Not Connected",
+                &format!("There is no active connection to {}", host_name),
+            );
+            return;
+        };
+
+        let manager = RemoteServiceManager::new(connection);
+        let app = self.clone();
+        let host_name = host_name.to_string();
+        spawn_tracked(
+            &self.runtime,
+            &self.progress_bar,
+            &format!("Reloading systemd on {}…", host_name),
+            async move { manager.daemon_reload(UnitScope::System).await },
+            move |result| match result {
+                Ok(()) => app.show_notification(
+                    &format!("Reloaded systemd on {}", host_name),
+                    NotificationKind::Success,
+                ),
+                Err(e) => app.show_notification(
+                    &format!("Failed to reload systemd on {}: {}", host_name, e),
+                    NotificationKind::Error,
+                ),
+            },
+        );
+    }
+
+    /// Prompts for a one-off command and runs it on a connected host
+    fn run_remote_command(self: &Rc<Self>, host_name: &str) {
+        let connection = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(host_name)
+            .cloned();
+        let Some(connection) = connection else {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Not Connected",
+                &format!("There is no active connection to {}", host_name),
+            );
+            return;
+        };
+
+        let history: Vec<String> = self
+            .command_histories
+            .borrow()
+            .get(host_name)
+            .map(|history| history.commands.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let app = Rc::downgrade(self);
+        let host = host_name.to_string();
+        show_remote_exec_dialog(
+            self.window.upcast_ref(),
+            host_name,
+            &history,
+            move |command, sudo| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                app.command_histories
+                    .borrow_mut()
+                    .entry(host.clone())
+                    .or_default()
+                    .record(&command);
+
+                let manager = RemoteServiceManager::new(connection.clone());
+                let full_command = if sudo {
+                    format!("sudo -n {}", command)
+                } else {
+                    command.clone()
+                };
+                let host_name = host.clone();
+                let window = app.window.clone();
+                spawn_tracked(
+                    &app.runtime,
+                    &app.progress_bar,
+                    &format!("Running {} on {}…", command, host_name),
+                    async move { manager.execute_command(&full_command).await },
+                    move |result| match result {
+                        Ok(output) => show_command_output_dialog(
+                            window.upcast_ref(),
+                            &host_name,
+                            &command,
+                            &output,
+                        ),
+                        Err(e) => show_error_dialog(
+                            window.upcast_ref(),
+                            "Run Command",
+                            &format!("Failed to run {} on {}: {}", command, host_name, e),
+                        ),
+                    },
+                );
+            },
+        );
+    }
+
+    fn log_connection_event(&self, host_name: &str, event_type: ConnectionEventType) {
+        self.connection_logs
+            .borrow_mut()
+            .entry(host_name.to_string())
+            .or_default()
+            .record(host_name, event_type);
+    }
+
+    fn open_host_session(self: &Rc<Self>, host: RemoteHost, secret: Option<String>) {
+        let host_name = host.name.clone();
+        self.status_label
+            .set_text(&format!("Connecting to {}…", host_name));
+        self.set_connection_state(&host_name, HostConnectionState::Connecting);
+
+        let trusted_host_key = load_known_hosts()
+            .fingerprint(&host.hostname, host.port)
+            .map(str::to_string);
+        let connect_host = host.clone();
+        let connect_secret = secret.clone();
+
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    connect(
+                        &connect_host,
+                        connect_secret.as_deref(),
+                        trusted_host_key.as_deref(),
+                    )
+                })
+                .await?
+            },
+            move |result| match result {
+                Ok(connection) => {
+                    app.keyring_password_hosts.borrow_mut().remove(&host_name);
+                    let remember = app.passwords_to_remember.borrow_mut().remove(&host_name);
+                    if let Some(password) = remember {
+                        app.remember_password(&host, password);
+                    }
+
+                    app.active_connections
+                        .lock()
+                        .unwrap()
+                        .insert(host_name.clone(), connection.clone());
+                    app.status_label
+                        .set_text(&format!("Connected to {}", host_name));
+                    app.set_connection_state(&host_name, HostConnectionState::Connected);
+                    app.log_connection_event(&host_name, ConnectionEventType::Connected);
+                    app.detect_host_capabilities(&host_name, connection);
+                    app.refresh_host_services(&host_name);
+                    app.start_host_auto_refresh(&host_name);
+                }
+                Err(e) => {
+                    if let Some(host_key) = e.downcast_ref::<UntrustedHostKey>() {
+                        app.set_connection_state(&host_name, HostConnectionState::Disconnected);
+                        app.verify_host_key(host, secret, host_key);
+                        return;
+                    }
+
+                    app.passwords_to_remember.borrow_mut().remove(&host_name);
+                    let saved_password = app.keyring_password_hosts.borrow_mut().remove(&host_name);
+                    let event = ConnectionEventType::from_connect_error(&e);
+                    if saved_password && event == ConnectionEventType::AuthFailed {
+                        // The saved password is out of date; ask for the new one
+                        app.log_connection_event(&host_name, event);
+                        app.set_connection_state(&host_name, HostConnectionState::Disconnected);
+                        app.show_notification(
+                            &format!("The saved password for {} was rejected", host_name),
+                            NotificationKind::Warning,
+                        );
+                        app.prompt_for_password(host);
+                        return;
+                    }
+
+                    app.status_label
+                        .set_text(&format!("Connection to {} failed", host_name));
+                    app.set_connection_state(&host_name, HostConnectionState::Disconnected);
+                    app.log_connection_event(&host_name, event);
+                    show_error_dialog(
+                        app.window.upcast_ref(),
+                        "Connection Failed",
+                        &format!("Could not connect to {}: {}", host_name, e),
+                    );
+                }
+            },
+        );
+    }
+
+    /// Asks the user to accept a host key that is unknown or changed since it
+    /// was accepted, then saves it and connects again
+    fn verify_host_key(
+        self: &Rc<Self>,
+        host: RemoteHost,
+        secret: Option<String>,
+        host_key: &UntrustedHostKey,
+    ) {
+        let previous =
+            match load_known_hosts().check(&host.hostname, host.port, &host_key.fingerprint_hex) {
+                HostKeyStatus::Changed(previous) => Some(previous),
+                _ => None,
+            };
+        self.status_label
+            .set_text(&format!("Verifying the host key of {}…", host.name));
+
+        let app = Rc::downgrade(self);
+        let fingerprint_hex = host_key.fingerprint_hex.clone();
+        show_host_key_dialog(
+            self.window.upcast_ref(),
+            &host_key.host,
+            &format!("{}\n{}", host_key.fingerprint, host_key.fingerprint_hex),
+            previous.as_deref(),
+            move |accepted| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                if !accepted {
+                    app.status_label
+                        .set_text(&format!("Host key of {} rejected", host.name));
+                    app.log_connection_event(
+                        &host.name,
+                        ConnectionEventType::Disconnected("Host key rejected".to_string()),
+                    );
+                    return;
+                }
+
+                let mut known_hosts = load_known_hosts();
+                known_hosts.trust(&host.hostname, host.port, &fingerprint_hex);
+                if let Err(e) = known_hosts.save() {
+                    show_error_dialog(
+                        app.window.upcast_ref(),
+                        "Host Key Verification",
+                        &format!("Failed to save the host key: {}", e),
+                    );
+                    return;
+                }
+                app.open_host_session(host.clone(), secret.clone());
+            },
+        );
+    }
+
+    /// Collects version info, unit files, boot analysis, recent journal
+    /// entries and the config (without credentials) into a zip for bug reports
+    fn export_diagnostics(self: &Rc<Self>) {
+        let service_manager = self.service_manager.clone();
+        let mut config = serde_json::to_value(&*self.config.borrow()).unwrap_or_default();
+        strip_credentials(&mut config);
+
+        let app = self.clone();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                let or_error =
+                    |result: Result<String>| result.unwrap_or_else(|e| format!("Error: {}\n", e));
+
+                let versions = format!(
+                    "systemd-pilot {}\n{}\nGTK {}.{}.{}\n",
+                    crate::APP_VERSION,
+                    or_error(service_manager.get_systemd_version().await),
+                    gtk4::major_version(),
+                    gtk4::minor_version(),
+                    gtk4::micro_version(),
+                );
+
+                vec![
+                    ("versions.txt", versions),
+                    (
+                        "unit-files.txt",
+                        or_error(service_manager.list_unit_files().await),
+                    ),
+                    (
+                        "systemd-analyze.txt",
+                        or_error(service_manager.get_boot_time_summary().await),
+                    ),
+                    (
+                        "journal.txt",
+                        or_error(
+                            service_manager
+                                .get_journal_tail(DIAGNOSTICS_LOG_LINES)
+                                .await,
+                        ),
+                    ),
+                    (
+                        "config.json",
+                        serde_json::to_string_pretty(&config).unwrap_or_default(),
+                    ),
+                ]
+            },
+            move |files| {
+                let file_dialog = gtk4::FileChooserDialog::new(
+                    Some("Export Diagnostics"),
+                    Some(&app.window),
+                    gtk4::FileChooserAction::Save,
+                    &[
+                        ("Cancel", ResponseType::Cancel),
+                        ("Save", ResponseType::Accept),
+                    ],
+                );
+                file_dialog.set_modal(true);
+                file_dialog.set_current_name(&bundle_file_name(chrono::Local::now()));
+
+                file_dialog.connect_response(move |file_dialog, response| {
+                    file_dialog.close();
+                    if response != ResponseType::Accept {
+                        return;
+                    }
+                    let Some(path) = file_dialog.file().and_then(|f| f.path()) else {
+                        return;
+                    };
+
+                    let result = std::fs::File::create(&path)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|file| write_bundle(file, &files));
+                    match result {
+                        Ok(()) => app.show_notification(
+                            &format!("Diagnostics exported to {}", path.display()),
+                            NotificationKind::Success,
+                        ),
+                        Err(e) => show_error_dialog(
+                            app.window.upcast_ref(),
+                            "Export Failed",
+                            &format!("Could not write {}: {}", path.display(), e),
+                        ),
+                    }
+                });
+
+                file_dialog.show();
+            },
+        );
+    }
+
+    /// Prompts for an Ansible YAML inventory and adds its hosts, keeping any
+    /// existing host with the same name
+    fn import_ansible_inventory(self: &Rc<Self>) {
+        let file_dialog = gtk4::FileChooserDialog::new(
+            Some("Import from Ansible Inventory"),
+            Some(&self.window),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Import", ResponseType::Accept),
+            ],
+        );
+        file_dialog.set_modal(true);
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("YAML inventory"));
+        filter.add_pattern("*.yml");
+        filter.add_pattern("*.yaml");
+        file_dialog.add_filter(&filter);
+
+        let app = Rc::downgrade(self);
+        file_dialog.connect_response(move |file_dialog, response| {
+            file_dialog.close();
+            if response != ResponseType::Accept {
+                return;
+            }
+            let (Some(app), Some(path)) =
+                (app.upgrade(), file_dialog.file().and_then(|f| f.path()))
+            else {
+                return;
+            };
+
+            match parse_ansible_inventory(&path) {
+                Ok(hosts) => {
+                    let total = hosts.len();
+                    let mut added = 0;
+                    {
+                        let mut remote_hosts = app.remote_hosts.borrow_mut();
+                        for host in hosts {
+                            if !remote_hosts.contains_key(&host.name) {
+                                remote_hosts.insert(host.name.clone(), host);
+                                added += 1;
+                            }
+                        }
+                    }
+
+                    if let Err(e) = app.save_hosts() {
+                        error!("Failed to save hosts: {}", e);
+                    }
+                    app.refresh_hosts_list();
+
+                    show_info_dialog(
+                        app.window.upcast_ref(),
+                        "Import Complete",
+                        &format!(
+                            "Imported {} of {} hosts. Hosts with existing names were skipped.",
+                            added, total
+                        ),
+                    );
+                }
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "Import Failed",
+                    &format!("Could not read {}: {}", path.display(), e),
+                ),
+            }
+        });
+
+        file_dialog.show();
+    }
+
+    /// Offers the hosts defined in `~/.ssh/config` for import
+    fn import_ssh_config(self: &Rc<Self>) {
+        let app = Rc::downgrade(self);
+        show_import_ssh_config_dialog(
+            self.window.upcast_ref(),
+            &self.remote_hosts,
+            move |imported| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                if let Err(e) = app.save_hosts() {
+                    error!("Failed to save hosts: {}", e);
+                }
+                app.refresh_hosts_list();
+                app.show_notification(
+                    &format!("Imported {} hosts from ~/.ssh/config", imported),
+                    NotificationKind::Success,
+                );
+            },
+        );
+    }
+
+    /// Saves the remote hosts as an Ansible YAML inventory
+    fn export_ansible_inventory(self: &Rc<Self>) {
+        let file_dialog = gtk4::FileChooserDialog::new(
+            Some("Export as Ansible Inventory"),
+            Some(&self.window),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Export", ResponseType::Accept),
+            ],
+        );
+        file_dialog.set_modal(true);
+        file_dialog.set_current_name("inventory.yml");
+
+        let app = Rc::downgrade(self);
+        file_dialog.connect_response(move |file_dialog, response| {
+            file_dialog.close();
+            if response != ResponseType::Accept {
+                return;
+            }
+            let (Some(app), Some(path)) =
+                (app.upgrade(), file_dialog.file().and_then(|f| f.path()))
+            else {
+                return;
+            };
+
+            let result = export_hosts_yaml(app.remote_hosts.borrow().values(), &path);
+            match result {
+                Ok(()) => app.show_notification(
+                    &format!("Exported hosts to {}", path.display()),
+                    NotificationKind::Success,
+                ),
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "Export Failed",
+                    &format!("Could not write {}: {}", path.display(), e),
+                ),
+            }
+        });
+
+        file_dialog.show();
+    }
+
+    /// Adds the hosts from a JSON file written by Export Hosts, or a copy of
+    /// `hosts.json`, asking whether to replace hosts whose names are taken
+    fn import_hosts_json(self: &Rc<Self>) {
+        let file_dialog = gtk4::FileChooserDialog::new(
+            Some("Import Hosts"),
+            Some(&self.window),
+            gtk4::FileChooserAction::Open,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Import", ResponseType::Accept),
+            ],
+        );
+        file_dialog.set_modal(true);
+
+        let filter = gtk4::FileFilter::new();
+        filter.set_name(Some("JSON"));
+        filter.add_pattern("*.json");
+        file_dialog.add_filter(&filter);
+
+        let app = Rc::downgrade(self);
+        file_dialog.connect_response(move |file_dialog, response| {
+            file_dialog.close();
+            if response != ResponseType::Accept {
+                return;
+            }
+            let (Some(app), Some(path)) =
+                (app.upgrade(), file_dialog.file().and_then(|f| f.path()))
+            else {
+                return;
+            };
+
+            let hosts = match std::fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| HostsConfig::from_json(&content))
+            {
+                Ok(config) => config.hosts,
+                Err(e) => {
+                    show_error_dialog(
+                        app.window.upcast_ref(),
+                        "Import Failed",
+                        &format!("Could not read {}: {}", path.display(), e),
+                    );
+                    return;
+                }
+            };
+
+            let conflicts = conflicting_host_names(&app.remote_hosts.borrow(), &hosts);
+            if conflicts.is_empty() {
+                app.merge_imported_hosts(hosts, false);
+                return;
+            }
+            let weak_app = Rc::downgrade(&app);
+            show_replace_or_skip_dialog(app.window.upcast_ref(), &conflicts, move |replace| {
+                if let Some(app) = weak_app.upgrade() {
+                    app.merge_imported_hosts(hosts.clone(), replace);
+                }
+            });
+        });
+
+        file_dialog.show();
+    }
+
+    fn merge_imported_hosts(self: &Rc<Self>, hosts: HashMap<String, RemoteHost>, replace: bool) {
+        let total = hosts.len();
+        let merged = merge_hosts(&mut self.remote_hosts.borrow_mut(), hosts, replace);
+
+        if let Err(e) = self.save_hosts() {
+            error!("Failed to save hosts: {}", e);
+        }
+        self.refresh_hosts_list();
+
+        show_info_dialog(
+            self.window.upcast_ref(),
+            "Import Complete",
+            &format!("Imported {} of {} hosts.", merged, total),
+        );
+    }
+
+    /// Saves the remote hosts as JSON, in the layout Import Hosts reads
+    fn export_hosts_json(self: &Rc<Self>) {
+        let file_dialog = gtk4::FileChooserDialog::new(
+            Some("Export Hosts"),
+            Some(&self.window),
+            gtk4::FileChooserAction::Save,
+            &[
+                ("Cancel", ResponseType::Cancel),
+                ("Export", ResponseType::Accept),
+            ],
+        );
+        file_dialog.set_modal(true);
+        file_dialog.set_current_name("hosts.json");
+
+        let app = Rc::downgrade(self);
+        file_dialog.connect_response(move |file_dialog, response| {
+            file_dialog.close();
+            if response != ResponseType::Accept {
+                return;
+            }
+            let (Some(app), Some(path)) =
+                (app.upgrade(), file_dialog.file().and_then(|f| f.path()))
+            else {
+                return;
+            };
+
+            let result = serde_json::to_string_pretty(&*app.remote_hosts.borrow())
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(std::fs::write(&path, content)?));
+            match result {
+                Ok(()) => show_info_dialog(
+                    app.window.upcast_ref(),
+                    "Export Complete",
+                    &format!(
+                        "Exported {} hosts to {}",
+                        app.remote_hosts.borrow().len(),
+                        path.display()
+                    ),
+                ),
+                Err(e) => show_error_dialog(
+                    app.window.upcast_ref(),
+                    "Export Failed",
+                    &format!("Could not write {}: {}", path.display(), e),
+                ),
+            }
+        });
+
+        file_dialog.show();
+    }
+
+    fn refresh_hosts_list(self: &Rc<Self>) {
+        let selected = self
+            .hosts_listbox
+            .selected_row()
+            .map(|row| row.widget_name());
+
+        // Clear existing items
+        while let Some(child) = self.hosts_listbox.first_child() {
+            self.hosts_listbox.remove(&child);
+        }
+
+        // Add hosts to UI
+        let hosts = self.remote_hosts.borrow();
+        let status = self.connection_status.lock().unwrap().clone();
+        for (name, host) in hosts.iter() {
+            let state = status.get(name).copied().unwrap_or_default();
+            let (row, connect_button) =
+                create_host_list_item(name, &host.hostname, &host.username, state);
+            // The widget name identifies the host when the row is activated
+            row.set_widget_name(name);
+
+            let app = Rc::downgrade(self);
+            let host_name = name.clone();
+            connect_button.connect_clicked(move |_| {
+                if let Some(app) = app.upgrade() {
+                    match state {
+                        HostConnectionState::Connected => app.disconnect_from_host(&host_name),
+                        _ => app.connect_to_host(&host_name),
+                    }
+                }
+            });
+
+            self.hosts_listbox.append(&row);
+            if selected.as_deref() == Some(name.as_str()) {
+                self.hosts_listbox.select_row(Some(&row));
+            }
+        }
+
+        self.hosts_listbox.show();
+        self.update_hosts_no_match();
+    }
+
+    fn host_matches_search(&self, host_name: &str) -> bool {
+        let query = self.host_search_entry.text();
+        self.remote_hosts
+            .borrow()
+            .get(host_name)
+            .is_none_or(|host| host.matches_search(&query))
+    }
+
+    /// Shows the "No hosts found" message when a search hides every host
+    fn update_hosts_no_match(&self) {
+        let query = self.host_search_entry.text();
+        let no_match = !query.is_empty()
+            && !self
+                .remote_hosts
+                .borrow()
+                .values()
+                .any(|host| host.matches_search(&query));
+        self.hosts_no_match.set_visible(no_match);
+    }
+
+    /// Records a host's connection state and redraws its indicator
+    fn set_connection_state(self: &Rc<Self>, host_name: &str, state: HostConnectionState) {
+        self.connection_status
+            .lock()
+            .unwrap()
+            .insert(host_name.to_string(), state);
+        self.refresh_hosts_list();
+    }
+
+    fn disconnect_from_host(self: &Rc<Self>, host_name: &str) {
+        if self
+            .active_connections
+            .lock()
+            .unwrap()
+            .remove(host_name)
+            .is_some()
+        {
+            self.status_label
+                .set_text(&format!("Disconnected from {}", host_name));
+            self.log_connection_event(
+                host_name,
+                ConnectionEventType::Disconnected("Closed by user".to_string()),
+            );
+        }
+        self.set_connection_state(host_name, HostConnectionState::Disconnected);
+        self.stop_host_auto_refresh(host_name);
+        update_host_service_rows(&self.remote_services_store, host_name, &[]);
+    }
+
+    /// Refreshes a connected host's services on the host's own interval,
+    /// when it has auto-refresh turned on
+    fn start_host_auto_refresh(self: &Rc<Self>, host_name: &str) {
+        self.stop_host_auto_refresh(host_name);
+
+        let Some(refresh) = self
+            .remote_hosts
+            .borrow()
+            .get(host_name)
+            .map(RemoteHost::refresh)
+        else {
+            return;
+        };
+        if !refresh.enabled {
+            return;
+        }
+
+        let app = Rc::downgrade(self);
+        let name = host_name.to_string();
+        let source =
+            glib::timeout_add_seconds_local(refresh.interval(), move || match app.upgrade() {
+                Some(app) => {
+                    app.refresh_host_services(&name);
+                    glib::ControlFlow::Continue
+                }
+                None => glib::ControlFlow::Break,
+            });
+        self.host_refresh_sources
+            .borrow_mut()
+            .insert(host_name.to_string(), source);
+    }
+
+    fn stop_host_auto_refresh(&self, host_name: &str) {
+        if let Some(source) = self.host_refresh_sources.borrow_mut().remove(host_name) {
+            source.remove();
+        }
+    }
+
+    /// Removes a saved host after confirmation, closing its connection first
+    fn remove_host(self: &Rc<Self>, host_name: &str) {
+        let app = Rc::downgrade(self);
+        let name = host_name.to_string();
+        show_confirmation_dialog(
+            self.window.upcast_ref(),
+            "Remove Host",
+            &format!("Remove {} from the saved hosts?", host_name),
+            move |confirmed| {
+                if let Some(app) = app.upgrade().filter(|_| confirmed) {
+                    app.forget_host(&name);
+                }
+            },
+        );
+    }
+
+    /// Drops a host from the saved hosts and the list
+    fn forget_host(self: &Rc<Self>, host_name: &str) {
+        self.disconnect_from_host(host_name);
+        self.connection_status.lock().unwrap().remove(host_name);
+        if self.remote_hosts.borrow_mut().remove(host_name).is_none() {
+            return;
+        }
+
+        if let Err(e) = self.save_hosts() {
+            show_error_dialog(
+                self.window.upcast_ref(),
+                "Error",
+                &format!("Failed to save hosts: {}", e),
+            );
+        }
+        self.refresh_hosts_list();
+        self.status_label
+            .set_text(&format!("Removed host {}", host_name));
+    }
+
+    /// Remembers the outcome of an action and lists it below the service's
+    /// details. `save_action_log` saves it once the batch is done.
+    fn record_action(&self, service_name: &str, action: ServiceAction, result: &Result<()>) {
+        self.config
+            .borrow_mut()
+            .action_log
+            .record(service_name, action, result);
+        self.record_audit(service_name, None, action, result);
+
+        let selected = get_selected_service_name(&self.local_services_list.selection());
+        if selected.as_deref() == Some(service_name) {
+            self.show_recent_actions(service_name);
+        }
+    }
+
+    /// Saves the action log as recorded in memory, so the saved entries are
+    /// the ones shown
+    fn save_action_log(&self) {
+        let action_log = self.config.borrow().action_log.clone();
+        if let Err(e) = Config::update_saved(|saved| saved.action_log = action_log) {
+            warn!("Failed to save the action log: {}", e);
+        }
+    }
+
+    /// Adds a service operation to the audit log on disk
+    fn record_audit(
+        &self,
+        service_name: &str,
+        host_name: Option<&str>,
+        action: ServiceAction,
+        result: &Result<()>,
+    ) {
+        let mut audit_log = self.audit_log.borrow_mut();
+        audit_log.record(
+            action.command(),
+            service_name,
+            host_name,
+            result.as_ref().map(|_| ()).map_err(|e| e.to_string()),
+        );
+        if let Err(e) = audit_log.save() {
+            warn!("Failed to save the audit log: {}", e);
+        }
+    }
+
+    /// Pins or unpins services, saving the choice and moving their rows
+    fn set_services_pinned(&self, service_names: &[String], pinned: bool) {
+        {
+            let mut config = self.config.borrow_mut();
+            config
+                .pinned_services
+                .retain(|name| !service_names.contains(name));
+            if pinned {
+                config.pinned_services.extend(service_names.iter().cloned());
+            }
+            let pinned_services = config.pinned_services.clone();
+            if let Err(e) = Config::update_saved(|saved| saved.pinned_services = pinned_services) {
+                warn!("Failed to save pinned services: {}", e);
+            }
+        }
+
+        for service_name in service_names {
+            if let Some(iter) = find_service_row(&self.local_services_store, service_name) {
+                self.local_services_store.set(&iter, &[(4, &pinned)]);
+            }
+        }
+        self.local_services_list
+            .selection()
+            .emit_by_name::<()>("changed", &[]);
+    }
+
+    /// Names of the services in the local services list
+    fn local_service_names(&self) -> Vec<String> {
+        service_row_names(self.local_services_store.upcast_ref(), None)
+    }
+
+    fn show_recent_actions(&self, service_name: &str) {
+        let config = self.config.borrow();
+        self.action_history
+            .set_entries(&config.action_log.recent(service_name, RECENT_ACTIONS_SHOWN));
+    }
+
+    /// Briefly shows `message` at the bottom of the window
+    pub fn show_notification(&self, message: &str, kind: NotificationKind) {
+        self.notification_bar.show(message, kind);
+    }
+
+    /// Runs `action` on the selected local services, asking first when it
+    /// is disruptive
+    fn confirm_service_command(self: &Rc<Self>, action: ServiceAction) {
+        let service_names = get_all_selected_service_names(&self.local_services_list.selection());
+        let app = Rc::downgrade(self);
+        self.confirm_action(action, &service_names, move || {
+            if let Some(app) = app.upgrade() {
+                MainContext::default().spawn_local(async move {
+                    if let Err(e) = app.run_service_command(action).await {
+                        debug!("{} failed: {}", action, e);
+                    }
+                });
+            }
+        });
+    }
+
+    /// Runs `action` on the units selected in `list`, such as timers or
+    /// sockets, asking first when it is disruptive. `refresh` updates the
+    /// list afterwards.
+    fn confirm_unit_command(
+        self: &Rc<Self>,
+        list: &TreeView,
+        action: ServiceAction,
+        refresh: fn(&Self, &[String]),
+    ) {
+        let unit_names = get_all_selected_service_names(&list.selection());
+        let app = Rc::downgrade(self);
+        let names = unit_names.clone();
+        self.confirm_action(action, &unit_names, move || {
+            if let Some(app) = app.upgrade() {
+                app.run_unit_command(action, names.clone(), refresh);
+            }
+        });
+    }
+
+    /// Runs `action` on each of `unit_names` in turn, then reports failures
+    /// like `run_service_command` and calls `refresh` with the units
+    fn run_unit_command(
+        self: &Rc<Self>,
+        action: ServiceAction,
+        unit_names: Vec<String>,
+        refresh: fn(&Self, &[String]),
+    ) {
+        let service_manager = self.service_manager.clone();
+        let scope = self.unit_scope();
+        let app = Rc::downgrade(self);
+        let description = match unit_names.as_slice() {
+            [unit_name] => format!("{} {}…", action.progress_label(), unit_name),
+            _ => format!("{} {} units…", action.progress_label(), unit_names.len()),
+        };
+        spawn_tracked(
+            &self.runtime,
+            &self.progress_bar,
+            &description,
+            async move {
+                let mut errors = Vec::new();
+                for unit_name in &unit_names {
+                    if let Err(e) = service_manager.run_action(action, unit_name, scope).await {
+                        errors.push((unit_name.clone(), e.to_string()));
+                    }
+                }
+                (unit_names, errors)
+            },
+            move |(unit_names, errors)| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                if unit_names.len() > 1 {
+                    let summary = action.batch_summary(unit_names.len(), errors.len());
+                    if errors.is_empty() {
+                        app.show_notification(&summary, NotificationKind::Success);
+                    } else {
+                        show_batch_errors_dialog(
+                            app.window.upcast_ref(),
+                            &format!("{} Failed", action),
+                            &summary,
+                            &errors,
+                        );
+                    }
+                } else if let Some((unit_name, e)) = errors.first() {
+                    app.show_notification(
+                        &format!("Failed to {} {}: {}", action.command(), unit_name, e),
+                        NotificationKind::Error,
+                    );
+                } else if let Some(unit_name) = unit_names.first() {
+                    app.show_notification(
+                        &format!("{} {}", unit_name, action.past_tense()),
+                        NotificationKind::Success,
+                    );
+                }
+                refresh(&app, &unit_names);
+            },
+        );
+    }
+
+    /// Runs `action` on the selected remote services, asking first when it
+    /// is disruptive
+    fn confirm_remote_service_command(self: &Rc<Self>, action: ServiceAction) {
+        let service_names: Vec<String> =
+            get_all_selected_remote_services(&self.remote_services_list.selection())
+                .into_iter()
+                .map(|(host_name, service_name)| format!("{} on {}", service_name, host_name))
+                .collect();
+        let app = Rc::downgrade(self);
+        self.confirm_action(action, &service_names, move || {
+            if let Some(app) = app.upgrade() {
+                MainContext::default().spawn_local(async move {
+                    app.run_remote_service_command(action).await;
+                });
+            }
+        });
+    }
+
+    /// Calls `proceed` straight away for harmless actions, or once the user
+    /// confirms running `action` on `service_names`
+    fn confirm_action(
+        &self,
+        action: ServiceAction,
+        service_names: &[String],
+        proceed: impl Fn() + 'static,
+    ) {
+        let target = match service_names {
+            [] => return,
+            [service_name] => service_name.clone(),
+            _ => format!("{} services", service_names.len()),
+        };
+        match action.confirmation_message(&target) {
+            Some(message) => show_confirmation_dialog(
+                self.window.upcast_ref(),
+                &format!("{} Service", action),
+                &message,
+                move |confirmed| {
+                    if confirmed {
+                        proceed();
+                    }
+                },
+            ),
+            None => proceed(),
+        }
+    }
+
+    /// Runs `action` on each selected local service in turn. A failure is
+    /// reported in an error dialog, or for several services in a summary of
+    /// how many succeeded. A batch can be cancelled between services.
+    async fn run_service_command(&self, action: ServiceAction) -> Result<()> {
+        let service_names = get_all_selected_service_names(&self.local_services_list.selection());
+        if service_names.is_empty() {
+            return Err(anyhow!("No service selected"));
+        }
+
+        // Several services get an overall bar, whose Cancel button stops
+        // the batch before the next service
+        let cancelled = Rc::new(Cell::new(false));
+        let batch_id = (service_names.len() > 1).then(|| {
+            let cancelled = cancelled.clone();
+            let id = self.progress_bar.add(
+                &format!(
+                    "{} {} services…",
+                    action.progress_label(),
+                    service_names.len()
+                ),
+                Some(std::boxed::Box::new(move || cancelled.set(true))),
+            );
+            self.progress_bar.set_progress(id, 0.0);
+            id
+        });
+
+        let mut errors = Vec::new();
+        let mut attempted = 0;
+        for service_name in &service_names {
+            if cancelled.get() {
+                break;
+            }
+            attempted += 1;
+            if let Err(e) = self.run_service_action(action, service_name).await {
+                errors.push((service_name.clone(), e.to_string()));
+            }
+            if let Some(id) = batch_id {
+                self.progress_bar
+                    .set_progress(id, attempted as f32 / service_names.len() as f32);
+            }
+        }
+        if let Some(id) = batch_id {
+            self.progress_bar.remove(id);
+        }
+        self.save_action_log();
+
+        if service_names.len() > 1 {
+            let mut summary = action.batch_summary(attempted, errors.len());
+            if attempted < service_names.len() {
+                summary.push_str(&format!(
+                    "; cancelled before {} more",
+                    service_names.len() - attempted
+                ));
+            }
+            self.status_label.set_text(&summary);
+            if errors.is_empty() {
+                self.show_notification(&summary, NotificationKind::Success);
+            } else {
+                show_batch_errors_dialog(
+                    self.window.upcast_ref(),
+                    &format!("{} Failed", action),
+                    &summary,
+                    &errors,
+                );
+            }
+        } else if let Some((service_name, e)) = errors.first() {
+            self.show_notification(
+                &format!("Failed to {} {}: {}", action.command(), service_name, e),
+                NotificationKind::Error,
+            );
+        } else {
+            self.show_notification(
+                &format!("{} {}", service_names[0], action.past_tense()),
+                NotificationKind::Success,
+            );
+        }
+
+        match errors.len() {
+            0 => Ok(()),
+            failed => Err(anyhow!(
+                "{} of {} services failed to {}",
+                failed,
+                attempted,
+                action.command()
+            )),
+        }
+    }
+
+    /// Runs `action` on one local service, reporting progress in the status
+    /// bar and refreshing its row once it succeeds
+    async fn run_service_action(&self, action: ServiceAction, service_name: &str) -> Result<()> {
+        info!(
+            "{} local service: {}",
+            action.progress_label(),
+            service_name
+        );
+        self.status_label
+            .set_text(&format!("{} {}…", action.progress_label(), service_name));
+        let progress_id = self.progress_bar.add(
+            &format!("{} {}…", action.progress_label(), service_name),
+            None,
+        );
+
+        let service_manager = self.service_manager.clone();
+        let name = service_name.to_string();
+        let scope = self.unit_scope();
+        let result = self
+            .runtime
+            .spawn(async move { service_manager.run_action(action, &name, scope).await })
+            .await
+            .map_err(|e| anyhow!("Service task failed: {}", e))
+            .and_then(|result| result);
+        self.progress_bar.remove(progress_id);
+        self.record_action(service_name, action, &result);
+
+        match &result {
+            Ok(()) => {
+                self.status_label
+                    .set_text(&format!("{} {}", service_name, action.past_tense()));
+                if let Err(e) = self.refresh_single_service(service_name).await {
+                    warn!("Failed to refresh {}: {}", service_name, e);
+                }
+            }
+            Err(_) => {
+                self.status_label
+                    .set_text(&format!("{} {} failed", action, service_name));
+            }
+        }
+
+        result
+    }
+
+    /// Runs `action` on each selected remote service in turn over its host's
+    /// connection, then reports failures like `run_service_command`
+    async fn run_remote_service_command(&self, action: ServiceAction) {
+        let services = get_all_selected_remote_services(&self.remote_services_list.selection());
+        if services.is_empty() {
+            return;
+        }
+
+        let mut errors = Vec::new();
+        for (host_name, service_name) in &services {
+            let label = format!("{} on {}", service_name, host_name);
+            let connection = self
+                .active_connections
+                .lock()
+                .unwrap()
+                .get(host_name)
+                .cloned();
+            let Some(connection) = connection else {
+                errors.push((
+                    label,
+                    format!("There is no active connection to {}", host_name),
+                ));
+                continue;
+            };
+
+            let progress_id = self
+                .progress_bar
+                .add(&format!("{} {}…", action.progress_label(), label), None);
+            let manager = RemoteServiceManager::new(connection);
+            let name = service_name.clone();
+            let result = self
+                .runtime
+                .spawn(async move { manager.run_action(action, &name, UnitScope::System).await })
+                .await
+                .map_err(|e| anyhow!("Service task failed: {}", e))
+                .and_then(|result| result);
+            self.progress_bar.remove(progress_id);
+            self.record_audit(service_name, Some(host_name), action, &result);
+
+            if let Err(e) = result {
+                errors.push((label, e.to_string()));
+            }
+        }
+
+        let summary = action.batch_summary(services.len(), errors.len());
+        self.status_label.set_text(&summary);
+        if services.len() > 1 && !errors.is_empty() {
+            show_batch_errors_dialog(
+                self.window.upcast_ref(),
+                &format!("{} Failed", action),
+                &summary,
+                &errors,
+            );
+        } else if let Some((label, e)) = errors.first() {
+            self.show_notification(
+                &format!("Failed to {} {}: {}", action.command(), label, e),
+                NotificationKind::Error,
+            );
+        } else {
+            self.show_notification(&summary, NotificationKind::Success);
+        }
+    }
+
+    /// Updates the row of one local service in place, keeping the user's
+    /// selection and scroll position, unlike a full refresh
+    async fn refresh_single_service(&self, service_name: &str) -> Result<()> {
+        let service_manager = self.service_manager.clone();
+        let name = service_name.to_string();
+        let scope = self.unit_scope();
+        let service = self
+            .runtime
+            .spawn(async move { service_manager.get_service_status(&name, scope).await })
+            .await
+            .map_err(|e| anyhow!("Status task failed: {}", e))??;
+
+        let store = &self.local_services_store;
+        let Some(iter) = find_service_row(store, service_name) else {
+            return Err(anyhow!("{} is not listed", service_name));
+        };
+        update_service_row(store, &iter, &service);
+        Ok(())
+    }
+
+    /// Lists how long each unit took to start, letting the user jump to
+    /// one of the services
+    fn show_analyze_blame(self: &Rc<Self>) {
+        let service_manager = self.service_manager.clone();
+        let app = Rc::downgrade(self);
+        spawn_with_callback(
+            &self.runtime,
+            async move { service_manager.get_analyze_blame().await },
+            move |result| {
+                let Some(app) = app.upgrade() else {
+                    return;
+                };
+                match result {
+                    Ok(blame) => {
+                        let select_app = Rc::downgrade(&app);
+                        show_blame_dialog(app.window.upcast_ref(), &blame, move |unit| {
+                            if let Some(app) = select_app.upgrade() {
+                                app.select_local_service(unit);
+                            }
+                        });
+                    }
+                    Err(e) => show_error_dialog(
+                        app.window.upcast_ref(),
+                        "Startup Blame",
+                        &format!("Failed to analyze boot: {}", e),
+                    ),
+                }
+            },
+        );
+    }
+
+    /// Switches to the Local tab and selects `unit`, if it is a listed service
+    fn select_local_service(&self, unit: &str) {
+        let service_name = unit.trim_end_matches(".service");
+        let Some(model) = self.local_services_list.model() else {
+            return;
+        };
+        match find_row(&model, None, &|name| name == service_name) {
+            Some(iter) => {
+                self.notebook.set_current_page(Some(0));
+                let path = model.path(&iter);
+                let tree_view = &self.local_services_list;
+                tree_view.expand_to_path(&path);
+                tree_view.selection().unselect_all();
+                tree_view.selection().select_iter(&iter);
+                tree_view.scroll_to_cell(Some(&path), None::<&TreeViewColumn>, false, 0.0, 0.0);
+            }
+            None => self.show_notification(
+                &format!("{} is not in the services list", unit),
+                NotificationKind::Warning,
+            ),
+        }
+    }
+
+    /// Filters the local services to `filter`, as given on the command line,
+    /// and selects the first service whose name starts with it once the
+    /// services are loaded
+    pub fn apply_command_line_filter(&self, filter: &str) {
+        self.local_search_entry.set_text(filter);
+        self.pending_selection.replace(Some(filter.to_string()));
+    }
+
+    fn refresh_all_services(&self) {
+        self.refresh_local_services();
+        self.refresh_timers();
+        self.refresh_sockets();
+        self.refresh_remote_services();
+    }
+
+    fn refresh_sockets(&self) {
+        let service_manager = self.service_manager.clone();
+        let store = self.sockets_store.clone();
+        let show_inactive = self.show_inactive_sockets_button.is_active();
+        let scope = self.unit_scope();
+        let loading = self.sockets_loading.clone();
+        loading.start();
+
+        spawn_with_callback(
+            &self.runtime,
+            tokio::time::timeout(SERVICE_LIST_TIMEOUT, async move {
+                service_manager
+                    .list_local_sockets(show_inactive, scope)
+                    .await
+            }),
+            move |result| {
+                loading.finish();
+                match result {
+                    Ok(Ok(sockets)) => {
+                        store.clear();
+                        for socket in &sockets {
+                            let iter = store.append();
+                            set_socket_row(&store, &iter, socket);
+                        }
+                    }
+                    Ok(Err(e)) => error!("Failed to list sockets: {}", e),
+                    // The local services report the timeout, which is
+                    // usually shared
+                    Err(_) => warn!("Timed out listing sockets"),
+                }
+            },
+        );
+    }
+
+    /// Updates the rows of `socket_names` with their current status
+    fn refresh_socket_rows(&self, socket_names: &[String]) {
+        for socket_name in socket_names {
+            let service_manager = self.service_manager.clone();
+            let store = self.sockets_store.clone();
+            let name = socket_name.clone();
+            let scope = self.unit_scope();
+            spawn_with_callback(
+                &self.runtime,
+                async move { service_manager.get_socket_status(&name, scope).await },
+                move |result| match result {
+                    Ok(socket) => {
+                        if let Some(iter) =
+                            find_row(store.upcast_ref(), None, &|name| name == socket.name)
+                        {
+                            set_socket_row(&store, &iter, &socket);
+                        }
+                    }
+                    Err(e) => warn!("Failed to refresh socket: {}", e),
+                },
+            );
+        }
+    }
+
+    fn refresh_timers(&self) {
+        let service_manager = self.service_manager.clone();
+        let store = self.timers_store.clone();
+        let show_inactive = self.show_inactive_timers_button.is_active();
+        let scope = self.unit_scope();
+        let loading = self.timers_loading.clone();
+        loading.start();
+
+        spawn_with_callback(
+            &self.runtime,
+            tokio::time::timeout(SERVICE_LIST_TIMEOUT, async move {
+                service_manager
+                    .list_local_timers(show_inactive, scope)
+                    .await
+            }),
+            move |result| {
+                loading.finish();
+                match result {
+                    Ok(Ok(timers)) => {
+                        store.clear();
+                        for timer in &timers {
+                            let next = timer.next_trigger.as_deref();
+                            let last = timer.last_trigger.as_deref();
+                            let sort_key = |time: Option<&str>| {
+                                time.and_then(parse_systemd_timestamp)
+                                    .map_or(0, |dt| dt.timestamp_micros())
+                            };
+                            let tooltip = format!(
+                                "Next: {}\nLast: {}",
+                                next.unwrap_or("never"),
+                                last.unwrap_or("never")
+                            );
+                            store.insert_with_values(
+                                None,
+                                &[
+                                    (0, &timer.name),
+                                    (1, &timer.status.to_string()),
+                                    (2, &next.map(format_systemd_timestamp).unwrap_or_default()),
+                                    (3, &last.map(format_systemd_timestamp).unwrap_or_default()),
+                                    (4, &timer.description.as_deref().unwrap_or("")),
+                                    (5, &sort_key(next)),
+                                    (6, &sort_key(last)),
+                                    (7, &glib::markup_escape_text(&tooltip).to_string()),
+                                ],
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => error!("Failed to list timers: {}", e),
+                    // The local services report the timeout, which is
+                    // usually shared
+                    Err(_) => warn!("Timed out listing timers"),
+                }
+            },
+        );
+    }
+
+    fn refresh_local_services(&self) {
+        let service_manager = self.service_manager.clone();
+        let store = self.local_services_store.clone();
+        let show_inactive = self.show_inactive_button.is_active();
+        let scope = self.unit_scope();
+        let tree_view = self.local_services_list.clone();
+        let pending_selection = self.pending_selection.clone();
+        let pinned = self.config.borrow().pinned_services.clone();
+        let loading = self.local_loading.clone();
+        let window = self.window.clone();
+        let usage_fetch = (self.memory_column.is_visible() || self.cpu_column.is_visible())
+            .then(|| (self.runtime.clone(), self.service_manager.clone()));
+        loading.start();
+
+        if self.group_by_slice_button.is_active() {
+            spawn_with_callback(
+                &self.runtime,
+                tokio::time::timeout(SERVICE_LIST_TIMEOUT, async move {
+                    let mut slices = service_manager
+                        .list_services_by_slice(show_inactive, scope)
+                        .await?;
+                    let condition_failed = list_condition_failures(&service_manager, scope).await;
+                    for services in slices.values_mut() {
+                        mark_condition_failures(services, &condition_failed);
+                    }
+                    Ok::<_, anyhow::Error>(slices)
+                }),
+                move |result| match result {
+                    Ok(Ok(slices)) => {
+                        loading.finish();
+                        update_slice_rows(&store, &slices, &pinned);
+                        tree_view.expand_all();
+                        select_pending_service(&tree_view, &pending_selection);
+                        if let Some((runtime, service_manager)) = usage_fetch {
+                            let services = slices.into_values().flatten().collect();
+                            fetch_resource_columns(
+                                &runtime,
+                                service_manager,
+                                store,
+                                services,
+                                scope,
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        loading.finish();
+                        error!("Failed to list services by slice: {}", e);
+                    }
+                    Err(_) => {
+                        loading.finish();
+                        show_service_list_timeout(&window);
+                    }
+                },
+            );
+        } else {
+            spawn_with_callback(
+                &self.runtime,
+                tokio::time::timeout(SERVICE_LIST_TIMEOUT, async move {
+                    let mut services = service_manager
+                        .list_local_services(show_inactive, scope)
+                        .await?;
+                    let condition_failed = list_condition_failures(&service_manager, scope).await;
+                    mark_condition_failures(&mut services, &condition_failed);
+                    Ok::<_, anyhow::Error>(services)
+                }),
+                move |result| match result {
+                    Ok(Ok(services)) => {
+                        loading.finish();
+                        update_service_rows(&store, None, &services, &pinned);
+                        select_pending_service(&tree_view, &pending_selection);
+                        if let Some((runtime, service_manager)) = usage_fetch {
+                            fetch_resource_columns(
+                                &runtime,
+                                service_manager,
+                                store,
+                                services,
+                                scope,
+                            );
+                        }
+                    }
+                    Ok(Err(e)) => {
+                        loading.finish();
+                        error!("Failed to list services: {}", e);
+                    }
+                    Err(_) => {
+                        loading.finish();
+                        show_service_list_timeout(&window);
+                    }
+                },
+            );
+        }
+    }
+
+    fn refresh_remote_services(&self) {
+        let host_names: Vec<String> = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        for host_name in host_names {
+            self.refresh_host_services(&host_name);
+        }
+    }
+
+    /// Lists the services of a connected host, updating its rows in the
+    /// remote services list
+    fn refresh_host_services(&self, host_name: &str) {
+        let connection = self
+            .active_connections
+            .lock()
+            .unwrap()
+            .get(host_name)
+            .cloned();
+        let Some(connection) = connection else {
+            return;
+        };
+
+        let manager = RemoteServiceManager::new(connection);
+        let show_inactive = self.preferences.borrow().show_inactive_default;
+        let store = self.remote_services_store.clone();
+        let host_name = host_name.to_string();
+        spawn_with_callback(
+            &self.runtime,
+            async move {
+                manager
+                    .list_services(show_inactive, UnitScope::System)
+                    .await
+            },
+            move |result| match result {
+                Ok(services) => update_host_service_rows(&store, &host_name, &services),
+                Err(e) => warn!("Failed to list the services of {}: {}", host_name, e),
+            },
+        );
+    }
+}
+
+/// The accepted host keys, or none when they can't be read
+fn load_known_hosts() -> KnownHosts {
+    KnownHosts::load().unwrap_or_else(|e| {
+        warn!("Failed to load known hosts: {}", e);
+        KnownHosts::default()
+    })
+}
+
+/// Reports that listing the local services took longer than
+/// `SERVICE_LIST_TIMEOUT` and was abandoned
+fn show_service_list_timeout(window: &ApplicationWindow) {
+    show_error_dialog(
+        window.upcast_ref(),
+        "Refresh Failed",
+        "Timeout fetching service list",
+    );
+}
+
+/// Runs `future` on the Tokio runtime and hands its output to `callback` on the GTK main loop
+fn spawn_with_callback<T, F, C>(runtime: &Runtime, future: F, callback: C)
+where
+    T: Send + 'static,
+    F: std::future::Future<Output = T> + Send + 'static,
+    C: FnOnce(T) + 'static,
+{
+    let handle = runtime.spawn(future);
+    glib::MainContext::default().spawn_local(async move {
+        match handle.await {
+            Ok(output) => callback(output),
+            Err(e) => error!("Background task failed: {}", e),
+        }
+    });
+}
+
+/// Like `spawn_with_callback`, but lists `description` in `progress_bar`
+/// until the future finishes, so closing the window waits for it. Anything
+/// that changes units runs this way.
+fn spawn_tracked<T, F, C>(
+    runtime: &Runtime,
+    progress_bar: &ProgressNotificationBar,
+    description: &str,
+    future: F,
+    callback: C,
+) where
+    T: Send + 'static,
+    F: std::future::Future<Output = T> + Send + 'static,
+    C: FnOnce(T) + 'static,
+{
+    let progress_bar = progress_bar.clone();
+    let progress_id = progress_bar.add(description, None);
+    let handle = runtime.spawn(future);
+    glib::MainContext::default().spawn_local(async move {
+        let output = handle.await;
+        progress_bar.remove(progress_id);
+        match output {
+            Ok(output) => callback(output),
+            Err(e) => error!("Background task failed: {}", e),
+        }
+    });
+}
+
+/// Appends one row per service to `store`, optionally nested under `parent`
+fn append_service_rows(
+    store: &TreeStore,
+    parent: Option<&TreeIter>,
+    services: &[ServiceInfo],
+    pinned: &[String],
+) {
+    for service in services {
+        store.insert_with_values(
+            parent,
+            None,
+            &[
+                (0, &service.name),
+                (1, &service.status.to_string()),
+                (2, &service.description.as_deref().unwrap_or("")),
+                (3, &service_tooltip(service)),
+                (4, &pinned.contains(&service.name)),
+                (7, &service.enabled),
+            ],
+        );
+    }
+}
+
+/// Brings the rows under `parent` in line with `services`: rows of listed
+/// services are updated in place, others removed and new services
+/// appended. Unlike clearing the store, this keeps the selection and
+/// scroll position.
+fn update_service_rows(
+    store: &TreeStore,
+    parent: Option<&TreeIter>,
+    services: &[ServiceInfo],
+    pinned: &[String],
+) {
+    let mut remaining: HashMap<&str, &ServiceInfo> = services
+        .iter()
+        .map(|service| (service.name.as_str(), service))
+        .collect();
+
+    if let Some(iter) = store.iter_children(parent) {
+        loop {
+            let name = store.get::<Option<String>>(&iter, 0).unwrap_or_default();
+            // Slice rows left from grouping by slice hold no service
+            let service = if store.iter_has_child(&iter) {
+                None
+            } else {
+                remaining.remove(name.as_str())
+            };
+
+            match service {
+                Some(service) => {
+                    update_service_row(store, &iter, service);
+                    let is_pinned = pinned.contains(&service.name);
+                    if store.get::<bool>(&iter, 4) != is_pinned {
+                        store.set_value(&iter, 4, &is_pinned.to_value());
+                    }
+                    if !store.iter_next(&iter) {
+                        break;
+                    }
+                }
+                // Removing moves the iter to the next row, if there is one
+                None => {
+                    if !store.remove(&iter) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let added: Vec<ServiceInfo> = services
+        .iter()
+        .filter(|service| remaining.contains_key(service.name.as_str()))
+        .cloned()
+        .collect();
+    append_service_rows(store, parent, &added, pinned);
+}
+
+/// Like `update_service_rows`, for services grouped under a row per slice
+fn update_slice_rows(
+    store: &TreeStore,
+    slices: &HashMap<String, Vec<ServiceInfo>>,
+    pinned: &[String],
+) {
+    let mut remaining: HashSet<&str> = slices.keys().map(String::as_str).collect();
+
+    if let Some(iter) = store.iter_children(None) {
+        loop {
+            let name = store.get::<Option<String>>(&iter, 0).unwrap_or_default();
+            // Service rows left from the flat list match no slice and go
+            match slices.get_key_value(&name) {
+                Some((slice, services)) => {
+                    remaining.remove(slice.as_str());
+                    update_service_rows(store, Some(&iter), services, pinned);
+                    if !store.iter_next(&iter) {
+                        break;
+                    }
+                }
+                None => {
+                    if !store.remove(&iter) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut added: Vec<&str> = remaining.into_iter().collect();
+    added.sort();
+    for slice in added {
+        let parent = store.insert_with_values(None, None, &[(0, &slice)]);
+        append_service_rows(store, Some(&parent), &slices[slice], pinned);
+    }
+}
+
+/// Fills in the memory and CPU columns of the listed `services` once their
+/// usage is fetched, which happens after the list itself is shown
+fn fetch_resource_columns(
+    runtime: &Runtime,
+    service_manager: Arc<ServiceManager>,
+    store: TreeStore,
+    mut services: Vec<ServiceInfo>,
+    scope: UnitScope,
+) {
+    let names: Vec<String> = services
+        .iter()
+        .map(|service| service.name.clone())
+        .collect();
+    spawn_with_callback(
+        runtime,
+        async move {
+            service_manager
+                .get_resource_usage_batch(&names, scope)
+                .await
+        },
+        move |result| {
+            let usage = match result {
+                Ok(usage) => usage,
+                Err(e) => {
+                    warn!("Failed to fetch the services' resource usage: {}", e);
+                    return;
+                }
+            };
+
+            for service in &mut services {
+                let usage = usage.get(&service.name).copied().unwrap_or_default();
+                service.memory_bytes = usage.memory_current;
+                service.cpu_ns = usage.cpu_usage_nsec;
+
+                if let Some(iter) = find_service_row(&store, &service.name) {
+                    let values = [(5, service.memory_bytes), (6, service.cpu_ns)];
+                    for (column, value) in values {
+                        let value = value.unwrap_or_default();
+                        if store.get::<u64>(&iter, column) != value {
+                            store.set_value(&iter, column as u32, &value.to_value());
+                        }
+                    }
+                }
+            }
+        },
+    );
+}
+
+/// Sets the status, description and tooltip of a service row, leaving the
+/// columns that didn't change alone so their rows aren't redrawn or resorted
+fn update_service_row(store: &TreeStore, iter: &TreeIter, service: &ServiceInfo) {
+    let values = [
+        (1, Some(service.status.to_string())),
+        (2, Some(service.description.clone().unwrap_or_default())),
+        (3, service_tooltip(service)),
+    ];
+    for (column, value) in values {
+        if store.get::<Option<String>>(iter, column) != value {
+            store.set_value(iter, column as u32, &value.to_value());
+        }
+    }
+    if store.get::<bool>(iter, 7) != service.enabled {
+        store.set_value(iter, 7, &service.enabled.to_value());
+    }
+}
+
+/// Brings the remote services rows of `host_name` in line with `services`,
+/// in place like `update_service_rows`. Rows of other hosts are left alone.
+fn update_host_service_rows(store: &TreeStore, host_name: &str, services: &[ServiceInfo]) {
+    let mut remaining: HashMap<&str, &ServiceInfo> = services
+        .iter()
+        .map(|service| (service.name.as_str(), service))
+        .collect();
+
+    if let Some(iter) = store.iter_children(None) {
+        loop {
+            let host = store.get::<Option<String>>(&iter, 0).unwrap_or_default();
+            let name = store.get::<Option<String>>(&iter, 1).unwrap_or_default();
+            if host != host_name {
+                if !store.iter_next(&iter) {
+                    break;
+                }
+                continue;
+            }
+
+            match remaining.remove(name.as_str()) {
+                Some(service) => {
+                    let values = [
+                        (2, service.status.to_string()),
+                        (3, service.description.clone().unwrap_or_default()),
+                    ];
+                    for (column, value) in values {
+                        if store.get::<Option<String>>(&iter, column).as_ref() != Some(&value) {
+                            store.set_value(&iter, column as u32, &value.to_value());
+                        }
+                    }
+                    if !store.iter_next(&iter) {
+                        break;
+                    }
+                }
+                // Removing moves the iter to the next row, if there is one
+                None => {
+                    if !store.remove(&iter) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    for service in services
+        .iter()
+        .filter(|service| remaining.contains_key(service.name.as_str()))
+    {
+        store.insert_with_values(
+            None,
+            None,
+            &[
+                (0, &host_name),
+                (1, &service.name),
+                (2, &service.status.to_string()),
+                (3, &service.description.as_deref().unwrap_or("")),
+            ],
+        );
+    }
+}
+
+/// Fills in a row of the sockets list
+fn set_socket_row(store: &ListStore, iter: &TreeIter, socket: &ServiceInfo) {
+    store.set(
+        iter,
+        &[
+            (0, &socket.name),
+            (1, &socket.status.to_string()),
+            (2, &socket.listen.join(", ")),
+            (3, &socket.description.as_deref().unwrap_or("")),
+        ],
+    );
+}
+
+/// Finds the row of a service, including rows nested under a slice
+/// Names of the service rows under `parent`, including those grouped
+/// under a slice row
+fn service_row_names(model: &TreeModel, parent: Option<&TreeIter>) -> Vec<String> {
+    let mut names = Vec::new();
+    let Some(iter) = model.iter_children(parent) else {
+        return names;
+    };
+    loop {
+        if model.iter_has_child(&iter) {
+            names.extend(service_row_names(model, Some(&iter)));
+        } else if let Some(name) = model.get::<Option<String>>(&iter, 0) {
+            names.push(name);
+        }
+        if !model.iter_next(&iter) {
+            return names;
+        }
+    }
+}
+
+fn find_service_row(store: &TreeStore, service_name: &str) -> Option<TreeIter> {
+    find_row(store.upcast_ref(), None, &|name| name == service_name)
+}
+
+/// Finds the first service row, depth first, whose name satisfies `matches`.
+/// Slice rows, which hold services, are searched but never matched.
+fn find_row(
+    model: &TreeModel,
+    parent: Option<&TreeIter>,
+    matches: &dyn Fn(&str) -> bool,
+) -> Option<TreeIter> {
+    let iter = model.iter_children(parent)?;
+    loop {
+        if model.iter_has_child(&iter) {
+            if let Some(found) = find_row(model, Some(&iter), matches) {
+                return Some(found);
+            }
+        } else if model
+            .get::<Option<String>>(&iter, 0)
+            .is_some_and(|name| matches(&name))
+        {
+            return Some(iter);
+        }
+        if !model.iter_next(&iter) {
+            return None;
+        }
+    }
+}
+
+/// Selects the first visible service starting with the prefix left by
+/// `apply_command_line_filter`, then forgets the prefix
+fn select_pending_service(tree_view: &TreeView, pending_selection: &RefCell<Option<String>>) {
+    let Some(prefix) = pending_selection.take() else {
+        return;
+    };
+    let Some(model) = tree_view.model() else {
+        return;
+    };
+
+    let prefix = prefix.to_lowercase();
+    match find_row(&model, None, &|name| {
+        name.to_lowercase().starts_with(&prefix)
+    }) {
+        Some(iter) => {
+            let path = model.path(&iter);
+            tree_view.expand_to_path(&path);
+            tree_view.selection().select_iter(&iter);
+            tree_view.scroll_to_cell(Some(&path), None::<&TreeViewColumn>, false, 0.0, 0.0);
+        }
+        None => warn!("No service name starts with {}", prefix),
+    }
+}
+
+/// Tooltip markup for a service row, explaining an unmet condition
+fn service_tooltip(service: &ServiceInfo) -> Option<String> {
+    if service.status != ServiceStatus::ConditionFailed {
+        return None;
+    }
+
+    Some(match service.failed_condition.as_deref() {
+        Some(condition) => {
+            let (name, expected) = condition.split_once('=').unwrap_or((condition, ""));
+            format!(
+                "Not started: <b>{}</b> expected <tt>{}</tt>",
+                glib::markup_escape_text(name),
+                glib::markup_escape_text(expected)
+            )
+        }
+        None => "Not started: a start condition was not met".to_string(),
+    })
+}
+
+/// Services skipped by an unmet condition; failures to find them are logged
+/// rather than failing the whole refresh
+async fn list_condition_failures(
+    service_manager: &ServiceManager,
+    scope: UnitScope,
+) -> Vec<ServiceInfo> {
+    service_manager
+        .list_condition_failed_services(scope)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("Failed to list services with unmet conditions: {}", e);
+            Vec::new()
+        })
+}
+
+/// Shows services skipped by an unmet condition as such rather than inactive
+fn mark_condition_failures(services: &mut [ServiceInfo], condition_failed: &[ServiceInfo]) {
+    for service in services.iter_mut() {
+        if let Some(failed) = condition_failed
+            .iter()
+            .find(|failed| failed.name == service.name && !service.active)
+        {
+            service.status = ServiceStatus::ConditionFailed;
+            service.failed_condition = failed.failed_condition.clone();
+        }
+    }
+}
+
+fn show_unit_file(
+    parent: &ApplicationWindow,
+    path: &std::path::Path,
+    exec_steps: Option<&ExecSteps>,
+) {
+    match std::fs::read_to_string(path) {
+        Ok(content) => show_service_details_dialog(
+            parent.upcast_ref(),
+            &path.display().to_string(),
+            &content,
+            None,
+            exec_steps,
+        ),
+        Err(e) => show_error_dialog(
+            parent.upcast_ref(),
+            "Unit File",
+            &format!("Failed to read {}: {}", path.display(), e),
+        ),
+    }
+}
+
+fn auto_refresh_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Auto-refresh: ON"
+    } else {
+        "Auto-refresh: OFF"
+    }
+}
+
+/// Size of the primary monitor, which a restored window must fit within
+fn monitor_size() -> Option<(i32, i32)> {
+    let monitor = gdk4::Display::default()?
+        .monitors()
+        .item(0)?
+        .downcast::<gdk4::Monitor>()
+        .ok()?;
+    let geometry = monitor.geometry();
+    Some((geometry.width(), geometry.height()))
+}
+
+/// Returns the (host, service) pair of the first selected remote services row
+fn get_selected_remote_service(selection: &TreeSelection) -> Option<(String, String)> {
+    get_all_selected_remote_services(selection)
+        .into_iter()
+        .next()
+}
+
+/// Returns the (host, service) pairs of every selected remote services row
+fn get_all_selected_remote_services(selection: &TreeSelection) -> Vec<(String, String)> {
+    let (paths, model) = selection.selected_rows();
+    paths
+        .iter()
+        .filter_map(|path| {
+            let iter = model.iter(path)?;
+            let host = model.get::<Option<String>>(&iter, 0)?;
+            let service = model.get::<Option<String>>(&iter, 1)?;
+            Some((host, service))
+        })
+        .collect()
+}
+
+/// Makes the remote toolbar reflect what `host_name` supports, explaining
+/// disabled buttons in their tooltips
+fn apply_host_capabilities(
+    host_name: &str,
+    capabilities: &HostCapabilities,
+    sudo_buttons: &[Button],
+    logs_button: &Button,
+) {
+    let sudo_tooltip = format!("sudo on {} requires a password", host_name);
+    for button in sudo_buttons {
+        button.set_sensitive(capabilities.sudo_available);
+        button.set_tooltip_text((!capabilities.sudo_available).then_some(sudo_tooltip.as_str()));
+    }
+
+    logs_button.set_sensitive(capabilities.journalctl_available);
+    let logs_tooltip = format!("journalctl is not installed on {}", host_name);
+    logs_button
+        .set_tooltip_text((!capabilities.journalctl_available).then_some(logs_tooltip.as_str()));
+}
+
+/// Activates `name` from `widget`, which resolves it against the action
+/// groups of the widget and its ancestors
+fn activate_action(widget: &impl IsA<gtk4::Widget>, name: &str) {
+    if let Err(e) = widget.activate_action(name, None) {
+        warn!("Failed to activate {}: {}", name, e);
+    }
+}
+
+/// The first selected local service; see `get_all_selected_service_names`
+fn get_selected_service_name(selection: &TreeSelection) -> Option<String> {
+    get_all_selected_service_names(selection).into_iter().next()
+}
+
+/// Names of every selected local service, in list order. Selected slice
+/// rows are skipped, as they aren't services.
+fn get_all_selected_service_names(selection: &TreeSelection) -> Vec<String> {
+    let (paths, model) = selection.selected_rows();
+    paths
+        .iter()
+        .filter_map(|path| model.iter(path))
+        .filter(|iter| !model.iter_has_child(iter))
+        .filter_map(|iter| model.get::<Option<String>>(&iter, 0))
+        .collect()
+}
+
+/// Returns the status of every selected service row, skipping group rows
+fn get_all_selected_service_statuses(selection: &TreeSelection) -> Vec<ServiceStatus> {
+    let (paths, model) = selection.selected_rows();
+    paths
+        .iter()
+        .filter_map(|path| model.iter(path))
+        .filter(|iter| !model.iter_has_child(iter))
+        .filter_map(|iter| model.get::<Option<String>>(&iter, 1))
+        .map(|status| ServiceStatus::from(status.as_str()))
+        .collect()
+}
+
+fn show_add_host_dialog(
+    parent: &ApplicationWindow,
+    remote_hosts: &Rc<RefCell<HashMap<String, RemoteHost>>>,
+) {
+    let dialog = Dialog::with_buttons(
+        Some("Add Remote Host"),
+        Some(parent),
+        DialogFlags::MODAL | DialogFlags::DESTROY_WITH_PARENT,
+        &[("Cancel", ResponseType::Cancel), ("Add", ResponseType::Ok)],
+    );
+
+    let grid = Grid::new();
+    grid.set_row_spacing(6);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(12);
+    grid.set_margin_end(12);
+    grid.set_margin_top(12);
+    grid.set_margin_bottom(12);
+
+    // Name field
+    let name_label = Label::new(Some("Name:"));
+    let name_entry = Entry::new();
+    grid.attach(&name_label, 0, 0, 1, 1);
+    grid.attach(&name_entry, 1, 0, 1, 1);
+
+    // Hostname field
+    let hostname_label = Label::new(Some("Hostname:"));
+    let hostname_entry = Entry::new();
+    grid.attach(&hostname_label, 0, 1, 1, 1);
+    grid.attach(&hostname_entry, 1, 1, 1, 1);
+
+    // Username field
+    let username_label = Label::new(Some("Username:"));
+    let username_entry = Entry::new();
+    grid.attach(&username_label, 0, 2, 1, 1);
+    grid.attach(&username_entry, 1, 2, 1, 1);
+
+    // Port field
+    let port_label = Label::new(Some("Port:"));
+    let port_entry = Entry::new();
+    port_entry.set_text(&DEFAULT_SSH_PORT.to_string());
+    grid.attach(&port_label, 0, 3, 1, 1);
+    grid.attach(&port_entry, 1, 3, 1, 1);
+
+    // Auth type
+    let auth_label = Label::new(Some("Authentication:"));
+    let auth_combo = ComboBoxText::new();
+    auth_combo.append_text("Password");
+    auth_combo.append_text("SSH Key");
+    auth_combo.append_text("SSH Agent");
+    auth_combo.set_active(Some(0));
+    grid.attach(&auth_label, 0, 4, 1, 1);
+    grid.attach(&auth_combo, 1, 4, 1, 1);
+
+    // Per-host auto-refresh, off until asked for
+    let auto_refresh_check = CheckButton::with_label("Auto-refresh this host");
+    grid.attach(&auto_refresh_check, 0, 5, 2, 1);
+
+    let interval_label = Label::new(Some("Refresh Interval (seconds):"));
+    let interval_spin = gtk4::SpinButton::with_range(
+        RefreshConfig::MIN_INTERVAL_SECS as f64,
+        RefreshConfig::MAX_INTERVAL_SECS as f64,
+        5.0,
+    );
+    interval_spin.set_value(RefreshConfig::DEFAULT_INTERVAL_SECS as f64);
+    interval_spin.set_sensitive(false);
+    grid.attach(&interval_label, 0, 6, 1, 1);
+    grid.attach(&interval_spin, 1, 6, 1, 1);
+
+    let interval_spin_clone = interval_spin.clone();
+    auto_refresh_check.connect_toggled(move |check| {
+        interval_spin_clone.set_sensitive(check.is_active());
+    });
+
+    let content_area = dialog.content_area();
+    content_area.append(&grid);
+
+    let remote_hosts_clone = remote_hosts.clone();
     dialog.connect_response(move |dialog, response| {
         if response == ResponseType::Ok {
             let name = name_entry.text().to_string();
             let hostname = hostname_entry.text().to_string();
             let username = username_entry.text().to_string();
-            let auth_type = if auth_combo.active() == Some(0) {
-                AuthType::Password
-            } else {
-                AuthType::Key { path: None }
+            let port = match parse_port(&port_entry.text()) {
+                Ok(port) => port,
+                Err(e) => {
+                    show_error_dialog(dialog.upcast_ref(), "Invalid Port", &e.message);
+                    return;
+                }
+            };
+            let auth_type = match auth_combo.active() {
+                Some(0) => AuthType::Password,
+                Some(2) => AuthType::Agent,
+                _ => AuthType::Key { path: None },
             };
 
             if !name.is_empty() && !hostname.is_empty() && !username.is_empty() {
@@ -689,7 +5944,12 @@ fn show_add_host_dialog(
                     name: name.clone(),
                     hostname,
                     username,
+                    port,
                     auth_type,
+                    ssh_control_path: None,
+                    auto_refresh: auto_refresh_check.is_active(),
+                    refresh_interval_secs: interval_spin.value_as_int() as u32,
+                    capabilities: HostCapabilities::default(),
                 };
 
                 remote_hosts_clone.borrow_mut().insert(name, host);
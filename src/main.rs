@@ -22,11 +22,26 @@ fn main() -> glib::ExitCode {
     // Create GTK application
     let app = Application::builder().application_id(APP_ID).build();
 
-    app.connect_activate(build_ui);
-    app.run()
+    // `systemd-pilot nginx` starts with the list filtered to nginx; GTK
+    // would otherwise try to open the argument as a file
+    let (gtk_args, filter) = split_filter_arg(std::env::args().collect());
+    app.connect_activate(move |app| build_ui(app, filter.as_deref()));
+    app.run_with_args(&gtk_args)
 }
 
-fn build_ui(app: &Application) {
+/// Takes the first positional argument, the service filter, out of `args`,
+/// leaving the program name and options for GTK
+fn split_filter_arg(mut args: Vec<String>) -> (Vec<String>, Option<String>) {
+    let position = args
+        .iter()
+        .skip(1)
+        .position(|arg| !arg.starts_with('-'))
+        .map(|position| position + 1);
+    let filter = position.map(|position| args.remove(position));
+    (args, filter)
+}
+
+fn build_ui(app: &Application, filter: Option<&str>) {
     // Create main application window
     let window = ApplicationWindow::builder()
         .application(app)
@@ -44,6 +59,37 @@ fn build_ui(app: &Application) {
     // Load saved configuration
     systemd_app.load_saved_hosts();
 
+    if let Some(filter) = filter {
+        systemd_app.apply_command_line_filter(filter);
+    }
+
+    // Reopen at the size the window was left
+    systemd_app.restore_window_state();
+
     // Show the window
     window.present();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_split_filter_arg() {
+        assert_eq!(
+            split_filter_arg(args(&["systemd-pilot", "--verbose", "nginx", "extra"])),
+            (
+                args(&["systemd-pilot", "--verbose", "extra"]),
+                Some("nginx".to_string())
+            )
+        );
+        assert_eq!(
+            split_filter_arg(args(&["systemd-pilot"])),
+            (args(&["systemd-pilot"]), None)
+        );
+    }
+}
@@ -1,18 +1,302 @@
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use crate::utils::config::RefreshConfig;
+
+/// Number of events kept per host in a `ConnectionLog`
+const CONNECTION_LOG_CAPACITY: usize = 100;
+
+/// Number of commands remembered per host in a `CommandHistory`
+const COMMAND_HISTORY_CAPACITY: usize = 10;
+
+/// Version of the `hosts.json` layout written by `HostsConfig`
+pub const HOSTS_CONFIG_VERSION: u32 = 2;
+
+/// Port used when a host doesn't specify one
+pub const DEFAULT_SSH_PORT: u16 = 22;
+
+fn default_port() -> u16 {
+    DEFAULT_SSH_PORT
+}
+
+/// Loaded through `StoredRemoteHost`, so hosts saved by older versions
+/// still load and unknown fields are reported rather than rejected
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct RemoteHost {
     pub name: String,
     pub hostname: String,
     pub username: String,
+    pub port: u16,
     pub auth_type: AuthType,
+    /// OpenSSH control socket (`ControlPath`) to reuse instead of opening a
+    /// new session, when it exists
+    pub ssh_control_path: Option<PathBuf>,
+    /// Refresh the host's services periodically while it is connected,
+    /// independently of the other hosts
+    pub auto_refresh: bool,
+    pub refresh_interval_secs: u32,
+    /// Tools found on the host, detected when connecting
+    #[serde(skip)]
+    pub capabilities: HostCapabilities,
+}
+
+/// Every field `StoredRemoteHost` reads
+const REMOTE_HOST_FIELDS: &[&str] = &[
+    "name",
+    "hostname",
+    "username",
+    "port",
+    "auth_type",
+    "ssh_control_path",
+    "auto_refresh",
+    "refresh_interval_secs",
+];
+
+/// A `RemoteHost` as saved in `hosts.json`. Fields added after the first
+/// release must have a default so that older files keep loading.
+#[derive(Deserialize)]
+struct StoredRemoteHost {
+    name: String,
+    hostname: String,
+    username: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    auth_type: AuthType,
+    #[serde(default)]
+    ssh_control_path: Option<PathBuf>,
+    #[serde(default)]
+    auto_refresh: bool,
+    #[serde(default = "default_refresh_interval")]
+    refresh_interval_secs: u32,
+}
+
+fn default_refresh_interval() -> u32 {
+    RefreshConfig::DEFAULT_INTERVAL_SECS
+}
+
+impl From<StoredRemoteHost> for RemoteHost {
+    fn from(stored: StoredRemoteHost) -> Self {
+        Self {
+            name: stored.name,
+            hostname: stored.hostname,
+            username: stored.username,
+            port: stored.port,
+            auth_type: stored.auth_type,
+            ssh_control_path: stored.ssh_control_path,
+            auto_refresh: stored.auto_refresh,
+            refresh_interval_secs: stored.refresh_interval_secs,
+            capabilities: HostCapabilities::default(),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RemoteHost {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        // Fields from newer versions, or removed ones, are ignored with a
+        // warning instead of failing the whole file
+        if let Some(fields) = value.as_object() {
+            let name = fields
+                .get("name")
+                .and_then(serde_json::Value::as_str)
+                .unwrap_or("unnamed");
+            for key in fields
+                .keys()
+                .filter(|key| !REMOTE_HOST_FIELDS.contains(&key.as_str()))
+            {
+                warn!("Ignoring unknown field '{}' of host {}", key, name);
+            }
+        }
+
+        serde_json::from_value::<StoredRemoteHost>(value)
+            .map(Into::into)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// The saved hosts, as written to `hosts.json`. Version 1 files are a bare
+/// map of host name to host; `config_version` lets later layouts be migrated.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HostsConfig {
+    pub config_version: u32,
+    pub hosts: HashMap<String, RemoteHost>,
+}
+
+impl HostsConfig {
+    pub fn new(hosts: HashMap<String, RemoteHost>) -> Self {
+        Self {
+            config_version: HOSTS_CONFIG_VERSION,
+            hosts,
+        }
+    }
+
+    /// Parses `hosts.json` in any known layout, migrating it to the current one
+    pub fn from_json(content: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(content)?;
+
+        if value.get("config_version").is_none() {
+            return Ok(migrate_v1_to_v2(serde_json::from_value(value)?));
+        }
+
+        let config: Self = serde_json::from_value(value)?;
+        if config.config_version > HOSTS_CONFIG_VERSION {
+            warn!(
+                "hosts.json is version {}, newer than the supported version {}",
+                config.config_version, HOSTS_CONFIG_VERSION
+            );
+        }
+        Ok(config)
+    }
+}
+
+/// Wraps the bare host map saved by version 1 in a `HostsConfig`
+pub fn migrate_v1_to_v2(hosts: HashMap<String, RemoteHost>) -> HostsConfig {
+    HostsConfig {
+        config_version: 2,
+        hosts,
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Names of `imported` hosts that are already saved, sorted
+pub fn conflicting_host_names(
+    existing: &HashMap<String, RemoteHost>,
+    imported: &HashMap<String, RemoteHost>,
+) -> Vec<String> {
+    let mut names: Vec<String> = imported
+        .keys()
+        .filter(|name| existing.contains_key(*name))
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// Adds `imported` hosts to `existing`, replacing hosts with the same name
+/// when `replace` is set and keeping them otherwise. Returns how many hosts
+/// were added or replaced.
+pub fn merge_hosts(
+    existing: &mut HashMap<String, RemoteHost>,
+    imported: HashMap<String, RemoteHost>,
+    replace: bool,
+) -> usize {
+    let mut merged = 0;
+    for (name, host) in imported {
+        if replace || !existing.contains_key(&name) {
+            existing.insert(name, host);
+            merged += 1;
+        }
+    }
+    merged
+}
+
+/// Shell snippet printing one `key=value` line per capability
+pub const DETECT_CAPABILITIES_COMMAND: &str = "\
+    sudo -n true 2>/dev/null && echo sudo=1 || echo sudo=0; \
+    command -v journalctl >/dev/null && echo journalctl=1 || echo journalctl=0; \
+    command -v systemd-run >/dev/null && echo systemd_run=1 || echo systemd_run=0; \
+    echo \"systemd=$(systemctl --version 2>/dev/null | head -n 1)\"";
+
+/// Tools available on a remote host. Until detection has run, or when a
+/// check gives no answer, everything is assumed to be available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostCapabilities {
+    /// Whether `sudo` works without a password prompt
+    pub sudo_available: bool,
+    /// Major systemd version, 0 when unknown
+    pub systemd_version: u32,
+    pub journalctl_available: bool,
+    pub systemd_run_available: bool,
+}
+
+impl Default for HostCapabilities {
+    fn default() -> Self {
+        Self {
+            sudo_available: true,
+            systemd_version: 0,
+            journalctl_available: true,
+            systemd_run_available: true,
+        }
+    }
+}
+
+impl HostCapabilities {
+    /// Parses the output of `DETECT_CAPABILITIES_COMMAND`
+    pub fn parse(output: &str) -> Self {
+        let mut capabilities = Self::default();
+
+        for (key, value) in output
+            .lines()
+            .filter_map(|line| line.trim().split_once('='))
+        {
+            match key {
+                "sudo" => capabilities.sudo_available = value != "0",
+                "journalctl" => capabilities.journalctl_available = value != "0",
+                "systemd_run" => capabilities.systemd_run_available = value != "0",
+                // e.g. `systemd 252 (252.22-1~deb12u1)`
+                "systemd" => {
+                    capabilities.systemd_version = value
+                        .split_whitespace()
+                        .nth(1)
+                        .and_then(|version| version.parse().ok())
+                        .unwrap_or(0)
+                }
+                _ => {}
+            }
+        }
+
+        capabilities
+    }
+}
+
+/// A problem with a host's settings that would prevent connecting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl ValidationError {
+    fn new(field: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            field,
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+/// Parses the text of a port entry, which must be a number from 1 to 65535
+pub fn parse_port(text: &str) -> Result<u16, ValidationError> {
+    let port = text
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| ValidationError::new("port", format!("'{}' is not a number", text.trim())))?;
+
+    u16::try_from(port)
+        .ok()
+        .filter(|port| *port != 0)
+        .ok_or_else(|| ValidationError::new("port", "Port must be between 1 and 65535"))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AuthType {
     Password,
-    Key { path: Option<PathBuf> },
+    Key {
+        path: Option<PathBuf>,
+    },
+    /// Keys offered by the running SSH agent (`SSH_AUTH_SOCK`)
+    #[serde(rename = "agent")]
+    Agent,
 }
 
 impl RemoteHost {
@@ -21,11 +305,107 @@ impl RemoteHost {
             name,
             hostname,
             username,
+            port: DEFAULT_SSH_PORT,
             auth_type,
+            ssh_control_path: None,
+            auto_refresh: false,
+            refresh_interval_secs: RefreshConfig::DEFAULT_INTERVAL_SECS,
+            capabilities: HostCapabilities::default(),
+        }
+    }
+
+    /// The host's own auto-refresh setting
+    pub fn refresh(&self) -> RefreshConfig {
+        RefreshConfig {
+            enabled: self.auto_refresh,
+            interval_secs: self.refresh_interval_secs,
+        }
+    }
+
+    /// Builds a host from an `ssh://` or `sftp://` URI such as
+    /// `sftp://user@example.com:2222/home/user`, as provided by file manager
+    /// bookmarks. The host is named after its hostname and uses key
+    /// authentication; the username defaults to the current user.
+    pub fn from_ssh_uri(uri: &str) -> Result<Self> {
+        let uri = uri.trim();
+        let (scheme, rest) = uri
+            .split_once("://")
+            .ok_or_else(|| anyhow!("'{}' is not a URI", uri))?;
+        if !matches!(scheme.to_lowercase().as_str(), "ssh" | "sftp") {
+            return Err(anyhow!(
+                "Unsupported URI scheme '{}'; only ssh:// and sftp:// are supported",
+                scheme
+            ));
+        }
+
+        let authority = rest.split('/').next().unwrap_or_default();
+        let (username, host_port) = match authority.rsplit_once('@') {
+            // Drop any password or `;fingerprint=` parameter
+            Some((user_info, host_port)) => (
+                user_info
+                    .split([':', ';'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                host_port,
+            ),
+            None => (std::env::var("USER").unwrap_or_default(), authority),
+        };
+
+        let (hostname, port) = match host_port.strip_prefix('[') {
+            Some(bracketed) => {
+                let (hostname, rest) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| anyhow!("Unterminated IPv6 address in '{}'", uri))?;
+                (hostname, rest.strip_prefix(':'))
+            }
+            None => match host_port.split_once(':') {
+                Some((hostname, port)) => (hostname, Some(port)),
+                None => (host_port, None),
+            },
+        };
+
+        if hostname.is_empty() {
+            return Err(anyhow!("'{}' has no hostname", uri));
         }
+
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| anyhow!("Invalid port '{}' in '{}'", port, uri))?,
+            None => DEFAULT_SSH_PORT,
+        };
+
+        Ok(Self::new(
+            hostname.to_string(),
+            hostname.to_string(),
+            username,
+            AuthType::Key { path: None },
+        )
+        .with_port(port))
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Whether the name, hostname or username contains `query`, ignoring
+    /// case. An empty query matches every host.
+    pub fn matches_search(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.name, &self.hostname, &self.username]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
     }
 
+    /// `user@host:port`, for display
     pub fn connection_string(&self) -> String {
+        format!("{}:{}", self.ssh_destination(), self.port)
+    }
+
+    /// `user@host`, the destination argument of the `ssh` command
+    pub fn ssh_destination(&self) -> String {
         format!("{}@{}", self.username, self.hostname)
     }
 
@@ -37,22 +417,75 @@ impl RemoteHost {
         matches!(self.auth_type, AuthType::Password)
     }
 
+    /// Whether the host authenticates with a key file; agent keys don't count
     pub fn is_key_auth(&self) -> bool {
         matches!(self.auth_type, AuthType::Key { .. })
     }
 
+    pub fn is_agent_auth(&self) -> bool {
+        matches!(self.auth_type, AuthType::Agent)
+    }
+
     pub fn key_path(&self) -> Option<&PathBuf> {
         match &self.auth_type {
             AuthType::Key { path } => path.as_ref(),
             _ => None,
         }
     }
+
+    /// The key path with a leading `~` expanded to the home directory
+    pub fn resolved_key_path(&self) -> Option<PathBuf> {
+        self.key_path().map(|path| expand_home(path))
+    }
+
+    /// The control socket path with a leading `~` expanded to the home directory
+    pub fn resolved_control_path(&self) -> Option<PathBuf> {
+        self.ssh_control_path.as_deref().map(expand_home)
+    }
+
+    /// Checks the settings needed to connect, returning every problem found
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+
+        if self.hostname.trim().is_empty() {
+            errors.push(ValidationError::new(
+                "hostname",
+                "Hostname must not be empty",
+            ));
+        }
+
+        if self.port == 0 {
+            errors.push(ValidationError::new(
+                "port",
+                "Port must be between 1 and 65535",
+            ));
+        }
+
+        if self.username.trim().is_empty() {
+            errors.push(ValidationError::new(
+                "username",
+                "Username must not be empty",
+            ));
+        }
+
+        if let Some(path) = self.resolved_key_path() {
+            if !path.exists() {
+                errors.push(ValidationError::new(
+                    "key",
+                    format!("Key file {} does not exist", path.display()),
+                ));
+            }
+        }
+
+        errors
+    }
 }
 
 impl std::fmt::Display for AuthType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AuthType::Password => write!(f, "Password"),
+            AuthType::Agent => write!(f, "SSH Agent"),
             AuthType::Key { path } => {
                 if let Some(p) = path {
                     write!(f, "SSH Key ({})", p.display())
@@ -64,6 +497,144 @@ impl std::fmt::Display for AuthType {
     }
 }
 
+// libssh2 session error codes, which `ssh2` doesn't re-export
+const LIBSSH2_ERROR_TIMEOUT: std::os::raw::c_int = -9;
+const LIBSSH2_ERROR_AUTHENTICATION_FAILED: std::os::raw::c_int = -18;
+const LIBSSH2_ERROR_PUBLICKEY_UNVERIFIED: std::os::raw::c_int = -19;
+
+/// Where the connection to a remote host stands, as shown in the hosts list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HostConnectionState {
+    #[default]
+    Disconnected,
+    Connecting,
+    Connected,
+}
+
+impl HostConnectionState {
+    /// Coloured dot shown next to the host
+    pub fn indicator(&self) -> &'static str {
+        match self {
+            HostConnectionState::Disconnected => "🔴",
+            HostConnectionState::Connecting => "🟡",
+            HostConnectionState::Connected => "🟢",
+        }
+    }
+}
+
+impl std::fmt::Display for HostConnectionState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostConnectionState::Disconnected => write!(f, "Disconnected"),
+            HostConnectionState::Connecting => write!(f, "Connecting"),
+            HostConnectionState::Connected => write!(f, "Connected"),
+        }
+    }
+}
+
+/// Something that happened to the connection to a remote host
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEventType {
+    Connected,
+    Disconnected(String),
+    AuthFailed,
+    Timeout,
+}
+
+impl ConnectionEventType {
+    /// Classifies an error from opening a connection
+    pub fn from_connect_error(error: &anyhow::Error) -> Self {
+        for cause in error.chain() {
+            if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+                if e.kind() == std::io::ErrorKind::TimedOut {
+                    return ConnectionEventType::Timeout;
+                }
+            }
+            if let Some(e) = cause.downcast_ref::<ssh2::Error>() {
+                match e.code() {
+                    ssh2::ErrorCode::Session(LIBSSH2_ERROR_TIMEOUT) => {
+                        return ConnectionEventType::Timeout
+                    }
+                    ssh2::ErrorCode::Session(
+                        LIBSSH2_ERROR_AUTHENTICATION_FAILED | LIBSSH2_ERROR_PUBLICKEY_UNVERIFIED,
+                    ) => return ConnectionEventType::AuthFailed,
+                    _ => {}
+                }
+            }
+        }
+
+        if error.to_string().starts_with("Authentication failed") {
+            ConnectionEventType::AuthFailed
+        } else {
+            ConnectionEventType::Disconnected(error.to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for ConnectionEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConnectionEventType::Connected => write!(f, "Connected"),
+            ConnectionEventType::Disconnected(reason) => write!(f, "Disconnected: {}", reason),
+            ConnectionEventType::AuthFailed => write!(f, "Authentication failed"),
+            ConnectionEventType::Timeout => write!(f, "Timed out"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionEvent {
+    pub timestamp: DateTime<Utc>,
+    pub host_name: String,
+    pub event_type: ConnectionEventType,
+}
+
+/// The most recent connection events for a host, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct ConnectionLog {
+    pub entries: VecDeque<ConnectionEvent>,
+}
+
+impl ConnectionLog {
+    /// Records an event now, dropping the oldest once the log is full
+    pub fn record(&mut self, host_name: &str, event_type: ConnectionEventType) {
+        self.push(ConnectionEvent {
+            timestamp: Utc::now(),
+            host_name: host_name.to_string(),
+            event_type,
+        });
+    }
+
+    fn push(&mut self, event: ConnectionEvent) {
+        if self.entries.len() == CONNECTION_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(event);
+    }
+}
+
+/// Commands recently run on a host, most recent first and without repeats
+#[derive(Debug, Clone, Default)]
+pub struct CommandHistory {
+    pub commands: VecDeque<String>,
+}
+
+impl CommandHistory {
+    /// Moves `command` to the front, dropping the oldest once the history is full
+    pub fn record(&mut self, command: &str) {
+        self.commands.retain(|existing| existing != command);
+        self.commands.push_front(command.to_string());
+        self.commands.truncate(COMMAND_HISTORY_CAPACITY);
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -93,7 +664,20 @@ mod tests {
             AuthType::Password,
         );
 
-        assert_eq!(host.connection_string(), "user@example.com");
+        assert_eq!(host.connection_string(), "user@example.com:22");
+        assert_eq!(
+            host.with_port(2222).connection_string(),
+            "user@example.com:2222"
+        );
+    }
+
+    #[test]
+    fn test_parse_port() {
+        assert_eq!(parse_port(" 2222 ").unwrap(), 2222);
+        assert_eq!(parse_port("abc").unwrap_err().field, "port");
+        assert!(parse_port("0").is_err());
+        assert!(parse_port("65536").is_err());
+        assert_eq!(parse_port("65535").unwrap(), 65535);
     }
 
     #[test]
@@ -113,6 +697,110 @@ mod tests {
         assert_eq!(host.key_path(), Some(&key_path));
     }
 
+    #[test]
+    fn test_agent_auth() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "example.com".to_string(),
+            "user".to_string(),
+            AuthType::Agent,
+        );
+
+        assert!(host.is_agent_auth());
+        assert!(!host.is_key_auth());
+        assert_eq!(host.key_path(), None);
+
+        let json = serde_json::to_value(&host).unwrap();
+        assert_eq!(json["auth_type"], "agent");
+        let loaded: RemoteHost = serde_json::from_value(json).unwrap();
+        assert!(loaded.is_agent_auth());
+    }
+
+    #[test]
+    fn test_validate_valid_host() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "example.com".to_string(),
+            "user".to_string(),
+            AuthType::Key { path: None },
+        );
+
+        assert!(host.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_empty_hostname() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "  ".to_string(),
+            "user".to_string(),
+            AuthType::Password,
+        );
+
+        let errors = host.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "hostname");
+    }
+
+    #[test]
+    fn test_validate_port_out_of_range() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "example.com".to_string(),
+            "user".to_string(),
+            AuthType::Password,
+        )
+        .with_port(0);
+
+        let errors = host.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "port");
+    }
+
+    #[test]
+    fn test_validate_empty_username() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "example.com".to_string(),
+            String::new(),
+            AuthType::Password,
+        );
+
+        let errors = host.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "username");
+    }
+
+    #[test]
+    fn test_validate_missing_key_file() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            "example.com".to_string(),
+            "user".to_string(),
+            AuthType::Key {
+                path: Some(PathBuf::from("/nonexistent/systemd-pilot/id_rsa")),
+            },
+        );
+
+        let errors = host.validate();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "key");
+    }
+
+    #[test]
+    fn test_validate_reports_all_errors() {
+        let host = RemoteHost::new(
+            "test-server".to_string(),
+            String::new(),
+            String::new(),
+            AuthType::Password,
+        )
+        .with_port(0);
+
+        let fields: Vec<_> = host.validate().iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["hostname", "port", "username"]);
+    }
+
     #[test]
     fn test_serialization() {
         let host = RemoteHost::new(
@@ -129,4 +817,259 @@ mod tests {
         assert_eq!(host.hostname, deserialized.hostname);
         assert_eq!(host.username, deserialized.username);
     }
+
+    #[test]
+    fn test_control_path() {
+        let mut host: RemoteHost = serde_json::from_str(
+            r#"{"name": "web1", "hostname": "example.com", "username": "user", "auth_type": "Password"}"#,
+        )
+        .unwrap();
+        assert_eq!(host.resolved_control_path(), None);
+
+        host.ssh_control_path = Some(PathBuf::from("~/.ssh/cm-web1"));
+        let resolved = host.resolved_control_path().unwrap();
+        assert!(resolved.ends_with(".ssh/cm-web1"));
+        if let Some(home) = dirs::home_dir() {
+            assert!(resolved.starts_with(home));
+        }
+    }
+
+    #[test]
+    fn test_from_ssh_uri() {
+        let host = RemoteHost::from_ssh_uri("sftp://deploy@example.com:2222/var/www").unwrap();
+        assert_eq!(host.name, "example.com");
+        assert_eq!(host.hostname, "example.com");
+        assert_eq!(host.username, "deploy");
+        assert_eq!(host.port, 2222);
+        assert!(host.is_key_auth());
+
+        let host = RemoteHost::from_ssh_uri("ssh://admin:secret@[::1]").unwrap();
+        assert_eq!(host.hostname, "::1");
+        assert_eq!(host.username, "admin");
+        assert_eq!(host.port, DEFAULT_SSH_PORT);
+    }
+
+    #[test]
+    fn test_from_ssh_uri_rejects_other_schemes() {
+        assert!(RemoteHost::from_ssh_uri("https://example.com/").is_err());
+        assert!(RemoteHost::from_ssh_uri("example.com").is_err());
+        assert!(RemoteHost::from_ssh_uri("ssh://user@example.com:port").is_err());
+    }
+
+    #[test]
+    fn test_connection_log_is_capped() {
+        let mut log = ConnectionLog::default();
+        for i in 0..CONNECTION_LOG_CAPACITY + 5 {
+            log.record(&format!("host{}", i), ConnectionEventType::Connected);
+        }
+
+        assert_eq!(log.entries.len(), CONNECTION_LOG_CAPACITY);
+        assert_eq!(log.entries.front().unwrap().host_name, "host5");
+    }
+
+    #[test]
+    fn test_deserialize_minimal_host() {
+        let host: RemoteHost = serde_json::from_str(
+            r#"{"name": "web1", "hostname": "example.com", "username": "deploy", "auth_type": "Password"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(host.name, "web1");
+        assert_eq!(host.port, DEFAULT_SSH_PORT);
+        assert_eq!(host.ssh_control_path, None);
+        assert!(!host.auto_refresh);
+        assert_eq!(host.refresh(), RefreshConfig::default());
+        assert_eq!(host.capabilities, HostCapabilities::default());
+    }
+
+    #[test]
+    fn test_deserialize_ignores_unknown_fields() {
+        let host: RemoteHost = serde_json::from_str(
+            r#"{"name": "db1", "hostname": "db.example.com", "username": "admin", "port": 2222,
+                "auth_type": {"Key": {"path": "~/.ssh/db"}}, "jump_host": "bastion"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(host.port, 2222);
+        assert_eq!(host.key_path(), Some(&PathBuf::from("~/.ssh/db")));
+    }
+
+    #[test]
+    fn test_deserialize_requires_hostname() {
+        assert!(serde_json::from_str::<RemoteHost>(
+            r#"{"name": "web1", "username": "deploy", "auth_type": "Password"}"#
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_migrate_v1_hosts_config() {
+        let v1 = r#"{"web1": {"name": "web1", "hostname": "example.com",
+                     "username": "deploy", "auth_type": "Password"}}"#;
+        let config = HostsConfig::from_json(v1).unwrap();
+
+        let expected = RemoteHost::new(
+            "web1".to_string(),
+            "example.com".to_string(),
+            "deploy".to_string(),
+            AuthType::Password,
+        );
+        assert_eq!(config.config_version, HOSTS_CONFIG_VERSION);
+        assert_eq!(config.hosts.get("web1"), Some(&expected));
+        assert_eq!(config.hosts["web1"].port, 22);
+    }
+
+    #[test]
+    fn test_hosts_config_round_trip() {
+        let host = RemoteHost::new(
+            "db1".to_string(),
+            "db.example.com".to_string(),
+            "admin".to_string(),
+            AuthType::Agent,
+        )
+        .with_port(2222);
+        let config = HostsConfig::new(HashMap::from([("db1".to_string(), host.clone())]));
+
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded = HostsConfig::from_json(&json).unwrap();
+        assert_eq!(loaded.config_version, HOSTS_CONFIG_VERSION);
+        assert_eq!(loaded.hosts["db1"], host);
+    }
+
+    #[test]
+    fn test_matches_search() {
+        let host = RemoteHost::new(
+            "Web1".to_string(),
+            "web1.example.com".to_string(),
+            "deploy".to_string(),
+            AuthType::Agent,
+        );
+        assert!(host.matches_search(""));
+        assert!(host.matches_search("web1"));
+        assert!(host.matches_search("EXAMPLE"));
+        assert!(host.matches_search("dep"));
+        assert!(!host.matches_search("db"));
+    }
+
+    #[test]
+    fn test_merge_hosts() {
+        let host = |name: &str, hostname: &str| {
+            (
+                name.to_string(),
+                RemoteHost::new(
+                    name.to_string(),
+                    hostname.to_string(),
+                    "deploy".to_string(),
+                    AuthType::Agent,
+                ),
+            )
+        };
+        let existing = HashMap::from([host("web1", "old.example.com")]);
+        let imported = HashMap::from([
+            host("web1", "new.example.com"),
+            host("db1", "db.example.com"),
+        ]);
+        assert_eq!(conflicting_host_names(&existing, &imported), ["web1"]);
+
+        let mut skipped = existing.clone();
+        assert_eq!(merge_hosts(&mut skipped, imported.clone(), false), 1);
+        assert_eq!(skipped["web1"].hostname, "old.example.com");
+        assert!(skipped.contains_key("db1"));
+
+        let mut replaced = existing;
+        assert_eq!(merge_hosts(&mut replaced, imported, true), 2);
+        assert_eq!(replaced["web1"].hostname, "new.example.com");
+    }
+
+    #[test]
+    fn test_known_fields_match_serialized_fields() {
+        let host = RemoteHost::new(
+            "web1".to_string(),
+            "example.com".to_string(),
+            "deploy".to_string(),
+            AuthType::Password,
+        );
+        let value = serde_json::to_value(&host).unwrap();
+        let mut fields: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort_unstable();
+
+        let mut known = REMOTE_HOST_FIELDS.to_vec();
+        known.sort_unstable();
+        assert_eq!(fields, known);
+
+        let round_trip: RemoteHost = serde_json::from_value(value).unwrap();
+        assert_eq!(round_trip.hostname, "example.com");
+    }
+
+    #[test]
+    fn test_command_history() {
+        let mut history = CommandHistory::default();
+        for i in 0..COMMAND_HISTORY_CAPACITY + 2 {
+            history.record(&format!("uptime {}", i));
+        }
+        history.record("uptime 5");
+
+        assert_eq!(history.commands.len(), COMMAND_HISTORY_CAPACITY);
+        assert_eq!(history.commands[0], "uptime 5");
+        assert_eq!(history.commands[1], "uptime 11");
+        assert_eq!(
+            history.commands.iter().filter(|c| *c == "uptime 5").count(),
+            1
+        );
+        assert!(!history.commands.contains(&"uptime 0".to_string()));
+    }
+
+    #[test]
+    fn test_host_connection_state() {
+        assert_eq!(
+            HostConnectionState::default(),
+            HostConnectionState::Disconnected
+        );
+        assert_eq!(HostConnectionState::Connecting.indicator(), "🟡");
+        assert_eq!(HostConnectionState::Connected.to_string(), "Connected");
+    }
+
+    #[test]
+    fn test_connect_error_classification() {
+        let timeout = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut));
+        assert_eq!(
+            ConnectionEventType::from_connect_error(&timeout),
+            ConnectionEventType::Timeout
+        );
+
+        let auth = anyhow!("Authentication failed for user@example.com");
+        assert_eq!(
+            ConnectionEventType::from_connect_error(&auth),
+            ConnectionEventType::AuthFailed
+        );
+
+        let refused = anyhow!("Connection refused");
+        assert_eq!(
+            ConnectionEventType::from_connect_error(&refused),
+            ConnectionEventType::Disconnected("Connection refused".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_capabilities() {
+        let capabilities = HostCapabilities::parse(
+            "sudo=0\njournalctl=1\nsystemd_run=0\nsystemd=systemd 252 (252.22-1~deb12u1)\n",
+        );
+        assert_eq!(
+            capabilities,
+            HostCapabilities {
+                sudo_available: false,
+                systemd_version: 252,
+                journalctl_available: true,
+                systemd_run_available: false,
+            }
+        );
+
+        assert_eq!(HostCapabilities::parse(""), HostCapabilities::default());
+    }
 }
@@ -1,12 +1,23 @@
 use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command as TokioCommand;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::remote_host::{HostCapabilities, DETECT_CAPABILITIES_COMMAND};
+use crate::utils::format::parse_systemd_timestamp;
+use crate::utils::ssh::HostConnection;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceInfo {
@@ -17,6 +28,28 @@ pub struct ServiceInfo {
     pub active: bool,
     pub load_state: String,
     pub sub_state: String,
+    /// When the unit last entered the active state, if known
+    pub active_since: Option<DateTime<Utc>>,
+    /// The `Condition*=` or `Assert*=` setting that kept the unit from
+    /// starting, e.g. `ConditionPathExists=/etc/foo`
+    #[serde(default)]
+    pub failed_condition: Option<String>,
+    /// When a timer unit elapses next, as systemd prints it
+    #[serde(default)]
+    pub next_trigger: Option<String>,
+    /// When a timer unit last elapsed, as systemd prints it
+    #[serde(default)]
+    pub last_trigger: Option<String>,
+    /// Addresses and paths a socket unit listens on, with their type,
+    /// e.g. `/run/foo.sock (Stream)`
+    #[serde(default)]
+    pub listen: Vec<String>,
+    /// Memory in use, filled in separately by `get_resource_usage_batch`
+    #[serde(default)]
+    pub memory_bytes: Option<u64>,
+    /// CPU time used since the unit started, filled in like `memory_bytes`
+    #[serde(default)]
+    pub cpu_ns: Option<u64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,6 +57,13 @@ pub enum ServiceStatus {
     Active,
     Inactive,
     Failed,
+    /// Skipped because a `Condition*=` or `Assert*=` check didn't hold
+    ConditionFailed,
+    Activating,
+    Deactivating,
+    Reloading,
+    /// The unit file is linked to `/dev/null`, so the service can't be started
+    Masked,
     Unknown,
 }
 
@@ -33,6 +73,11 @@ impl fmt::Display for ServiceStatus {
             ServiceStatus::Active => write!(f, "Active"),
             ServiceStatus::Inactive => write!(f, "Inactive"),
             ServiceStatus::Failed => write!(f, "Failed"),
+            ServiceStatus::ConditionFailed => write!(f, "Condition failed"),
+            ServiceStatus::Activating => write!(f, "Activating"),
+            ServiceStatus::Deactivating => write!(f, "Deactivating"),
+            ServiceStatus::Reloading => write!(f, "Reloading"),
+            ServiceStatus::Masked => write!(f, "Masked"),
             ServiceStatus::Unknown => write!(f, "Unknown"),
         }
     }
@@ -44,405 +89,3034 @@ impl From<&str> for ServiceStatus {
             "active" => ServiceStatus::Active,
             "inactive" => ServiceStatus::Inactive,
             "failed" => ServiceStatus::Failed,
+            "condition failed" => ServiceStatus::ConditionFailed,
+            "activating" => ServiceStatus::Activating,
+            "deactivating" => ServiceStatus::Deactivating,
+            "reloading" => ServiceStatus::Reloading,
+            "masked" => ServiceStatus::Masked,
             _ => ServiceStatus::Unknown,
         }
     }
 }
 
-pub struct ServiceManager {
-    runtime: Arc<Runtime>,
-}
+impl ServiceStatus {
+    pub const ALL: [ServiceStatus; 9] = [
+        ServiceStatus::Active,
+        ServiceStatus::Inactive,
+        ServiceStatus::Failed,
+        ServiceStatus::ConditionFailed,
+        ServiceStatus::Activating,
+        ServiceStatus::Deactivating,
+        ServiceStatus::Reloading,
+        ServiceStatus::Masked,
+        ServiceStatus::Unknown,
+    ];
 
-impl ServiceManager {
-    pub fn new(runtime: Arc<Runtime>) -> Self {
-        Self { runtime }
+    /// Symbol shown before the status for states that need to stand out
+    pub fn badge(&self) -> Option<&'static str> {
+        match self {
+            ServiceStatus::Masked => Some("🚫"),
+            _ => None,
+        }
     }
 
-    pub async fn list_local_services(&self, show_inactive: bool) -> Result<Vec<ServiceInfo>> {
-        let mut cmd = TokioCommand::new("systemctl");
-        cmd.args(&["list-units", "--type=service", "--no-pager"])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        if show_inactive {
-            cmd.arg("--all");
+    /// The status of a unit from its `ActiveState` and `LoadState`. A masked
+    /// unit reports `inactive`, which hides why it won't start.
+    pub fn for_unit(active_state: &str, load_state: &str) -> Self {
+        if load_state == "masked" {
+            ServiceStatus::Masked
+        } else {
+            ServiceStatus::from(active_state)
         }
+    }
+}
 
-        let output = cmd.output().await?;
+/// Exit codes `systemctl` uses to report why a command failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemctlExitCode {
+    Success,
+    /// Generic or unspecified failure, including permission problems
+    Failure,
+    /// The unit is not active; expected for `is-active` on a stopped unit
+    NotActive,
+    /// No such unit
+    NoSuchUnit,
+    Other(i32),
+}
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to list services: {}", stderr));
+impl From<i32> for SystemctlExitCode {
+    fn from(code: i32) -> Self {
+        match code {
+            0 => SystemctlExitCode::Success,
+            1 => SystemctlExitCode::Failure,
+            3 => SystemctlExitCode::NotActive,
+            4 => SystemctlExitCode::NoSuchUnit,
+            other => SystemctlExitCode::Other(other),
         }
-
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        self.parse_service_list(&stdout)
     }
+}
 
-    pub async fn get_service_status(&self, service_name: &str) -> Result<ServiceInfo> {
-        let cmd = TokioCommand::new("systemctl")
-            .args(&["show", service_name, "--no-pager"])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        if !cmd.status.success() {
-            let stderr = String::from_utf8_lossy(&cmd.stderr);
-            return Err(anyhow!("Failed to get service status: {}", stderr));
-        }
+#[derive(Debug, thiserror::Error)]
+pub enum ServiceManagerError {
+    #[error("Service not found: {0}")]
+    ServiceNotFound(String),
+    #[error("Service is not active: {0}")]
+    NotActive(String),
+    #[error("systemctl command failed ({code:?}): {stderr}")]
+    CommandFailed {
+        code: SystemctlExitCode,
+        stderr: String,
+    },
+}
 
-        let stdout = String::from_utf8_lossy(&cmd.stdout);
-        self.parse_service_status(service_name, &stdout)
-    }
+/// Which systemd instance a unit belongs to: the system manager, or the
+/// user's own manager running session services such as pipewire
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitScope {
+    #[default]
+    System,
+    User,
+}
 
-    pub async fn start_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["start", service_name]).await
+impl UnitScope {
+    /// Arguments selecting this scope's manager, the same for `systemctl`
+    /// and `journalctl`
+    pub fn args(self) -> &'static [&'static str] {
+        match self {
+            UnitScope::System => &[],
+            UnitScope::User => &["--user"],
+        }
     }
 
-    pub async fn stop_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["stop", service_name]).await
+    /// Directory the app writes this scope's unit files and drop-ins to:
+    /// `/etc/systemd/system`, or `~/.config/systemd/user` for the user
+    pub fn unit_dir(self) -> Result<PathBuf> {
+        match self {
+            UnitScope::System => Ok(PathBuf::from("/etc/systemd/system")),
+            UnitScope::User => {
+                let config_dir =
+                    dirs::config_dir().ok_or_else(|| anyhow!("Could not find config directory"))?;
+                Ok(config_dir.join("systemd").join("user"))
+            }
+        }
     }
+}
 
-    pub async fn restart_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["restart", service_name]).await
-    }
+/// Operations that can be performed on a single unit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ServiceAction {
+    Start,
+    Stop,
+    Restart,
+    Enable,
+    Disable,
+    Reload,
+    /// Link the unit to `/dev/null` so it can't be started at all
+    Mask,
+    Unmask,
+}
 
-    pub async fn enable_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["enable", service_name]).await
+impl ServiceAction {
+    /// The `systemctl` verb for this action
+    pub fn command(&self) -> &'static str {
+        match self {
+            ServiceAction::Start => "start",
+            ServiceAction::Stop => "stop",
+            ServiceAction::Restart => "restart",
+            ServiceAction::Enable => "enable",
+            ServiceAction::Disable => "disable",
+            ServiceAction::Reload => "reload",
+            ServiceAction::Mask => "mask",
+            ServiceAction::Unmask => "unmask",
+        }
     }
 
-    pub async fn disable_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["disable", service_name]).await
+    /// Progressive form used in status messages, e.g. "Starting"
+    pub fn progress_label(&self) -> &'static str {
+        match self {
+            ServiceAction::Start => "Starting",
+            ServiceAction::Stop => "Stopping",
+            ServiceAction::Restart => "Restarting",
+            ServiceAction::Enable => "Enabling",
+            ServiceAction::Disable => "Disabling",
+            ServiceAction::Reload => "Reloading",
+            ServiceAction::Mask => "Masking",
+            ServiceAction::Unmask => "Unmasking",
+        }
     }
 
-    pub async fn reload_service(&self, service_name: &str) -> Result<()> {
-        self.run_systemctl_command(&["reload", service_name]).await
+    /// Past tense used to report success, e.g. "started"
+    pub fn past_tense(&self) -> &'static str {
+        match self {
+            ServiceAction::Start => "started",
+            ServiceAction::Stop => "stopped",
+            ServiceAction::Restart => "restarted",
+            ServiceAction::Enable => "enabled",
+            ServiceAction::Disable => "disabled",
+            ServiceAction::Reload => "reloaded",
+            ServiceAction::Mask => "masked",
+            ServiceAction::Unmask => "unmasked",
+        }
     }
 
-    pub async fn get_service_logs(&self, service_name: &str, lines: Option<u32>) -> Result<String> {
-        let mut cmd = TokioCommand::new("journalctl");
-        cmd.args(&["-u", service_name, "--no-pager"]);
+    /// Whether the action makes sense for a service in `status`, e.g. not
+    /// starting one that is already active. Unknown states allow anything.
+    pub fn applies_to(&self, status: &ServiceStatus) -> bool {
+        use ServiceStatus::*;
 
-        if let Some(n) = lines {
-            cmd.args(&["-n", &n.to_string()]);
+        if *status == Unknown {
+            return true;
         }
-
-        let output = cmd
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to get service logs: {}", stderr));
+        match self {
+            ServiceAction::Start => !matches!(status, Active | Activating | Reloading | Masked),
+            ServiceAction::Stop => !matches!(status, Inactive | Failed | ConditionFailed | Masked),
+            ServiceAction::Reload => matches!(status, Active),
+            ServiceAction::Restart
+            | ServiceAction::Enable
+            | ServiceAction::Disable
+            | ServiceAction::Mask => *status != Masked,
+            ServiceAction::Unmask => *status == Masked,
         }
-
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
-    pub async fn daemon_reload(&self) -> Result<()> {
-        self.run_systemctl_command(&["daemon-reload"]).await
+    /// Question asked before running the action on `target` (a service
+    /// name, or e.g. "3 services"), or `None` when it is safe to run
+    /// without asking
+    pub fn confirmation_message(&self, target: &str) -> Option<String> {
+        let consequence = match self {
+            ServiceAction::Stop => "Running processes will be terminated.",
+            ServiceAction::Disable => "Disabled services no longer start at boot.",
+            ServiceAction::Mask => "Masked services cannot be started until they are unmasked.",
+            _ => return None,
+        };
+        Some(format!("{} {}? {}", self, target, consequence))
     }
 
-    pub async fn create_service_file(&self, service_name: &str, content: &str) -> Result<()> {
-        let service_path = format!("/etc/systemd/system/{}.service", service_name);
-
-        // Write service file (requires sudo)
-        let mut cmd = TokioCommand::new("sudo");
-        cmd.args(&["tee", &service_path])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        let mut child = cmd.spawn()?;
+    /// Reports the outcome of running the action on several services, e.g.
+    /// "3 of 5 services started successfully"
+    pub fn batch_summary(&self, total: usize, failed: usize) -> String {
+        format!(
+            "{} of {} service{} {} successfully",
+            total.saturating_sub(failed),
+            total,
+            if total == 1 { "" } else { "s" },
+            self.past_tense()
+        )
+    }
+}
 
-        if let Some(stdin) = child.stdin.as_mut() {
-            use tokio::io::AsyncWriteExt;
-            stdin.write_all(content.as_bytes()).await?;
+impl fmt::Display for ServiceAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceAction::Start => write!(f, "Start"),
+            ServiceAction::Stop => write!(f, "Stop"),
+            ServiceAction::Restart => write!(f, "Restart"),
+            ServiceAction::Enable => write!(f, "Enable"),
+            ServiceAction::Disable => write!(f, "Disable"),
+            ServiceAction::Reload => write!(f, "Reload"),
+            ServiceAction::Mask => write!(f, "Mask"),
+            ServiceAction::Unmask => write!(f, "Unmask"),
         }
+    }
+}
 
-        let output = child.wait_with_output().await?;
+/// How many actions are remembered per service
+pub const ACTION_LOG_CAPACITY: usize = 20;
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Failed to create service file: {}", stderr));
-        }
+/// An action run on a service from the app, and how it went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActionEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: ServiceAction,
+    pub success: bool,
+    pub error: Option<String>,
+}
 
-        // Reload systemd after creating new service
-        self.daemon_reload().await?;
+/// The actions most recently run on each service, oldest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionLog {
+    pub entries: HashMap<String, VecDeque<ActionEntry>>,
+}
 
-        Ok(())
+impl ActionLog {
+    /// Records the outcome of `action` now, dropping the service's oldest
+    /// entry once it has `ACTION_LOG_CAPACITY`
+    pub fn record(&mut self, service_name: &str, action: ServiceAction, result: &Result<()>) {
+        self.push(
+            service_name,
+            ActionEntry {
+                timestamp: Utc::now(),
+                action,
+                success: result.is_ok(),
+                error: result.as_ref().err().map(|e| e.to_string()),
+            },
+        );
     }
 
-    async fn run_systemctl_command(&self, args: &[&str]) -> Result<()> {
-        let cmd = TokioCommand::new("systemctl")
-            .args(args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        if !cmd.status.success() {
-            let stderr = String::from_utf8_lossy(&cmd.stderr);
-            return Err(anyhow!("systemctl command failed: {}", stderr));
+    fn push(&mut self, service_name: &str, entry: ActionEntry) {
+        let entries = self.entries.entry(service_name.to_string()).or_default();
+        if entries.len() == ACTION_LOG_CAPACITY {
+            entries.pop_front();
         }
+        entries.push_back(entry);
+    }
 
-        Ok(())
+    /// Up to `count` of the service's latest actions, newest first
+    pub fn recent(&self, service_name: &str, count: usize) -> Vec<&ActionEntry> {
+        self.entries
+            .get(service_name)
+            .map(|entries| entries.iter().rev().take(count).collect())
+            .unwrap_or_default()
     }
+}
 
-    fn parse_service_list(&self, output: &str) -> Result<Vec<ServiceInfo>> {
-        let mut services = Vec::new();
-        let lines: Vec<&str> = output.lines().collect();
+/// Which journal entries of a unit to read
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogOptions {
+    /// Only the most recent entries, up to this many
+    pub lines: Option<u32>,
+    /// Boot ID to read from, rather than every boot
+    pub boot: Option<String>,
+    /// RFC 3339 time of the earliest entry
+    pub since: Option<String>,
+    /// RFC 3339 time of the latest entry
+    pub until: Option<String>,
+}
 
-        // Skip header lines and find the start of service listings
-        let mut start_idx = 0;
-        for (i, line) in lines.iter().enumerate() {
-            if line.starts_with("UNIT") {
-                start_idx = i + 1;
-                break;
-            }
+impl LogOptions {
+    pub fn lines(lines: u32) -> Self {
+        Self {
+            lines: Some(lines),
+            ..Default::default()
         }
+    }
 
-        for line in lines.iter().skip(start_idx) {
-            if line.trim().is_empty() || line.starts_with("LOAD") {
-                break;
-            }
+    /// Limits these options to the most recent `lines` entries, unless a
+    /// line count or time range is already set
+    pub fn with_default_lines(mut self, lines: u32) -> Self {
+        if self.since.is_none() && self.until.is_none() {
+            self.lines.get_or_insert(lines);
+        }
+        self
+    }
 
-            if let Some(service) = self.parse_service_line(line) {
-                services.push(service);
+    /// Arguments selecting these entries, to pass to `journalctl -u <unit>`.
+    /// Times are converted to UTC, which journalctl reads on any host
+    /// regardless of its time zone.
+    pub fn journalctl_args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        if let Some(lines) = self.lines {
+            args.push("-n".to_string());
+            args.push(lines.to_string());
+        }
+        if let Some(boot) = &self.boot {
+            args.push(format!("--boot={}", boot));
+        }
+        for (flag, time) in [("--since", &self.since), ("--until", &self.until)] {
+            if let Some(time) = time {
+                let time = DateTime::parse_from_rfc3339(time.trim())
+                    .map_err(|e| anyhow!("Invalid time {:?}: {}", time, e))?;
+                args.push(flag.to_string());
+                args.push(
+                    time.with_timezone(&Utc)
+                        .format("%Y-%m-%d %H:%M:%S UTC")
+                        .to_string(),
+                );
             }
         }
-
-        Ok(services)
+        Ok(args)
     }
+}
 
-    fn parse_service_line(&self, line: &str) -> Option<ServiceInfo> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            return None;
-        }
+/// A boot recorded in the journal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootEntry {
+    pub boot_id: String,
+    /// 0 for the current boot, -1 for the one before, and so on
+    pub index: i32,
+    pub first_entry: DateTime<Utc>,
+    pub last_entry: DateTime<Utc>,
+}
 
-        let name = parts[0].trim_end_matches(".service").to_string();
-        let load_state = parts[1].to_string();
-        let active_state = parts[2].to_string();
-        let sub_state = parts[3].to_string();
+/// A journal record of an operation performed on a unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user_uid: Option<u32>,
+    pub action: String,
+}
 
-        let description = if parts.len() > 4 {
-            Some(parts[4..].join(" "))
-        } else {
-            None
-        };
+/// Every file that contributes to a unit's configuration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnitFilePaths {
+    pub fragment: Option<PathBuf>,
+    pub dropin_paths: Vec<PathBuf>,
+    pub source_path: Option<PathBuf>,
+}
 
-        let status = ServiceStatus::from(active_state.as_str());
-        let active = active_state == "active";
+/// Environment a service is started with, from its `Environment=` and
+/// `EnvironmentFile=` settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServiceEnvironment {
+    /// Variables set directly in the unit, in order
+    pub variables: Vec<(String, String)>,
+    /// Files variables are read from; optional ones are marked `(optional)`
+    pub files: Vec<String>,
+}
 
-        Some(ServiceInfo {
-            name,
-            status,
-            description,
-            enabled: false, // This would need a separate query
-            active,
-            load_state,
-            sub_state,
-        })
-    }
+/// Directories systemd searches for environment generators, highest priority first
+pub const ENVIRONMENT_GENERATOR_DIRECTORIES: &[&str] = &[
+    "/etc/systemd/system-environment-generators",
+    "/run/systemd/system-environment-generators",
+    "/usr/local/lib/systemd/system-environment-generators",
+    "/usr/lib/systemd/system-environment-generators",
+];
 
-    fn parse_service_status(&self, service_name: &str, output: &str) -> Result<ServiceInfo> {
-        let mut properties = HashMap::new();
+/// An executable that systemd runs to set environment variables for all services
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorInfo {
+    pub name: String,
+    pub path: PathBuf,
+    /// The link target for symlinks, otherwise the script's first comment
+    pub description: String,
+    pub is_symlink: bool,
+    /// Whether systemd will run it: executable and not masked with `/dev/null`
+    pub enabled: bool,
+}
 
-        for line in output.lines() {
-            if let Some((key, value)) = line.split_once('=') {
-                properties.insert(key.trim(), value.trim());
-            }
+/// How a unit gets a service started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerType {
+    Socket,
+    Timer,
+    Path,
+    WantedBy,
+}
+
+impl fmt::Display for TriggerType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerType::Socket => write!(f, "socket"),
+            TriggerType::Timer => write!(f, "timer"),
+            TriggerType::Path => write!(f, "path"),
+            TriggerType::WantedBy => write!(f, "wanted by"),
         }
+    }
+}
 
-        let active_state = properties.get("ActiveState").unwrap_or(&"unknown");
-        let sub_state = properties.get("SubState").unwrap_or(&"unknown");
-        let load_state = properties.get("LoadState").unwrap_or(&"unknown");
-        let unit_file_state = properties.get("UnitFileState").unwrap_or(&"unknown");
-        let description = properties.get("Description").map(|s| s.to_string());
+/// A unit that can start a service
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerInfo {
+    pub unit_name: String,
+    pub trigger_type: TriggerType,
+}
 
-        let status = ServiceStatus::from(*active_state);
-        let active = *active_state == "active";
-        let enabled = *unit_file_state == "enabled";
+/// Commands systemd runs for a service, grouped by phase in execution order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExecSteps {
+    pub condition: Vec<String>,
+    pub pre: Vec<String>,
+    pub start: Vec<String>,
+    pub post: Vec<String>,
+    pub stop_post: Vec<String>,
+}
 
-        Ok(ServiceInfo {
-            name: service_name.to_string(),
-            status,
-            description,
-            enabled,
-            active,
-            load_state: load_state.to_string(),
-            sub_state: sub_state.to_string(),
-        })
+impl ExecSteps {
+    /// Each phase as `(property, commands)`, in the order systemd runs them
+    pub fn phases(&self) -> [(&'static str, &[String]); 5] {
+        [
+            ("ExecCondition", &self.condition),
+            ("ExecStartPre", &self.pre),
+            ("ExecStart", &self.start),
+            ("ExecStartPost", &self.post),
+            ("ExecStopPost", &self.stop_post),
+        ]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.phases()
+            .iter()
+            .all(|(_, commands)| commands.is_empty())
     }
 }
 
-// Remote service management
-pub struct RemoteServiceManager {
-    session: ssh2::Session,
+/// Settings for a `.timer` unit that activates a service
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TimerSpec {
+    pub service: String,
+    /// Calendar event expression, e.g. `Mon..Fri *-*-* 02:00:00`
+    pub on_calendar: Option<String>,
+    /// Time span after boot, e.g. `15min`
+    pub on_boot_sec: Option<String>,
+    /// Catch up on runs missed while the machine was off
+    pub persistent: bool,
+    pub randomized_delay_sec: Option<String>,
 }
 
-impl RemoteServiceManager {
-    pub fn new(session: ssh2::Session) -> Self {
-        Self { session }
+impl TimerSpec {
+    /// Name of the timer unit, matching the service it activates
+    pub fn unit_name(&self) -> String {
+        let service = self.service.trim();
+        format!(
+            "{}.timer",
+            service.strip_suffix(".service").unwrap_or(service)
+        )
     }
+}
 
-    pub async fn list_services(&self, show_inactive: bool) -> Result<Vec<ServiceInfo>> {
-        let mut command = "systemctl list-units --type=service --no-pager".to_string();
-        if show_inactive {
-            command.push_str(" --all");
-        }
+/// Kinds of service the new service wizard can create, matching the
+/// `Type=` systemd starts them with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ServiceTemplate {
+    #[default]
+    Simple,
+    Oneshot,
+    Forking,
+    Notify,
+    DBus,
+}
 
-        let output = self.execute_command(&command).await?;
-        self.parse_service_list(&output)
-    }
+impl ServiceTemplate {
+    pub const ALL: [ServiceTemplate; 5] = [
+        ServiceTemplate::Simple,
+        ServiceTemplate::Oneshot,
+        ServiceTemplate::Forking,
+        ServiceTemplate::Notify,
+        ServiceTemplate::DBus,
+    ];
 
-    pub async fn get_service_status(&self, service_name: &str) -> Result<ServiceInfo> {
-        let command = format!("systemctl show {} --no-pager", service_name);
-        let output = self.execute_command(&command).await?;
-        self.parse_service_status(service_name, &output)
+    pub fn label(self) -> &'static str {
+        match self {
+            ServiceTemplate::Simple => "Simple",
+            ServiceTemplate::Oneshot => "Oneshot",
+            ServiceTemplate::Forking => "Forking",
+            ServiceTemplate::Notify => "Notify",
+            ServiceTemplate::DBus => "DBus",
+        }
     }
 
-    pub async fn start_service(&self, service_name: &str) -> Result<()> {
-        let command = format!("sudo systemctl start {}", service_name);
-        self.execute_command(&command).await?;
-        Ok(())
+    /// One line on when to pick the template
+    pub fn summary(self) -> &'static str {
+        match self {
+            ServiceTemplate::Simple => "A long-running process that stays in the foreground",
+            ServiceTemplate::Oneshot => "A command that runs to completion, such as a setup script",
+            ServiceTemplate::Forking => "A traditional daemon that forks into the background",
+            ServiceTemplate::Notify => "A process that tells systemd when it is ready (sd_notify)",
+            ServiceTemplate::DBus => "A process that is ready once it takes a D-Bus name",
+        }
     }
 
-    pub async fn stop_service(&self, service_name: &str) -> Result<()> {
-        let command = format!("sudo systemctl stop {}", service_name);
-        self.execute_command(&command).await?;
-        Ok(())
+    fn template(self) -> &'static str {
+        match self {
+            ServiceTemplate::Simple => SIMPLE_SERVICE_TEMPLATE,
+            ServiceTemplate::Oneshot => ONESHOT_SERVICE_TEMPLATE,
+            ServiceTemplate::Forking => FORKING_SERVICE_TEMPLATE,
+            ServiceTemplate::Notify => NOTIFY_SERVICE_TEMPLATE,
+            ServiceTemplate::DBus => DBUS_SERVICE_TEMPLATE,
+        }
     }
+}
 
-    pub async fn restart_service(&self, service_name: &str) -> Result<()> {
-        let command = format!("sudo systemctl restart {}", service_name);
-        self.execute_command(&command).await?;
-        Ok(())
-    }
+/// Settings for a new `.service` unit, filled into its template
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServiceSpec {
+    pub name: String,
+    pub description: String,
+    pub template: ServiceTemplate,
+    pub exec_start: String,
+    pub user: Option<String>,
+    pub working_directory: Option<String>,
+    /// One of `RESTART_POLICIES`
+    pub restart: String,
+    /// Oneshot only: count the service as active once the command exits
+    pub remain_after_exit: bool,
+    /// Forking only: where the daemon writes its main PID
+    pub pid_file: Option<String>,
+    /// DBus only: the bus name the service takes
+    pub bus_name: Option<String>,
+}
 
-    pub async fn enable_service(&self, service_name: &str) -> Result<()> {
-        let command = format!("sudo systemctl enable {}", service_name);
-        self.execute_command(&command).await?;
-        Ok(())
+impl ServiceSpec {
+    pub fn unit_name(&self) -> String {
+        unit_file_name(self.name.trim())
     }
+}
 
-    pub async fn disable_service(&self, service_name: &str) -> Result<()> {
-        let command = format!("sudo systemctl disable {}", service_name);
-        self.execute_command(&command).await?;
-        Ok(())
-    }
+/// Values the wizard offers for `Restart=`
+pub const RESTART_POLICIES: &[&str] = &[
+    "no",
+    "on-failure",
+    "on-abnormal",
+    "on-abort",
+    "on-success",
+    "always",
+];
 
-    pub async fn get_service_logs(&self, service_name: &str, lines: Option<u32>) -> Result<String> {
-        let mut command = format!("journalctl -u {} --no-pager", service_name);
-        if let Some(n) = lines {
-            command.push_str(&format!(" -n {}", n));
-        }
+const SIMPLE_SERVICE_TEMPLATE: &str = "\
+[Unit]
+Description={DESCRIPTION}
 
-        self.execute_command(&command).await
-    }
+[Service]
+Type=simple
+ExecStart={EXEC_START}
+User={USER}
+WorkingDirectory={WORKING_DIRECTORY}
+Restart={RESTART}
 
-    async fn execute_command(&self, command: &str) -> Result<String> {
-        // This is a simplified version - in practice you'd need proper async SSH handling
-        // For now, we'll use a blocking approach wrapped in spawn_blocking
-        let command = command.to_string();
+[Install]
+WantedBy=multi-user.target
+";
 
-        tokio::task::spawn_blocking(move || {
-            // SSH command execution would go here
-            // This is a placeholder implementation
-            Ok("".to_string())
-        })
-        .await?
-    }
+const ONESHOT_SERVICE_TEMPLATE: &str = "\
+[Unit]
+Description={DESCRIPTION}
 
-    fn parse_service_list(&self, output: &str) -> Result<Vec<ServiceInfo>> {
-        // Same parsing logic as local service manager
-        let mut services = Vec::new();
-        let lines: Vec<&str> = output.lines().collect();
+[Service]
+Type=oneshot
+ExecStart={EXEC_START}
+RemainAfterExit={REMAIN_AFTER_EXIT}
+User={USER}
+WorkingDirectory={WORKING_DIRECTORY}
+Restart={RESTART}
 
-        let mut start_idx = 0;
-        for (i, line) in lines.iter().enumerate() {
-            if line.starts_with("UNIT") {
-                start_idx = i + 1;
-                break;
-            }
-        }
+[Install]
+WantedBy=multi-user.target
+";
 
-        for line in lines.iter().skip(start_idx) {
-            if line.trim().is_empty() || line.starts_with("LOAD") {
-                break;
-            }
+const FORKING_SERVICE_TEMPLATE: &str = "\
+[Unit]
+Description={DESCRIPTION}
 
-            if let Some(service) = self.parse_service_line(line) {
-                services.push(service);
-            }
-        }
+[Service]
+Type=forking
+ExecStart={EXEC_START}
+PIDFile={PID_FILE}
+User={USER}
+WorkingDirectory={WORKING_DIRECTORY}
+Restart={RESTART}
 
-        Ok(services)
-    }
+[Install]
+WantedBy=multi-user.target
+";
 
-    fn parse_service_line(&self, line: &str) -> Option<ServiceInfo> {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() < 4 {
-            return None;
-        }
+const NOTIFY_SERVICE_TEMPLATE: &str = "\
+[Unit]
+Description={DESCRIPTION}
 
-        let name = parts[0].trim_end_matches(".service").to_string();
-        let load_state = parts[1].to_string();
-        let active_state = parts[2].to_string();
-        let sub_state = parts[3].to_string();
+[Service]
+Type=notify
+ExecStart={EXEC_START}
+User={USER}
+WorkingDirectory={WORKING_DIRECTORY}
+Restart={RESTART}
 
-        let description = if parts.len() > 4 {
-            Some(parts[4..].join(" "))
-        } else {
-            None
-        };
+[Install]
+WantedBy=multi-user.target
+";
 
-        let status = ServiceStatus::from(active_state.as_str());
-        let active = active_state == "active";
+const DBUS_SERVICE_TEMPLATE: &str = "\
+[Unit]
+Description={DESCRIPTION}
 
-        Some(ServiceInfo {
-            name,
-            status,
-            description,
-            enabled: false,
-            active,
-            load_state,
-            sub_state,
-        })
-    }
+[Service]
+Type=dbus
+BusName={BUS_NAME}
+ExecStart={EXEC_START}
+User={USER}
+WorkingDirectory={WORKING_DIRECTORY}
+Restart={RESTART}
 
-    fn parse_service_status(&self, service_name: &str, output: &str) -> Result<ServiceInfo> {
-        let mut properties = HashMap::new();
+[Install]
+WantedBy=multi-user.target
+";
 
-        for line in output.lines() {
-            if let Some((key, value)) = line.split_once('=') {
-                properties.insert(key.trim(), value.trim());
-            }
+/// Resource limits systemd applies to a service; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceLimits {
+    pub nofile: Option<u64>,
+    pub nofile_soft: Option<u64>,
+    pub nproc: Option<u64>,
+    pub core: Option<u64>,
+    pub stack: Option<u64>,
+    pub tasks_max: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Each limit as `(property, value, typical system default)`. Limits
+    /// without a meaningful default, such as `LimitCORE`, have `None`.
+    pub fn entries(&self) -> [(&'static str, Option<u64>, Option<u64>); 6] {
+        [
+            ("LimitNOFILE", self.nofile, Some(524_288)),
+            ("LimitNOFILESoft", self.nofile_soft, Some(1024)),
+            ("LimitNPROC", self.nproc, Some(4096)),
+            ("LimitCORE", self.core, None),
+            ("LimitSTACK", self.stack, Some(8 * 1024 * 1024)),
+            ("TasksMax", self.tasks_max, Some(4915)),
+        ]
+    }
+}
+
+/// Whether a limit is stricter than the typical default; unlimited never is
+pub fn is_below_default(value: Option<u64>, typical: Option<u64>) -> bool {
+    matches!((value, typical), (Some(value), Some(typical)) if value < typical)
+}
+
+/// Resource counters systemd keeps for a unit with accounting enabled
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceUsage {
+    pub cpu_usage_nsec: Option<u64>,
+    pub memory_current: Option<u64>,
+}
+
+/// Number of samples a `ResourceTimeSeries` keeps before dropping the oldest
+const RESOURCE_HISTORY_LEN: usize = 120;
+
+/// Rolling history of a unit's CPU and memory usage
+#[derive(Debug, Clone, Default)]
+pub struct ResourceTimeSeries {
+    pub timestamps: Vec<Instant>,
+    /// CPU usage in percent of one core since the previous sample
+    pub cpu_values: Vec<f64>,
+    /// Memory usage in bytes
+    pub memory_values: Vec<u64>,
+    last_cpu_sample: Option<(Instant, u64)>,
+}
+
+impl ResourceTimeSeries {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sample taken at `at`. CPU usage is derived from the change in
+    /// `CPUUsageNSec` since the previous sample, so the first sample reads 0%.
+    pub fn record(&mut self, at: Instant, usage: &ResourceUsage) {
+        let cpu_percent = match (self.last_cpu_sample, usage.cpu_usage_nsec) {
+            (Some((last_at, last_nsec)), Some(nsec)) => {
+                let elapsed = at.duration_since(last_at).as_nanos() as f64;
+                if elapsed > 0.0 {
+                    nsec.saturating_sub(last_nsec) as f64 / elapsed * 100.0
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        };
+        self.last_cpu_sample = usage.cpu_usage_nsec.map(|nsec| (at, nsec));
+
+        self.timestamps.push(at);
+        self.cpu_values.push(cpu_percent);
+        self.memory_values.push(usage.memory_current.unwrap_or(0));
+
+        if self.timestamps.len() > RESOURCE_HISTORY_LEN {
+            self.timestamps.remove(0);
+            self.cpu_values.remove(0);
+            self.memory_values.remove(0);
+        }
+    }
+}
+
+/// Journal line fragments that usually explain why a unit failed
+const FAILURE_MESSAGE_PATTERNS: &[&str] = &["error", "failed", "exit code"];
+
+/// Journal message fragments that mark an operation on a unit
+const AUDIT_MESSAGE_PATTERNS: &[&str] = &[
+    "start request",
+    "stop request",
+    "restart request",
+    "reload request",
+    "starting",
+    "stopping",
+    "reloading",
+];
+
+#[derive(Clone)]
+pub struct ServiceManager {
+    runtime: Arc<Runtime>,
+}
+
+impl ServiceManager {
+    pub fn new(runtime: Arc<Runtime>) -> Self {
+        Self { runtime }
+    }
+
+    pub async fn list_local_services(
+        &self,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        self.list_services_with("systemctl", show_inactive, scope)
+            .await
+    }
+
+    /// Timer units, with when each elapses next and last elapsed. Timers
+    /// keep their `.timer` suffix, so actions apply to the timer itself.
+    pub async fn list_local_timers(
+        &self,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        let mut timers = self
+            .list_units_with("systemctl", "timer", show_inactive, scope)
+            .await?;
+        if timers.is_empty() {
+            return Ok(timers);
+        }
+
+        let mut args = vec![
+            "show",
+            "--property=Id,NextElapseUSecRealtime,LastTriggerUSec",
+            "--no-pager",
+        ];
+        args.extend(timers.iter().map(|timer| timer.name.as_str()));
+        let output = self.run_systemctl_output(scope, None, &args).await?;
+        apply_timer_triggers(&mut timers, &output);
+
+        Ok(timers)
+    }
+
+    /// Socket units, with the addresses each listens on. Sockets keep their
+    /// `.socket` suffix, so actions apply to the socket itself.
+    pub async fn list_local_sockets(
+        &self,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        let mut sockets = self
+            .list_units_with("systemctl", "socket", show_inactive, scope)
+            .await?;
+        if sockets.is_empty() {
+            return Ok(sockets);
+        }
+
+        let mut args = vec!["show", "--property=Id,Listen", "--no-pager"];
+        args.extend(sockets.iter().map(|socket| socket.name.as_str()));
+        let output = self.run_systemctl_output(scope, None, &args).await?;
+        apply_socket_listens(&mut sockets, &output);
+
+        Ok(sockets)
+    }
+
+    /// Status of a socket unit, with the addresses it listens on
+    pub async fn get_socket_status(
+        &self,
+        socket_name: &str,
+        scope: UnitScope,
+    ) -> Result<ServiceInfo> {
+        let stdout = self
+            .run_systemctl_output(
+                scope,
+                Some(socket_name),
+                &["show", socket_name, "--no-pager"],
+            )
+            .await?;
+
+        let mut info = self.parse_service_status(socket_name, &stdout)?;
+        if info.load_state == "not-found" {
+            return Err(ServiceManagerError::ServiceNotFound(socket_name.to_string()).into());
+        }
+        info.listen = parse_listen_addresses(&stdout);
+
+        Ok(info)
+    }
+
+    /// Lists services with `systemctl`, found at `program`
+    async fn list_services_with(
+        &self,
+        program: impl AsRef<std::ffi::OsStr>,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        self.list_units_with(program, "service", show_inactive, scope)
+            .await
+    }
+
+    /// Lists units of `unit_type` with `systemctl`, found at `program`.
+    /// Inactive units are only listed by `systemctl` when asked for with `--all`.
+    async fn list_units_with(
+        &self,
+        program: impl AsRef<std::ffi::OsStr>,
+        unit_type: &str,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        let mut cmd = TokioCommand::new(program);
+        cmd.args(scope.args())
+            .args(["list-units", "--no-pager"])
+            .arg(format!("--type={}", unit_type))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        if show_inactive {
+            cmd.arg("--all");
+        }
+
+        let output = cmd.output().await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to list services: {}", stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        self.parse_service_list(&stdout)
+    }
+
+    /// Services that were skipped at their last start because a
+    /// `Condition*=` or `Assert*=` check didn't hold, with the unmet setting
+    pub async fn list_condition_failed_services(
+        &self,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                None,
+                &[
+                    "show",
+                    "--property=Id,Description,ActiveState,SubState,LoadState,UnitFileState,\
+                 ConditionResult,ConditionTimestamp,AssertResult,AssertTimestamp",
+                    "--no-pager",
+                    "*.service",
+                ],
+            )
+            .await?;
+
+        let mut services = parse_condition_failures(&output);
+        if services.is_empty() {
+            return Ok(services);
+        }
+
+        // The unmet setting is only reported by `systemctl status`, which
+        // exits non-zero for inactive units. One call covers every unit.
+        let status = TokioCommand::new("systemctl")
+            .args(scope.args())
+            .args(["status", "--no-pager", "--lines=0"])
+            .args(
+                services
+                    .iter()
+                    .map(|service| format!("{}.service", service.name)),
+            )
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+        let mut conditions = parse_unmet_conditions(&String::from_utf8_lossy(&status.stdout));
+        for service in &mut services {
+            service.failed_condition = conditions.remove(&service.name);
+        }
+
+        Ok(services)
+    }
+
+    pub async fn list_services_by_slice(
+        &self,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<HashMap<String, Vec<ServiceInfo>>> {
+        let services = self.list_local_services(show_inactive, scope).await?;
+        if services.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // Query every unit's slice in a single systemctl invocation
+        let units: Vec<String> = services
+            .iter()
+            .map(|service| format!("{}.service", service.name))
+            .collect();
+        let mut args = vec!["show", "--property=Id,Slice", "--no-pager"];
+        args.extend(units.iter().map(String::as_str));
+        let stdout = self.run_systemctl_output(scope, None, &args).await?;
+
+        let slices: HashMap<String, String> = parse_property_blocks(&stdout)
+            .into_iter()
+            .filter_map(|block| {
+                let id = block.get("Id")?.trim_end_matches(".service").to_string();
+                let slice = block.get("Slice").filter(|s| !s.is_empty())?.to_string();
+                Some((id, slice))
+            })
+            .collect();
+
+        let mut grouped: HashMap<String, Vec<ServiceInfo>> = HashMap::new();
+        for service in services {
+            let slice = slices
+                .get(&service.name)
+                .cloned()
+                .unwrap_or_else(|| "(no slice)".to_string());
+            grouped.entry(slice).or_default().push(service);
+        }
+
+        Ok(grouped)
+    }
+
+    pub async fn get_service_status(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<ServiceInfo> {
+        let stdout = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &["show", service_name, "--no-pager"],
+            )
+            .await?;
+
+        let info = self.parse_service_status(service_name, &stdout)?;
+
+        // `systemctl show` succeeds for unknown units and reports them as not-found
+        if info.load_state == "not-found" {
+            return Err(ServiceManagerError::ServiceNotFound(service_name.to_string()).into());
+        }
+
+        Ok(info)
+    }
+
+    pub async fn get_service_unit_path(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<UnitFilePaths> {
+        let stdout = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &[
+                    "show",
+                    service_name,
+                    "--property=FragmentPath,DropInPaths,SourcePath",
+                    "--no-pager",
+                ],
+            )
+            .await?;
+        Ok(parse_unit_file_paths(&stdout))
+    }
+
+    /// Variables and environment files a service is started with
+    pub async fn get_service_environment(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<ServiceEnvironment> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &[
+                    "show",
+                    service_name,
+                    "--property=Environment,EnvironmentFiles",
+                    "--no-pager",
+                ],
+            )
+            .await?;
+        Ok(parse_service_environment(&output))
+    }
+
+    /// The unit's own file, without its drop-ins, preceded by a `# /path`
+    /// comment as `systemctl cat` prints it. Drop-ins are left out so that
+    /// saving an edited copy doesn't fold them into the unit.
+    pub async fn get_unit_file_contents(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<String> {
+        let paths = self.get_service_unit_path(service_name, scope).await?;
+        let fragment = paths
+            .fragment
+            .ok_or_else(|| anyhow!("No unit file found for {}", service_name))?;
+        let content = tokio::fs::read_to_string(&fragment).await?;
+        Ok(format!("# {}\n{}", fragment.display(), content))
+    }
+
+    /// Whether systemd knows a unit by this name, based on the exit code of `systemctl cat`
+    pub async fn check_service_exists(&self, name: &str, scope: UnitScope) -> Result<bool> {
+        let status = TokioCommand::new("systemctl")
+            .args(scope.args())
+            .args(["cat", name])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await?;
+
+        Ok(status.success())
+    }
+
+    pub async fn start_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "start", service_name).await
+    }
+
+    pub async fn stop_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "stop", service_name).await
+    }
+
+    pub async fn restart_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "restart", service_name).await
+    }
+
+    pub async fn enable_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "enable", service_name).await
+    }
+
+    pub async fn disable_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "disable", service_name).await
+    }
+
+    pub async fn reload_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_unit_command(scope, "reload", service_name).await
+    }
+
+    pub async fn mask_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_systemctl_command(scope, Some(service_name), &["mask", service_name])
+            .await
+    }
+
+    pub async fn unmask_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        self.run_systemctl_command(scope, Some(service_name), &["unmask", service_name])
+            .await
+    }
+
+    pub async fn run_action(
+        &self,
+        action: ServiceAction,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<()> {
+        match action {
+            ServiceAction::Start => self.start_service(service_name, scope).await,
+            ServiceAction::Stop => self.stop_service(service_name, scope).await,
+            ServiceAction::Restart => self.restart_service(service_name, scope).await,
+            ServiceAction::Enable => self.enable_service(service_name, scope).await,
+            ServiceAction::Disable => self.disable_service(service_name, scope).await,
+            ServiceAction::Reload => self.reload_service(service_name, scope).await,
+            ServiceAction::Mask => self.mask_service(service_name, scope).await,
+            ServiceAction::Unmask => self.unmask_service(service_name, scope).await,
+        }
+    }
+
+    /// The service's journal, narrowed down to the entries `options` selects
+    pub async fn get_service_logs(
+        &self,
+        service_name: &str,
+        options: &LogOptions,
+        scope: UnitScope,
+    ) -> Result<String> {
+        let mut cmd = TokioCommand::new("journalctl");
+        cmd.args(scope.args())
+            .args(["-u", service_name, "--no-pager"]);
+        cmd.args(options.journalctl_args()?);
+
+        let output = cmd
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to get service logs: {}", stderr));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Follows the service's journal, sending each new line to `tx` until the
+    /// receiver is dropped or the returned task is aborted, which kills
+    /// `journalctl`
+    pub fn stream_service_logs(
+        &self,
+        service_name: &str,
+        tx: mpsc::Sender<String>,
+        scope: UnitScope,
+    ) -> JoinHandle<()> {
+        let service_name = service_name.to_string();
+
+        self.runtime.spawn(async move {
+            // Only new entries; callers already show the recent ones
+            let child = TokioCommand::new("journalctl")
+                .args(scope.args())
+                .args(["-u", &service_name, "-f", "-n", "0", "--no-pager"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn();
+            let mut child = match child {
+                Ok(child) => child,
+                Err(e) => {
+                    error!("Failed to follow logs of {}: {}", service_name, e);
+                    return;
+                }
+            };
+            let Some(stdout) = child.stdout.take() else {
+                return;
+            };
+
+            let mut lines = BufReader::new(stdout).lines();
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => {
+                        if tx.send(line).await.is_err() {
+                            break;
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        warn!("Failed to read logs of {}: {}", service_name, e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Polls the service's status every `interval` and yields it whenever it
+    /// changes, starting with the current status. Polling errors are logged
+    /// and retried on the next tick.
+    pub async fn watch_service_status(
+        &self,
+        service: &str,
+        interval: Duration,
+        scope: UnitScope,
+    ) -> Result<impl Stream<Item = ServiceStatus>> {
+        let initial = self.get_service_status(service, scope).await?.status;
+
+        let manager = self.clone();
+        let service = service.to_string();
+        let polls = futures::stream::unfold((), move |()| {
+            let manager = manager.clone();
+            let service = service.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(interval).await;
+                    match manager.get_service_status(&service, scope).await {
+                        Ok(info) => return Some((info.status, ())),
+                        Err(e) => debug!("Failed to poll status of {}: {}", service, e),
+                    }
+                }
+            }
+        });
+
+        Ok(distinct_until_changed(
+            futures::stream::once(async move { initial }).chain(polls),
+        ))
+    }
+
+    /// Units the service depends on, or with `reverse` the units that depend on it
+    pub async fn get_service_dependencies(
+        &self,
+        service_name: &str,
+        reverse: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<String>> {
+        let mut args = vec!["list-dependencies", "--plain", "--no-pager"];
+        if reverse {
+            args.push("--reverse");
+        }
+        args.push(service_name);
+
+        let output = self
+            .run_systemctl_output(scope, Some(service_name), &args)
+            .await?;
+        Ok(parse_dependency_list(&output))
+    }
+
+    /// Boots recorded in the journal, most recent first
+    pub async fn list_boots(&self) -> Result<Vec<BootEntry>> {
+        let output = TokioCommand::new("journalctl")
+            .args(["--list-boots", "--no-pager", "-o", "json"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to list boots: {}", stderr));
+        }
+
+        parse_boot_list(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Units that depend on the service and would be affected by stopping it
+    pub async fn get_reverse_dependencies(
+        &self,
+        service: &str,
+        scope: UnitScope,
+    ) -> Result<Vec<String>> {
+        self.get_service_dependencies(service, true, scope).await
+    }
+
+    /// Collects the recent journal lines that explain why a service failed
+    pub async fn get_failure_reason(&self, service: &str, scope: UnitScope) -> Result<String> {
+        let logs = self
+            .get_service_logs(service, &LogOptions::lines(20), scope)
+            .await?;
+        Ok(extract_failure_lines(&logs))
+    }
+
+    pub async fn get_resource_usage(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<ResourceUsage> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &[
+                    "show",
+                    "--property=CPUUsageNSec,MemoryCurrent",
+                    "--no-pager",
+                    service_name,
+                ],
+            )
+            .await?;
+
+        Ok(parse_resource_usage(&output))
+    }
+
+    /// Resource usage of every one of `service_names` with a single
+    /// `systemctl show`, by unit name
+    pub async fn get_resource_usage_batch(
+        &self,
+        service_names: &[String],
+        scope: UnitScope,
+    ) -> Result<HashMap<String, ResourceUsage>> {
+        if service_names.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut args = vec![
+            "show",
+            "--property=Id,CPUUsageNSec,MemoryCurrent",
+            "--no-pager",
+        ];
+        args.extend(service_names.iter().map(String::as_str));
+        let output = self.run_systemctl_output(scope, None, &args).await?;
+        Ok(parse_resource_usage_batch(&output))
+    }
+
+    pub async fn get_resource_limits(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<ResourceLimits> {
+        let output = self
+            .run_systemctl_output(scope, Some(service_name), &[
+                "show",
+                "--property=LimitNOFILE,LimitNOFILESoft,LimitNPROC,LimitCORE,LimitSTACK,TasksMax",
+                "--no-pager",
+                service_name,
+            ])
+            .await?;
+
+        Ok(parse_resource_limits(&output))
+    }
+
+    /// Units that start `service_name`: activating sockets, timers and path
+    /// units, followed by the units that want it
+    pub async fn get_service_triggers(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<Vec<TriggerInfo>> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &[
+                    "show",
+                    "--property=TriggeredBy,WantedBy",
+                    "--no-pager",
+                    service_name,
+                ],
+            )
+            .await?;
+
+        Ok(parse_service_triggers(&output))
+    }
+
+    /// The commands run while starting and after stopping `service_name`
+    pub async fn get_service_exec_steps(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<ExecSteps> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &[
+                    "show",
+                    "--property=ExecCondition,ExecStartPre,ExecStart,ExecStartPost,ExecStopPost",
+                    "--no-pager",
+                    service_name,
+                ],
+            )
+            .await?;
+
+        Ok(parse_exec_steps(&output))
+    }
+
+    pub async fn get_service_audit_log(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<Vec<AuditEntry>> {
+        let output = TokioCommand::new("journalctl")
+            .args(scope.args())
+            .args([
+                "-u",
+                service_name,
+                "-t",
+                "systemd",
+                "--no-pager",
+                "-o",
+                "json",
+            ])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to get service audit log: {}", stderr));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(parse_audit_log(&stdout))
+    }
+
+    pub async fn daemon_reload(&self, scope: UnitScope) -> Result<()> {
+        self.run_systemctl_command(scope, None, &["daemon-reload"])
+            .await
+    }
+
+    /// Writes a unit file to the scope's `UnitScope::unit_dir` and reloads
+    /// its manager. `service_name` gets a `.service` suffix unless it
+    /// already names another unit type, e.g. `backup.path`.
+    pub async fn create_service_file(
+        &self,
+        service_name: &str,
+        content: &str,
+        scope: UnitScope,
+    ) -> Result<()> {
+        let service_path = scope.unit_dir()?.join(unit_file_name(service_name));
+        self.write_unit_file(&service_path, content, scope).await?;
+
+        // Reload systemd after creating new service
+        self.daemon_reload(scope).await?;
+
+        Ok(())
+    }
+
+    /// Drop-in files overriding the unit, in the order systemd applies them
+    pub async fn list_drop_ins(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<Vec<PathBuf>> {
+        let output = self
+            .run_systemctl_output(
+                scope,
+                Some(service_name),
+                &["show", service_name, "--property=DropInPaths", "--no-pager"],
+            )
+            .await?;
+        Ok(parse_unit_file_paths(&output).dropin_paths)
+    }
+
+    /// Replaces the drop-in at `path`, creating its directory if needed,
+    /// and reloads the scope's manager
+    pub async fn write_drop_in(
+        &self,
+        path: &std::path::Path,
+        content: &str,
+        scope: UnitScope,
+    ) -> Result<()> {
+        self.write_unit_file(path, content, scope).await?;
+        self.daemon_reload(scope).await
+    }
+
+    /// Writes a unit file or drop-in, creating its directory if needed.
+    /// System files go through sudo; the user's own are written directly.
+    async fn write_unit_file(
+        &self,
+        path: &std::path::Path,
+        content: &str,
+        scope: UnitScope,
+    ) -> Result<()> {
+        if scope == UnitScope::User {
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await?;
+            }
+            tokio::fs::write(path, content).await?;
+            return Ok(());
+        }
+
+        if let Some(dir) = path.parent() {
+            let output = TokioCommand::new("sudo")
+                .arg("mkdir")
+                .arg("-p")
+                .arg(dir)
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .output()
+                .await?;
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                return Err(anyhow!("Failed to create {}: {}", dir.display(), stderr));
+            }
+        }
+
+        self.write_system_file(path, content).await
+    }
+
+    /// Writes `content` to a file only root may change, through `sudo tee`
+    async fn write_system_file(&self, path: &std::path::Path, content: &str) -> Result<()> {
+        let mut cmd = TokioCommand::new("sudo");
+        cmd.arg("tee")
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd.spawn()?;
+
+        if let Some(stdin) = child.stdin.as_mut() {
+            use tokio::io::AsyncWriteExt;
+            stdin.write_all(content.as_bytes()).await?;
+        }
+
+        let output = child.wait_with_output().await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to write {}: {}", path.display(), stderr));
+        }
+
+        Ok(())
+    }
+
+    /// Problems `systemd-analyze verify` finds in a unit, one per line, or
+    /// none when the unit is fine. `service_name` is completed as in
+    /// `create_service_file`.
+    pub async fn verify_unit(&self, service_name: &str, scope: UnitScope) -> Result<Vec<String>> {
+        let output = TokioCommand::new("systemd-analyze")
+            .args(scope.args())
+            .args(["verify", &unit_file_name(service_name)])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        // It exits non-zero when it finds problems, which all go to stderr
+        Ok(String::from_utf8_lossy(&output.stderr)
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Creates, enables and starts `<name>.path`, which starts
+    /// `triggered_service` whenever `path_modified` is written to. Started
+    /// right away, it watches the path without waiting for the next boot.
+    pub async fn create_path_unit(
+        &self,
+        name: &str,
+        path_modified: &str,
+        triggered_service: &str,
+    ) -> Result<()> {
+        let unit_name = format!("{}.path", name);
+        let content = path_unit_content(path_modified, triggered_service);
+
+        self.create_service_file(&unit_name, &content, UnitScope::System)
+            .await?;
+        self.enable_service(&unit_name, UnitScope::System).await?;
+        self.start_service(&unit_name, UnitScope::System).await
+    }
+
+    /// Creates the timer described by `spec`, enabling and starting it
+    /// right away when `enable` is set
+    pub async fn create_timer_unit(&self, spec: &TimerSpec, enable: bool) -> Result<()> {
+        let unit_name = spec.unit_name();
+
+        self.create_service_file(&unit_name, &timer_unit_content(spec), UnitScope::System)
+            .await?;
+        if enable {
+            self.enable_service(&unit_name, UnitScope::System).await?;
+            self.start_service(&unit_name, UnitScope::System).await?;
+        }
+        Ok(())
+    }
+
+    /// Checks an `OnCalendar` expression with `systemd-analyze calendar`,
+    /// returning when it next elapses
+    pub async fn validate_calendar_expression(&self, expression: &str) -> Result<String> {
+        let output = self.run_analyze_command(&["calendar", expression]).await?;
+        parse_next_elapse(&output).ok_or_else(|| anyhow!("{} never elapses", expression.trim()))
+    }
+
+    /// Lists the environment generators in every generator directory
+    pub async fn list_environment_generators(&self) -> Result<Vec<GeneratorInfo>> {
+        tokio::task::spawn_blocking(|| {
+            ENVIRONMENT_GENERATOR_DIRECTORIES
+                .iter()
+                .flat_map(|dir| read_generator_dir(std::path::Path::new(dir)))
+                .collect()
+        })
+        .await
+        .map_err(Into::into)
+    }
+
+    /// Summary of the last boot, as printed by `systemd-analyze time`
+    pub async fn get_boot_time_summary(&self) -> Result<String> {
+        self.run_analyze_command(&["time"]).await
+    }
+
+    /// How long each unit took to start during the last boot, slowest
+    /// first, as listed by `systemd-analyze blame`
+    pub async fn get_analyze_blame(&self) -> Result<Vec<(String, Duration)>> {
+        let output = self.run_analyze_command(&["blame", "--no-pager"]).await?;
+        Ok(parse_analyze_blame(&output))
+    }
+
+    /// Exposure score of a service from `systemd-analyze security`, from 0
+    /// (well sandboxed) to 10 (fully exposed), with every sandboxing setting
+    /// it checked
+    pub async fn get_security_score(
+        &self,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<(f32, Vec<SecuritySetting>)> {
+        let mut args = scope.args().to_vec();
+        args.extend(["security", service_name, "--no-pager"]);
+        let output = self.run_analyze_command(&args).await?;
+        parse_security_report(&output)
+            .ok_or_else(|| anyhow!("No exposure level was reported for {}", service_name))
+    }
+
+    /// SVG timeline of the last boot, as generated by `systemd-analyze plot`
+    pub async fn get_boot_plot_svg(&self) -> Result<String> {
+        self.run_analyze_command(&["plot"]).await
+    }
+
+    /// First line of `systemctl --version`, e.g. `systemd 255 (255.4-1)`
+    pub async fn get_systemd_version(&self) -> Result<String> {
+        let output = self
+            .run_systemctl_output(UnitScope::System, None, &["--version"])
+            .await?;
+        Ok(output.lines().next().unwrap_or_default().to_string())
+    }
+
+    /// Output of `systemctl list-unit-files`
+    pub async fn list_unit_files(&self) -> Result<String> {
+        self.run_systemctl_output(UnitScope::System, None, &["list-unit-files", "--no-pager"])
+            .await
+    }
+
+    /// The last `lines` entries of the system journal
+    pub async fn get_journal_tail(&self, lines: u32) -> Result<String> {
+        let output = TokioCommand::new("journalctl")
+            .args(["-n", &lines.to_string(), "--no-pager"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Failed to read the journal: {}", stderr));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    async fn run_analyze_command(&self, args: &[&str]) -> Result<String> {
+        let output = TokioCommand::new("systemd-analyze")
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("systemd-analyze command failed: {}", stderr));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Runs `systemctl <verb> <service_name>`, failing with `ServiceNotFound`
+    /// up front when the unit doesn't exist
+    async fn run_unit_command(
+        &self,
+        scope: UnitScope,
+        verb: &str,
+        service_name: &str,
+    ) -> Result<()> {
+        if !self.check_service_exists(service_name, scope).await? {
+            return Err(ServiceManagerError::ServiceNotFound(service_name.to_string()).into());
+        }
+
+        self.run_systemctl_command(scope, Some(service_name), &[verb, service_name])
+            .await
+    }
+
+    async fn run_systemctl_command(
+        &self,
+        scope: UnitScope,
+        unit: Option<&str>,
+        args: &[&str],
+    ) -> Result<()> {
+        self.run_systemctl_output(scope, unit, args).await?;
+        Ok(())
+    }
+
+    /// Runs `systemctl` and returns its stdout, mapping failing exit codes to
+    /// a `ServiceManagerError` about `unit`. Commands on no single unit fail
+    /// with `CommandFailed`.
+    async fn run_systemctl_output(
+        &self,
+        scope: UnitScope,
+        unit: Option<&str>,
+        args: &[&str],
+    ) -> Result<String> {
+        let cmd = TokioCommand::new("systemctl")
+            .args(scope.args())
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await?;
+
+        let code = SystemctlExitCode::from(cmd.status.code().unwrap_or(-1));
+
+        match (code, unit) {
+            (SystemctlExitCode::Success, _) => Ok(String::from_utf8_lossy(&cmd.stdout).to_string()),
+            (SystemctlExitCode::NoSuchUnit, Some(unit)) => {
+                Err(ServiceManagerError::ServiceNotFound(unit.to_string()).into())
+            }
+            (SystemctlExitCode::NotActive, Some(unit)) => {
+                Err(ServiceManagerError::NotActive(unit.to_string()).into())
+            }
+            (code, _) => Err(ServiceManagerError::CommandFailed {
+                code,
+                stderr: String::from_utf8_lossy(&cmd.stderr).trim().to_string(),
+            }
+            .into()),
+        }
+    }
+
+    fn parse_service_list(&self, output: &str) -> Result<Vec<ServiceInfo>> {
+        let mut services = Vec::new();
+        let lines: Vec<&str> = output.lines().collect();
+
+        // Skip header lines and find the start of service listings
+        let mut start_idx = 0;
+        for (i, line) in lines.iter().enumerate() {
+            if line.starts_with("UNIT") {
+                start_idx = i + 1;
+                break;
+            }
+        }
+
+        for line in lines.iter().skip(start_idx) {
+            if line.trim().is_empty() || line.starts_with("LOAD") {
+                break;
+            }
+
+            if let Some(service) = self.parse_service_line(line) {
+                services.push(service);
+            }
+        }
+
+        Ok(services)
+    }
+
+    fn parse_service_line(&self, line: &str) -> Option<ServiceInfo> {
+        parse_service_line(line)
+    }
+
+    fn parse_service_status(&self, service_name: &str, output: &str) -> Result<ServiceInfo> {
+        let mut properties = HashMap::new();
+
+        for line in output.lines() {
+            if let Some((key, value)) = line.split_once('=') {
+                properties.insert(key.trim(), value.trim());
+            }
+        }
+
+        let active_state = properties.get("ActiveState").unwrap_or(&"unknown");
+        let sub_state = properties.get("SubState").unwrap_or(&"unknown");
+        let load_state = properties.get("LoadState").unwrap_or(&"unknown");
+        let unit_file_state = properties.get("UnitFileState").unwrap_or(&"unknown");
+        let description = properties.get("Description").map(|s| s.to_string());
+
+        let status = ServiceStatus::for_unit(active_state, load_state);
+        let active = *active_state == "active";
+        let enabled = *unit_file_state == "enabled";
+
+        Ok(ServiceInfo {
+            name: service_name.to_string(),
+            status,
+            description,
+            enabled,
+            active,
+            load_state: load_state.to_string(),
+            sub_state: sub_state.to_string(),
+            active_since: properties
+                .get("ActiveEnterTimestamp")
+                .and_then(|timestamp| parse_systemd_timestamp(timestamp)),
+            failed_condition: None,
+            next_trigger: None,
+            last_trigger: None,
+            listen: Vec::new(),
+            memory_bytes: None,
+            cpu_ns: None,
+        })
+    }
+}
+
+/// Reads the generators in `dir`, sorted by name; a missing directory has none
+fn read_generator_dir(dir: &std::path::Path) -> Vec<GeneratorInfo> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut generators: Vec<GeneratorInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let is_symlink = entry.file_type().ok()?.is_symlink();
+
+            let (description, masked) = if is_symlink {
+                let target = std::fs::read_link(&path).ok()?;
+                let masked = target == std::path::Path::new("/dev/null");
+                (format!("→ {}", target.display()), masked)
+            } else {
+                let description = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| {
+                        content
+                            .lines()
+                            .filter(|line| !line.starts_with("#!"))
+                            .find_map(|line| line.strip_prefix('#'))
+                            .map(|comment| comment.trim().to_string())
+                    })
+                    .unwrap_or_default();
+                (description, false)
+            };
+
+            // Follows symlinks, so a dangling link counts as disabled
+            let executable = std::fs::metadata(&path)
+                .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false);
+
+            Some(GeneratorInfo {
+                name: entry.file_name().to_string_lossy().to_string(),
+                path,
+                description,
+                is_symlink,
+                enabled: executable && !masked,
+            })
+        })
+        .collect();
+
+    generators.sort_by(|a, b| a.name.cmp(&b.name));
+    generators
+}
+
+/// Unit suffixes that `create_service_file` keeps instead of appending `.service`
+const UNIT_FILE_SUFFIXES: &[&str] = &[
+    ".service", ".path", ".timer", ".socket", ".target", ".mount",
+];
+
+/// The full unit name for a service listed without its `.service` suffix
+pub fn unit_file_name(name: &str) -> String {
+    if UNIT_FILE_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        name.to_string()
+    } else {
+        format!("{}.service", name)
+    }
+}
+
+/// Contents of a `.path` unit activating `triggered_service` when
+/// `path_modified` changes
+fn path_unit_content(path_modified: &str, triggered_service: &str) -> String {
+    format!(
+        "[Unit]\n\
+         Description=Start {service} when {path} changes\n\
+         \n\
+         [Path]\n\
+         PathModified={path}\n\
+         Unit={service}\n\
+         \n\
+         [Install]\n\
+         WantedBy=multi-user.target\n",
+        path = path_modified,
+        service = unit_file_name(triggered_service),
+    )
+}
+
+/// Contents of the `.service` unit described by `spec`, from its
+/// template. Settings left empty are dropped rather than written blank.
+pub fn service_unit_content(spec: &ServiceSpec) -> String {
+    let optional = |value: &Option<String>| value.as_deref().unwrap_or("").trim().to_string();
+    let placeholders = [
+        ("{DESCRIPTION}", spec.description.trim().to_string()),
+        ("{EXEC_START}", spec.exec_start.trim().to_string()),
+        ("{USER}", optional(&spec.user)),
+        ("{WORKING_DIRECTORY}", optional(&spec.working_directory)),
+        ("{RESTART}", spec.restart.trim().to_string()),
+        ("{REMAIN_AFTER_EXIT}", spec.remain_after_exit.to_string()),
+        ("{PID_FILE}", optional(&spec.pid_file)),
+        ("{BUS_NAME}", optional(&spec.bus_name)),
+    ];
+
+    let mut content = String::new();
+    for line in spec.template.template().lines() {
+        // Each line holds at most one placeholder, so a value is never
+        // itself searched for placeholders
+        let line = match placeholders
+            .iter()
+            .find(|(placeholder, _)| line.contains(placeholder))
+        {
+            Some((_, value)) if value.is_empty() => continue,
+            Some((placeholder, value)) => line.replace(placeholder, value),
+            None => line.to_string(),
+        };
+        content.push_str(&line);
+        content.push('\n');
+    }
+    content
+}
+
+/// Contents of the `.timer` unit described by `spec`
+pub fn timer_unit_content(spec: &TimerSpec) -> String {
+    let service = unit_file_name(spec.service.trim());
+    let mut content = format!(
+        "[Unit]\n\
+         Description=Timer for {service}\n\
+         \n\
+         [Timer]\n\
+         Unit={service}\n",
+        service = service,
+    );
+
+    let settings = [
+        ("OnCalendar", spec.on_calendar.as_deref()),
+        ("OnBootSec", spec.on_boot_sec.as_deref()),
+        ("RandomizedDelaySec", spec.randomized_delay_sec.as_deref()),
+    ];
+    for (key, value) in settings {
+        if let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) {
+            content.push_str(&format!("{}={}\n", key, value));
+        }
+    }
+    if spec.persistent {
+        content.push_str("Persistent=true\n");
+    }
+
+    content.push_str("\n[Install]\nWantedBy=timers.target\n");
+    content
+}
+
+/// Parses `systemctl show --property=Environment,EnvironmentFiles` output.
+/// `Environment=` holds every variable on one line, quoting those whose
+/// values contain spaces; each environment file gets its own line.
+fn parse_service_environment(output: &str) -> ServiceEnvironment {
+    let mut environment = ServiceEnvironment::default();
+    for line in output.lines() {
+        if let Some(variables) = line.strip_prefix("Environment=") {
+            environment
+                .variables
+                .extend(
+                    split_unquoted(variables)
+                        .into_iter()
+                        .filter_map(|assignment| {
+                            let (key, value) = assignment.split_once('=')?;
+                            Some((key.to_string(), value.to_string()))
+                        }),
+                );
+        } else if let Some(file) = line.strip_prefix("EnvironmentFiles=") {
+            // e.g. `/etc/default/ssh (ignore_errors=yes)`
+            let file = file.trim();
+            if file.is_empty() {
+                continue;
+            }
+            environment.files.push(match file.rsplit_once(" (") {
+                Some((path, "ignore_errors=yes)")) => format!("{} (optional)", path),
+                Some((path, _)) => path.to_string(),
+                None => file.to_string(),
+            });
+        }
+    }
+    environment
+}
+
+/// Splits `s` on whitespace outside double or single quotes, removing the
+/// quotes and backslash escapes
+fn split_unquoted(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut quote = None;
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', _) => {
+                word.extend(chars.next());
+                in_word = true;
+            }
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                in_word = true;
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            (c, _) => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+    words
+}
+
+/// Quotes `s` as a single word for a POSIX shell, for building commands run
+/// on remote hosts. Single quotes in `s` become `'\''`.
+pub fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+/// Extracts the `Next elapse:` time from `systemd-analyze calendar` output
+fn parse_next_elapse(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "Next elapse").then(|| value.trim().to_string())
+    })
+}
+
+/// Parses `systemd-analyze blame` lines such as `1min 2.345s nginx.service`
+fn parse_analyze_blame(output: &str) -> Vec<(String, Duration)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (span, unit) = line.trim().rsplit_once(char::is_whitespace)?;
+            Some((unit.to_string(), parse_timespan(span)?))
+        })
+        .collect()
+}
+
+/// A sandboxing setting checked by `systemd-analyze security`, as
+/// (setting, `safe`, `unsafe` or empty, description)
+pub type SecuritySetting = (String, String, String);
+
+/// Parses `systemd-analyze security` output: a table of lines such as
+/// `✗ PrivateTmp=   Service has access to other software's temporary files   0.2`
+/// followed by `→ Overall exposure level for nginx.service: 9.6 UNSAFE 😨`.
+/// Settings that don't apply to the service have no ✓ or ✗.
+fn parse_security_report(output: &str) -> Option<(f32, Vec<SecuritySetting>)> {
+    let mut score = None;
+    let mut rows = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some((_, level)) = line.split_once("Overall exposure level for") {
+            score = level
+                .split_once(':')
+                .and_then(|(_, rest)| rest.split_whitespace().next()?.parse().ok());
+            continue;
+        }
+
+        let (value, rest) = if let Some(rest) = line.strip_prefix('✓') {
+            ("safe", rest)
+        } else if let Some(rest) = line.strip_prefix('✗') {
+            ("unsafe", rest)
+        } else {
+            ("", line)
+        };
+
+        // Columns are padded with runs of spaces, while settings and
+        // descriptions only have single ones
+        let mut columns = rest
+            .split("  ")
+            .map(str::trim)
+            .filter(|column| !column.is_empty());
+        let Some(setting) = columns.next().filter(|setting| setting.contains('=')) else {
+            continue;
+        };
+        rows.push((
+            setting.to_string(),
+            value.to_string(),
+            columns.next().unwrap_or_default().to_string(),
+        ));
+    }
+    Some((score?, rows))
+}
+
+/// Directives that fix `systemd-analyze security` settings, for those that
+/// can be tightened without knowing what the service needs. Settings such
+/// as `ProtectSystem=` or `ProtectHome=` easily break a service and are
+/// left to the user.
+const SECURITY_FIXES: &[(&str, &str)] = &[
+    ("NoNewPrivileges=", "NoNewPrivileges=yes"),
+    ("PrivateTmp=", "PrivateTmp=yes"),
+    ("PrivateDevices=", "PrivateDevices=yes"),
+    ("ProtectKernelTunables=", "ProtectKernelTunables=yes"),
+    ("ProtectKernelModules=", "ProtectKernelModules=yes"),
+    ("ProtectKernelLogs=", "ProtectKernelLogs=yes"),
+    ("ProtectControlGroups=", "ProtectControlGroups=yes"),
+    ("ProtectClock=", "ProtectClock=yes"),
+    ("ProtectHostname=", "ProtectHostname=yes"),
+    ("ProtectProc=", "ProtectProc=invisible"),
+    ("RestrictSUIDSGID=", "RestrictSUIDSGID=yes"),
+    ("RestrictRealtime=", "RestrictRealtime=yes"),
+    ("LockPersonality=", "LockPersonality=yes"),
+    ("RemoveIPC=", "RemoveIPC=yes"),
+    ("KeyringMode=", "KeyringMode=private"),
+    ("SystemCallArchitectures=", "SystemCallArchitectures=native"),
+];
+
+/// The directives fixing the unsafe settings among `rows`, as returned by
+/// `get_security_score`, that have a known fix
+pub fn security_fix_directives(rows: &[SecuritySetting]) -> Vec<&'static str> {
+    SECURITY_FIXES
+        .iter()
+        .filter(|(setting, _)| {
+            rows.iter()
+                .any(|(name, value, _)| name == setting && value == "unsafe")
+        })
+        .map(|(_, directive)| *directive)
+        .collect()
+}
+
+/// Parses a time span as systemd prints it, e.g. `1min 2.345s` or `350ms`
+fn parse_timespan(span: &str) -> Option<Duration> {
+    let mut seconds = 0.0;
+    for part in span.split_whitespace() {
+        let unit_start = part.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+        let (value, unit) = part.split_at(unit_start);
+        let scale = match unit {
+            "d" => 86_400.0,
+            "h" => 3600.0,
+            "min" => 60.0,
+            "s" => 1.0,
+            "ms" => 1e-3,
+            "us" | "µs" => 1e-6,
+            _ => return None,
+        };
+        seconds += value.parse::<f64>().ok()? * scale;
+    }
+    Some(Duration::from_secs_f64(seconds))
+}
+
+/// Fills in each timer's next and last trigger times from `systemctl show
+/// --property=Id,NextElapseUSecRealtime,LastTriggerUSec` output. Timers that
+/// never elapsed, or won't again, are left without a time.
+fn apply_timer_triggers(timers: &mut [ServiceInfo], output: &str) {
+    let triggers: HashMap<&str, (Option<&str>, Option<&str>)> = parse_property_blocks(output)
+        .into_iter()
+        .filter_map(|block| {
+            let time = |key| {
+                block
+                    .get(key)
+                    .copied()
+                    .filter(|time: &&str| !time.is_empty() && *time != "n/a")
+            };
+            Some((
+                *block.get("Id")?,
+                (time("NextElapseUSecRealtime"), time("LastTriggerUSec")),
+            ))
+        })
+        .collect();
+
+    for timer in timers {
+        if let Some((next, last)) = triggers.get(timer.name.as_str()) {
+            timer.next_trigger = next.map(str::to_string);
+            timer.last_trigger = last.map(str::to_string);
+        }
+    }
+}
+
+/// The `Listen=` lines of `systemctl show` output for one socket. systemd
+/// prints one line per address, which a property map would collapse.
+fn parse_listen_addresses(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Listen="))
+        .map(|address| address.trim().to_string())
+        .filter(|address| !address.is_empty())
+        .collect()
+}
+
+/// Fills in each socket's addresses from `systemctl show
+/// --property=Id,Listen` output covering several sockets
+fn apply_socket_listens(sockets: &mut [ServiceInfo], output: &str) {
+    for block in output.split("\n\n") {
+        let Some(id) = block.lines().find_map(|line| line.strip_prefix("Id=")) else {
+            continue;
+        };
+        if let Some(socket) = sockets.iter_mut().find(|socket| socket.name == id.trim()) {
+            socket.listen = parse_listen_addresses(block);
+        }
+    }
+}
+
+/// Splits multi-unit `systemctl show` output into one property map per unit
+fn parse_property_blocks(output: &str) -> Vec<HashMap<&str, &str>> {
+    let mut blocks = Vec::new();
+    let mut current = HashMap::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            current.insert(key.trim(), value.trim());
+        }
+    }
+
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+
+    blocks
+}
+
+/// Section headings every service unit file needs
+const REQUIRED_UNIT_SECTIONS: &[&str] = &["[Unit]", "[Service]", "[Install]"];
+
+/// Where the drop-in `drop_in_name` for a unit in `scope` goes, e.g.
+/// `/etc/systemd/system/nginx.service.d/override.conf`. The `.conf` suffix
+/// is added unless given.
+pub fn drop_in_path(service_name: &str, drop_in_name: &str, scope: UnitScope) -> Result<PathBuf> {
+    let name = drop_in_name.trim();
+    let name = name.strip_suffix(".conf").unwrap_or(name);
+    if name.is_empty() || name.starts_with('.') || name.contains('/') {
+        return Err(anyhow!("{:?} is not a valid drop-in name", drop_in_name));
+    }
+
+    Ok(scope
+        .unit_dir()?
+        .join(format!("{}.d", unit_file_name(service_name)))
+        .join(format!("{}.conf", name)))
+}
+
+/// Where an edited copy of the drop-in at `path` is saved. Drop-ins in the
+/// scope's `UnitScope::unit_dir` are changed in place; vendor ones, e.g.
+/// under `/usr/lib`, would be replaced on upgrade, so a drop-in of the same
+/// name there overrides them instead.
+pub fn editable_drop_in_path(
+    service_name: &str,
+    path: &std::path::Path,
+    scope: UnitScope,
+) -> Result<PathBuf> {
+    if path.starts_with(scope.unit_dir()?) {
+        return Ok(path.to_path_buf());
+    }
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("{} is not a drop-in", path.display()))?;
+    drop_in_path(service_name, &file_name.to_string_lossy(), scope)
+}
+
+/// The required sections that have no heading in `content`
+pub fn missing_unit_sections(content: &str) -> Vec<&'static str> {
+    REQUIRED_UNIT_SECTIONS
+        .iter()
+        .copied()
+        .filter(|section| !content.lines().any(|line| line.trim() == *section))
+        .collect()
+}
+
+/// Paths of the files concatenated in `systemctl cat` output, taken from the
+/// `# /path` comment heading each one
+pub fn unit_file_sources(contents: &str) -> Vec<&str> {
+    contents
+        .lines()
+        .filter_map(|line| line.strip_prefix("# /").map(|_| &line[2..]))
+        .collect()
+}
+
+/// `contents` without the `# /path` comments `unit_file_sources` reads,
+/// which must not end up in a saved unit file
+pub fn strip_unit_file_sources(contents: &str) -> String {
+    contents
+        .lines()
+        .filter(|line| !line.starts_with("# /"))
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn parse_unit_file_paths(output: &str) -> UnitFilePaths {
+    let properties = parse_property_blocks(output)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let path_property = |key: &str| {
+        properties
+            .get(key)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+    };
+
+    UnitFilePaths {
+        fragment: path_property("FragmentPath"),
+        dropin_paths: properties
+            .get("DropInPaths")
+            .map(|value| value.split_whitespace().map(PathBuf::from).collect())
+            .unwrap_or_default(),
+        source_path: path_property("SourcePath"),
+    }
+}
+
+/// Drops items equal to the one yielded just before them
+fn distinct_until_changed<S, T>(stream: S) -> impl Stream<Item = T>
+where
+    S: Stream<Item = T>,
+    T: PartialEq + Clone,
+{
+    let mut last = None;
+    stream.filter(move |item| {
+        let changed = last.as_ref() != Some(item);
+        if changed {
+            last = Some(item.clone());
+        }
+        futures::future::ready(changed)
+    })
+}
+
+/// Parses one row of `systemctl list-units`: `UNIT LOAD ACTIVE SUB DESCRIPTION`.
+/// Failed units are prefixed with a `●` bullet, and template instances such
+/// as `getty@tty1.service` keep their instance name. The description is the
+/// rest of the line with its spacing preserved.
+fn parse_service_line(line: &str) -> Option<ServiceInfo> {
+    let mut rest = line.trim_start().trim_start_matches(['●', '○', '×', '*']);
+    let mut fields = [""; 4];
+    for field in &mut fields {
+        rest = rest.trim_start();
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        *field = &rest[..end];
+        rest = &rest[end..];
+    }
+
+    let [unit, load_state, active_state, sub_state] = fields;
+    let description = Some(rest.trim()).filter(|description| !description.is_empty());
+
+    Some(ServiceInfo {
+        name: unit.trim_end_matches(".service").to_string(),
+        status: ServiceStatus::for_unit(active_state, load_state),
+        description: description.map(str::to_string),
+        enabled: false, // This would need a separate query
+        active: active_state == "active",
+        load_state: load_state.to_string(),
+        sub_state: sub_state.to_string(),
+        active_since: None,
+        failed_condition: None,
+        next_trigger: None,
+        last_trigger: None,
+        listen: Vec::new(),
+        memory_bytes: None,
+        cpu_ns: None,
+    })
+}
+
+/// Parses `systemctl list-dependencies --plain` output. The first line is the
+/// queried unit itself; the rest are its dependencies, indented by depth and
+/// possibly prefixed with a state bullet.
+fn parse_dependency_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .map(|line| line.trim().trim_start_matches(['●', '○', '×']).trim())
+        .filter(|unit| !unit.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn extract_failure_lines(logs: &str) -> String {
+    logs.lines()
+        .filter(|line| {
+            let lowercase = line.to_lowercase();
+            FAILURE_MESSAGE_PATTERNS
+                .iter()
+                .any(|pattern| lowercase.contains(pattern))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn parse_resource_usage(output: &str) -> ResourceUsage {
+    let properties = parse_property_blocks(output)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    resource_usage_from(&properties)
+}
+
+/// Parses one `Id=` block per unit, as printed when `systemctl show` is
+/// given several units
+fn parse_resource_usage_batch(output: &str) -> HashMap<String, ResourceUsage> {
+    parse_property_blocks(output)
+        .iter()
+        .filter_map(|properties| {
+            Some((
+                properties.get("Id")?.to_string(),
+                resource_usage_from(properties),
+            ))
+        })
+        .collect()
+}
+
+fn resource_usage_from(properties: &HashMap<&str, &str>) -> ResourceUsage {
+    // Unset counters are reported as "[not set]" or as u64::MAX
+    let counter = |key: &str| {
+        properties
+            .get(key)
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|value| *value != u64::MAX)
+    };
+
+    ResourceUsage {
+        cpu_usage_nsec: counter("CPUUsageNSec"),
+        memory_current: counter("MemoryCurrent"),
+    }
+}
+
+fn parse_resource_limits(output: &str) -> ResourceLimits {
+    let properties = parse_property_blocks(output)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    // Unlimited values are reported as "infinity" or as u64::MAX
+    let limit = |key: &str| {
+        properties
+            .get(key)
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|value| *value != u64::MAX)
+    };
+
+    ResourceLimits {
+        nofile: limit("LimitNOFILE"),
+        nofile_soft: limit("LimitNOFILESoft"),
+        nproc: limit("LimitNPROC"),
+        core: limit("LimitCORE"),
+        stack: limit("LimitSTACK"),
+        tasks_max: limit("TasksMax"),
+    }
+}
+
+/// Parses the `TriggeredBy` and `WantedBy` properties of `systemctl show`.
+/// Triggering units of other kinds, such as automounts, are left out.
+fn parse_service_triggers(output: &str) -> Vec<TriggerInfo> {
+    let properties = parse_property_blocks(output)
+        .into_iter()
+        .next()
+        .unwrap_or_default();
+    let units = |key: &str| {
+        properties
+            .get(key)
+            .map(|value| value.split_whitespace().collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+
+    let triggered_by = units("TriggeredBy").into_iter().filter_map(|unit| {
+        let trigger_type = match unit.rsplit_once('.')?.1 {
+            "socket" => TriggerType::Socket,
+            "timer" => TriggerType::Timer,
+            "path" => TriggerType::Path,
+            _ => return None,
+        };
+        Some(TriggerInfo {
+            unit_name: unit.to_string(),
+            trigger_type,
+        })
+    });
+    let wanted_by = units("WantedBy").into_iter().map(|unit| TriggerInfo {
+        unit_name: unit.to_string(),
+        trigger_type: TriggerType::WantedBy,
+    });
+
+    triggered_by.chain(wanted_by).collect()
+}
+
+/// Parses the `Exec*` properties of `systemctl show`. Each command is printed
+/// on its own line, e.g. `ExecStart={ path=/usr/bin/foo ; argv[]=/usr/bin/foo
+/// -v ; ignore_errors=no ; ... }`; only the command line is kept.
+fn parse_exec_steps(output: &str) -> ExecSteps {
+    let mut steps = ExecSteps::default();
+
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let phase = match key {
+            "ExecCondition" => &mut steps.condition,
+            "ExecStartPre" => &mut steps.pre,
+            "ExecStart" => &mut steps.start,
+            "ExecStartPost" => &mut steps.post,
+            "ExecStopPost" => &mut steps.stop_post,
+            _ => continue,
+        };
+
+        // The command line itself may contain " ; ", so cut at the field
+        // that follows it rather than at the first separator
+        let command = value.split_once("argv[]=").map(|(_, rest)| {
+            rest.rsplit_once(" ; ignore_errors=")
+                .map_or(rest, |(argv, _)| argv)
+                .trim()
+        });
+        if let Some(command) = command.filter(|command| !command.is_empty()) {
+            phase.push(command.to_string());
+        }
+    }
+
+    steps
+}
+
+/// Picks the services whose last condition or assertion check failed out of
+/// multi-unit `systemctl show` output. Units that were never started also
+/// report `ConditionResult=no`, so only checks with a timestamp count.
+fn parse_condition_failures(output: &str) -> Vec<ServiceInfo> {
+    parse_property_blocks(output)
+        .into_iter()
+        .filter(|block| {
+            let failed = |result: &str, timestamp: &str| {
+                block.get(result) == Some(&"no")
+                    && block
+                        .get(timestamp)
+                        .and_then(|timestamp| parse_systemd_timestamp(timestamp))
+                        .is_some()
+            };
+            failed("ConditionResult", "ConditionTimestamp")
+                || failed("AssertResult", "AssertTimestamp")
+        })
+        .filter_map(|block| {
+            let property = |key: &str| block.get(key).copied().unwrap_or("unknown").to_string();
+            Some(ServiceInfo {
+                name: block.get("Id")?.trim_end_matches(".service").to_string(),
+                status: ServiceStatus::ConditionFailed,
+                description: block.get("Description").map(|s| s.to_string()),
+                enabled: block.get("UnitFileState") == Some(&"enabled"),
+                active: block.get("ActiveState") == Some(&"active"),
+                load_state: property("LoadState"),
+                sub_state: property("SubState"),
+                active_since: None,
+                failed_condition: None,
+                next_trigger: None,
+                last_trigger: None,
+                listen: Vec::new(),
+                memory_bytes: None,
+                cpu_ns: None,
+            })
+        })
+        .collect()
+}
+
+/// Extracts the setting from the `… was not met` line of `systemctl status`,
+/// e.g. `ConditionPathExists=/etc/foo`
+fn parse_unmet_condition(output: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let condition = line.trim().strip_suffix("was not met")?;
+        let condition = condition.trim_start_matches(|c: char| !c.is_ascii_alphabetic());
+        Some(condition.trim().to_string())
+    })
+}
+
+/// The unmet setting of each service in multi-unit `systemctl status`
+/// output, keyed by service name. Each unit's block starts with an
+/// unindented `● name.service - Description` line.
+fn parse_unmet_conditions(output: &str) -> HashMap<String, String> {
+    let mut conditions = HashMap::new();
+    let mut current = None;
+
+    for line in output.lines() {
+        if !line.is_empty() && !line.starts_with(char::is_whitespace) {
+            current = line
+                .trim_start_matches(|c: char| !c.is_ascii_alphanumeric())
+                .split_whitespace()
+                .next()
+                .map(|unit| unit.trim_end_matches(".service").to_string());
+        } else if let (Some(name), Some(condition)) = (&current, parse_unmet_condition(line)) {
+            conditions.entry(name.clone()).or_insert(condition);
+        }
+    }
+
+    conditions
+}
+
+/// Parses `journalctl --list-boots -o json` output, most recent boot first
+fn parse_boot_list(output: &str) -> Result<Vec<BootEntry>> {
+    let entries: Vec<serde_json::Value> = serde_json::from_str(output)?;
+
+    let mut boots: Vec<BootEntry> = entries
+        .iter()
+        .filter_map(|entry| {
+            let timestamp = |key: &str| {
+                entry
+                    .get(key)?
+                    .as_i64()
+                    .and_then(DateTime::from_timestamp_micros)
+            };
+
+            Some(BootEntry {
+                boot_id: entry.get("boot_id")?.as_str()?.to_string(),
+                index: entry.get("index")?.as_i64()?.try_into().ok()?,
+                first_entry: timestamp("first_entry")?,
+                last_entry: timestamp("last_entry")?,
+            })
+        })
+        .collect();
+
+    boots.sort_by_key(|boot| std::cmp::Reverse(boot.index));
+    Ok(boots)
+}
+
+/// Parses `journalctl -o json` output, keeping only entries that record an operation
+fn parse_audit_log(output: &str) -> Vec<AuditEntry> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|entry| {
+            let message = entry.get("MESSAGE")?.as_str()?;
+            let lowercase = message.to_lowercase();
+            if !AUDIT_MESSAGE_PATTERNS
+                .iter()
+                .any(|pattern| lowercase.contains(pattern))
+            {
+                return None;
+            }
+
+            let micros = entry
+                .get("__REALTIME_TIMESTAMP")?
+                .as_str()?
+                .parse::<i64>()
+                .ok()?;
+            let timestamp = DateTime::from_timestamp_micros(micros)?;
+            let user_uid = entry
+                .get("_UID")
+                .and_then(|uid| uid.as_str())
+                .and_then(|uid| uid.parse().ok());
+
+            Some(AuditEntry {
+                timestamp,
+                user_uid,
+                action: message.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Splits `journalctl --show-cursor` output into the log lines and the
+/// cursor of the last entry, if any entries were printed
+pub fn split_journal_cursor(output: &str) -> (Vec<&str>, Option<&str>) {
+    let mut lines: Vec<&str> = output.lines().collect();
+    let cursor = lines
+        .last()
+        .and_then(|line| line.strip_prefix("-- cursor: "))
+        .map(str::trim);
+    if cursor.is_some() {
+        lines.pop();
+    }
+    (lines, cursor)
+}
+
+// Remote service management
+/// `systemctl` for units in `scope` on a remote host. Changes to system
+/// units go through sudo, but the user's manager is never run through it,
+/// as that would reach root's manager instead.
+fn remote_systemctl(scope: UnitScope, privileged: bool) -> &'static str {
+    match (scope, privileged) {
+        (UnitScope::System, true) => "sudo systemctl",
+        (UnitScope::System, false) => "systemctl",
+        (UnitScope::User, _) => "systemctl --user",
+    }
+}
+
+/// `journalctl` reading the journal of units in `scope` on a remote host
+fn remote_journalctl(scope: UnitScope) -> &'static str {
+    match scope {
+        UnitScope::System => "journalctl",
+        UnitScope::User => "journalctl --user",
+    }
+}
+
+pub struct RemoteServiceManager {
+    connection: HostConnection,
+}
+
+impl RemoteServiceManager {
+    pub fn new(connection: HostConnection) -> Self {
+        Self { connection }
+    }
+
+    pub async fn list_services(
+        &self,
+        show_inactive: bool,
+        scope: UnitScope,
+    ) -> Result<Vec<ServiceInfo>> {
+        let mut command = format!(
+            "{} list-units --type=service --no-pager",
+            remote_systemctl(scope, false)
+        );
+        if show_inactive {
+            command.push_str(" --all");
+        }
+
+        let output = self.execute_command(&command).await?;
+        self.parse_service_list(&output)
+    }
+
+    pub async fn start_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} start {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn stop_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} stop {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn restart_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} restart {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn enable_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} enable {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn disable_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} disable {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn reload_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} reload {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn daemon_reload(&self, scope: UnitScope) -> Result<()> {
+        let command = format!("{} daemon-reload", remote_systemctl(scope, true));
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn mask_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} mask {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn unmask_service(&self, service_name: &str, scope: UnitScope) -> Result<()> {
+        let command = format!(
+            "{} unmask {}",
+            remote_systemctl(scope, true),
+            shell_quote(service_name)
+        );
+        self.execute_command(&command).await?;
+        Ok(())
+    }
+
+    pub async fn run_action(
+        &self,
+        action: ServiceAction,
+        service_name: &str,
+        scope: UnitScope,
+    ) -> Result<()> {
+        match action {
+            ServiceAction::Start => self.start_service(service_name, scope).await,
+            ServiceAction::Stop => self.stop_service(service_name, scope).await,
+            ServiceAction::Restart => self.restart_service(service_name, scope).await,
+            ServiceAction::Enable => self.enable_service(service_name, scope).await,
+            ServiceAction::Disable => self.disable_service(service_name, scope).await,
+            ServiceAction::Reload => self.reload_service(service_name, scope).await,
+            ServiceAction::Mask => self.mask_service(service_name, scope).await,
+            ServiceAction::Unmask => self.unmask_service(service_name, scope).await,
+        }
+    }
+
+    pub async fn get_service_logs(
+        &self,
+        service_name: &str,
+        options: &LogOptions,
+        scope: UnitScope,
+    ) -> Result<String> {
+        let mut command = format!(
+            "{} -u {} --no-pager",
+            remote_journalctl(scope),
+            shell_quote(service_name)
+        );
+        for arg in options.journalctl_args()? {
+            command.push(' ');
+            command.push_str(&shell_quote(&arg));
+        }
+
+        self.execute_command(&command).await
+    }
+
+    /// Follows the service's journal by polling it every `interval`,
+    /// sending each new line to `tx` until the receiver is dropped. There
+    /// is no long-running channel to the host, so entries are picked up
+    /// after the last journal cursor seen.
+    pub async fn follow_service_logs(
+        &self,
+        service_name: &str,
+        tx: mpsc::Sender<String>,
+        interval: Duration,
+        scope: UnitScope,
+    ) {
+        // Start from the latest entry, which callers already show
+        let mut cursor = match self
+            .execute_command(&format!(
+                "{} -u {} -n 1 -q --show-cursor --no-pager",
+                remote_journalctl(scope),
+                shell_quote(service_name)
+            ))
+            .await
+        {
+            Ok(output) => split_journal_cursor(&output).1.map(str::to_string),
+            Err(e) => {
+                error!("Failed to follow logs of {}: {}", service_name, e);
+                return;
+            }
+        };
+
+        loop {
+            tokio::time::sleep(interval).await;
+            if tx.is_closed() {
+                break;
+            }
+
+            // Without a cursor the service has not logged anything yet, so
+            // every entry is new
+            let mut command = format!(
+                "{} -u {} -q --show-cursor --no-pager",
+                remote_journalctl(scope),
+                shell_quote(service_name)
+            );
+            if let Some(cursor) = &cursor {
+                command.push_str(&format!(" --after-cursor {}", shell_quote(cursor)));
+            }
+            let output = match self.execute_command(&command).await {
+                Ok(output) => output,
+                Err(e) => {
+                    warn!("Failed to read logs of {}: {}", service_name, e);
+                    continue;
+                }
+            };
+
+            let (lines, next_cursor) = split_journal_cursor(&output);
+            for line in lines {
+                if tx.send(line.to_string()).await.is_err() {
+                    return;
+                }
+            }
+            if let Some(next_cursor) = next_cursor {
+                cursor = Some(next_cursor.to_string());
+            }
+        }
+    }
+
+    pub async fn get_service_unit_path(&self, service_name: &str) -> Result<UnitFilePaths> {
+        let command = format!(
+            "systemctl show {} --property=FragmentPath,DropInPaths,SourcePath --no-pager",
+            shell_quote(service_name)
+        );
+        let output = self.execute_command(&command).await?;
+        Ok(parse_unit_file_paths(&output))
+    }
+
+    pub async fn read_remote_file(&self, path: &str) -> Result<String> {
+        let session = match &self.connection {
+            HostConnection::Session(session) => session.clone(),
+            HostConnection::ControlMaster(control_master) => {
+                return control_master
+                    .execute(&format!("cat {}", shell_quote(path)))
+                    .await;
+            }
+        };
+        let path = path.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            use std::io::Read;
+
+            let sftp = session
+                .lock()
+                .map_err(|_| anyhow!("SSH session lock poisoned"))?
+                .sftp()?;
+            let mut file = sftp.open(std::path::Path::new(&path))?;
+            let mut content = String::new();
+            file.read_to_string(&mut content)?;
+            Ok(content)
+        })
+        .await?
+    }
+
+    /// The host's unit file, headed by a `# /path` comment like the local
+    /// `ServiceManager::get_unit_file_contents`, so the two can be compared
+    pub async fn get_unit_file_contents(&self, service_name: &str) -> Result<String> {
+        let paths = self.get_service_unit_path(service_name).await?;
+        let fragment = paths
+            .fragment
+            .ok_or_else(|| anyhow!("No unit file found for {}", service_name))?;
+        let content = self.read_remote_file(&fragment.to_string_lossy()).await?;
+        Ok(format!("# {}\n{}", fragment.display(), content))
+    }
+
+    /// Fetches the contents of every file that makes up a remote unit
+    pub async fn copy_unit_file_from_host(
+        &self,
+        service_name: &str,
+    ) -> Result<Vec<(PathBuf, String)>> {
+        let paths = self.get_service_unit_path(service_name).await?;
+        let mut files = Vec::new();
+
+        for path in paths.fragment.iter().chain(paths.dropin_paths.iter()) {
+            let content = self.read_remote_file(&path.to_string_lossy()).await?;
+            files.push((path.clone(), content));
         }
 
-        let active_state = properties.get("ActiveState").unwrap_or(&"unknown");
-        let sub_state = properties.get("SubState").unwrap_or(&"unknown");
-        let load_state = properties.get("LoadState").unwrap_or(&"unknown");
-        let unit_file_state = properties.get("UnitFileState").unwrap_or(&"unknown");
-        let description = properties.get("Description").map(|s| s.to_string());
+        if files.is_empty() {
+            return Err(anyhow!("No unit file found for {}", service_name));
+        }
+
+        Ok(files)
+    }
+
+    /// Checks which tools the host has, in a single round trip
+    pub async fn detect_capabilities(&self) -> Result<HostCapabilities> {
+        let output = self.execute_command(DETECT_CAPABILITIES_COMMAND).await?;
+        Ok(HostCapabilities::parse(&output))
+    }
+
+    /// Runs a shell command on the host and returns its output. A non-zero
+    /// exit status is an error carrying the command's stderr.
+    pub async fn execute_command(&self, command: &str) -> Result<String> {
+        let session = match &self.connection {
+            HostConnection::Session(session) => session.clone(),
+            HostConnection::ControlMaster(control_master) => {
+                return control_master.execute(command).await;
+            }
+        };
+        let command = command.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            use std::io::Read;
 
-        let status = ServiceStatus::from(*active_state);
-        let active = *active_state == "active";
-        let enabled = *unit_file_state == "enabled";
+            // ssh2 channels are tied to their session, so hold the lock
+            // until the command has finished
+            let session = session
+                .lock()
+                .map_err(|_| anyhow!("SSH session lock poisoned"))?;
+            let mut channel = session.channel_session()?;
+            channel.exec(&command)?;
 
-        Ok(ServiceInfo {
-            name: service_name.to_string(),
-            status,
-            description,
-            enabled,
-            active,
-            load_state: load_state.to_string(),
-            sub_state: sub_state.to_string(),
+            let mut stdout = String::new();
+            channel.read_to_string(&mut stdout)?;
+            let mut stderr = String::new();
+            channel.stderr().read_to_string(&mut stderr)?;
+            channel.wait_close()?;
+
+            match channel.exit_status()? {
+                0 => Ok(stdout),
+                status => Err(anyhow!(
+                    "Remote command exited with status {}: {}",
+                    status,
+                    stderr.trim()
+                )),
+            }
         })
+        .await?
+    }
+
+    fn parse_service_list(&self, output: &str) -> Result<Vec<ServiceInfo>> {
+        // Same parsing logic as local service manager
+        let mut services = Vec::new();
+        let lines: Vec<&str> = output.lines().collect();
+
+        let mut start_idx = 0;
+        for (i, line) in lines.iter().enumerate() {
+            if line.starts_with("UNIT") {
+                start_idx = i + 1;
+                break;
+            }
+        }
+
+        for line in lines.iter().skip(start_idx) {
+            if line.trim().is_empty() || line.starts_with("LOAD") {
+                break;
+            }
+
+            if let Some(service) = self.parse_service_line(line) {
+                services.push(service);
+            }
+        }
+
+        Ok(services)
+    }
+
+    fn parse_service_line(&self, line: &str) -> Option<ServiceInfo> {
+        parse_service_line(line)
     }
 }
 
@@ -458,11 +3132,993 @@ mod tests {
         assert_eq!(ServiceStatus::from("unknown"), ServiceStatus::Unknown);
     }
 
+    #[test]
+    fn test_parse_service_line_template_instance() {
+        let service =
+            parse_service_line("  getty@tty1.service   loaded active running   Getty on tty1")
+                .unwrap();
+        assert_eq!(service.name, "getty@tty1");
+        assert_eq!(service.load_state, "loaded");
+        assert_eq!(service.sub_state, "running");
+        assert_eq!(service.status, ServiceStatus::Active);
+        assert_eq!(service.description.as_deref(), Some("Getty on tty1"));
+    }
+
+    #[test]
+    fn test_parse_service_line_other_unit_types() {
+        let slice = parse_service_line(
+            "  system-getty.slice   loaded active active    Slice /system/getty",
+        )
+        .unwrap();
+        assert_eq!(slice.name, "system-getty.slice");
+        assert_eq!(slice.description.as_deref(), Some("Slice /system/getty"));
+
+        let socket = parse_service_line(
+            "  dbus.socket          loaded active running   D-Bus System Message Bus Socket",
+        )
+        .unwrap();
+        assert_eq!(socket.name, "dbus.socket");
+        assert_eq!(
+            socket.description.as_deref(),
+            Some("D-Bus System Message Bus Socket")
+        );
+    }
+
+    #[test]
+    fn test_parse_service_line_failed_bullet() {
+        let service =
+            parse_service_line("● foo@bar.service loaded failed failed  Foo  for  bar").unwrap();
+        assert_eq!(service.name, "foo@bar");
+        assert_eq!(service.status, ServiceStatus::Failed);
+        assert_eq!(service.description.as_deref(), Some("Foo  for  bar"));
+
+        assert!(parse_service_line("  nginx.service loaded").is_none());
+        assert!(
+            parse_service_line("getty@tty1.service loaded active running")
+                .unwrap()
+                .description
+                .is_none()
+        );
+    }
+
     #[test]
     fn test_service_status_display() {
         assert_eq!(format!("{}", ServiceStatus::Active), "Active");
         assert_eq!(format!("{}", ServiceStatus::Inactive), "Inactive");
         assert_eq!(format!("{}", ServiceStatus::Failed), "Failed");
         assert_eq!(format!("{}", ServiceStatus::Unknown), "Unknown");
+        assert_eq!(
+            ServiceStatus::from(ServiceStatus::ConditionFailed.to_string().as_str()),
+            ServiceStatus::ConditionFailed
+        );
+    }
+
+    #[test]
+    fn test_action_applies_to() {
+        assert!(!ServiceAction::Start.applies_to(&ServiceStatus::Active));
+        assert!(ServiceAction::Start.applies_to(&ServiceStatus::Failed));
+        assert!(!ServiceAction::Stop.applies_to(&ServiceStatus::Inactive));
+        assert!(!ServiceAction::Restart.applies_to(&ServiceStatus::Masked));
+        assert!(ServiceAction::Unmask.applies_to(&ServiceStatus::Masked));
+        assert!(!ServiceAction::Unmask.applies_to(&ServiceStatus::Active));
+        assert!(ServiceAction::Stop.applies_to(&ServiceStatus::Unknown));
+    }
+
+    #[test]
+    fn test_batch_summary() {
+        assert_eq!(
+            ServiceAction::Start.batch_summary(5, 2),
+            "3 of 5 services started successfully"
+        );
+        assert_eq!(
+            ServiceAction::Stop.batch_summary(1, 0),
+            "1 of 1 service stopped successfully"
+        );
+    }
+
+    #[test]
+    fn test_confirmation_message() {
+        assert_eq!(
+            ServiceAction::Stop.confirmation_message("nginx.service"),
+            Some("Stop nginx.service? Running processes will be terminated.".to_string())
+        );
+        assert!(ServiceAction::Mask
+            .confirmation_message("3 services")
+            .is_some());
+        assert_eq!(
+            ServiceAction::Start.confirmation_message("nginx.service"),
+            None
+        );
+        assert_eq!(
+            ServiceAction::Restart.confirmation_message("nginx.service"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_service_status_round_trip() {
+        let statuses = [
+            ServiceStatus::Active,
+            ServiceStatus::Inactive,
+            ServiceStatus::Failed,
+            ServiceStatus::ConditionFailed,
+            ServiceStatus::Activating,
+            ServiceStatus::Deactivating,
+            ServiceStatus::Reloading,
+            ServiceStatus::Masked,
+        ];
+
+        for status in statuses {
+            assert_eq!(ServiceStatus::from(status.to_string().as_str()), status);
+        }
+        assert_eq!(ServiceStatus::from("reloading"), ServiceStatus::Reloading);
+        assert_eq!(
+            ServiceStatus::for_unit("inactive", "masked"),
+            ServiceStatus::Masked
+        );
+        assert_eq!(ServiceStatus::Masked.badge(), Some("🚫"));
+        assert_eq!(ServiceStatus::Active.badge(), None);
+    }
+
+    #[test]
+    fn test_status_changes_are_deduplicated() {
+        let statuses = futures::stream::iter(vec![
+            ServiceStatus::Active,
+            ServiceStatus::Active,
+            ServiceStatus::Failed,
+            ServiceStatus::Failed,
+            ServiceStatus::Active,
+        ]);
+
+        let changes: Vec<ServiceStatus> =
+            futures::executor::block_on(distinct_until_changed(statuses).collect());
+        assert_eq!(
+            changes,
+            vec![
+                ServiceStatus::Active,
+                ServiceStatus::Failed,
+                ServiceStatus::Active
+            ]
+        );
+    }
+
+    #[test]
+    fn test_list_services_show_inactive() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("systemd-pilot-systemctl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // Stands in for systemctl, adding an inactive service with --all and
+        // listing a user service with --user
+        let systemctl = dir.join("systemctl");
+        std::fs::write(
+            &systemctl,
+            r#"#!/bin/sh
+echo "UNIT                LOAD   ACTIVE   SUB     DESCRIPTION"
+echo "  cron.service      loaded active   running Regular background program processing daemon"
+echo "  ssh.service       loaded active   running OpenBSD Secure Shell server"
+case " $* " in
+*" --all "*) echo "  rsync.service     loaded inactive dead    fast remote file copy program daemon" ;;
+esac
+case " $* " in
+*" --user "*) echo "  syncthing.service loaded active   running Syncthing - Open Source Continuous File Synchronization" ;;
+esac
+echo
+echo "LOAD   = Reflects whether the unit definition was properly loaded."
+"#,
+        )
+        .unwrap();
+        std::fs::set_permissions(&systemctl, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let runtime = Arc::new(Runtime::new().unwrap());
+        let manager = ServiceManager::new(runtime.clone());
+        let active = runtime
+            .block_on(manager.list_services_with(&systemctl, false, UnitScope::System))
+            .unwrap();
+        let all = runtime
+            .block_on(manager.list_services_with(&systemctl, true, UnitScope::System))
+            .unwrap();
+        let user = runtime
+            .block_on(manager.list_services_with(&systemctl, false, UnitScope::User))
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(active.len(), 2);
+        assert_eq!(all.len(), 3);
+        assert!(all
+            .iter()
+            .any(|service| service.name == "rsync" && !service.active));
+        assert!(!active.iter().any(|service| service.name == "syncthing"));
+        assert!(user.iter().any(|service| service.name == "syncthing"));
+    }
+
+    #[test]
+    fn test_remote_user_units_skip_sudo() {
+        assert_eq!(remote_systemctl(UnitScope::System, true), "sudo systemctl");
+        assert_eq!(remote_systemctl(UnitScope::System, false), "systemctl");
+        assert_eq!(remote_systemctl(UnitScope::User, true), "systemctl --user");
+        assert_eq!(remote_journalctl(UnitScope::User), "journalctl --user");
+    }
+
+    #[test]
+    fn test_read_generator_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir =
+            std::env::temp_dir().join(format!("systemd-pilot-generators-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let script = dir.join("60-proxy");
+        std::fs::write(
+            &script,
+            "#!/bin/sh\n# Sets proxy variables\necho http_proxy=x\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let plain = dir.join("70-disabled");
+        std::fs::write(&plain, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&plain, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        std::os::unix::fs::symlink("/dev/null", dir.join("80-masked")).unwrap();
+
+        let generators = read_generator_dir(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<_> = generators.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["60-proxy", "70-disabled", "80-masked"]);
+
+        assert_eq!(generators[0].description, "Sets proxy variables");
+        assert!(generators[0].enabled && !generators[0].is_symlink);
+        assert!(!generators[1].enabled);
+        assert!(generators[2].is_symlink && !generators[2].enabled);
+        assert_eq!(generators[2].description, "→ /dev/null");
+    }
+
+    #[test]
+    fn test_resource_limits_parsing() {
+        let limits = parse_resource_limits(
+            "LimitNOFILE=524288\nLimitNOFILESoft=256\nLimitNPROC=infinity\n\
+             LimitCORE=18446744073709551615\nLimitSTACK=8388608\nTasksMax=100\n",
+        );
+
+        assert_eq!(
+            limits,
+            ResourceLimits {
+                nofile: Some(524_288),
+                nofile_soft: Some(256),
+                nproc: None,
+                core: None,
+                stack: Some(8_388_608),
+                tasks_max: Some(100),
+            }
+        );
+
+        let low: Vec<_> = limits
+            .entries()
+            .iter()
+            .filter(|(_, value, typical)| is_below_default(*value, *typical))
+            .map(|(name, _, _)| *name)
+            .collect();
+        assert_eq!(low, vec!["LimitNOFILESoft", "TasksMax"]);
+    }
+
+    #[test]
+    fn test_unit_file_name() {
+        assert_eq!(unit_file_name("nginx"), "nginx.service");
+        assert_eq!(unit_file_name("nginx.service"), "nginx.service");
+        assert_eq!(unit_file_name("backup.path"), "backup.path");
+    }
+
+    #[test]
+    fn test_path_unit_content() {
+        let content = path_unit_content("/srv/incoming", "process-upload");
+
+        assert!(
+            content.contains("[Path]\nPathModified=/srv/incoming\nUnit=process-upload.service\n")
+        );
+        assert!(content.contains("[Install]\nWantedBy=multi-user.target\n"));
+    }
+
+    #[test]
+    fn test_condition_failures_parsing() {
+        let output = "\
+Id=kdump.service
+Description=Crash recovery kernel arming
+ActiveState=inactive
+SubState=dead
+LoadState=loaded
+UnitFileState=enabled
+ConditionResult=no
+ConditionTimestamp=Tue 2024-03-12 14:22:03 UTC
+AssertResult=no
+AssertTimestamp=
+
+Id=never-started.service
+ActiveState=inactive
+ConditionResult=no
+ConditionTimestamp=
+AssertResult=no
+AssertTimestamp=
+
+Id=sshd.service
+ActiveState=active
+ConditionResult=yes
+ConditionTimestamp=Tue 2024-03-12 14:22:03 UTC
+AssertResult=yes
+AssertTimestamp=Tue 2024-03-12 14:22:03 UTC
+";
+
+        let services = parse_condition_failures(output);
+        assert_eq!(services.len(), 1);
+        assert_eq!(services[0].name, "kdump");
+        assert_eq!(services[0].status, ServiceStatus::ConditionFailed);
+        assert!(services[0].enabled);
+    }
+
+    #[test]
+    fn test_unmet_condition_parsing() {
+        let output = "\
+○ kdump.service - Crash recovery kernel arming
+     Loaded: loaded (/usr/lib/systemd/system/kdump.service; enabled)
+     Active: inactive (dead)
+  Condition: start condition unmet at Tue 2024-03-12 14:22:03 UTC; 2h ago
+             └─ ConditionKernelCommandLine=crashkernel was not met
+";
+        assert_eq!(
+            parse_unmet_condition(output),
+            Some("ConditionKernelCommandLine=crashkernel".to_string())
+        );
+        assert_eq!(parse_unmet_condition("Active: active (running)\n"), None);
+    }
+
+    #[test]
+    fn test_unmet_conditions_parsing() {
+        let output = "\
+○ kdump.service - Crash recovery kernel arming
+     Loaded: loaded (/usr/lib/systemd/system/kdump.service; enabled)
+     Active: inactive (dead)
+  Condition: start condition unmet at Tue 2024-03-12 14:22:03 UTC; 2h ago
+             └─ ConditionKernelCommandLine=crashkernel was not met
+
+○ ua-reboot-cmds.service - Ubuntu Pro reboot cmds
+     Loaded: loaded (/lib/systemd/system/ua-reboot-cmds.service; enabled)
+     Active: inactive (dead)
+  Condition: start condition unmet at Tue 2024-03-12 14:20:11 UTC; 2h ago
+             └─ ConditionPathExists=/var/lib/ubuntu-advantage/marker was not met
+";
+        let conditions = parse_unmet_conditions(output);
+        assert_eq!(conditions.len(), 2);
+        assert_eq!(
+            conditions["kdump"],
+            "ConditionKernelCommandLine=crashkernel"
+        );
+        assert_eq!(
+            conditions["ua-reboot-cmds"],
+            "ConditionPathExists=/var/lib/ubuntu-advantage/marker"
+        );
+    }
+
+    #[test]
+    fn test_boot_list_parsing() {
+        let output = r#"[
+            {"index":-1,"boot_id":"5f1c","first_entry":1710150000000000,"last_entry":1710160000000000},
+            {"index":0,"boot_id":"9a2e","first_entry":1710170000000000,"last_entry":1710253323000000}
+        ]"#;
+
+        let boots = parse_boot_list(output).unwrap();
+        assert_eq!(boots.len(), 2);
+        assert_eq!(boots[0].boot_id, "9a2e");
+        assert_eq!(boots[0].index, 0);
+        assert_eq!(
+            boots[0].last_entry,
+            DateTime::from_timestamp_micros(1_710_253_323_000_000).unwrap()
+        );
+        assert_eq!(boots[1].index, -1);
+
+        assert!(parse_boot_list("not json").is_err());
+    }
+
+    #[test]
+    fn test_action_log() {
+        let mut log = ActionLog::default();
+        for _ in 0..ACTION_LOG_CAPACITY {
+            log.record("nginx.service", ServiceAction::Start, &Ok(()));
+        }
+        log.record(
+            "nginx.service",
+            ServiceAction::Stop,
+            &Err(anyhow!("Access denied")),
+        );
+
+        assert_eq!(log.entries["nginx.service"].len(), ACTION_LOG_CAPACITY);
+
+        let recent = log.recent("nginx.service", 5);
+        assert_eq!(recent.len(), 5);
+        assert_eq!(recent[0].action, ServiceAction::Stop);
+        assert!(!recent[0].success);
+        assert_eq!(recent[0].error.as_deref(), Some("Access denied"));
+        assert_eq!(recent[1].action, ServiceAction::Start);
+
+        assert!(log.recent("sshd.service", 5).is_empty());
+    }
+
+    #[test]
+    fn test_service_triggers_parsing() {
+        let triggers = parse_service_triggers(
+            "TriggeredBy=cups.path cups.socket cups.automount
+WantedBy=printer.target
+",
+        );
+
+        let trigger = |unit_name: &str, trigger_type| TriggerInfo {
+            unit_name: unit_name.to_string(),
+            trigger_type,
+        };
+        assert_eq!(
+            triggers,
+            vec![
+                trigger("cups.path", TriggerType::Path),
+                trigger("cups.socket", TriggerType::Socket),
+                trigger("printer.target", TriggerType::WantedBy),
+            ]
+        );
+        assert!(parse_service_triggers(
+            "TriggeredBy=
+WantedBy=
+"
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_exec_steps_parsing() {
+        let steps = parse_exec_steps(
+            "ExecCondition=
+ExecStartPre={ path=/usr/bin/mkdir ; argv[]=/usr/bin/mkdir -p /run/app ; ignore_errors=yes ; start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }
+ExecStartPre={ path=/bin/sh ; argv[]=/bin/sh -c \"echo a ; echo b\" ; ignore_errors=no ; start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }
+ExecStart={ path=/usr/bin/app ; argv[]=/usr/bin/app --serve ; ignore_errors=no ; start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }
+ExecStartPost=
+ExecStopPost=
+",
+        );
+
+        assert_eq!(
+            steps,
+            ExecSteps {
+                pre: vec![
+                    "/usr/bin/mkdir -p /run/app".to_string(),
+                    "/bin/sh -c \"echo a ; echo b\"".to_string(),
+                ],
+                start: vec!["/usr/bin/app --serve".to_string()],
+                ..Default::default()
+            }
+        );
+        assert!(parse_exec_steps("ExecStart=\n").is_empty());
+    }
+
+    #[test]
+    fn test_timer_unit_content() {
+        let spec = TimerSpec {
+            service: "backup.service".to_string(),
+            on_calendar: Some("daily".to_string()),
+            on_boot_sec: Some(" ".to_string()),
+            persistent: true,
+            randomized_delay_sec: Some("10min".to_string()),
+        };
+
+        assert_eq!(spec.unit_name(), "backup.timer");
+        assert_eq!(
+            timer_unit_content(&spec),
+            "[Unit]\nDescription=Timer for backup.service\n\n\
+             [Timer]\nUnit=backup.service\nOnCalendar=daily\nRandomizedDelaySec=10min\n\
+             Persistent=true\n\n[Install]\nWantedBy=timers.target\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_next_elapse() {
+        let output = "\
+  Original form: daily
+Normalized form: *-*-* 00:00:00
+    Next elapse: Wed 2024-03-13 00:00:00 UTC
+       From now: 9h left
+";
+        assert_eq!(
+            parse_next_elapse(output),
+            Some("Wed 2024-03-13 00:00:00 UTC".to_string())
+        );
+        assert_eq!(
+            parse_next_elapse("Normalized form: 2020-01-01 00:00:00\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dependency_list_parsing() {
+        let output = "\
+nginx.service
+  -.slice
+  system.slice
+  sysinit.target
+    ● dev-hugepages.mount
+    systemd-journald.service
+";
+
+        assert_eq!(
+            parse_dependency_list(output),
+            vec![
+                "-.slice",
+                "system.slice",
+                "sysinit.target",
+                "dev-hugepages.mount",
+                "systemd-journald.service"
+            ]
+        );
+        assert!(parse_dependency_list("nginx.service\n").is_empty());
+    }
+
+    #[test]
+    fn test_failure_lines_extraction() {
+        let logs = "\
+Jan 01 10:00:00 host app[1]: Listening on port 80
+Jan 01 10:00:01 host app[1]: ERROR: could not bind socket
+Jan 01 10:00:01 host systemd[1]: app.service: Main process exited, code=exited, status=1/FAILURE
+Jan 01 10:00:01 host systemd[1]: app.service: Failed with result 'exit-code'.
+Jan 01 10:00:02 host systemd[1]: app.service: Control process Exit code 3";
+
+        let reason = extract_failure_lines(logs);
+        let lines: Vec<&str> = reason.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains("could not bind socket"));
+        assert!(lines[1].contains("Failed with result"));
+        assert!(lines[2].contains("Exit code 3"));
+        assert_eq!(extract_failure_lines("all good"), "");
+    }
+
+    #[test]
+    fn test_resource_usage_parsing() {
+        let usage = parse_resource_usage("CPUUsageNSec=1500000000\nMemoryCurrent=[not set]\n");
+        assert_eq!(usage.cpu_usage_nsec, Some(1_500_000_000));
+        assert_eq!(usage.memory_current, None);
+
+        let usage = parse_resource_usage("CPUUsageNSec=18446744073709551615\nMemoryCurrent=4096\n");
+        assert_eq!(usage.cpu_usage_nsec, None);
+        assert_eq!(usage.memory_current, Some(4096));
+
+        let usage = parse_resource_usage_batch(
+            "Id=nginx.service\nCPUUsageNSec=1500000000\nMemoryCurrent=4096\n\n\
+             Id=cron.service\nCPUUsageNSec=[not set]\nMemoryCurrent=[not set]\n",
+        );
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage["nginx.service"].memory_current, Some(4096));
+        assert_eq!(usage["cron.service"], ResourceUsage::default());
+    }
+
+    #[test]
+    fn test_resource_time_series_cpu_percent() {
+        let start = Instant::now();
+        let mut series = ResourceTimeSeries::new();
+
+        series.record(
+            start,
+            &ResourceUsage {
+                cpu_usage_nsec: Some(1_000_000_000),
+                memory_current: Some(1024),
+            },
+        );
+        series.record(
+            start + std::time::Duration::from_secs(2),
+            &ResourceUsage {
+                cpu_usage_nsec: Some(2_000_000_000),
+                memory_current: Some(2048),
+            },
+        );
+
+        assert_eq!(series.timestamps.len(), 2);
+        assert_eq!(series.cpu_values, vec![0.0, 50.0]);
+        assert_eq!(series.memory_values, vec![1024, 2048]);
+    }
+
+    #[test]
+    fn test_systemctl_exit_codes() {
+        assert_eq!(SystemctlExitCode::from(0), SystemctlExitCode::Success);
+        assert_eq!(SystemctlExitCode::from(1), SystemctlExitCode::Failure);
+        assert_eq!(SystemctlExitCode::from(3), SystemctlExitCode::NotActive);
+        assert_eq!(SystemctlExitCode::from(4), SystemctlExitCode::NoSuchUnit);
+        assert_eq!(SystemctlExitCode::from(5), SystemctlExitCode::Other(5));
+    }
+
+    #[test]
+    fn test_service_action_labels() {
+        assert_eq!(format!("{}", ServiceAction::Restart), "Restart");
+        assert_eq!(ServiceAction::Reload.command(), "reload");
+        assert_eq!(ServiceAction::Stop.progress_label(), "Stopping");
+        assert_eq!(ServiceAction::Enable.past_tense(), "enabled");
+        assert_eq!(ServiceAction::Unmask.command(), "unmask");
+    }
+
+    #[test]
+    fn test_property_blocks_parsing() {
+        let output = "Id=nginx.service\nSlice=system.slice\n\nId=foo.service\nSlice=\n";
+        let blocks = parse_property_blocks(output);
+
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].get("Id"), Some(&"nginx.service"));
+        assert_eq!(blocks[0].get("Slice"), Some(&"system.slice"));
+        assert_eq!(blocks[1].get("Slice"), Some(&""));
+    }
+
+    #[test]
+    fn test_missing_unit_sections() {
+        let content = "[Unit]\nDescription=Test\n\n[Service]\nExecStart=/bin/true\n";
+        assert_eq!(missing_unit_sections(content), vec!["[Install]"]);
+        assert!(missing_unit_sections(&format!("{}[Install]\n", content)).is_empty());
+        assert_eq!(missing_unit_sections("# [Unit]\n").len(), 3);
+    }
+
+    #[test]
+    fn test_unit_file_sources() {
+        let contents = "# /lib/systemd/system/nginx.service\n[Unit]\n# comment\n\n\
+                        # /etc/systemd/system/nginx.service.d/override.conf\n[Service]\n";
+        assert_eq!(
+            unit_file_sources(contents),
+            vec![
+                "/lib/systemd/system/nginx.service",
+                "/etc/systemd/system/nginx.service.d/override.conf"
+            ]
+        );
+        assert_eq!(
+            strip_unit_file_sources(contents),
+            "[Unit]\n# comment\n\n[Service]\n"
+        );
+    }
+
+    #[test]
+    fn test_unit_file_paths_parsing() {
+        let output = "FragmentPath=/usr/lib/systemd/system/nginx.service\n\
+                      DropInPaths=/etc/systemd/system/nginx.service.d/a.conf /run/systemd/system/nginx.service.d/b.conf\n\
+                      SourcePath=\n";
+        let paths = parse_unit_file_paths(output);
+
+        assert_eq!(
+            paths.fragment,
+            Some(PathBuf::from("/usr/lib/systemd/system/nginx.service"))
+        );
+        assert_eq!(paths.dropin_paths.len(), 2);
+        assert_eq!(paths.source_path, None);
+    }
+
+    #[test]
+    fn test_timer_triggers() {
+        let mut timers: Vec<ServiceInfo> = [
+            "  apt-daily.timer   loaded active waiting Daily apt download activities",
+            "  fstrim.timer      loaded active waiting Discard unused blocks once a week",
+        ]
+        .iter()
+        .filter_map(|line| parse_service_line(line))
+        .collect();
+        assert_eq!(timers[0].name, "apt-daily.timer");
+
+        let output = "Id=apt-daily.timer\n\
+                      NextElapseUSecRealtime=Sat 2024-03-09 18:02:11 CET\n\
+                      LastTriggerUSec=Sat 2024-03-09 06:41:03 CET\n\
+                      \n\
+                      Id=fstrim.timer\n\
+                      NextElapseUSecRealtime=Mon 2024-03-11 00:00:00 CET\n\
+                      LastTriggerUSec=n/a\n";
+        apply_timer_triggers(&mut timers, output);
+
+        assert_eq!(
+            timers[0].next_trigger.as_deref(),
+            Some("Sat 2024-03-09 18:02:11 CET")
+        );
+        assert_eq!(
+            timers[0].last_trigger.as_deref(),
+            Some("Sat 2024-03-09 06:41:03 CET")
+        );
+        assert_eq!(
+            timers[1].next_trigger.as_deref(),
+            Some("Mon 2024-03-11 00:00:00 CET")
+        );
+        assert_eq!(timers[1].last_trigger, None);
+    }
+
+    #[test]
+    fn test_socket_listens() {
+        let mut sockets: Vec<ServiceInfo> = [
+            "  dbus.socket    loaded active running D-Bus System Message Bus Socket",
+            "  sshd.socket    loaded active listening OpenSSH Server Socket",
+        ]
+        .iter()
+        .filter_map(|line| parse_service_line(line))
+        .collect();
+
+        let output = "Id=dbus.socket\n\
+                      Listen=/run/dbus/system_bus_socket (Stream)\n\
+                      \n\
+                      Id=sshd.socket\n\
+                      Listen=0.0.0.0:22 (Stream)\n\
+                      Listen=[::]:22 (Stream)\n";
+        apply_socket_listens(&mut sockets, output);
+
+        assert_eq!(sockets[0].name, "dbus.socket");
+        assert_eq!(sockets[0].listen, ["/run/dbus/system_bus_socket (Stream)"]);
+        assert_eq!(
+            sockets[1].listen,
+            ["0.0.0.0:22 (Stream)", "[::]:22 (Stream)"]
+        );
+    }
+
+    #[test]
+    fn test_security_report_parsing() {
+        let output = "  NAME                        DESCRIPTION                                      EXPOSURE\n\
+                      ✗ PrivateTmp=                 Service has access to other software's temporary files  0.2\n\
+                      ✓ User=/DynamicUser=          Service runs under a static non-root user identity\n\
+                      \x20 SystemCallFilter=~@swap   System call allow list defined for service\n\
+                      ✗ ProtectHome=                Service has full access to home directories      0.2\n\
+                      ✗ NoNewPrivileges=            Service processes may acquire new privileges     0.2\n\
+                      \n\
+                      → Overall exposure level for nginx.service: 9.2 UNSAFE 😨\n";
+        let (score, rows) = parse_security_report(output).unwrap();
+        assert_eq!(score, 9.2);
+        assert_eq!(rows.len(), 5);
+        assert_eq!(
+            rows[0],
+            (
+                "PrivateTmp=".to_string(),
+                "unsafe".to_string(),
+                "Service has access to other software's temporary files".to_string()
+            )
+        );
+        assert_eq!(rows[1].1, "safe");
+        assert_eq!(rows[2].0, "SystemCallFilter=~@swap");
+        assert_eq!(rows[2].1, "");
+
+        // ProtectHome= has no automatic fix
+        assert_eq!(
+            security_fix_directives(&rows),
+            ["NoNewPrivileges=yes", "PrivateTmp=yes"]
+        );
+
+        assert_eq!(parse_security_report("NAME DESCRIPTION EXPOSURE\n"), None);
+    }
+
+    #[test]
+    fn test_analyze_blame_parsing() {
+        let output = "1min 2.345s apt-daily-upgrade.service\n\
+                      \x20    1.234s nginx.service\n\
+                      \x20     350ms systemd-journald.service\n\
+                      \x20     812us dev-sda1.device\n";
+        assert_eq!(
+            parse_analyze_blame(output),
+            [
+                (
+                    "apt-daily-upgrade.service".to_string(),
+                    Duration::from_millis(62_345)
+                ),
+                ("nginx.service".to_string(), Duration::from_millis(1234)),
+                (
+                    "systemd-journald.service".to_string(),
+                    Duration::from_millis(350)
+                ),
+                ("dev-sda1.device".to_string(), Duration::from_micros(812)),
+            ]
+        );
+        assert_eq!(parse_timespan("2 weeks"), None);
+    }
+
+    #[test]
+    fn test_service_unit_content() {
+        let spec = ServiceSpec {
+            name: "backup".to_string(),
+            description: "Nightly backup".to_string(),
+            template: ServiceTemplate::Oneshot,
+            exec_start: "/usr/local/bin/backup --all".to_string(),
+            user: Some("backup".to_string()),
+            working_directory: Some(" ".to_string()),
+            restart: "on-failure".to_string(),
+            remain_after_exit: true,
+            ..Default::default()
+        };
+        assert_eq!(spec.unit_name(), "backup.service");
+        assert_eq!(
+            service_unit_content(&spec),
+            "[Unit]\n\
+             Description=Nightly backup\n\
+             \n\
+             [Service]\n\
+             Type=oneshot\n\
+             ExecStart=/usr/local/bin/backup --all\n\
+             RemainAfterExit=true\n\
+             User=backup\n\
+             Restart=on-failure\n\
+             \n\
+             [Install]\n\
+             WantedBy=multi-user.target\n"
+        );
+
+        // Values are inserted as given, even when they look like placeholders
+        let spec = ServiceSpec {
+            template: ServiceTemplate::DBus,
+            exec_start: "/usr/bin/echo {USER}".to_string(),
+            bus_name: Some("org.example.Daemon".to_string()),
+            restart: "no".to_string(),
+            ..Default::default()
+        };
+        let content = service_unit_content(&spec);
+        assert!(content.contains("Type=dbus\nBusName=org.example.Daemon\n"));
+        assert!(content.contains("ExecStart=/usr/bin/echo {USER}\n"));
+        assert!(!content.contains("Description="));
+        assert!(!content.contains("User="));
+    }
+
+    #[test]
+    fn test_split_journal_cursor() {
+        let output = "Oct 16 09:12:01 web01 nginx[812]: reloading\n\
+                      Oct 16 09:12:02 web01 nginx[812]: reloaded\n\
+                      -- cursor: s=3a1f;i=4c2;b=9e0d;m=1f3\n";
+        assert_eq!(
+            split_journal_cursor(output),
+            (
+                vec![
+                    "Oct 16 09:12:01 web01 nginx[812]: reloading",
+                    "Oct 16 09:12:02 web01 nginx[812]: reloaded",
+                ],
+                Some("s=3a1f;i=4c2;b=9e0d;m=1f3")
+            )
+        );
+        assert_eq!(split_journal_cursor(""), (vec![], None));
+    }
+
+    #[test]
+    fn test_service_environment_parsing() {
+        let output = "Environment=LANG=C \"GREETING=hello world\" EMPTY= PATH=/usr/bin\n\
+                      EnvironmentFiles=/etc/default/nginx (ignore_errors=no)\n\
+                      EnvironmentFiles=/etc/nginx/env (ignore_errors=yes)\n";
+        let environment = parse_service_environment(output);
+
+        assert_eq!(
+            environment.variables,
+            [
+                ("LANG".to_string(), "C".to_string()),
+                ("GREETING".to_string(), "hello world".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("PATH".to_string(), "/usr/bin".to_string()),
+            ]
+        );
+        assert_eq!(
+            environment.files,
+            ["/etc/default/nginx", "/etc/nginx/env (optional)"]
+        );
+
+        assert_eq!(
+            parse_service_environment("Environment=\nEnvironmentFiles=\n"),
+            ServiceEnvironment::default()
+        );
+    }
+
+    #[test]
+    fn test_split_unquoted() {
+        assert_eq!(
+            split_unquoted(r#"A=1  "B=two words" C='x y' D=a\ b"#),
+            ["A=1", "B=two words", "C=x y", "D=a b"]
+        );
+        assert!(split_unquoted("   ").is_empty());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("nginx.service"), "'nginx.service'");
+        assert_eq!(shell_quote("a b; rm -rf /"), "'a b; rm -rf /'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(split_unquoted(&shell_quote("$(id) `id`")), ["$(id) `id`"]);
+    }
+
+    #[test]
+    fn test_drop_in_path() {
+        assert_eq!(
+            drop_in_path("nginx", "override", UnitScope::System).unwrap(),
+            PathBuf::from("/etc/systemd/system/nginx.service.d/override.conf")
+        );
+        assert_eq!(
+            drop_in_path("backup.timer", "10-schedule.conf", UnitScope::System).unwrap(),
+            PathBuf::from("/etc/systemd/system/backup.timer.d/10-schedule.conf")
+        );
+        assert!(drop_in_path("nginx", "", UnitScope::System).is_err());
+        assert!(drop_in_path("nginx", "../evil", UnitScope::System).is_err());
+        assert!(drop_in_path("nginx", ".conf", UnitScope::System).is_err());
+        assert!(drop_in_path("syncthing", "override", UnitScope::User)
+            .unwrap()
+            .ends_with("systemd/user/syncthing.service.d/override.conf"));
+    }
+
+    #[test]
+    fn test_vendor_drop_ins_are_saved_under_etc() {
+        let etc = std::path::Path::new("/etc/systemd/system/nginx.service.d/override.conf");
+        assert_eq!(
+            editable_drop_in_path("nginx", etc, UnitScope::System).unwrap(),
+            etc
+        );
+        assert_eq!(
+            editable_drop_in_path(
+                "nginx",
+                std::path::Path::new("/usr/lib/systemd/system/nginx.service.d/10-limits.conf"),
+                UnitScope::System
+            )
+            .unwrap(),
+            PathBuf::from("/etc/systemd/system/nginx.service.d/10-limits.conf")
+        );
+    }
+
+    #[test]
+    fn test_log_options_args() {
+        assert_eq!(
+            LogOptions::lines(500).journalctl_args().unwrap(),
+            ["-n", "500"]
+        );
+
+        let options = LogOptions {
+            boot: Some("abc123".to_string()),
+            since: Some("2024-03-09T14:05:00+01:00".to_string()),
+            until: Some("2024-03-10T00:00:00Z".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.journalctl_args().unwrap(),
+            [
+                "--boot=abc123",
+                "--since",
+                "2024-03-09 13:05:00 UTC",
+                "--until",
+                "2024-03-10 00:00:00 UTC"
+            ]
+        );
+
+        let invalid = LogOptions {
+            since: Some("yesterday".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.journalctl_args().is_err());
+    }
+
+    #[test]
+    fn test_log_options_default_lines() {
+        assert_eq!(
+            LogOptions::default().with_default_lines(200),
+            LogOptions::lines(200)
+        );
+        assert_eq!(
+            LogOptions::lines(50).with_default_lines(200),
+            LogOptions::lines(50)
+        );
+
+        let range = LogOptions {
+            since: Some("2024-03-09T14:05:00+01:00".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(range.clone().with_default_lines(200), range);
+    }
+
+    #[test]
+    fn test_audit_log_parsing() {
+        let output = concat!(
+            r#"{"__REALTIME_TIMESTAMP":"1710253323000000","_UID":"0","MESSAGE":"Starting nginx.service - A high performance web server..."}"#,
+            "\n",
+            r#"{"__REALTIME_TIMESTAMP":"1710253324000000","_UID":"0","MESSAGE":"nginx.service: Deactivated successfully."}"#,
+            "\n",
+            r#"{"__REALTIME_TIMESTAMP":"1710253325000000","_UID":"1000","MESSAGE":"nginx.service: Stop request repeated too quickly."}"#,
+            "\n",
+            "not json",
+        );
+
+        let entries = parse_audit_log(output);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].user_uid, Some(0));
+        assert!(entries[0].action.starts_with("Starting nginx.service"));
+        assert_eq!(entries[0].timestamp.timestamp(), 1710253323);
+        assert_eq!(entries[1].user_uid, Some(1000));
     }
 }
@@ -1,12 +1,23 @@
 use gtk4::prelude::*;
 use gtk4::{
     Box, Button, CellRendererText, CheckButton, ComboBoxText, Entry, Grid, Label, ListBox,
-    ListBoxRow, Paned, ScrolledWindow, Separator, TreeView, TreeViewColumn, Widget,
+    ListBoxRow, Separator, SortColumn, SortType, TreeIter, TreeModel, TreeModelFilter,
+    TreeModelSort, TreeView, TreeViewColumn, Widget,
 };
 use log::{debug, error, info, warn};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::rc::Rc;
 
-use crate::service_manager::{ServiceInfo, ServiceStatus};
+use crate::remote_host::HostConnectionState;
+use crate::service_manager::{
+    is_below_default, ActionEntry, ResourceLimits, ServiceInfo, ServiceStatus, TriggerInfo,
+    UnitFilePaths, UnitScope,
+};
+use crate::ui::styles::{get_service_row_css_class, log_line_priority, LOG_PRIORITY_COLORS};
+use crate::utils::format::{format_match_count, format_relative};
+use crate::utils::theme::ThemeManager;
 
 /// Creates a styled service control button with icon and text
 pub fn create_service_button(icon: &str, text: &str, tooltip: Option<&str>) -> Button {
@@ -65,8 +76,26 @@ pub fn create_service_control_buttons() -> (Box, Button, Button, Button, Button,
     )
 }
 
+/// Makes Mask available when a selected service isn't masked and Unmask
+/// when one is
+pub fn update_mask_buttons(
+    mask_button: &Button,
+    unmask_button: &Button,
+    selected: &[ServiceStatus],
+) {
+    let masked = selected
+        .iter()
+        .filter(|status| **status == ServiceStatus::Masked)
+        .count();
+    mask_button.set_sensitive(masked < selected.len());
+    unmask_button.set_sensitive(masked > 0);
+}
+
 /// Creates a styled TreeView for displaying services
-pub fn create_services_tree_view(columns: &[&str]) -> (TreeView, gtk4::TreeStore) {
+pub fn create_services_tree_view(
+    columns: &[&str],
+    theme_manager: &Rc<ThemeManager>,
+) -> (TreeView, gtk4::TreeStore) {
     let tree_view = TreeView::new();
     tree_view.set_search_column(0);
     tree_view.set_enable_search(true);
@@ -89,7 +118,11 @@ pub fn create_services_tree_view(columns: &[&str]) -> (TreeView, gtk4::TreeStore
 
         // Special styling for status column
         if column_name == "Status" {
-            column.set_cell_data_func(&renderer, format_status_cell);
+            let status_column = i as i32;
+            let theme_manager = theme_manager.clone();
+            column.set_cell_data_func(&renderer, move |_, cell, model, iter| {
+                format_status_cell(cell, model, iter, status_column, None, &theme_manager)
+            });
         }
 
         tree_view.append_column(&column);
@@ -98,42 +131,52 @@ pub fn create_services_tree_view(columns: &[&str]) -> (TreeView, gtk4::TreeStore
     (tree_view, tree_store)
 }
 
-/// Custom cell data function for status column styling
-fn format_status_cell(
-    _column: &TreeViewColumn,
+/// Cell data for a status column, styled by the status in `status_column`
+/// and the bool in `enabled_column`. Models without an enabled column are
+/// treated as enabled.
+pub fn format_status_cell(
     cell: &gtk4::CellRenderer,
     model: &gtk4::TreeModel,
     iter: &gtk4::TreeIter,
+    status_column: i32,
+    enabled_column: Option<i32>,
+    theme_manager: &ThemeManager,
 ) {
-    if let Some(cell_text) = cell.downcast_ref::<CellRendererText>() {
-        if let Ok(status_text) = model.get_value(iter, 1).get::<String>() {
-            let css_class = match status_text.as_str() {
-                "Active" => "service-active",
-                "Inactive" => "service-inactive",
-                "Failed" => "service-failed",
-                _ => "service-unknown",
-            };
+    let Some(cell_text) = cell.downcast_ref::<CellRendererText>() else {
+        return;
+    };
 
-            // Apply CSS class for styling
-            // Note: CellRendererText doesn't have style_context in GTK4
-            // We can set markup instead
-            let markup = format!(
-                "<span class=\"{}\">{}</span>",
-                css_class,
-                glib::markup_escape_text(&status_text)
-            );
-            cell_text.set_markup(Some(&markup));
-        }
-    }
+    // Slice rows have no status; renderers are shared between rows
+    let Some(status_text) = model.get::<Option<String>>(iter, status_column) else {
+        cell_text.set_markup(None);
+        return;
+    };
+
+    let enabled = enabled_column.is_none_or(|column| model.get::<bool>(iter, column));
+    let status = ServiceStatus::from(status_text.as_str());
+    let label = match status.badge() {
+        Some(badge) => format!("{} {}", badge, status_text),
+        None => status_text,
+    };
+
+    // CellRendererText has no style context in GTK4, so the theme's rule for
+    // the row class is given as span attributes instead
+    let markup = format!(
+        "<span {}>{}</span>",
+        theme_manager.service_row_span_attributes(get_service_row_css_class(&status, enabled)),
+        glib::markup_escape_text(&label)
+    );
+    cell_text.set_markup(Some(&markup));
 }
 
-/// Creates a host list item widget
+/// Creates a hosts list row showing the connection state, with a button that
+/// reads "Connect" or "Disconnect" to match it
 pub fn create_host_list_item(
     name: &str,
     hostname: &str,
     username: &str,
-    connected: bool,
-) -> ListBoxRow {
+    state: HostConnectionState,
+) -> (ListBoxRow, Button) {
     let row = ListBoxRow::new();
     row.set_margin_start(6);
     row.set_margin_end(6);
@@ -147,12 +190,8 @@ pub fn create_host_list_item(
     main_box.set_margin_bottom(8);
 
     // Connection status indicator
-    let status_indicator = Label::new(Some(if connected { "🟢" } else { "🔴" }));
-    status_indicator.set_tooltip_text(Some(if connected {
-        "Connected"
-    } else {
-        "Disconnected"
-    }));
+    let status_indicator = Label::new(Some(state.indicator()));
+    status_indicator.set_tooltip_text(Some(&state.to_string()));
 
     // Host info
     let info_box = Box::new(gtk4::Orientation::Vertical, 4);
@@ -168,16 +207,26 @@ pub fn create_host_list_item(
 
     info_box.append(&name_label);
     info_box.append(&connection_label);
+    info_box.set_hexpand(true);
+
+    let connect_button = Button::with_label(match state {
+        HostConnectionState::Disconnected => "Connect",
+        HostConnectionState::Connecting => "Connecting…",
+        HostConnectionState::Connected => "Disconnect",
+    });
+    connect_button.set_valign(gtk4::Align::Center);
+    connect_button.set_sensitive(state != HostConnectionState::Connecting);
 
     main_box.append(&status_indicator);
     main_box.append(&info_box);
+    main_box.append(&connect_button);
 
     row.set_child(Some(&main_box));
-    row
+    (row, connect_button)
 }
 
 /// Creates a filter/search box for services
-pub fn create_service_filter_box() -> (Box, Entry, CheckButton, ComboBoxText) {
+pub fn create_service_filter_box() -> (Box, Entry, CheckButton, ComboBoxText, ComboBoxText) {
     let filter_box = Box::new(gtk4::Orientation::Horizontal, 12);
     filter_box.set_margin_start(12);
     filter_box.set_margin_end(12);
@@ -193,20 +242,185 @@ pub fn create_service_filter_box() -> (Box, Entry, CheckButton, ComboBoxText) {
     // Show inactive services toggle
     let show_inactive = CheckButton::with_label("Show inactive services");
 
+    // System or user manager selector
+    let unit_scope = ComboBoxText::new();
+    unit_scope.append_text("System");
+    unit_scope.append_text("User");
+    unit_scope.set_active(Some(0));
+
     // Status filter combo
     let status_filter = ComboBoxText::new();
     status_filter.append_text("All Services");
     status_filter.append_text("Active Only");
     status_filter.append_text("Failed Only");
     status_filter.append_text("Inactive Only");
-    status_filter.set_active(Some(1)); // Default to "Active Only"
+    status_filter.set_active(Some(0));
 
     search_entry.set_hexpand(true);
     filter_box.append(&search_entry);
     filter_box.append(&show_inactive);
+    filter_box.append(&unit_scope);
     filter_box.append(&status_filter);
 
-    (filter_box, search_entry, show_inactive, status_filter)
+    (
+        filter_box,
+        search_entry,
+        show_inactive,
+        unit_scope,
+        status_filter,
+    )
+}
+
+/// The manager picked in the scope combo of `create_service_filter_box`
+pub fn unit_scope_choice(unit_scope: &ComboBoxText) -> UnitScope {
+    match unit_scope.active() {
+        Some(1) => UnitScope::User,
+        _ => UnitScope::System,
+    }
+}
+
+/// The status picked in the combo of `create_service_filter_box`
+pub fn status_filter_choice(status_filter: &ComboBoxText) -> Option<ServiceStatus> {
+    match status_filter.active() {
+        Some(1) => Some(ServiceStatus::Active),
+        Some(2) => Some(ServiceStatus::Failed),
+        Some(3) => Some(ServiceStatus::Inactive),
+        _ => None,
+    }
+}
+
+/// Filters a services model (name in column 0, status text in column 1) by
+/// name and status. Rows with children, such as slices, stay visible while
+/// any of their services match.
+#[derive(Clone)]
+pub struct ServiceListFilter {
+    filter: TreeModelFilter,
+    search_text: Rc<RefCell<String>>,
+    status: Rc<RefCell<Option<ServiceStatus>>>,
+}
+
+impl ServiceListFilter {
+    pub fn new(model: &impl IsA<TreeModel>) -> Self {
+        let filter = TreeModelFilter::new(model, None);
+        let search_text = Rc::new(RefCell::new(String::new()));
+        let status: Rc<RefCell<Option<ServiceStatus>>> = Rc::new(RefCell::new(None));
+
+        let query = search_text.clone();
+        let wanted_status = status.clone();
+        filter.set_visible_func(move |model, iter| {
+            row_visible(
+                model,
+                iter,
+                &query.borrow(),
+                wanted_status.borrow().as_ref(),
+            )
+        });
+
+        Self {
+            filter,
+            search_text,
+            status,
+        }
+    }
+
+    /// The filtered model, for a view or a sorting model to wrap
+    pub fn model(&self) -> &TreeModelFilter {
+        &self.filter
+    }
+
+    /// Shows only services whose name contains `text`, ignoring case
+    pub fn set_search_text(&self, text: &str) {
+        self.search_text.replace(text.trim().to_lowercase());
+        self.filter.refilter();
+    }
+
+    /// Shows only services with `status`, or every status for `None`
+    pub fn set_status_filter(&self, status: Option<ServiceStatus>) {
+        self.status.replace(status);
+        self.filter.refilter();
+    }
+}
+
+fn row_visible(
+    model: &TreeModel,
+    iter: &TreeIter,
+    query: &str,
+    status: Option<&ServiceStatus>,
+) -> bool {
+    if let Some(child) = model.iter_children(Some(iter)) {
+        loop {
+            if row_visible(model, &child, query, status) {
+                return true;
+            }
+            if !model.iter_next(&child) {
+                return false;
+            }
+        }
+    }
+
+    let name = model.get::<Option<String>>(iter, 0).unwrap_or_default();
+    let row_status = model.get::<Option<String>>(iter, 1).unwrap_or_default();
+    service_matches(&name, &row_status, query, status)
+}
+
+/// Whether a service named `name`, showing `row_status`, passes a lowercase
+/// search `query` and a status filter
+fn service_matches(
+    name: &str,
+    row_status: &str,
+    query: &str,
+    status: Option<&ServiceStatus>,
+) -> bool {
+    name.to_lowercase().contains(query)
+        && status.is_none_or(|status| row_status == status.to_string())
+}
+
+/// Sorts `sort_model` so rows flagged in the `bool` `pinned_column` come
+/// before all others in either direction. Sorting by one of `text_columns`
+/// orders the rest by that column's text; the default order leaves them as
+/// they are.
+pub fn sort_pinned_first(sort_model: &TreeModelSort, pinned_column: i32, text_columns: &[u32]) {
+    let sort_func = |text_column: Option<i32>| {
+        let sort_model = sort_model.downgrade();
+        move |model: &TreeModelSort, a: &TreeIter, b: &TreeIter| {
+            // GTK passes the child model here, not the sort model itself
+            let model = model.upcast_ref::<TreeModel>();
+            let descending = sort_model
+                .upgrade()
+                .and_then(|sort_model| sort_model.sort_column_id())
+                .is_some_and(|(_, order)| order == SortType::Descending);
+            let row = |iter: &TreeIter| {
+                let text = text_column
+                    .and_then(|column| model.get::<Option<String>>(iter, column))
+                    .unwrap_or_default();
+                (model.get::<bool>(iter, pinned_column), text)
+            };
+            let (a, b) = (row(a), row(b));
+            compare_pinned_first((a.0, &a.1), (b.0, &b.1), descending).into()
+        }
+    };
+
+    for &column in text_columns {
+        sort_model.set_sort_func(SortColumn::Index(column), sort_func(Some(column as i32)));
+    }
+    sort_model.set_default_sort_func(sort_func(None));
+}
+
+/// Compares two `(pinned, text)` rows so that pinned rows sort first even
+/// once GTK reverses the result for a descending sort
+fn compare_pinned_first(a: (bool, &str), b: (bool, &str), descending: bool) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let pinned_first = if descending {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    };
+    match (a.0, b.0) {
+        (true, false) => pinned_first,
+        (false, true) => pinned_first.reverse(),
+        _ => a.1.cmp(b.1),
+    }
 }
 
 /// Creates a connection status bar
@@ -230,109 +444,873 @@ pub fn create_connection_status_bar() -> (Box, Label, Button) {
     (status_bar, status_label, refresh_button)
 }
 
-/// Creates a details panel for displaying service information
-pub fn create_service_details_panel() -> (Box, Label, Label, Label, Label) {
-    let details_box = Box::new(gtk4::Orientation::Vertical, 8);
-    details_box.set_margin_start(12);
-    details_box.set_margin_end(12);
-    details_box.set_margin_top(8);
-    details_box.set_margin_bottom(8);
-
-    // Title
-    let title_label = Label::new(Some("Service Details"));
-    title_label.set_markup("<b>Service Details</b>");
-    title_label.set_halign(gtk4::Align::Start);
-
-    // Service info grid
-    let info_grid = Grid::new();
-    info_grid.set_row_spacing(6);
-    info_grid.set_column_spacing(12);
-
-    // Labels for service properties
-    let name_key = Label::new(Some("Name:"));
-    name_key.set_halign(gtk4::Align::Start);
-    name_key.set_markup("<b>Name:</b>");
-
-    let status_key = Label::new(Some("Status:"));
-    status_key.set_halign(gtk4::Align::Start);
-    status_key.set_markup("<b>Status:</b>");
-
-    let enabled_key = Label::new(Some("Enabled:"));
-    enabled_key.set_halign(gtk4::Align::Start);
-    enabled_key.set_markup("<b>Enabled:</b>");
-
-    let description_key = Label::new(Some("Description:"));
-    description_key.set_halign(gtk4::Align::Start);
-    description_key.set_markup("<b>Description:</b>");
-
-    // Value labels
-    let name_value = Label::new(Some("-"));
-    name_value.set_halign(gtk4::Align::Start);
-    name_value.set_selectable(true);
-
-    let status_value = Label::new(Some("-"));
-    status_value.set_halign(gtk4::Align::Start);
-
-    let enabled_value = Label::new(Some("-"));
-    enabled_value.set_halign(gtk4::Align::Start);
-
-    let description_value = Label::new(Some("-"));
-    description_value.set_halign(gtk4::Align::Start);
-    description_value.set_wrap(true);
-    description_value.set_selectable(true);
-
-    // Arrange in grid
-    info_grid.attach(&name_key, 0, 0, 1, 1);
-    info_grid.attach(&name_value, 1, 0, 1, 1);
-    info_grid.attach(&status_key, 0, 1, 1, 1);
-    info_grid.attach(&status_value, 1, 1, 1, 1);
-    info_grid.attach(&enabled_key, 0, 2, 1, 1);
-    info_grid.attach(&enabled_value, 1, 2, 1, 1);
-    info_grid.attach(&description_key, 0, 3, 1, 1);
-    info_grid.attach(&description_value, 1, 3, 1, 1);
-
-    details_box.append(&title_label);
-    details_box.append(&Separator::new(gtk4::Orientation::Horizontal));
-    details_box.append(&info_grid);
+/// Details panel describing the currently selected service
+#[derive(Clone)]
+pub struct ServiceDetailsPanel {
+    container: Box,
+    name_value: Label,
+    status_value: Label,
+    enabled_value: Label,
+    since_value: Label,
+    description_value: Label,
+    triggers_value: Label,
+    files_list: Box,
+    graph_button: Button,
+    failure_expander: gtk4::Expander,
+    failure_value: Label,
+    limits_expander: gtk4::Expander,
+    limits_grid: Grid,
+}
 
-    (
-        details_box,
-        name_value,
-        status_value,
-        enabled_value,
-        description_value,
-    )
+impl ServiceDetailsPanel {
+    pub fn new() -> Self {
+        let details_box = Box::new(gtk4::Orientation::Vertical, 8);
+        details_box.set_margin_start(12);
+        details_box.set_margin_end(12);
+        details_box.set_margin_top(8);
+        details_box.set_margin_bottom(8);
+
+        // Title
+        let title_label = Label::new(Some("Service Details"));
+        title_label.set_markup("<b>Service Details</b>");
+        title_label.set_halign(gtk4::Align::Start);
+
+        // Service info grid
+        let info_grid = Grid::new();
+        info_grid.set_row_spacing(6);
+        info_grid.set_column_spacing(12);
+
+        // Labels for service properties
+        let name_key = Label::new(Some("Name:"));
+        name_key.set_halign(gtk4::Align::Start);
+        name_key.set_markup("<b>Name:</b>");
+
+        let status_key = Label::new(Some("Status:"));
+        status_key.set_halign(gtk4::Align::Start);
+        status_key.set_markup("<b>Status:</b>");
+
+        let enabled_key = Label::new(Some("Enabled:"));
+        enabled_key.set_halign(gtk4::Align::Start);
+        enabled_key.set_markup("<b>Enabled:</b>");
+
+        let since_key = Label::new(Some("Active since:"));
+        since_key.set_halign(gtk4::Align::Start);
+        since_key.set_markup("<b>Active since:</b>");
+
+        let description_key = Label::new(Some("Description:"));
+        description_key.set_halign(gtk4::Align::Start);
+        description_key.set_markup("<b>Description:</b>");
+
+        let triggers_key = Label::new(Some("Started by:"));
+        triggers_key.set_halign(gtk4::Align::Start);
+        triggers_key.set_valign(gtk4::Align::Start);
+        triggers_key.set_markup("<b>Started by:</b>");
+
+        // Value labels
+        let name_value = Label::new(Some("-"));
+        name_value.set_halign(gtk4::Align::Start);
+        name_value.set_selectable(true);
+
+        let status_value = Label::new(Some("-"));
+        status_value.set_halign(gtk4::Align::Start);
+
+        let enabled_value = Label::new(Some("-"));
+        enabled_value.set_halign(gtk4::Align::Start);
+
+        let since_value = Label::new(Some("-"));
+        since_value.set_halign(gtk4::Align::Start);
+
+        let description_value = Label::new(Some("-"));
+        description_value.set_halign(gtk4::Align::Start);
+        description_value.set_wrap(true);
+        description_value.set_selectable(true);
+
+        let triggers_value = Label::new(Some("-"));
+        triggers_value.set_halign(gtk4::Align::Start);
+        triggers_value.set_selectable(true);
+
+        // Arrange in grid
+        info_grid.attach(&name_key, 0, 0, 1, 1);
+        info_grid.attach(&name_value, 1, 0, 1, 1);
+        info_grid.attach(&status_key, 0, 1, 1, 1);
+        info_grid.attach(&status_value, 1, 1, 1, 1);
+        info_grid.attach(&enabled_key, 0, 2, 1, 1);
+        info_grid.attach(&enabled_value, 1, 2, 1, 1);
+        info_grid.attach(&since_key, 0, 3, 1, 1);
+        info_grid.attach(&since_value, 1, 3, 1, 1);
+        info_grid.attach(&description_key, 0, 4, 1, 1);
+        info_grid.attach(&description_value, 1, 4, 1, 1);
+        info_grid.attach(&triggers_key, 0, 5, 1, 1);
+        info_grid.attach(&triggers_value, 1, 5, 1, 1);
+
+        let graph_button = Button::with_label("📈 Graph");
+        graph_button.set_tooltip_text(Some("Plot CPU and memory usage over time"));
+        graph_button.set_halign(gtk4::Align::Start);
+        graph_button.set_sensitive(false);
+
+        // Last failure section, shown only for failed services
+        let failure_value = Label::new(None);
+        failure_value.set_halign(gtk4::Align::Start);
+        failure_value.set_wrap(true);
+        failure_value.set_selectable(true);
+
+        let failure_expander = gtk4::Expander::new(Some("Last Failure"));
+        failure_expander.set_child(Some(&failure_value));
+        failure_expander.set_visible(false);
+
+        // Resource limits section, filled in once the limits are known
+        let limits_grid = Grid::new();
+        limits_grid.set_row_spacing(4);
+        limits_grid.set_column_spacing(12);
+        limits_grid.set_margin_top(4);
+
+        let limits_expander = gtk4::Expander::new(Some("Resource Limits"));
+        limits_expander.set_child(Some(&limits_grid));
+        limits_expander.set_visible(false);
+
+        // Unit files section
+        let files_title = Label::new(Some("Files"));
+        files_title.set_markup("<b>Files</b>");
+        files_title.set_halign(gtk4::Align::Start);
+
+        let files_list = Box::new(gtk4::Orientation::Vertical, 2);
+
+        details_box.append(&title_label);
+        details_box.append(&Separator::new(gtk4::Orientation::Horizontal));
+        details_box.append(&info_grid);
+        details_box.append(&graph_button);
+        details_box.append(&failure_expander);
+        details_box.append(&limits_expander);
+        details_box.append(&Separator::new(gtk4::Orientation::Horizontal));
+        details_box.append(&files_title);
+        details_box.append(&files_list);
+
+        Self {
+            container: details_box,
+            name_value,
+            status_value,
+            enabled_value,
+            since_value,
+            description_value,
+            triggers_value,
+            files_list,
+            graph_button,
+            failure_expander,
+            failure_value,
+            limits_expander,
+            limits_grid,
+        }
+    }
+
+    pub fn widget(&self) -> &Box {
+        &self.container
+    }
+
+    /// Calls `f` when the graph button is clicked
+    pub fn connect_graph_clicked<F: Fn() + 'static>(&self, f: F) {
+        self.graph_button.connect_clicked(move |_| f());
+    }
+
+    /// Updates the panel with service information
+    pub fn update(&self, service: &ServiceInfo) {
+        self.name_value.set_text(&service.name);
+        self.graph_button.set_sensitive(true);
+        self.set_failure_reason(None);
+
+        self.set_status(&service.status);
+
+        self.enabled_value
+            .set_text(if service.enabled { "Yes" } else { "No" });
+
+        // Relative time, with the exact time in the tooltip
+        match service.active_since.filter(|_| service.active) {
+            Some(since) => {
+                self.since_value.set_text(&format_relative(&since));
+                self.since_value.set_tooltip_text(Some(
+                    &since
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string(),
+                ));
+            }
+            None => {
+                self.since_value.set_text("-");
+                self.since_value.set_tooltip_text(None);
+            }
+        }
+        self.description_value.set_text(
+            service
+                .description
+                .as_deref()
+                .unwrap_or("No description available"),
+        );
+    }
+
+    /// Updates the status shown for the current service
+    pub fn set_status(&self, status: &ServiceStatus) {
+        // The theme colours the label by its status class
+        self.status_value.set_css_classes(&[&format!(
+            "service-{}",
+            match status {
+                ServiceStatus::Active => "active",
+                ServiceStatus::Inactive => "inactive",
+                ServiceStatus::Failed => "failed",
+                ServiceStatus::ConditionFailed => "condition-failed",
+                ServiceStatus::Activating => "activating",
+                ServiceStatus::Deactivating => "deactivating",
+                ServiceStatus::Reloading => "reloading",
+                ServiceStatus::Masked => "masked",
+                ServiceStatus::Unknown => "unknown",
+            }
+        )]);
+        self.status_value.set_markup(&format!(
+            "<b>{}{}</b>",
+            status
+                .badge()
+                .map(|badge| format!("{} ", badge))
+                .unwrap_or_default(),
+            status
+        ));
+    }
+
+    /// Shows why the service last failed, or hides the section when `reason` is `None`
+    pub fn set_failure_reason(&self, reason: Option<&str>) {
+        match reason {
+            Some(reason) => {
+                self.failure_value.set_text(if reason.is_empty() {
+                    "No error messages in the recent journal"
+                } else {
+                    reason
+                });
+                self.failure_expander.set_visible(true);
+            }
+            None => self.failure_expander.set_visible(false),
+        }
+    }
+
+    /// Lists the units that start the service, one per line
+    pub fn set_triggers(&self, triggers: &[TriggerInfo]) {
+        if triggers.is_empty() {
+            self.triggers_value.set_text("-");
+            return;
+        }
+
+        let text = triggers
+            .iter()
+            .map(|trigger| format!("{} ({})", trigger.unit_name, trigger.trigger_type))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.triggers_value.set_text(&text);
+    }
+
+    /// Shows the service's resource limits, highlighting any set lower than
+    /// the typical system default
+    pub fn set_resource_limits(&self, limits: &ResourceLimits) {
+        while let Some(child) = self.limits_grid.first_child() {
+            self.limits_grid.remove(&child);
+        }
+
+        for (row, (name, value, typical)) in limits.entries().into_iter().enumerate() {
+            let name_label = Label::new(Some(name));
+            name_label.set_halign(gtk4::Align::Start);
+
+            let value_label = Label::new(Some(
+                &value.map_or_else(|| "unlimited".to_string(), |value| value.to_string()),
+            ));
+            value_label.set_halign(gtk4::Align::Start);
+
+            if let (true, Some(typical)) = (is_below_default(value, typical), typical) {
+                value_label.add_css_class("limit-low");
+                value_label.set_tooltip_text(Some(&format!(
+                    "Lower than the typical default of {}",
+                    typical
+                )));
+            }
+
+            self.limits_grid.attach(&name_label, 0, row as i32, 1, 1);
+            self.limits_grid.attach(&value_label, 1, row as i32, 1, 1);
+        }
+
+        self.limits_expander.set_visible(true);
+    }
+
+    /// Lists the unit's files as links; activating one calls `on_open` with its path
+    pub fn set_unit_files<F>(&self, paths: &UnitFilePaths, on_open: F)
+    where
+        F: Fn(PathBuf) + Clone + 'static,
+    {
+        while let Some(child) = self.files_list.first_child() {
+            self.files_list.remove(&child);
+        }
+
+        let entries = paths
+            .fragment
+            .iter()
+            .map(|path| (path, "Unit file"))
+            .chain(paths.dropin_paths.iter().map(|path| (path, "Drop-in")))
+            .chain(
+                paths
+                    .source_path
+                    .iter()
+                    .map(|path| (path, "Generated from")),
+            );
+
+        let mut has_files = false;
+        for (path, kind) in entries {
+            has_files = true;
+
+            let link = gtk4::LinkButton::with_label(
+                &format!("file://{}", path.display()),
+                &path.display().to_string(),
+            );
+            link.set_halign(gtk4::Align::Start);
+            link.set_tooltip_text(Some(kind));
+
+            let on_open = on_open.clone();
+            let path = path.clone();
+            link.connect_activate_link(move |_| {
+                on_open(path.clone());
+                glib::Propagation::Stop
+            });
+
+            self.files_list.append(&link);
+        }
+
+        if !has_files {
+            let none_label = Label::new(Some("No unit files"));
+            none_label.set_halign(gtk4::Align::Start);
+            none_label.style_context().add_class("dim-label");
+            self.files_list.append(&none_label);
+        }
+    }
+}
+
+impl Default for ServiceDetailsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compact legend explaining the colours used to highlight journal priorities
+#[derive(Clone)]
+pub struct LogLevelLegend {
+    container: Box,
+}
+
+impl LogLevelLegend {
+    pub fn new() -> Self {
+        let container = Box::new(gtk4::Orientation::Horizontal, 12);
+        container.add_css_class("log-level-legend");
+
+        for (name, color) in LOG_PRIORITY_COLORS {
+            let item = Label::new(None);
+            item.set_markup(&format!(
+                "<span foreground=\"{}\">■</span> {}",
+                color,
+                glib::markup_escape_text(name)
+            ));
+            container.append(&item);
+        }
+
+        Self { container }
+    }
+
+    pub fn widget(&self) -> &Box {
+        &self.container
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.container.set_visible(visible);
+    }
 }
 
-/// Updates service details panel with service information
-pub fn update_service_details_panel(
-    name_label: &Label,
-    status_label: &Label,
-    enabled_label: &Label,
-    description_label: &Label,
-    service: &ServiceInfo,
+impl Default for LogLevelLegend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Adds a text tag for each journal priority to `buffer`'s tag table, coloured
+/// as in [`LogLevelLegend`] and keyed by priority name. Call once per buffer.
+pub fn create_log_tags(buffer: &gtk4::TextBuffer) -> HashMap<&'static str, gtk4::TextTag> {
+    LOG_PRIORITY_COLORS
+        .iter()
+        .map(|(name, color)| {
+            let tag = gtk4::TextTag::builder().foreground(*color).build();
+            buffer.tag_table().add(&tag);
+            (*name, tag)
+        })
+        .collect()
+}
+
+/// Colours each line of `buffer` from `first_line` on by its journal priority
+pub fn highlight_log_lines(
+    buffer: &gtk4::TextBuffer,
+    tags: &HashMap<&'static str, gtk4::TextTag>,
+    first_line: i32,
 ) {
-    name_label.set_text(&service.name);
-
-    // Set status with color
-    status_label.set_markup(&format!(
-        "<span class=\"service-{}\"><b>{}</b></span>",
-        match service.status {
-            ServiceStatus::Active => "active",
-            ServiceStatus::Inactive => "inactive",
-            ServiceStatus::Failed => "failed",
-            ServiceStatus::Unknown => "unknown",
-        },
-        service.status
-    ));
+    for line in first_line..buffer.line_count() {
+        let Some(start) = buffer.iter_at_line(line) else {
+            continue;
+        };
+        let end = buffer
+            .iter_at_line(line + 1)
+            .unwrap_or_else(|| buffer.end_iter());
+        let Some(priority) = log_line_priority(&buffer.text(&start, &end, false)) else {
+            continue;
+        };
+        if let Some(tag) = tags.get(LOG_PRIORITY_COLORS[priority].0) {
+            buffer.apply_tag(tag, &start, &end);
+        }
+    }
+}
 
-    enabled_label.set_text(if service.enabled { "Yes" } else { "No" });
-    description_label.set_text(
-        &service
-            .description
-            .as_deref()
-            .unwrap_or("No description available"),
-    );
+/// Combo box with an editable entry that suggests matching items as the user types
+#[derive(Clone)]
+pub struct SearchableComboBox {
+    combo: ComboBoxText,
+    entry: Entry,
+    store: gtk4::ListStore,
+}
+
+impl SearchableComboBox {
+    pub fn new(items: &[&str]) -> Self {
+        let combo = ComboBoxText::with_entry();
+        let entry = combo
+            .child()
+            .and_downcast::<Entry>()
+            .expect("ComboBoxText::with_entry has an Entry child");
+        entry.set_placeholder_text(Some("Type to search…"));
+
+        let store = gtk4::ListStore::new(&[glib::Type::STRING]);
+
+        // Match anywhere in the name, not just the prefix
+        let completion = gtk4::EntryCompletion::new();
+        completion.set_model(Some(&store));
+        completion.set_text_column(0);
+        completion.set_popup_completion(true);
+        completion.set_match_func(|completion, key, iter| {
+            completion
+                .model()
+                .and_then(|model| model.get_value(iter, 0).get::<String>().ok())
+                .map(|item| item.to_lowercase().contains(key))
+                .unwrap_or(false)
+        });
+        entry.set_completion(Some(&completion));
+
+        let searchable = Self {
+            combo,
+            entry,
+            store,
+        };
+        searchable.set_items(items);
+        searchable
+    }
+
+    pub fn widget(&self) -> &ComboBoxText {
+        &self.combo
+    }
+
+    /// The entry the user types in
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+
+    /// Replaces the items offered in the drop-down and completion list
+    pub fn set_items(&self, items: &[&str]) {
+        self.combo.remove_all();
+        self.store.clear();
+        for item in items {
+            self.combo.append_text(item);
+            self.store.insert_with_values(None, &[(0, item)]);
+        }
+    }
+
+    /// The typed or chosen item, if any
+    pub fn selected_text(&self) -> Option<String> {
+        let text = self.entry.text().trim().to_string();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+}
+
+/// Compact list of the latest actions run on the selected service, shown
+/// below the details panel
+pub struct ServiceActionHistory {
+    container: Box,
+    list: ListBox,
+}
+
+impl ServiceActionHistory {
+    pub fn new() -> Self {
+        let container = Box::new(gtk4::Orientation::Vertical, 4);
+        container.set_margin_start(12);
+        container.set_margin_end(12);
+        container.set_margin_bottom(8);
+
+        let title = Label::new(Some("Recent Actions"));
+        title.set_markup("<b>Recent Actions</b>");
+        title.set_halign(gtk4::Align::Start);
+
+        let placeholder = Label::new(Some("No recent actions"));
+        placeholder.add_css_class("dim-label");
+
+        let list = ListBox::new();
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        list.set_placeholder(Some(&placeholder));
+
+        container.append(&Separator::new(gtk4::Orientation::Horizontal));
+        container.append(&title);
+        container.append(&list);
+
+        Self { container, list }
+    }
+
+    pub fn widget(&self) -> &Box {
+        &self.container
+    }
+
+    /// Replaces the listed actions; `entries` are expected newest first
+    pub fn set_entries(&self, entries: &[&ActionEntry]) {
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+
+        for entry in entries {
+            let row_box = Box::new(gtk4::Orientation::Horizontal, 8);
+
+            let time_label = Label::new(Some(
+                &entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%m-%d %H:%M:%S")
+                    .to_string(),
+            ));
+            time_label.add_css_class("monospace");
+            row_box.append(&time_label);
+
+            let action_label = Label::new(Some(&entry.action.to_string()));
+            action_label.set_halign(gtk4::Align::Start);
+            action_label.set_hexpand(true);
+            row_box.append(&action_label);
+
+            let outcome_label = Label::new(Some(if entry.success { "✓" } else { "✗" }));
+            outcome_label.add_css_class(if entry.success {
+                "service-active"
+            } else {
+                "service-failed"
+            });
+            outcome_label.set_tooltip_text(entry.error.as_deref());
+            row_box.append(&outcome_label);
+
+            self.list.append(&row_box);
+        }
+    }
+}
+
+impl Default for ServiceActionHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Interval between pulses of progress bars whose progress is unknown
+const PROGRESS_PULSE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// An operation shown in a `ProgressNotificationBar`
+pub struct ProgressItem {
+    /// Fraction done from 0.0 to 1.0; negative while the amount of work is unknown
+    pub progress: f32,
+    id: u64,
+    row: ListBoxRow,
+    bar: gtk4::ProgressBar,
+}
+
+/// Slide-down panel listing every running operation with its own progress
+/// bar and, for cancellable operations, a Cancel button. The panel hides
+/// itself when the last operation finishes.
+#[derive(Clone)]
+pub struct ProgressNotificationBar {
+    revealer: gtk4::Revealer,
+    list: ListBox,
+    items: Rc<RefCell<Vec<ProgressItem>>>,
+    next_id: Rc<Cell<u64>>,
+    pulsing: Rc<Cell<bool>>,
+    idle_senders: Rc<RefCell<Vec<tokio::sync::mpsc::UnboundedSender<()>>>>,
+}
+
+impl ProgressNotificationBar {
+    pub fn new() -> Self {
+        let list = ListBox::new();
+        list.set_selection_mode(gtk4::SelectionMode::None);
+        list.add_css_class("progress-notifications");
+
+        let revealer = gtk4::Revealer::new();
+        revealer.set_transition_type(gtk4::RevealerTransitionType::SlideDown);
+        revealer.set_child(Some(&list));
+
+        Self {
+            revealer,
+            list,
+            items: Rc::new(RefCell::new(Vec::new())),
+            next_id: Rc::new(Cell::new(0)),
+            pulsing: Rc::new(Cell::new(false)),
+            idle_senders: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    pub fn widget(&self) -> &gtk4::Revealer {
+        &self.revealer
+    }
+
+    /// Adds an operation of unknown length and returns its id. When
+    /// `on_cancel` is given the row gets a Cancel button calling it.
+    pub fn add(&self, description: &str, on_cancel: Option<std::boxed::Box<dyn Fn()>>) -> u64 {
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+
+        let row_box = Box::new(gtk4::Orientation::Horizontal, 12);
+        row_box.set_margin_start(12);
+        row_box.set_margin_end(12);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+
+        let label = Label::new(Some(description));
+        label.set_halign(gtk4::Align::Start);
+        label.set_hexpand(true);
+        row_box.append(&label);
+
+        let bar = gtk4::ProgressBar::new();
+        bar.set_valign(gtk4::Align::Center);
+        bar.set_size_request(200, -1);
+        row_box.append(&bar);
+
+        if let Some(on_cancel) = on_cancel {
+            let cancel_button = Button::with_label("Cancel");
+            cancel_button.connect_clicked(move |button| {
+                button.set_sensitive(false);
+                on_cancel();
+            });
+            row_box.append(&cancel_button);
+        }
+
+        let row = ListBoxRow::new();
+        row.set_child(Some(&row_box));
+        self.list.append(&row);
+
+        self.items.borrow_mut().push(ProgressItem {
+            progress: -1.0,
+            id,
+            row,
+            bar,
+        });
+
+        self.revealer.set_reveal_child(true);
+        self.start_pulsing();
+        id
+    }
+
+    /// Sets how far along an operation is, from 0.0 to 1.0
+    pub fn set_progress(&self, id: u64, progress: f32) {
+        if let Some(item) = self
+            .items
+            .borrow_mut()
+            .iter_mut()
+            .find(|item| item.id == id)
+        {
+            item.progress = progress;
+            item.bar.set_fraction(progress.clamp(0.0, 1.0) as f64);
+        }
+    }
+
+    /// Removes a finished operation, hiding the panel if it was the last one
+    pub fn remove(&self, id: u64) {
+        let mut items = self.items.borrow_mut();
+        if let Some(index) = items.iter().position(|item| item.id == id) {
+            let item = items.remove(index);
+            self.list.remove(&item.row);
+        }
+
+        if items.is_empty() {
+            self.revealer.set_reveal_child(false);
+            for sender in self.idle_senders.borrow_mut().drain(..) {
+                let _ = sender.send(());
+            }
+        }
+    }
+
+    /// Whether any operation is still running
+    pub fn is_busy(&self) -> bool {
+        !self.items.borrow().is_empty()
+    }
+
+    /// Returns a receiver signalled once no operations are left running,
+    /// immediately if none are
+    pub fn notify_when_idle(&self) -> tokio::sync::mpsc::UnboundedReceiver<()> {
+        let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+        if self.is_busy() {
+            self.idle_senders.borrow_mut().push(sender);
+        } else {
+            let _ = sender.send(());
+        }
+        receiver
+    }
+
+    /// Animates the bars of operations with unknown progress until none are left
+    fn start_pulsing(&self) {
+        if self.pulsing.replace(true) {
+            return;
+        }
+
+        let items = Rc::downgrade(&self.items);
+        let pulsing = self.pulsing.clone();
+        glib::timeout_add_local(PROGRESS_PULSE_INTERVAL, move || {
+            let Some(items) = items.upgrade() else {
+                return glib::ControlFlow::Break;
+            };
+            let items = items.borrow();
+            if items.is_empty() {
+                pulsing.set(false);
+                return glib::ControlFlow::Break;
+            }
+
+            for item in items.iter().filter(|item| item.progress < 0.0) {
+                item.bar.pulse();
+            }
+            glib::ControlFlow::Continue
+        });
+    }
+}
+
+impl Default for ProgressNotificationBar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Fluent builder for right-click menus backed by `gio::Menu`.
+/// Items refer to actions by detailed name, e.g. `"local.start"`.
+pub struct ContextMenuBuilder {
+    menu: gio::Menu,
+    section: gio::Menu,
+}
+
+impl ContextMenuBuilder {
+    pub fn new() -> Self {
+        Self {
+            menu: gio::Menu::new(),
+            section: gio::Menu::new(),
+        }
+    }
+
+    pub fn add_item(self, label: &str, action: &str) -> Self {
+        self.section.append(Some(label), Some(action));
+        self
+    }
+
+    /// Starts a new section; sections are drawn with a separator between them
+    pub fn add_separator(self) -> Self {
+        if self.section.n_items() == 0 {
+            return self;
+        }
+        self.menu.append_section(None, &self.section);
+        Self {
+            menu: self.menu,
+            section: gio::Menu::new(),
+        }
+    }
+
+    pub fn add_submenu(self, label: &str, submenu: ContextMenuBuilder) -> Self {
+        self.section
+            .append_submenu(Some(label), &submenu.into_model());
+        self
+    }
+
+    pub fn into_model(self) -> gio::Menu {
+        if self.section.n_items() > 0 {
+            self.menu.append_section(None, &self.section);
+        }
+        self.menu
+    }
+
+    pub fn build(self) -> gtk4::PopoverMenu {
+        let popover = gtk4::PopoverMenu::from_model(Some(&self.into_model()));
+        popover.set_has_arrow(false);
+        popover.set_halign(gtk4::Align::Start);
+        popover
+    }
+}
+
+impl Default for ContextMenuBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pops up `popover` at the pointer when `widget` is right-clicked.
+/// `select_at` selects whatever is under the pointer and returns whether
+/// there was anything there to show a menu for.
+pub fn attach_context_menu<W, F>(widget: &W, popover: &gtk4::PopoverMenu, select_at: F)
+where
+    W: IsA<Widget>,
+    F: Fn(f64, f64) -> bool + 'static,
+{
+    popover.set_parent(widget);
+
+    let gesture = gtk4::GestureClick::new();
+    gesture.set_button(gdk4::BUTTON_SECONDARY);
+    let popover = popover.clone();
+    gesture.connect_pressed(move |_, _, x, y| {
+        if select_at(x, y) {
+            popover.set_pointing_to(Some(&gdk4::Rectangle::new(x as i32, y as i32, 1, 1)));
+            popover.popup();
+        }
+    });
+    widget.add_controller(gesture);
+}
+
+/// `attach_context_menu` for tree views: selects the row under the pointer.
+/// The Menu key, or Shift+F10, opens it at the selected row.
+pub fn attach_tree_view_context_menu(tree_view: &TreeView, popover: &gtk4::PopoverMenu) {
+    let view = tree_view.clone();
+    let menu = popover.clone();
+    let controller = gtk4::ShortcutController::new();
+    controller.add_shortcut(gtk4::Shortcut::new(
+        gtk4::ShortcutTrigger::parse_string("Menu|<Shift>F10"),
+        Some(gtk4::CallbackAction::new(move |_, _| {
+            let (paths, _) = view.selection().selected_rows();
+            let Some(path) = paths.first() else {
+                return glib::Propagation::Proceed;
+            };
+            let area = view.cell_area(Some(path), None::<&TreeViewColumn>);
+            let (x, y) = view.convert_bin_window_to_widget_coords(area.x(), area.y());
+            menu.set_pointing_to(Some(&gdk4::Rectangle::new(x, y, 1, area.height())));
+            menu.popup();
+            glib::Propagation::Stop
+        })),
+    ));
+    tree_view.add_controller(controller);
+
+    let view = tree_view.clone();
+    attach_context_menu(tree_view, popover, move |x, y| {
+        let (bin_x, bin_y) = view.convert_widget_to_bin_window_coords(x as i32, y as i32);
+        match view.path_at_pos(bin_x, bin_y) {
+            Some((Some(path), _, _, _)) => {
+                // Keep a multiple selection the click falls within, so the
+                // menu acts on all of it
+                let selection = view.selection();
+                if !selection.path_is_selected(&path) {
+                    selection.unselect_all();
+                    selection.select_path(&path);
+                }
+                true
+            }
+            _ => false,
+        }
+    });
 }
 
 /// Creates a loading spinner widget
@@ -352,6 +1330,356 @@ pub fn create_loading_spinner(text: &str) -> Box {
     spinner_box
 }
 
+/// Search bar for a text view that highlights every match and steps
+/// through them with Previous and Next, scrolling to the selected one
+#[derive(Clone)]
+pub struct TextSearchBar {
+    search_bar: gtk4::SearchBar,
+    entry: gtk4::SearchEntry,
+    count_label: Label,
+    text_view: gtk4::TextView,
+    match_tag: gtk4::TextTag,
+    current_tag: gtk4::TextTag,
+    current_mark: gtk4::TextMark,
+    /// Start and end offsets of each match
+    matches: Rc<RefCell<Vec<(i32, i32)>>>,
+    current: Rc<Cell<Option<usize>>>,
+}
+
+impl TextSearchBar {
+    pub fn new(text_view: &gtk4::TextView) -> Self {
+        let buffer = text_view.buffer();
+        let match_tag = gtk4::TextTag::builder()
+            .background("yellow")
+            .foreground("black")
+            .build();
+        // Added later, so it takes priority over the plain match colour
+        let current_tag = gtk4::TextTag::builder()
+            .background("orange")
+            .foreground("black")
+            .build();
+        buffer.tag_table().add(&match_tag);
+        buffer.tag_table().add(&current_tag);
+        let current_mark = buffer.create_mark(None, &buffer.start_iter(), true);
+
+        let entry = gtk4::SearchEntry::new();
+        entry.set_hexpand(true);
+        let previous_button = Button::from_icon_name("go-up-symbolic");
+        previous_button.set_tooltip_text(Some("Previous match"));
+        let next_button = Button::from_icon_name("go-down-symbolic");
+        next_button.set_tooltip_text(Some("Next match"));
+        let count_label = Label::new(None);
+
+        let search_box = Box::new(gtk4::Orientation::Horizontal, 6);
+        search_box.append(&entry);
+        search_box.append(&previous_button);
+        search_box.append(&next_button);
+        search_box.append(&count_label);
+
+        let search_bar = gtk4::SearchBar::new();
+        search_bar.set_child(Some(&search_box));
+        search_bar.connect_entry(&entry);
+        search_bar.set_show_close_button(true);
+
+        let bar = Self {
+            search_bar,
+            entry,
+            count_label,
+            text_view: text_view.clone(),
+            match_tag,
+            current_tag,
+            current_mark,
+            matches: Rc::new(RefCell::new(Vec::new())),
+            current: Rc::new(Cell::new(None)),
+        };
+
+        let search = bar.clone();
+        bar.entry.connect_search_changed(move |_| search.search());
+        let search = bar.clone();
+        bar.entry
+            .connect_activate(move |_| search.select_next(true));
+        let search = bar.clone();
+        bar.entry
+            .connect_next_match(move |_| search.select_next(true));
+        let search = bar.clone();
+        bar.entry
+            .connect_previous_match(move |_| search.select_next(false));
+        let search = bar.clone();
+        next_button.connect_clicked(move |_| search.select_next(true));
+        let search = bar.clone();
+        previous_button.connect_clicked(move |_| search.select_next(false));
+
+        // Keep the highlights in step when the text is replaced or followed
+        let search = bar.clone();
+        buffer.connect_changed(move |_| {
+            if search.search_bar.is_search_mode() && !search.entry.text().is_empty() {
+                search.search();
+            }
+        });
+
+        bar
+    }
+
+    pub fn widget(&self) -> &gtk4::SearchBar {
+        &self.search_bar
+    }
+
+    /// Opens the bar with Ctrl+F, or by typing, anywhere in `window`
+    pub fn attach_to(&self, window: &impl IsA<Widget>) {
+        self.search_bar.set_key_capture_widget(Some(window));
+
+        let search_bar = self.search_bar.clone();
+        let controller = gtk4::ShortcutController::new();
+        controller.add_shortcut(gtk4::Shortcut::new(
+            gtk4::ShortcutTrigger::parse_string("<Ctrl>f"),
+            Some(gtk4::CallbackAction::new(move |_, _| {
+                search_bar.set_search_mode(true);
+                glib::Propagation::Stop
+            })),
+        ));
+        window.add_controller(controller);
+    }
+
+    /// Highlights every case-insensitive match of the entry's text
+    fn search(&self) {
+        let buffer = self.text_view.buffer();
+        let (start, end) = buffer.bounds();
+        buffer.remove_tag(&self.match_tag, &start, &end);
+        buffer.remove_tag(&self.current_tag, &start, &end);
+        self.current.set(None);
+
+        let query = self.entry.text();
+        let mut matches = self.matches.borrow_mut();
+        matches.clear();
+        if query.is_empty() {
+            self.count_label.set_text("");
+            return;
+        }
+
+        let flags = gtk4::TextSearchFlags::CASE_INSENSITIVE | gtk4::TextSearchFlags::TEXT_ONLY;
+        let mut iter = buffer.iter_at_offset(0);
+        while let Some((match_start, match_end)) = iter.forward_search(&query, flags, None) {
+            buffer.apply_tag(&self.match_tag, &match_start, &match_end);
+            matches.push((match_start.offset(), match_end.offset()));
+            iter = match_end;
+        }
+        self.count_label
+            .set_text(&format_match_count(None, matches.len()));
+    }
+
+    /// Selects the match after the current one, or before it when `forward`
+    /// is false, wrapping around at either end
+    fn select_next(&self, forward: bool) {
+        let matches = self.matches.borrow();
+        if matches.is_empty() {
+            return;
+        }
+
+        let index = match (self.current.get(), forward) {
+            (None, true) => 0,
+            (None, false) => matches.len() - 1,
+            (Some(index), true) => (index + 1) % matches.len(),
+            (Some(index), false) => (index + matches.len() - 1) % matches.len(),
+        };
+        self.current.set(Some(index));
+
+        let buffer = self.text_view.buffer();
+        let (start, end) = buffer.bounds();
+        buffer.remove_tag(&self.current_tag, &start, &end);
+        let (start_offset, end_offset) = matches[index];
+        let match_start = buffer.iter_at_offset(start_offset);
+        buffer.apply_tag(
+            &self.current_tag,
+            &match_start,
+            &buffer.iter_at_offset(end_offset),
+        );
+        buffer.move_mark(&self.current_mark, &match_start);
+        self.text_view
+            .scroll_to_mark(&self.current_mark, 0.1, false, 0.0, 0.0);
+
+        self.count_label
+            .set_text(&format_match_count(Some(index), matches.len()));
+    }
+}
+
+/// How long a notification stays up before hiding itself
+pub const NOTIFICATION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// What a notification reports, which sets its icon and colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    Success,
+    Error,
+    Warning,
+}
+
+impl NotificationKind {
+    pub const ALL: [NotificationKind; 3] = [
+        NotificationKind::Success,
+        NotificationKind::Error,
+        NotificationKind::Warning,
+    ];
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            NotificationKind::Success => "✓",
+            NotificationKind::Error => "✗",
+            NotificationKind::Warning => "⚠",
+        }
+    }
+
+    pub fn css_class(&self) -> &'static str {
+        match self {
+            NotificationKind::Success => "notification-success",
+            NotificationKind::Error => "notification-error",
+            NotificationKind::Warning => "notification-warning",
+        }
+    }
+}
+
+/// Bar sliding up from the bottom of the window to report the outcome of
+/// an operation without blocking like a dialog. It hides itself after
+/// `NOTIFICATION_TIMEOUT`, and a newer notification replaces the current one.
+#[derive(Clone)]
+pub struct NotificationBar {
+    revealer: gtk4::Revealer,
+    content: Box,
+    icon: Label,
+    message: Label,
+    hide_source: Rc<RefCell<Option<glib::SourceId>>>,
+}
+
+impl NotificationBar {
+    pub fn new() -> Self {
+        let content = Box::new(gtk4::Orientation::Horizontal, 12);
+        content.add_css_class("notification");
+
+        let icon = Label::new(None);
+        let message = Label::new(None);
+        message.set_wrap(true);
+        message.set_xalign(0.0);
+        let close_button = Button::with_label("✕");
+        close_button.add_css_class("flat");
+        close_button.set_tooltip_text(Some("Dismiss"));
+
+        content.append(&icon);
+        content.append(&message);
+        content.append(&close_button);
+
+        let revealer = gtk4::Revealer::new();
+        revealer.set_transition_type(gtk4::RevealerTransitionType::SlideUp);
+        revealer.set_halign(gtk4::Align::Center);
+        revealer.set_valign(gtk4::Align::End);
+        revealer.set_child(Some(&content));
+
+        let bar = Self {
+            revealer,
+            content,
+            icon,
+            message,
+            hide_source: Rc::new(RefCell::new(None)),
+        };
+
+        let close_bar = bar.clone();
+        close_button.connect_clicked(move |_| close_bar.hide());
+
+        bar
+    }
+
+    pub fn widget(&self) -> &gtk4::Revealer {
+        &self.revealer
+    }
+
+    pub fn show(&self, message: &str, kind: NotificationKind) {
+        for other in NotificationKind::ALL {
+            self.content.remove_css_class(other.css_class());
+        }
+        self.content.add_css_class(kind.css_class());
+        self.icon.set_text(kind.icon());
+        self.message.set_text(message);
+        self.revealer.set_reveal_child(true);
+
+        if let Some(source) = self.hide_source.take() {
+            source.remove();
+        }
+        let revealer = self.revealer.clone();
+        let hide_source = self.hide_source.clone();
+        let source = glib::timeout_add_local_once(NOTIFICATION_TIMEOUT, move || {
+            // The source is gone once it has fired
+            hide_source.take();
+            revealer.set_reveal_child(false);
+        });
+        self.hide_source.replace(Some(source));
+    }
+
+    pub fn hide(&self) {
+        if let Some(source) = self.hide_source.take() {
+            source.remove();
+        }
+        self.revealer.set_reveal_child(false);
+    }
+}
+
+/// Covers a widget with a loading spinner while its contents are fetched,
+/// making the related controls insensitive until every fetch has finished
+#[derive(Clone)]
+pub struct LoadingOverlay {
+    overlay: gtk4::Overlay,
+    spinner: Box,
+    controls: Rc<RefCell<Vec<Widget>>>,
+    pending: Rc<Cell<u32>>,
+}
+
+impl LoadingOverlay {
+    pub fn new(text: &str) -> Self {
+        Self {
+            overlay: gtk4::Overlay::new(),
+            spinner: create_loading_spinner(text),
+            controls: Rc::new(RefCell::new(Vec::new())),
+            pending: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn widget(&self) -> &gtk4::Overlay {
+        &self.overlay
+    }
+
+    /// Sets the widget the spinner is shown above
+    pub fn set_child(&self, child: &impl IsA<Widget>) {
+        self.overlay.set_child(Some(child));
+    }
+
+    /// Adds a widget that is insensitive while loading
+    pub fn add_control(&self, control: &impl IsA<Widget>) {
+        self.controls.borrow_mut().push(control.clone().upcast());
+    }
+
+    pub fn start(&self) {
+        let pending = self.pending.get();
+        self.pending.set(pending + 1);
+        if pending == 0 {
+            self.overlay.add_overlay(&self.spinner);
+            for control in self.controls.borrow().iter() {
+                control.set_sensitive(false);
+            }
+        }
+    }
+
+    pub fn finish(&self) {
+        match self.pending.get() {
+            0 => {}
+            1 => {
+                self.pending.set(0);
+                self.overlay.remove_overlay(&self.spinner);
+                for control in self.controls.borrow().iter() {
+                    control.set_sensitive(true);
+                }
+            }
+            pending => self.pending.set(pending - 1),
+        }
+    }
+}
+
 /// Creates an error message widget
 pub fn create_error_widget(message: &str) -> Box {
     let error_box = Box::new(gtk4::Orientation::Vertical, 8);
@@ -407,6 +1735,11 @@ pub fn apply_service_status_style(widget: &impl IsA<Widget>, status: &ServiceSta
     style_context.remove_class("service-active");
     style_context.remove_class("service-inactive");
     style_context.remove_class("service-failed");
+    style_context.remove_class("service-condition-failed");
+    style_context.remove_class("service-activating");
+    style_context.remove_class("service-deactivating");
+    style_context.remove_class("service-reloading");
+    style_context.remove_class("service-masked");
     style_context.remove_class("service-unknown");
 
     // Add appropriate class
@@ -414,6 +1747,11 @@ pub fn apply_service_status_style(widget: &impl IsA<Widget>, status: &ServiceSta
         ServiceStatus::Active => "service-active",
         ServiceStatus::Inactive => "service-inactive",
         ServiceStatus::Failed => "service-failed",
+        ServiceStatus::ConditionFailed => "service-condition-failed",
+        ServiceStatus::Activating => "service-activating",
+        ServiceStatus::Deactivating => "service-deactivating",
+        ServiceStatus::Reloading => "service-reloading",
+        ServiceStatus::Masked => "service-masked",
         ServiceStatus::Unknown => "service-unknown",
     };
 
@@ -431,4 +1769,34 @@ mod tests {
         // For now, we'll just test that the function exists and can be called
         assert!(true);
     }
+
+    #[test]
+    fn test_compare_pinned_first() {
+        use std::cmp::Ordering;
+
+        assert_eq!(
+            compare_pinned_first((true, "zram"), (false, "acpid"), false),
+            Ordering::Less
+        );
+        assert_eq!(
+            compare_pinned_first((true, "acpid"), (false, "zram"), true),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_pinned_first((false, "acpid"), (false, "zram"), true),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_service_matches() {
+        assert!(service_matches("NetworkManager", "Active", "network", None));
+        assert!(service_matches("cron", "Failed", "", None));
+        assert!(!service_matches("cron", "Active", "ssh", None));
+
+        let failed = ServiceStatus::Failed;
+        assert!(service_matches("cron", "Failed", "cr", Some(&failed)));
+        assert!(!service_matches("cron", "Active", "cr", Some(&failed)));
+        assert!(!service_matches("ssh", "Failed", "cr", Some(&failed)));
+    }
 }
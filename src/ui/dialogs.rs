@@ -5,11 +5,34 @@ use gtk4::{
     ComboBoxText, Dialog, Entry, Grid, Label, ResponseType, ScrolledWindow, TextView, Window,
 };
 use log::{debug, error, info, warn};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::rc::Rc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 
-use crate::remote_host::{AuthType, RemoteHost};
+use crate::remote_host::{
+    parse_port, AuthType, ConnectionEvent, RemoteHost, ValidationError, DEFAULT_SSH_PORT,
+};
+use crate::service_manager::{
+    missing_unit_sections, service_unit_content, timer_unit_content, unit_file_sources, AuditEntry,
+    BootEntry, ExecSteps, GeneratorInfo, LogOptions, ResourceTimeSeries, SecuritySetting,
+    ServiceEnvironment, ServiceSpec, ServiceTemplate, TimerSpec, RESTART_POLICIES,
+};
+use crate::ui::components::{
+    create_log_tags, highlight_log_lines, LogLevelLegend, SearchableComboBox, TextSearchBar,
+};
+use crate::utils::audit::AuditLog;
+use crate::utils::config::RefreshConfig;
+use crate::utils::diagnostics::log_export_file_name;
+use crate::utils::diff::unified_diff;
+use crate::utils::format::{format_relative, format_systemd_timestamp, format_timespan};
+use crate::utils::import::parse_user_ssh_config;
+use crate::utils::preferences::PreferencesConfig;
+
+/// Log lines that may queue up while following a journal before the
+/// reader waits for the dialog to catch up
+const LOG_FOLLOW_BUFFER: usize = 256;
 
 pub fn show_error_dialog(parent: &Window, title: &str, message: &str) {
     let dialog = gtk4::MessageDialog::new(
@@ -56,7 +79,47 @@ pub fn show_warning_dialog(parent: &Window, title: &str, message: &str) {
     });
 }
 
-pub fn show_confirmation_dialog(parent: &Window, title: &str, message: &str) -> bool {
+/// Reports the problems `systemd-analyze verify` found in `unit_name`.
+/// Unless the user `requested` the check, as opposed to it running after a
+/// save, only problems are shown.
+pub fn show_verify_results(
+    parent: &Window,
+    unit_name: &str,
+    result: Result<Vec<String>>,
+    requested: bool,
+) {
+    match result {
+        Ok(problems) if problems.is_empty() => {
+            if requested {
+                show_info_dialog(parent, "Unit file OK", "No issues found.");
+            }
+        }
+        Ok(problems) => show_warning_dialog(
+            parent,
+            "Unit File Problems",
+            &format!(
+                "systemd-analyze found problems in {}:\n\n{}",
+                unit_name,
+                problems.join("\n")
+            ),
+        ),
+        Err(e) if requested => show_error_dialog(
+            parent,
+            "Verify Failed",
+            &format!("Could not verify {}: {}", unit_name, e),
+        ),
+        Err(e) => warn!("Could not verify {}: {}", unit_name, e),
+    }
+}
+
+/// Asks the user to confirm `message`, then calls `on_response` with
+/// whether they clicked Confirm
+pub fn show_confirmation_dialog(
+    parent: &Window,
+    title: &str,
+    message: &str,
+    on_response: impl Fn(bool) + 'static,
+) {
     let dialog = gtk4::MessageDialog::new(
         Some(parent),
         gtk4::DialogFlags::MODAL,
@@ -69,181 +132,128 @@ pub fn show_confirmation_dialog(parent: &Window, title: &str, message: &str) ->
     dialog.add_button("Confirm", ResponseType::Accept);
     dialog.set_default_response(ResponseType::Accept);
 
-    // For now, return true - in a real implementation you'd use async callbacks
-    // This is a simplified version for the GTK4 upgrade
-    true
+    // Closing the dialog may emit another response; only the first counts
+    let answered = Rc::new(Cell::new(false));
+    dialog.connect_response(move |dialog, response| {
+        if !answered.replace(true) {
+            on_response(response == ResponseType::Accept);
+        }
+        dialog.close();
+    });
+    dialog.show();
 }
 
-pub fn show_add_host_dialog(
+/// Asks whether to trust the host key of `host`, seen for the first time,
+/// or with `previous` warns that it differs from the key accepted before.
+/// `on_response` is called with whether the user accepted the key.
+pub fn show_host_key_dialog(
     parent: &Window,
-    remote_hosts: &Rc<RefCell<HashMap<String, RemoteHost>>>,
+    host: &str,
+    fingerprint: &str,
+    previous: Option<&str>,
+    on_response: impl Fn(bool) + 'static,
 ) {
-    let dialog = Dialog::new();
-    dialog.set_title(Some("Add Remote Host"));
-    dialog.set_transient_for(Some(parent));
-    dialog.set_modal(true);
-    dialog.add_button("Cancel", ResponseType::Cancel);
-    dialog.add_button("Add", ResponseType::Ok);
-
-    dialog.set_default_size(400, 300);
-
-    let grid = Grid::new();
-    grid.set_row_spacing(12);
-    grid.set_column_spacing(12);
-    grid.set_margin_start(20);
-    grid.set_margin_end(20);
-    grid.set_margin_top(20);
-    grid.set_margin_bottom(20);
-
-    // Name field
-    let name_label = Label::new(Some("Host Name:"));
-    name_label.set_halign(gtk4::Align::Start);
-    let name_entry = Entry::new();
-    name_entry.set_placeholder_text(Some("My Server"));
-    grid.attach(&name_label, 0, 0, 1, 1);
-    grid.attach(&name_entry, 1, 0, 1, 1);
-
-    // Hostname field
-    let hostname_label = Label::new(Some("Hostname/IP:"));
-    hostname_label.set_halign(gtk4::Align::Start);
-    let hostname_entry = Entry::new();
-    hostname_entry.set_placeholder_text(Some("192.168.1.100"));
-    grid.attach(&hostname_label, 0, 1, 1, 1);
-    grid.attach(&hostname_entry, 1, 1, 1, 1);
-
-    // Username field
-    let username_label = Label::new(Some("Username:"));
-    username_label.set_halign(gtk4::Align::Start);
-    let username_entry = Entry::new();
-    username_entry.set_placeholder_text(Some("root"));
-    grid.attach(&username_label, 0, 2, 1, 1);
-    grid.attach(&username_entry, 1, 2, 1, 1);
-
-    // Port field
-    let port_label = Label::new(Some("Port:"));
-    port_label.set_halign(gtk4::Align::Start);
-    let port_entry = Entry::new();
-    port_entry.set_placeholder_text(Some("22"));
-    port_entry.set_text("22");
-    grid.attach(&port_label, 0, 3, 1, 1);
-    grid.attach(&port_entry, 1, 3, 1, 1);
-
-    // Auth type
-    let auth_label = Label::new(Some("Authentication:"));
-    auth_label.set_halign(gtk4::Align::Start);
-    let auth_combo = ComboBoxText::new();
-    auth_combo.append_text("Password");
-    auth_combo.append_text("SSH Key");
-    auth_combo.set_active(Some(0));
-    grid.attach(&auth_label, 0, 4, 1, 1);
-    grid.attach(&auth_combo, 1, 4, 1, 1);
-
-    // SSH Key path (initially hidden)
-    let key_label = Label::new(Some("SSH Key Path:"));
-    key_label.set_halign(gtk4::Align::Start);
-    let key_entry = Entry::new();
-    key_entry.set_placeholder_text(Some("/home/user/.ssh/id_rsa"));
-    let key_button = gtk4::Button::with_label("Browse...");
-
-    let key_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
-    key_entry.set_hexpand(true);
-    key_box.append(&key_entry);
-    key_box.append(&key_button);
-
-    grid.attach(&key_label, 0, 5, 1, 1);
-    grid.attach(&key_box, 1, 5, 1, 1);
-
-    // Initially hide key fields
-    key_label.set_visible(false);
-    key_box.set_visible(false);
-
-    // Auth type change handler
-    let key_label_clone = key_label.clone();
-    let key_box_clone = key_box.clone();
-    auth_combo.connect_changed(move |combo| {
-        let is_key_auth = combo.active() == Some(1);
-        key_label_clone.set_visible(is_key_auth);
-        key_box_clone.set_visible(is_key_auth);
-    });
+    let (message_type, text, detail) = match previous {
+        None => (
+            gtk4::MessageType::Question,
+            "Trust this host?",
+            format!(
+                "The authenticity of {} can't be established.\n\nKey fingerprint:\n{}",
+                host, fingerprint
+            ),
+        ),
+        Some(previous) => (
+            gtk4::MessageType::Warning,
+            "The host key has changed!",
+            format!(
+                "The key of {} differs from the one accepted before. Someone may be \
+                 intercepting the connection, or the host was reinstalled.\n\n\
+                 New fingerprint:\n{}\n\nPreviously accepted:\n{}",
+                host, fingerprint, previous
+            ),
+        ),
+    };
 
-    // SSH Key file chooser
-    let dialog_weak = dialog.downgrade();
-    let key_entry_clone = key_entry.clone();
-    key_button.connect_clicked(move |_| {
-        if let Some(dialog) = dialog_weak.upgrade() {
-            if let Some(parent) = dialog.transient_for() {
-                let file_dialog = gtk4::FileChooserDialog::new(
-                    Some("Select SSH Key"),
-                    Some(&parent),
-                    gtk4::FileChooserAction::Open,
-                    &[
-                        ("Cancel", ResponseType::Cancel),
-                        ("Select", ResponseType::Accept),
-                    ],
-                );
-                file_dialog.set_modal(true);
-
-                let key_entry_for_dialog = key_entry_clone.clone();
-                file_dialog.connect_response(move |dialog, response| {
-                    if response == ResponseType::Accept {
-                        if let Some(file) = dialog.file() {
-                            if let Some(path) = file.path() {
-                                key_entry_for_dialog.set_text(&path.display().to_string());
-                            }
-                        }
-                    }
-                    dialog.close();
-                });
+    let dialog = gtk4::MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        message_type,
+        gtk4::ButtonsType::None,
+        text,
+    );
+    dialog.set_title(Some("Host Key Verification"));
+    dialog.set_secondary_text(Some(&detail));
+    dialog.add_button("Reject", ResponseType::Reject);
+    let accept_button = dialog.add_button("Accept", ResponseType::Accept);
+    if previous.is_some() {
+        accept_button.add_css_class("destructive-action");
+    }
+    dialog.set_default_response(ResponseType::Reject);
 
-                file_dialog.show();
-            }
+    // Closing the dialog may emit another response; only the first counts
+    let answered = Rc::new(Cell::new(false));
+    dialog.connect_response(move |dialog, response| {
+        if !answered.replace(true) {
+            on_response(response == ResponseType::Accept);
         }
+        dialog.close();
     });
+    dialog.show();
+}
 
-    dialog.set_child(Some(&grid));
+/// Asks whether imported hosts should replace the saved hosts with the same
+/// `names`. `on_response` is called with true to replace them or false to
+/// skip them, and not at all when the import is cancelled.
+pub fn show_replace_or_skip_dialog(
+    parent: &Window,
+    names: &[String],
+    on_response: impl Fn(bool) + 'static,
+) {
+    let dialog = gtk4::MessageDialog::new(
+        Some(parent),
+        gtk4::DialogFlags::MODAL,
+        gtk4::MessageType::Question,
+        gtk4::ButtonsType::None,
+        "Replace or Skip?",
+    );
+    dialog.set_title(Some("Import Hosts"));
+    dialog.set_secondary_text(Some(&format!(
+        "{} already exist{}: {}",
+        if names.len() == 1 {
+            "A host with this name".to_string()
+        } else {
+            format!("{} hosts with these names", names.len())
+        },
+        if names.len() == 1 { "s" } else { "" },
+        names.join(", ")
+    )));
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Skip", ResponseType::No);
+    dialog.add_button("Replace", ResponseType::Yes);
+    dialog.set_default_response(ResponseType::No);
 
-    let remote_hosts_clone = remote_hosts.clone();
     dialog.connect_response(move |dialog, response| {
-        if response == ResponseType::Ok {
-            let name = name_entry.text().to_string();
-            let hostname = hostname_entry.text().to_string();
-            let username = username_entry.text().to_string();
-
-            if !name.is_empty() && !hostname.is_empty() && !username.is_empty() {
-                let auth_type = if auth_combo.active() == Some(0) {
-                    AuthType::Password
-                } else {
-                    let key_path = key_entry.text().to_string();
-                    AuthType::Key {
-                        path: if key_path.is_empty() {
-                            None
-                        } else {
-                            Some(key_path.into())
-                        },
-                    }
-                };
-
-                let host = RemoteHost {
-                    name: name.clone(),
-                    hostname,
-                    username,
-                    auth_type,
-                };
-
-                remote_hosts_clone.borrow_mut().insert(name.clone(), host);
-            }
+        match response {
+            ResponseType::Yes => on_response(true),
+            ResponseType::No => on_response(false),
+            _ => {}
         }
         dialog.close();
     });
-
     dialog.show();
 }
 
-pub fn show_edit_host_dialog(
+/// Edits `host` in `remote_hosts`, calling `on_saved` with the host's new
+/// name after the collection is updated. A host not yet in the collection
+/// is added on save.
+pub fn show_edit_host_dialog<F>(
     parent: &Window,
     host: &RemoteHost,
     remote_hosts: &Rc<RefCell<HashMap<String, RemoteHost>>>,
-) {
+    on_saved: F,
+) where
+    F: Fn(&str) + 'static,
+{
     let dialog = Dialog::new();
     dialog.set_title(Some("Edit Remote Host"));
     dialog.set_transient_for(Some(parent));
@@ -283,11 +293,20 @@ pub fn show_edit_host_dialog(
     grid.attach(&username_label, 0, 2, 1, 1);
     grid.attach(&username_entry, 1, 2, 1, 1);
 
+    let port_label = Label::new(Some("Port:"));
+    port_label.set_halign(gtk4::Align::Start);
+    let port_entry = Entry::new();
+    port_entry.set_placeholder_text(Some(&DEFAULT_SSH_PORT.to_string()));
+    port_entry.set_text(&host.port.to_string());
+    grid.attach(&port_label, 0, 3, 1, 1);
+    grid.attach(&port_entry, 1, 3, 1, 1);
+
     let auth_label = Label::new(Some("Authentication:"));
     auth_label.set_halign(gtk4::Align::Start);
     let auth_combo = ComboBoxText::new();
     auth_combo.append_text("Password");
     auth_combo.append_text("SSH Key");
+    auth_combo.append_text("SSH Agent");
 
     let key_label = Label::new(Some("SSH Key Path:"));
     key_label.set_halign(gtk4::Align::Start);
@@ -306,6 +325,11 @@ pub fn show_edit_host_dialog(
             key_label.set_visible(false);
             key_box.set_visible(false);
         }
+        AuthType::Agent => {
+            auth_combo.set_active(Some(2));
+            key_label.set_visible(false);
+            key_box.set_visible(false);
+        }
         AuthType::Key { path } => {
             auth_combo.set_active(Some(1));
             if let Some(p) = path {
@@ -316,10 +340,31 @@ pub fn show_edit_host_dialog(
         }
     }
 
-    grid.attach(&auth_label, 0, 3, 1, 1);
-    grid.attach(&auth_combo, 1, 3, 1, 1);
-    grid.attach(&key_label, 0, 4, 1, 1);
-    grid.attach(&key_box, 1, 4, 1, 1);
+    grid.attach(&auth_label, 0, 4, 1, 1);
+    grid.attach(&auth_combo, 1, 4, 1, 1);
+    grid.attach(&key_label, 0, 5, 1, 1);
+    grid.attach(&key_box, 1, 5, 1, 1);
+
+    let auto_refresh_check = gtk4::CheckButton::with_label("Auto-refresh this host");
+    auto_refresh_check.set_active(host.auto_refresh);
+    grid.attach(&auto_refresh_check, 0, 6, 2, 1);
+
+    let interval_label = Label::new(Some("Refresh Interval (seconds):"));
+    interval_label.set_halign(gtk4::Align::Start);
+    let interval_spin = gtk4::SpinButton::with_range(
+        RefreshConfig::MIN_INTERVAL_SECS as f64,
+        RefreshConfig::MAX_INTERVAL_SECS as f64,
+        5.0,
+    );
+    interval_spin.set_value(host.refresh().interval() as f64);
+    interval_spin.set_sensitive(host.auto_refresh);
+    grid.attach(&interval_label, 0, 7, 1, 1);
+    grid.attach(&interval_spin, 1, 7, 1, 1);
+
+    let interval_spin_clone = interval_spin.clone();
+    auto_refresh_check.connect_toggled(move |check| {
+        interval_spin_clone.set_sensitive(check.is_active());
+    });
 
     // Auth type change handler
     let key_label_clone = key_label.clone();
@@ -334,23 +379,34 @@ pub fn show_edit_host_dialog(
 
     let remote_hosts_clone = remote_hosts.clone();
     let old_name = host.name.clone();
+    let ssh_control_path = host.ssh_control_path.clone();
+    let capabilities = host.capabilities.clone();
     dialog.connect_response(move |dialog, response| {
         if response == ResponseType::Ok {
             let new_name = name_entry.text().to_string();
             let hostname = hostname_entry.text().to_string();
             let username = username_entry.text().to_string();
+            let port = match parse_port(&port_entry.text()) {
+                Ok(port) => port,
+                Err(e) => {
+                    show_error_dialog(dialog.upcast_ref(), "Invalid Port", &e.message);
+                    return;
+                }
+            };
 
             if !new_name.is_empty() && !hostname.is_empty() && !username.is_empty() {
-                let auth_type = if auth_combo.active() == Some(0) {
-                    AuthType::Password
-                } else {
-                    let key_path = key_entry.text().to_string();
-                    AuthType::Key {
-                        path: if key_path.is_empty() {
-                            None
-                        } else {
-                            Some(key_path.into())
-                        },
+                let auth_type = match auth_combo.active() {
+                    Some(0) => AuthType::Password,
+                    Some(2) => AuthType::Agent,
+                    _ => {
+                        let key_path = key_entry.text().to_string();
+                        AuthType::Key {
+                            path: if key_path.is_empty() {
+                                None
+                            } else {
+                                Some(key_path.into())
+                            },
+                        }
                     }
                 };
 
@@ -358,12 +414,20 @@ pub fn show_edit_host_dialog(
                     name: new_name.clone(),
                     hostname,
                     username,
+                    port,
                     auth_type,
+                    ssh_control_path: ssh_control_path.clone(),
+                    auto_refresh: auto_refresh_check.is_active(),
+                    refresh_interval_secs: interval_spin.value_as_int() as u32,
+                    capabilities: capabilities.clone(),
                 };
 
                 // Update hosts collection
                 remote_hosts_clone.borrow_mut().remove(&old_name);
-                remote_hosts_clone.borrow_mut().insert(new_name, new_host);
+                remote_hosts_clone
+                    .borrow_mut()
+                    .insert(new_name.clone(), new_host);
+                on_saved(&new_name);
             }
         }
         dialog.close();
@@ -372,11 +436,19 @@ pub fn show_edit_host_dialog(
     dialog.show();
 }
 
+/// Shows a service's logs. When `boots` are given, a Boot selector lets
+/// the user switch to an earlier boot's logs, which `reload` fetches for
+/// the chosen boot id. Without a time range, `reload` is expected to limit
+/// the logs to the most recent entries. The Follow toggle appends new
+/// entries as they are logged, sent by the task `follow` starts.
 pub fn show_service_logs_dialog(
     parent: &Window,
     service_name: &str,
     logs: &str,
     host: Option<&str>,
+    boots: &[BootEntry],
+    reload: impl Fn(LogOptions, std::boxed::Box<dyn FnOnce(Result<String>)>) + 'static,
+    follow: impl Fn(mpsc::Sender<String>) -> JoinHandle<()> + 'static,
 ) {
     let title = if let Some(h) = host {
         format!("Logs for {} on {}", service_name, h)
@@ -400,18 +472,369 @@ pub fn show_service_logs_dialog(
     text_view.set_cursor_visible(false);
     text_view.set_monospace(true);
 
-    // Set dark theme colors for logs
+    // Colour lines by journal priority; the tags go in before the search
+    // bar's so search highlights stay on top
     let text_buffer = text_view.buffer();
+    let log_tags = Rc::new(create_log_tags(&text_buffer));
     text_buffer.set_text(logs);
+    highlight_log_lines(&text_buffer, &log_tags, 0);
 
     scrolled.set_child(Some(&text_view));
+    scrolled.set_vexpand(true);
+
+    // Toolbar
+    let toolbar = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    let legend_toggle = gtk4::CheckButton::with_label("Legend");
+    legend_toggle.set_active(true);
+    toolbar.append(&legend_toggle);
+
+    let follow_toggle = gtk4::ToggleButton::with_label("Follow");
+    follow_toggle.set_tooltip_text(Some("Show new entries as they are logged"));
+    toolbar.append(&follow_toggle);
+
+    let copy_button = gtk4::Button::with_label("Copy All");
+    copy_button.set_tooltip_text(Some("Copy the logs to the clipboard"));
+    copy_button.connect_clicked(clone!(
+        #[weak]
+        text_buffer,
+        move |button| {
+            let (start, end) = text_buffer.bounds();
+            button
+                .clipboard()
+                .set_text(&text_buffer.text(&start, &end, false));
+        }
+    ));
+    toolbar.append(&copy_button);
+
+    let export_button = gtk4::Button::with_label("Export…");
+    export_button.set_tooltip_text(Some("Save the logs to a file"));
+    let export_name = service_name.to_string();
+    export_button.connect_clicked(clone!(
+        #[weak]
+        dialog,
+        #[weak]
+        text_buffer,
+        move |_| export_logs(dialog.upcast_ref(), &export_name, &text_buffer)
+    ));
+    toolbar.append(&export_button);
+
+    let end_mark = text_buffer.create_mark(None, &text_buffer.end_iter(), false);
+    let follow_task: Rc<RefCell<Option<JoinHandle<()>>>> = Rc::new(RefCell::new(None));
+    follow_toggle.connect_toggled(clone!(
+        #[weak]
+        text_view,
+        #[strong]
+        follow_task,
+        #[strong]
+        log_tags,
+        move |toggle| {
+            if let Some(task) = follow_task.take() {
+                task.abort();
+            }
+            if !toggle.is_active() {
+                return;
+            }
 
-    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+            let (sender, mut receiver) = mpsc::channel(LOG_FOLLOW_BUFFER);
+            follow_task.replace(Some(follow(sender)));
+            let end_mark = end_mark.clone();
+            let log_tags = log_tags.clone();
+            glib::MainContext::default().spawn_local(async move {
+                while let Some(line) = receiver.recv().await {
+                    let buffer = text_view.buffer();
+                    let first_line = buffer.end_iter().line();
+                    buffer.insert(&mut buffer.end_iter(), &format!("{}\n", line));
+                    highlight_log_lines(&buffer, &log_tags, first_line);
+                    buffer.move_mark(&end_mark, &buffer.end_iter());
+                    text_view.scroll_mark_onscreen(&end_mark);
+                }
+            });
+        }
+    ));
+
+    // Entries outside the default window, read again whenever the boot or
+    // time range changes
+    let range_expander = gtk4::Expander::new(Some("Time Range"));
+    let range_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    range_box.set_margin_top(6);
+    let now = chrono::Local::now();
+    let since_entry = Entry::new();
+    since_entry.set_text(
+        &(now - chrono::Duration::days(1)).to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    );
+    since_entry.set_hexpand(true);
+    let until_entry = Entry::new();
+    until_entry.set_text(&now.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+    until_entry.set_hexpand(true);
+    let range_reload_button = gtk4::Button::with_label("Reload");
+    range_box.append(&Label::new(Some("Since:")));
+    range_box.append(&since_entry);
+    range_box.append(&Label::new(Some("Until:")));
+    range_box.append(&until_entry);
+    range_box.append(&range_reload_button);
+
+    let range_error = Label::new(None);
+    range_error.add_css_class("error");
+    range_error.set_xalign(0.0);
+    range_error.set_visible(false);
+
+    let range_content = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    range_content.append(&range_box);
+    range_content.append(&range_error);
+    range_expander.set_child(Some(&range_content));
+
+    let selected_boot: Rc<RefCell<Option<String>>> = Rc::new(RefCell::new(None));
+    let reload_logs = Rc::new(clone!(
+        #[weak]
+        text_buffer,
+        #[weak]
+        range_expander,
+        #[weak]
+        since_entry,
+        #[weak]
+        until_entry,
+        #[weak]
+        range_error,
+        #[strong]
+        selected_boot,
+        #[strong]
+        log_tags,
+        move || {
+            let mut options = LogOptions {
+                boot: selected_boot.borrow().clone(),
+                ..Default::default()
+            };
+            if range_expander.is_expanded() {
+                options.since = Some(since_entry.text().to_string());
+                options.until = Some(until_entry.text().to_string());
+                if let Err(e) = options.journalctl_args() {
+                    range_error.set_text(&e.to_string());
+                    range_error.set_visible(true);
+                    return;
+                }
+            }
+            range_error.set_visible(false);
+
+            let log_tags = log_tags.clone();
+            reload(
+                options,
+                std::boxed::Box::new(move |result| {
+                    match result {
+                        Ok(logs) => text_buffer.set_text(&logs),
+                        Err(e) => text_buffer.set_text(&format!("Failed to read logs: {}", e)),
+                    }
+                    highlight_log_lines(&text_buffer, &log_tags, 0);
+                }),
+            );
+        }
+    ));
+    range_reload_button.connect_clicked(clone!(
+        #[strong]
+        reload_logs,
+        move |_| reload_logs()
+    ));
+
+    if !boots.is_empty() {
+        let boot_combo = ComboBoxText::new();
+        for boot in boots {
+            boot_combo.append(Some(&boot.boot_id), &boot_label(boot));
+        }
+        boot_combo.set_active(Some(0));
+
+        toolbar.append(&Label::new(Some("Boot:")));
+        toolbar.append(&boot_combo);
+
+        let current_boot = boots.iter().find(|boot| boot.index == 0).cloned();
+        boot_combo.connect_changed(clone!(
+            #[weak]
+            follow_toggle,
+            #[strong]
+            reload_logs,
+            move |combo| {
+                let boot_id = combo
+                    .active_id()
+                    .map(|id| id.to_string())
+                    .filter(|id| Some(id) != current_boot.as_ref().map(|boot| &boot.boot_id));
+                // Only the current boot gets new entries
+                if boot_id.is_some() {
+                    follow_toggle.set_active(false);
+                }
+                follow_toggle.set_sensitive(boot_id.is_none());
+                selected_boot.replace(boot_id);
+                reload_logs();
+            }
+        ));
+    }
+
+    let legend = LogLevelLegend::new();
+    let legend_clone = legend.clone();
+    legend_toggle.connect_toggled(move |toggle| {
+        legend_clone.set_visible(toggle.is_active());
+    });
+
+    // Ctrl+F searches the logs
+    let search_bar = TextSearchBar::new(&text_view);
+    search_bar.attach_to(&dialog);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
     content_box.set_margin_start(12);
     content_box.set_margin_end(12);
     content_box.set_margin_top(12);
     content_box.set_margin_bottom(12);
+    content_box.append(&toolbar);
+    content_box.append(&range_expander);
+    content_box.append(search_bar.widget());
     content_box.append(&scrolled);
+    content_box.append(legend.widget());
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(move |dialog, _| {
+        if let Some(task) = follow_task.take() {
+            task.abort();
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Saves the logs shown in `buffer` to a file chosen by the user
+fn export_logs(parent: &Window, service_name: &str, buffer: &gtk4::TextBuffer) {
+    let file_dialog = gtk4::FileChooserDialog::new(
+        Some("Export Logs"),
+        Some(parent),
+        gtk4::FileChooserAction::Save,
+        &[
+            ("Cancel", ResponseType::Cancel),
+            ("Export", ResponseType::Accept),
+        ],
+    );
+    file_dialog.set_modal(true);
+    file_dialog.set_current_name(&log_export_file_name(service_name, chrono::Local::now()));
+
+    let parent = parent.clone();
+    let buffer = buffer.clone();
+    file_dialog.connect_response(move |file_dialog, response| {
+        file_dialog.close();
+        if response != ResponseType::Accept {
+            return;
+        }
+        let Some(path) = file_dialog.file().and_then(|f| f.path()) else {
+            return;
+        };
+
+        let (start, end) = buffer.bounds();
+        if let Err(e) = std::fs::write(&path, buffer.text(&start, &end, false).as_str()) {
+            show_error_dialog(
+                &parent,
+                "Export Failed",
+                &format!("Could not write {}: {}", path.display(), e),
+            );
+        }
+    });
+
+    file_dialog.show();
+}
+
+/// Names a boot as "Current boot", or by index with how long ago it ended,
+/// e.g. "-1 (3 hours ago)"
+fn boot_label(boot: &BootEntry) -> String {
+    if boot.index == 0 {
+        "Current boot".to_string()
+    } else {
+        format!("{} ({})", boot.index, format_relative(&boot.last_entry))
+    }
+}
+
+/// Reports an operation run on several services: `summary` of how many
+/// succeeded, then each failed service with its error
+pub fn show_batch_errors_dialog(
+    parent: &Window,
+    title: &str,
+    summary: &str,
+    errors: &[(String, String)],
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(title));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(500, -1);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+
+    let label = Label::new(Some(summary));
+    label.set_halign(gtk4::Align::Start);
+    content_box.append(&label);
+
+    let list_box = gtk4::ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    for (service, error) in errors {
+        let row_label = Label::new(None);
+        row_label.set_markup(&format!(
+            "<b>{}</b>: {}",
+            glib::markup_escape_text(service),
+            glib::markup_escape_text(error)
+        ));
+        row_label.set_halign(gtk4::Align::Start);
+        row_label.set_wrap(true);
+        row_label.set_margin_start(6);
+        row_label.set_margin_end(6);
+        row_label.set_margin_top(6);
+        row_label.set_margin_bottom(6);
+        list_box.append(&row_label);
+    }
+    content_box.append(&list_box);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Lists every problem with a host's settings in one dialog
+pub fn show_validation_errors_dialog(parent: &Window, host_name: &str, errors: &[ValidationError]) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Cannot Connect to {}", host_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(20);
+    content_box.set_margin_end(20);
+    content_box.set_margin_top(20);
+    content_box.set_margin_bottom(20);
+
+    let label = Label::new(Some("Fix the following settings before connecting:"));
+    label.set_halign(gtk4::Align::Start);
+    content_box.append(&label);
+
+    let list_box = gtk4::ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    for error in errors {
+        let row_label = Label::new(None);
+        row_label.set_markup(&format!(
+            "<b>{}</b>: {}",
+            glib::markup_escape_text(error.field),
+            glib::markup_escape_text(&error.message)
+        ));
+        row_label.set_halign(gtk4::Align::Start);
+        row_label.set_margin_start(6);
+        row_label.set_margin_end(6);
+        row_label.set_margin_top(6);
+        row_label.set_margin_bottom(6);
+        list_box.append(&row_label);
+    }
+    content_box.append(&list_box);
 
     dialog.set_child(Some(&content_box));
 
@@ -422,10 +845,12 @@ pub fn show_service_logs_dialog(
     dialog.show();
 }
 
+/// Asks for the host's SSH password. `callback` gets the password, if one
+/// was entered, and whether the user asked for it to be remembered.
 pub fn show_password_dialog(
     parent: &Window,
     host: &RemoteHost,
-    callback: impl FnOnce(Option<String>) + 'static,
+    callback: impl FnOnce(Option<String>, bool) + 'static,
 ) {
     let dialog = Dialog::new();
     dialog.set_title(Some(&format!("Password for {}", host.connection_string())));
@@ -450,8 +875,12 @@ pub fn show_password_dialog(
     password_entry.set_visibility(false);
     password_entry.set_input_purpose(gtk4::InputPurpose::Password);
 
+    let remember_check = gtk4::CheckButton::with_label("Remember password");
+    remember_check.set_tooltip_text(Some("Save the password in the desktop keyring"));
+
     grid.attach(&label, 0, 0, 2, 1);
     grid.attach(&password_entry, 0, 1, 2, 1);
+    grid.attach(&remember_check, 0, 2, 2, 1);
 
     dialog.set_child(Some(&grid));
 
@@ -460,6 +889,8 @@ pub fn show_password_dialog(
         dialog.response(ResponseType::Ok);
     }));
 
+    // The response handler may run more than once, but the callback only once
+    let callback = RefCell::new(Some(callback));
     dialog.connect_response(move |dialog, response| {
         let result = if response == ResponseType::Ok {
             let password = password_entry.text().to_string();
@@ -471,45 +902,2105 @@ pub fn show_password_dialog(
         } else {
             None
         };
-        callback(result);
+        if let Some(callback) = callback.borrow_mut().take() {
+            callback(result, remember_check.is_active());
+        }
         dialog.close();
     });
 
     dialog.show();
 }
 
-pub fn show_service_details_dialog(
+/// Asks for a `.path` unit's name, the path to watch and the service to
+/// start when it changes, offering the known `services`, then calls
+/// `callback` with them
+pub fn show_create_path_unit_dialog(
     parent: &Window,
-    service_name: &str,
-    details: &str,
-    host: Option<&str>,
+    services: &[&str],
+    callback: impl Fn(String, String, String) + 'static,
 ) {
-    let title = if let Some(h) = host {
-        format!("Details for {} on {}", service_name, h)
-    } else {
-        format!("Details for {}", service_name)
-    };
-
     let dialog = Dialog::new();
-    dialog.set_title(Some(&title));
+    dialog.set_title(Some("New Service Triggered by File Change"));
     dialog.set_transient_for(Some(parent));
     dialog.set_modal(true);
-    dialog.add_button("Close", ResponseType::Close);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Create", ResponseType::Ok);
 
-    dialog.set_default_size(700, 500);
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
 
-    let scrolled = ScrolledWindow::new();
-    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    let name_label = Label::new(Some("Path Unit Name:"));
+    name_label.set_halign(gtk4::Align::Start);
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some("watch-uploads"));
+    grid.attach(&name_label, 0, 0, 1, 1);
+    grid.attach(&name_entry, 1, 0, 1, 1);
 
-    let text_view = TextView::new();
-    text_view.set_editable(false);
-    text_view.set_cursor_visible(false);
-    text_view.set_monospace(true);
+    let path_label = Label::new(Some("Watched Path:"));
+    path_label.set_halign(gtk4::Align::Start);
+    let path_entry = Entry::new();
+    path_entry.set_placeholder_text(Some("/srv/uploads"));
+    grid.attach(&path_label, 0, 1, 1, 1);
+    grid.attach(&path_entry, 1, 1, 1, 1);
+
+    let service_label = Label::new(Some("Service to Start:"));
+    service_label.set_halign(gtk4::Align::Start);
+    let service_combo = SearchableComboBox::new(services);
+    service_combo
+        .entry()
+        .set_placeholder_text(Some("process-uploads.service"));
+    grid.attach(&service_label, 0, 2, 1, 1);
+    grid.attach(service_combo.widget(), 1, 2, 1, 1);
 
-    let text_buffer = text_view.buffer();
-    text_buffer.set_text(details);
+    dialog.set_child(Some(&grid));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let name = name_entry.text().trim().to_string();
+            let path = path_entry.text().trim().to_string();
+            if let (false, false, Some(service)) = (
+                name.is_empty(),
+                path.is_empty(),
+                service_combo.selected_text(),
+            ) {
+                callback(name, path, service);
+            }
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Collects the settings for a new `.timer` unit, previewing the generated
+/// file as it's edited. The service is picked from `services` or typed in.
+/// `validate` checks an `OnCalendar` expression and
+/// reports when it next elapses; `callback` receives the timer and whether
+/// to enable it right away.
+pub fn show_create_timer_dialog(
+    parent: &Window,
+    services: &[&str],
+    validate: impl Fn(String, std::boxed::Box<dyn FnOnce(Result<String>)>) + 'static,
+    callback: impl Fn(TimerSpec, bool) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("New Timer"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.set_default_size(520, 560);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Create", ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
+
+    let service_label = Label::new(Some("Service:"));
+    service_label.set_halign(gtk4::Align::Start);
+    let service_combo = SearchableComboBox::new(services);
+    let service_entry = service_combo.entry().clone();
+    service_entry.set_placeholder_text(Some("backup.service"));
+    service_combo.widget().set_hexpand(true);
+    grid.attach(&service_label, 0, 0, 1, 1);
+    grid.attach(service_combo.widget(), 1, 0, 2, 1);
+
+    let calendar_label = Label::new(Some("OnCalendar:"));
+    calendar_label.set_halign(gtk4::Align::Start);
+    let calendar_entry = Entry::new();
+    calendar_entry.set_placeholder_text(Some("Mon..Fri *-*-* 02:00:00"));
+    let validate_button = gtk4::Button::with_label("Validate");
+    grid.attach(&calendar_label, 0, 1, 1, 1);
+    grid.attach(&calendar_entry, 1, 1, 1, 1);
+    grid.attach(&validate_button, 2, 1, 1, 1);
+
+    let boot_label = Label::new(Some("OnBootSec:"));
+    boot_label.set_halign(gtk4::Align::Start);
+    let boot_entry = Entry::new();
+    boot_entry.set_placeholder_text(Some("15min"));
+    grid.attach(&boot_label, 0, 2, 1, 1);
+    grid.attach(&boot_entry, 1, 2, 2, 1);
+
+    let delay_label = Label::new(Some("RandomizedDelaySec:"));
+    delay_label.set_halign(gtk4::Align::Start);
+    let delay_entry = Entry::new();
+    delay_entry.set_placeholder_text(Some("5min"));
+    grid.attach(&delay_label, 0, 3, 1, 1);
+    grid.attach(&delay_entry, 1, 3, 2, 1);
+
+    let persistent_check =
+        gtk4::CheckButton::with_label("Persistent (run missed triggers after boot)");
+    grid.attach(&persistent_check, 1, 4, 2, 1);
+
+    let enable_check = gtk4::CheckButton::with_label("Enable and start immediately");
+    enable_check.set_active(true);
+    grid.attach(&enable_check, 1, 5, 2, 1);
+
+    let next_trigger_label = Label::new(None);
+    next_trigger_label.set_halign(gtk4::Align::Start);
+    next_trigger_label.set_wrap(true);
+    next_trigger_label.add_css_class("dim-label");
+    grid.attach(&next_trigger_label, 0, 6, 3, 1);
+
+    let preview = TextView::new();
+    preview.set_editable(false);
+    preview.set_monospace(true);
+    let preview_scroll = ScrolledWindow::new();
+    preview_scroll.set_child(Some(&preview));
+    preview_scroll.set_vexpand(true);
+    preview_scroll.set_min_content_height(160);
+    grid.attach(&preview_scroll, 0, 7, 3, 1);
+
+    dialog.set_child(Some(&grid));
+
+    let read_spec = {
+        let service_entry = service_entry.clone();
+        let calendar_entry = calendar_entry.clone();
+        let boot_entry = boot_entry.clone();
+        let delay_entry = delay_entry.clone();
+        let persistent_check = persistent_check.clone();
+        move || {
+            let optional = |entry: &Entry| {
+                let text = entry.text().trim().to_string();
+                (!text.is_empty()).then_some(text)
+            };
+            TimerSpec {
+                service: service_entry.text().trim().to_string(),
+                on_calendar: optional(&calendar_entry),
+                on_boot_sec: optional(&boot_entry),
+                persistent: persistent_check.is_active(),
+                randomized_delay_sec: optional(&delay_entry),
+            }
+        }
+    };
+    let read_spec = Rc::new(read_spec);
+
+    let update_preview = Rc::new(clone!(
+        #[strong]
+        read_spec,
+        #[weak]
+        preview,
+        move || preview.buffer().set_text(&timer_unit_content(&read_spec()))
+    ));
+    for entry in [&service_entry, &calendar_entry, &boot_entry, &delay_entry] {
+        let update_preview = update_preview.clone();
+        entry.connect_changed(move |_| update_preview());
+    }
+    {
+        let update_preview = update_preview.clone();
+        persistent_check.connect_toggled(move |_| update_preview());
+    }
+    update_preview();
+
+    // A changed expression hasn't been validated yet
+    calendar_entry.connect_changed(clone!(
+        #[weak]
+        next_trigger_label,
+        move |_| next_trigger_label.set_text("")
+    ));
+
+    validate_button.connect_clicked(clone!(
+        #[weak]
+        calendar_entry,
+        #[weak]
+        next_trigger_label,
+        move |_| {
+            let expression = calendar_entry.text().trim().to_string();
+            if expression.is_empty() {
+                return;
+            }
+            next_trigger_label.set_text("Checking…");
+            validate(
+                expression,
+                std::boxed::Box::new(move |result| match result {
+                    Ok(next) => next_trigger_label.set_text(&format!(
+                        "Next trigger: {} ({})",
+                        next,
+                        format_systemd_timestamp(&next)
+                    )),
+                    Err(e) => next_trigger_label.set_text(&format!("Invalid expression: {}", e)),
+                }),
+            );
+        }
+    ));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let spec = read_spec();
+            let has_trigger = spec.on_calendar.is_some() || spec.on_boot_sec.is_some();
+            if spec.service.is_empty() || !has_trigger {
+                show_error_dialog(
+                    dialog.upcast_ref(),
+                    "New Timer",
+                    "A timer needs a service and either OnCalendar or OnBootSec",
+                );
+                return;
+            }
+            callback(spec, enable_check.is_active());
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Steps of the new service wizard, by stack page name
+const SERVICE_WIZARD_STEPS: [&str; 3] = ["template", "settings", "preview"];
+
+/// Walks through creating a `.service` unit: its name and template, the
+/// template's settings, then a preview of the file. Create passes the
+/// settings to `callback`.
+pub fn show_create_service_wizard(parent: &Window, callback: impl Fn(ServiceSpec) + 'static) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("New Service"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.set_default_size(560, 520);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Back", ResponseType::Other(1));
+    dialog.add_button("Next", ResponseType::Other(2));
+    dialog.add_button("Create", ResponseType::Ok);
+
+    let stack = gtk4::Stack::new();
+    stack.set_transition_type(gtk4::StackTransitionType::SlideLeftRight);
+    stack.set_vexpand(true);
+
+    let new_grid = || {
+        let grid = Grid::new();
+        grid.set_row_spacing(12);
+        grid.set_column_spacing(12);
+        grid.set_margin_start(20);
+        grid.set_margin_end(20);
+        grid.set_margin_top(20);
+        grid.set_margin_bottom(20);
+        grid
+    };
+    let attach_entry = |grid: &Grid, row: i32, text: &str, placeholder: &str| {
+        let label = Label::new(Some(text));
+        label.set_halign(gtk4::Align::Start);
+        let entry = Entry::new();
+        entry.set_placeholder_text(Some(placeholder));
+        entry.set_hexpand(true);
+        grid.attach(&label, 0, row, 1, 1);
+        grid.attach(&entry, 1, row, 1, 1);
+        (label, entry)
+    };
+
+    // Step 1: name and template
+    let template_grid = new_grid();
+    let (_, name_entry) = attach_entry(&template_grid, 0, "Name:", "myapp");
+    let (_, description_entry) = attach_entry(&template_grid, 1, "Description:", "My application");
+
+    let template_label = Label::new(Some("Template:"));
+    template_label.set_halign(gtk4::Align::Start);
+    template_label.set_valign(gtk4::Align::Start);
+    template_grid.attach(&template_label, 0, 2, 1, 1);
+    let template_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    let template = Rc::new(Cell::new(ServiceTemplate::default()));
+    let mut first_button: Option<gtk4::CheckButton> = None;
+    for choice in ServiceTemplate::ALL {
+        let button = gtk4::CheckButton::with_label(choice.label());
+        button.set_group(first_button.as_ref());
+        button.set_active(choice == template.get());
+        button.connect_toggled(clone!(
+            #[strong]
+            template,
+            move |button| {
+                if button.is_active() {
+                    template.set(choice);
+                }
+            }
+        ));
+        let summary = Label::new(Some(choice.summary()));
+        summary.set_halign(gtk4::Align::Start);
+        summary.set_margin_start(28);
+        summary.add_css_class("dim-label");
+        template_box.append(&button);
+        template_box.append(&summary);
+        first_button.get_or_insert(button);
+    }
+    template_grid.attach(&template_box, 1, 2, 1, 1);
+    stack.add_named(&template_grid, Some(SERVICE_WIZARD_STEPS[0]));
+
+    // Step 2: the template's settings
+    let settings_grid = new_grid();
+    let (_, exec_start_entry) = attach_entry(
+        &settings_grid,
+        0,
+        "ExecStart:",
+        "/usr/local/bin/myapp --serve",
+    );
+    let (_, user_entry) = attach_entry(&settings_grid, 1, "User:", "root");
+    let (_, working_directory_entry) =
+        attach_entry(&settings_grid, 2, "WorkingDirectory:", "/srv/myapp");
+
+    let restart_label = Label::new(Some("Restart:"));
+    restart_label.set_halign(gtk4::Align::Start);
+    let restart_combo = ComboBoxText::new();
+    for policy in RESTART_POLICIES {
+        restart_combo.append(Some(policy), policy);
+    }
+    restart_combo.set_active_id(Some("on-failure"));
+    settings_grid.attach(&restart_label, 0, 3, 1, 1);
+    settings_grid.attach(&restart_combo, 1, 3, 1, 1);
+
+    let remain_after_exit_check =
+        gtk4::CheckButton::with_label("RemainAfterExit (stay active after the command exits)");
+    settings_grid.attach(&remain_after_exit_check, 1, 4, 1, 1);
+    let (pid_file_label, pid_file_entry) =
+        attach_entry(&settings_grid, 5, "PIDFile:", "/run/myapp.pid");
+    let (bus_name_label, bus_name_entry) =
+        attach_entry(&settings_grid, 6, "BusName:", "org.example.MyApp");
+    stack.add_named(&settings_grid, Some(SERVICE_WIZARD_STEPS[1]));
+
+    // Step 3: preview
+    let preview_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    preview_box.set_margin_start(20);
+    preview_box.set_margin_end(20);
+    preview_box.set_margin_top(20);
+    preview_box.set_margin_bottom(20);
+    let destination = Label::new(None);
+    destination.set_halign(gtk4::Align::Start);
+    destination.set_wrap(true);
+    preview_box.append(&destination);
+    let preview = TextView::new();
+    preview.set_editable(false);
+    preview.set_monospace(true);
+    let preview_scroll = ScrolledWindow::new();
+    preview_scroll.set_child(Some(&preview));
+    preview_scroll.set_vexpand(true);
+    preview_box.append(&preview_scroll);
+    stack.add_named(&preview_box, Some(SERVICE_WIZARD_STEPS[2]));
+
+    // Explains why Next is unavailable
+    let hint = Label::new(None);
+    hint.set_halign(gtk4::Align::Start);
+    hint.set_margin_start(20);
+    hint.set_margin_bottom(12);
+    hint.add_css_class("dim-label");
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content_box.append(&stack);
+    content_box.append(&hint);
+    dialog.set_child(Some(&content_box));
+
+    let read_spec = Rc::new(clone!(
+        #[strong]
+        template,
+        #[weak]
+        name_entry,
+        #[weak]
+        description_entry,
+        #[weak]
+        exec_start_entry,
+        #[weak]
+        user_entry,
+        #[weak]
+        working_directory_entry,
+        #[weak]
+        restart_combo,
+        #[weak]
+        remain_after_exit_check,
+        #[weak]
+        pid_file_entry,
+        #[weak]
+        bus_name_entry,
+        #[upgrade_or_default]
+        move || {
+            let optional = |entry: &Entry| {
+                let text = entry.text().trim().to_string();
+                (!text.is_empty()).then_some(text)
+            };
+            let template = template.get();
+            ServiceSpec {
+                name: name_entry.text().trim().to_string(),
+                description: description_entry.text().trim().to_string(),
+                template,
+                exec_start: exec_start_entry.text().trim().to_string(),
+                user: optional(&user_entry),
+                working_directory: optional(&working_directory_entry),
+                restart: restart_combo
+                    .active_id()
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                remain_after_exit: template == ServiceTemplate::Oneshot
+                    && remain_after_exit_check.is_active(),
+                pid_file: optional(&pid_file_entry)
+                    .filter(|_| template == ServiceTemplate::Forking),
+                bus_name: optional(&bus_name_entry).filter(|_| template == ServiceTemplate::DBus),
+            }
+        }
+    ));
+
+    // What keeps the user on `step`, if anything
+    let step_problem = {
+        let read_spec = read_spec.clone();
+        move |step: &str| {
+            let spec = read_spec();
+            match step {
+                "template" if spec.name.is_empty() => Some("Enter a name for the service"),
+                "template" if spec.name.contains('/') => Some("The name can't contain '/'"),
+                "settings" if spec.exec_start.is_empty() => Some("Enter the command to run"),
+                "settings" if spec.template == ServiceTemplate::DBus && spec.bus_name.is_none() => {
+                    Some("Enter the bus name the service takes")
+                }
+                _ => None,
+            }
+        }
+    };
+
+    let update_buttons = Rc::new(clone!(
+        #[weak]
+        dialog,
+        #[weak]
+        stack,
+        #[weak]
+        hint,
+        move || {
+            let step = stack.visible_child_name().unwrap_or_default();
+            let last = step == SERVICE_WIZARD_STEPS[2];
+            let problem = step_problem(&step);
+            if let Some(back) = dialog.widget_for_response(ResponseType::Other(1)) {
+                back.set_sensitive(step != SERVICE_WIZARD_STEPS[0]);
+            }
+            if let Some(next) = dialog.widget_for_response(ResponseType::Other(2)) {
+                next.set_visible(!last);
+                next.set_sensitive(problem.is_none());
+            }
+            if let Some(create) = dialog.widget_for_response(ResponseType::Ok) {
+                create.set_visible(last);
+            }
+            hint.set_text(problem.unwrap_or(""));
+        }
+    ));
+    for entry in [&name_entry, &exec_start_entry, &bus_name_entry] {
+        let update_buttons = update_buttons.clone();
+        entry.connect_changed(move |_| update_buttons());
+    }
+    {
+        let update_buttons = update_buttons.clone();
+        stack.connect_visible_child_name_notify(move |_| update_buttons());
+    }
+    update_buttons();
+
+    dialog.connect_response(move |dialog, response| {
+        let step = stack.visible_child_name().unwrap_or_default();
+        let index = SERVICE_WIZARD_STEPS
+            .iter()
+            .position(|name| *name == step.as_str())
+            .unwrap_or(0);
+        match response {
+            ResponseType::Other(1) if index > 0 => {
+                stack.set_visible_child_name(SERVICE_WIZARD_STEPS[index - 1]);
+            }
+            ResponseType::Other(2) if index + 1 < SERVICE_WIZARD_STEPS.len() => {
+                // Only what the chosen template uses is asked for
+                let template = template.get();
+                remain_after_exit_check.set_visible(template == ServiceTemplate::Oneshot);
+                pid_file_label.set_visible(template == ServiceTemplate::Forking);
+                pid_file_entry.set_visible(template == ServiceTemplate::Forking);
+                bus_name_label.set_visible(template == ServiceTemplate::DBus);
+                bus_name_entry.set_visible(template == ServiceTemplate::DBus);
+
+                let spec = read_spec();
+                destination.set_text(&format!(
+                    "This will be written to /etc/systemd/system/{} and checked with \
+                     systemd-analyze verify.",
+                    spec.unit_name()
+                ));
+                preview.buffer().set_text(&service_unit_content(&spec));
+                stack.set_visible_child_name(SERVICE_WIZARD_STEPS[index + 1]);
+            }
+            ResponseType::Other(_) => {}
+            ResponseType::Ok => {
+                callback(read_spec());
+                dialog.close();
+            }
+            _ => dialog.close(),
+        }
+    });
+
+    dialog.show();
+}
+
+/// Prompts for a command to run on `host_name`, offering recently run
+/// commands as completions. `callback` receives the command and whether to
+/// run it with sudo.
+pub fn show_remote_exec_dialog(
+    parent: &Window,
+    host_name: &str,
+    history: &[String],
+    callback: impl Fn(String, bool) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Run Command on {}", host_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.set_default_size(480, -1);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Run", ResponseType::Ok);
+    dialog.set_default_response(ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
+
+    let command_label = Label::new(Some("Command:"));
+    command_label.set_halign(gtk4::Align::Start);
+    let command_entry = Entry::new();
+    command_entry.set_placeholder_text(Some("df -h"));
+    command_entry.set_hexpand(true);
+    command_entry.set_activates_default(true);
+    grid.attach(&command_label, 0, 0, 1, 1);
+    grid.attach(&command_entry, 1, 0, 1, 1);
+
+    let history_store = gtk4::ListStore::new(&[glib::Type::STRING]);
+    for command in history {
+        history_store.insert_with_values(None, &[(0, command)]);
+    }
+    let completion = gtk4::EntryCompletion::new();
+    completion.set_model(Some(&history_store));
+    completion.set_text_column(0);
+    completion.set_minimum_key_length(0);
+    completion.set_popup_completion(true);
+    command_entry.set_completion(Some(&completion));
+
+    let sudo_check = gtk4::CheckButton::with_label("Run with sudo");
+    grid.attach(&sudo_check, 1, 1, 1, 1);
+
+    dialog.set_child(Some(&grid));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let command = command_entry.text().trim().to_string();
+            if !command.is_empty() {
+                callback(command, sudo_check.is_active());
+            }
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Shows the output of a command run on a remote host
+pub fn show_command_output_dialog(parent: &Window, host_name: &str, command: &str, output: &str) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("{} on {}", command, host_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(700, 450);
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+    text_view.buffer().set_text(if output.is_empty() {
+        "(no output)"
+    } else {
+        output
+    });
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&text_view));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| dialog.close());
+
+    dialog.show();
+}
+
+/// What the unit file dialog shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnitFileKind {
+    /// A complete unit file, which must have every required section
+    Unit,
+    /// A drop-in, which only overrides some settings
+    DropIn,
+    /// A drop-in yet to be written, opened for editing straight away
+    NewDropIn,
+}
+
+/// Fetches a remote host's copy of a unit, passing it to the callback
+pub type RemoteUnitFetch = std::boxed::Box<dyn Fn(std::boxed::Box<dyn FnOnce(Result<String>)>)>;
+
+/// Shows a unit file headed by its `# /path` comment as printed by
+/// `systemctl cat`, or a single drop-in. Once "Edit" is toggled the text can be changed, and
+/// "Save" hands it to `save`, which reports back whether it was written.
+/// Given the name of a remote host in `remote` and a function fetching its
+/// copy of the unit, "Compare Remote" fetches it and shows how the two differ.
+pub fn show_unit_file_dialog(
+    parent: &Window,
+    service_name: &str,
+    content: &str,
+    kind: UnitFileKind,
+    remote: Option<(&str, RemoteUnitFetch)>,
+    save: impl Fn(String, std::boxed::Box<dyn FnOnce(Result<()>)>) + 'static,
+    verify: impl Fn(std::boxed::Box<dyn FnOnce(Result<Vec<String>>)>) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Unit File - {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Save", ResponseType::Ok);
+    dialog.set_response_sensitive(ResponseType::Ok, false);
+    dialog.set_default_size(700, 500);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    let sources = unit_file_sources(content);
+    let path_label = Label::new(Some(&if sources.is_empty() {
+        service_name.to_string()
+    } else {
+        sources.join("\n")
+    }));
+    path_label.set_halign(gtk4::Align::Start);
+    path_label.set_selectable(true);
+    path_label.add_css_class("heading");
+    content_box.append(&path_label);
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+    let buffer = text_view.buffer();
+    buffer.set_text(content);
+
+    let modified = Rc::new(std::cell::Cell::new(false));
+    buffer.connect_changed(clone!(
+        #[strong]
+        modified,
+        move |_| modified.set(true)
+    ));
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&text_view));
+    content_box.append(&scrolled);
+
+    let button_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    let copy_button = gtk4::Button::with_label("Copy");
+    copy_button.set_tooltip_text(Some("Copy the unit file to the clipboard"));
+    copy_button.connect_clicked(clone!(
+        #[weak]
+        buffer,
+        move |button| {
+            let (start, end) = buffer.bounds();
+            button
+                .clipboard()
+                .set_text(&buffer.text(&start, &end, false));
+        }
+    ));
+    let edit_button = gtk4::ToggleButton::with_label("Edit");
+    edit_button.set_tooltip_text(Some("Edit the unit file"));
+    edit_button.connect_toggled(clone!(
+        #[weak]
+        text_view,
+        #[weak]
+        dialog,
+        move |button| {
+            let editing = button.is_active();
+            text_view.set_editable(editing);
+            text_view.set_cursor_visible(editing);
+            dialog.set_response_sensitive(ResponseType::Ok, editing);
+        }
+    ));
+    let verify_button = gtk4::Button::with_label("Verify");
+    verify_button.set_tooltip_text(Some(
+        "Check the saved unit file with systemd-analyze verify",
+    ));
+    let verify_name = service_name.to_string();
+    verify_button.connect_clicked(clone!(
+        #[weak]
+        dialog,
+        move |button| {
+            button.set_sensitive(false);
+            let button = button.clone();
+            let verify_name = verify_name.clone();
+            verify(std::boxed::Box::new(move |result| {
+                button.set_sensitive(true);
+                show_verify_results(dialog.upcast_ref(), &verify_name, result, true);
+            }));
+        }
+    ));
+    button_box.append(&copy_button);
+    button_box.append(&edit_button);
+    button_box.append(&verify_button);
+    if let Some((host, fetch_remote)) = remote {
+        let compare_button = gtk4::Button::with_label("Compare Remote");
+        compare_button.set_tooltip_text(Some(&format!("Compare with the unit file on {}", host)));
+        let compare_name = service_name.to_string();
+        let host = host.to_string();
+        compare_button.connect_clicked(clone!(
+            #[weak]
+            dialog,
+            #[weak]
+            buffer,
+            move |button| {
+                button.set_sensitive(false);
+                let button = button.clone();
+                let compare_name = compare_name.clone();
+                let host = host.clone();
+                fetch_remote(std::boxed::Box::new(move |result| {
+                    button.set_sensitive(true);
+                    match result {
+                        Ok(remote_content) => {
+                            let (start, end) = buffer.bounds();
+                            show_unit_diff_dialog(
+                                dialog.upcast_ref(),
+                                &compare_name,
+                                &host,
+                                &buffer.text(&start, &end, false),
+                                &remote_content,
+                            );
+                        }
+                        Err(e) => show_error_dialog(
+                            dialog.upcast_ref(),
+                            "Compare Remote",
+                            &format!("Failed to read the unit file on {}: {}", host, e),
+                        ),
+                    }
+                }));
+            }
+        ));
+        button_box.append(&compare_button);
+    }
+    content_box.append(&button_box);
+    if kind == UnitFileKind::NewDropIn {
+        edit_button.set_active(true);
+    }
+
+    dialog.set_child(Some(&content_box));
+
+    let service_name = service_name.to_string();
+    dialog.connect_response(move |dialog, response| {
+        if response != ResponseType::Ok {
+            if !modified.get() {
+                dialog.close();
+                return;
+            }
+            show_confirmation_dialog(
+                dialog.upcast_ref(),
+                "Discard Changes",
+                &format!("Discard your changes to {}?", service_name),
+                clone!(
+                    #[weak]
+                    dialog,
+                    move |discard| if discard {
+                        dialog.close();
+                    }
+                ),
+            );
+            return;
+        }
+
+        let (start, end) = buffer.bounds();
+        let text = buffer.text(&start, &end, false).to_string();
+        let missing = match kind {
+            UnitFileKind::Unit => missing_unit_sections(&text),
+            UnitFileKind::DropIn | UnitFileKind::NewDropIn => Vec::new(),
+        };
+        if !missing.is_empty() {
+            show_warning_dialog(
+                dialog.upcast_ref(),
+                "Incomplete Unit File",
+                &format!("The unit file has no {} section", missing.join(", ")),
+            );
+            return;
+        }
+
+        dialog.set_response_sensitive(ResponseType::Ok, false);
+        let service_name = service_name.clone();
+        let dialog = dialog.clone();
+        save(
+            text,
+            std::boxed::Box::new(move |result| match result {
+                Ok(()) => dialog.close(),
+                Err(e) => {
+                    dialog.set_response_sensitive(ResponseType::Ok, true);
+                    show_error_dialog(
+                        dialog.upcast_ref(),
+                        "Save Failed",
+                        &format!("Failed to save {}: {}", service_name, e),
+                    );
+                }
+            }),
+        );
+    });
+
+    dialog.show();
+}
+
+/// Shows a unified diff of the local unit file against the one on `host`,
+/// with added lines in green, removed ones in red and context in grey
+pub fn show_unit_diff_dialog(
+    parent: &Window,
+    service_name: &str,
+    host: &str,
+    local_content: &str,
+    remote_content: &str,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Diff: {} local vs {}", service_name, host)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(800, 550);
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+    let buffer = text_view.buffer();
+
+    for tag in [
+        gtk4::TextTag::builder()
+            .name("added")
+            .paragraph_background("rgba(46, 194, 126, 0.25)")
+            .build(),
+        gtk4::TextTag::builder()
+            .name("removed")
+            .paragraph_background("rgba(224, 27, 36, 0.25)")
+            .build(),
+        gtk4::TextTag::builder()
+            .name("context")
+            .foreground("grey")
+            .build(),
+        gtk4::TextTag::builder().name("header").weight(700).build(),
+    ] {
+        buffer.tag_table().add(&tag);
+    }
+
+    let diff = unified_diff(local_content, remote_content, "local", host);
+    if diff.is_empty() {
+        buffer.set_text(&format!("The unit files of {} are identical", service_name));
+    }
+    for line in diff.lines() {
+        let tag = if line.starts_with("---") || line.starts_with("+++") || line.starts_with("@@") {
+            "header"
+        } else if line.starts_with('+') {
+            "added"
+        } else if line.starts_with('-') {
+            "removed"
+        } else {
+            "context"
+        };
+        let mut end = buffer.end_iter();
+        buffer.insert_with_tags_by_name(&mut end, &format!("{}\n", line), &[tag]);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&text_view));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| dialog.close());
+
+    dialog.show();
+}
+
+/// Lists the drop-in files overriding `service_name`. "View" calls
+/// `on_view` with a file's path; "New Drop-in…" asks for a name and calls
+/// `on_new` with it.
+pub fn show_drop_ins_dialog(
+    parent: &Window,
+    service_name: &str,
+    drop_ins: &[std::path::PathBuf],
+    on_view: impl Fn(&std::path::Path) + 'static,
+    on_new: impl Fn(String) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Drop-ins - {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(600, 350);
+
+    let on_view = Rc::new(on_view);
+    let list_box = gtk4::ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    for path in drop_ins {
+        let row_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+        row_box.set_margin_start(6);
+        row_box.set_margin_end(6);
+        row_box.set_margin_top(6);
+        row_box.set_margin_bottom(6);
+
+        let path_label = Label::new(Some(&path.display().to_string()));
+        path_label.set_halign(gtk4::Align::Start);
+        path_label.set_hexpand(true);
+        path_label.set_selectable(true);
+        row_box.append(&path_label);
+
+        let view_button = gtk4::Button::with_label("View");
+        let path = path.clone();
+        let on_view = on_view.clone();
+        view_button.connect_clicked(move |_| on_view(&path));
+        row_box.append(&view_button);
+
+        list_box.append(&row_box);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    if drop_ins.is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No drop-ins",
+            "Nothing overrides the unit file",
+        )));
+    } else {
+        scrolled.set_child(Some(&list_box));
+    }
+
+    let new_button = gtk4::Button::with_label("New Drop-in…");
+    new_button.set_tooltip_text(Some("Override some of the unit's settings"));
+    new_button.set_halign(gtk4::Align::Start);
+    let on_new = Rc::new(on_new);
+    new_button.connect_clicked(clone!(
+        #[weak]
+        dialog,
+        move |_| {
+            let on_new = on_new.clone();
+            prompt_drop_in_name(dialog.upcast_ref(), move |name| on_new(name));
+        }
+    ));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+    content_box.append(&new_button);
+
+    dialog.set_child(Some(&content_box));
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.show();
+}
+
+/// Asks for the name of a new drop-in, e.g. `override`
+fn prompt_drop_in_name(parent: &Window, callback: impl Fn(String) + 'static) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("New Drop-in"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Create", ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
+
+    let name_label = Label::new(Some("Drop-in Name:"));
+    name_label.set_halign(gtk4::Align::Start);
+    let name_entry = Entry::new();
+    name_entry.set_placeholder_text(Some("override"));
+    grid.attach(&name_label, 0, 0, 1, 1);
+    grid.attach(&name_entry, 1, 0, 1, 1);
+
+    dialog.set_child(Some(&grid));
+
+    name_entry.connect_activate(clone!(
+        #[weak]
+        dialog,
+        move |_| dialog.response(ResponseType::Ok)
+    ));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let name = name_entry.text().trim().to_string();
+            if !name.is_empty() {
+                callback(name);
+            }
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+pub fn show_forward_port_dialog(
+    parent: &Window,
+    host_name: &str,
+    callback: impl Fn(u16, String, u16) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Forward Port from {}", host_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Forward", ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
+
+    let local_port_label = Label::new(Some("Local Port:"));
+    local_port_label.set_halign(gtk4::Align::Start);
+    let local_port_spin = gtk4::SpinButton::with_range(1.0, 65535.0, 1.0);
+    local_port_spin.set_value(9100.0);
+    grid.attach(&local_port_label, 0, 0, 1, 1);
+    grid.attach(&local_port_spin, 1, 0, 1, 1);
+
+    let remote_host_label = Label::new(Some("Remote Address:"));
+    remote_host_label.set_halign(gtk4::Align::Start);
+    let remote_host_entry = Entry::new();
+    remote_host_entry.set_text("localhost");
+    grid.attach(&remote_host_label, 0, 1, 1, 1);
+    grid.attach(&remote_host_entry, 1, 1, 1, 1);
+
+    let remote_port_label = Label::new(Some("Remote Port:"));
+    remote_port_label.set_halign(gtk4::Align::Start);
+    let remote_port_spin = gtk4::SpinButton::with_range(1.0, 65535.0, 1.0);
+    remote_port_spin.set_value(9100.0);
+    grid.attach(&remote_port_label, 0, 2, 1, 1);
+    grid.attach(&remote_port_spin, 1, 2, 1, 1);
+
+    dialog.set_child(Some(&grid));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let remote_host = remote_host_entry.text().to_string();
+            if !remote_host.is_empty() {
+                callback(
+                    local_port_spin.value_as_int() as u16,
+                    remote_host,
+                    remote_port_spin.value_as_int() as u16,
+                );
+            }
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+pub fn show_service_details_dialog(
+    parent: &Window,
+    service_name: &str,
+    details: &str,
+    host: Option<&str>,
+    exec_steps: Option<&ExecSteps>,
+) {
+    let title = if let Some(h) = host {
+        format!("Details for {} on {}", service_name, h)
+    } else {
+        format!("Details for {}", service_name)
+    };
+
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&title));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    if host.is_some() {
+        dialog.add_button("Save Locally…", ResponseType::Apply);
+    }
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(700, 500);
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+
+    let text_view = TextView::new();
+    text_view.set_editable(false);
+    text_view.set_cursor_visible(false);
+    text_view.set_monospace(true);
+
+    let text_buffer = text_view.buffer();
+    text_buffer.set_text(details);
+
+    scrolled.set_child(Some(&text_view));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    match exec_steps {
+        Some(steps) => {
+            scrolled.set_vexpand(true);
+            let notebook = gtk4::Notebook::new();
+            notebook.append_page(&scrolled, Some(&Label::new(Some("Unit File"))));
+            notebook.append_page(
+                &create_exec_timeline(steps),
+                Some(&Label::new(Some("Execution"))),
+            );
+            content_box.append(&notebook);
+        }
+        None => content_box.append(&scrolled),
+    }
+
+    dialog.set_child(Some(&content_box));
+
+    let file_name = format!("{}.service", service_name.trim_end_matches(".service"));
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Apply {
+            let file_dialog = gtk4::FileChooserDialog::new(
+                Some("Save Locally"),
+                Some(dialog),
+                gtk4::FileChooserAction::Save,
+                &[
+                    ("Cancel", ResponseType::Cancel),
+                    ("Save", ResponseType::Accept),
+                ],
+            );
+            file_dialog.set_modal(true);
+            file_dialog.set_current_name(&file_name);
+
+            let parent = dialog.clone();
+            let text_buffer = text_buffer.clone();
+            file_dialog.connect_response(move |file_dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = file_dialog.file().and_then(|file| file.path()) {
+                        let text = text_buffer.text(
+                            &text_buffer.start_iter(),
+                            &text_buffer.end_iter(),
+                            false,
+                        );
+                        if let Err(e) = std::fs::write(&path, text.as_str()) {
+                            error!("Failed to save {}: {}", path.display(), e);
+                            show_error_dialog(
+                                parent.upcast_ref(),
+                                "Save Failed",
+                                &format!("Could not write {}: {}", path.display(), e),
+                            );
+                        }
+                    }
+                }
+                file_dialog.close();
+            });
+
+            file_dialog.show();
+            return;
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Lays out a service's execution phases top to bottom, with an arrow
+/// leading from each phase to the next
+fn create_exec_timeline(steps: &ExecSteps) -> ScrolledWindow {
+    let timeline = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    timeline.set_margin_start(12);
+    timeline.set_margin_end(12);
+    timeline.set_margin_top(12);
+    timeline.set_margin_bottom(12);
+
+    if steps.is_empty() {
+        let label = Label::new(Some("This service runs no commands"));
+        label.add_css_class("dim-label");
+        timeline.append(&label);
+    }
+
+    let phases = steps
+        .phases()
+        .into_iter()
+        .filter(|(_, commands)| !commands.is_empty());
+    for (index, (phase, commands)) in phases.enumerate() {
+        if index > 0 {
+            let arrow = Label::new(Some("↓"));
+            arrow.add_css_class("dim-label");
+            timeline.append(&arrow);
+        }
+
+        let frame = gtk4::Frame::new(Some(phase));
+        let command_list = gtk4::Box::new(gtk4::Orientation::Vertical, 4);
+        command_list.set_margin_start(8);
+        command_list.set_margin_end(8);
+        command_list.set_margin_top(4);
+        command_list.set_margin_bottom(8);
+        for command in commands {
+            let label = Label::new(Some(command));
+            label.set_halign(gtk4::Align::Start);
+            label.set_wrap(true);
+            label.set_selectable(true);
+            label.add_css_class("monospace");
+            command_list.append(&label);
+        }
+        frame.set_child(Some(&command_list));
+        timeline.append(&frame);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&timeline));
+    scrolled
+}
+
+/// Shows how long each unit took to start during the last boot, as bars
+/// scaled to the slowest one. Activating a row calls `on_select` with the
+/// unit's name.
+pub fn show_blame_dialog(
+    parent: &Window,
+    data: &[(String, std::time::Duration)],
+    on_select: impl Fn(&str) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Startup Blame"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(700, 500);
+
+    let slowest = data
+        .iter()
+        .map(|(_, duration)| duration.as_secs_f64())
+        .fold(0.0, f64::max);
+
+    let list = gtk4::ListBox::new();
+    list.set_selection_mode(gtk4::SelectionMode::None);
+    for (unit, duration) in data {
+        let row_box = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+        row_box.set_margin_top(4);
+        row_box.set_margin_bottom(4);
+
+        let name_label = Label::new(Some(unit));
+        name_label.set_xalign(0.0);
+        name_label.set_width_chars(32);
+        name_label.set_max_width_chars(32);
+        name_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+        name_label.set_tooltip_text(Some(unit));
+        row_box.append(&name_label);
+
+        let bar = gtk4::ProgressBar::new();
+        if slowest > 0.0 {
+            bar.set_fraction(duration.as_secs_f64() / slowest);
+        }
+        bar.set_hexpand(true);
+        bar.set_valign(gtk4::Align::Center);
+        row_box.append(&bar);
+
+        let duration_label = Label::new(Some(&format_timespan(*duration)));
+        duration_label.set_xalign(1.0);
+        duration_label.set_width_chars(12);
+        row_box.append(&duration_label);
+
+        list.append(&row_box);
+    }
+
+    let units: Vec<String> = data.iter().map(|(unit, _)| unit.clone()).collect();
+    list.connect_row_activated(move |_, row| {
+        if let Some(unit) = usize::try_from(row.index())
+            .ok()
+            .and_then(|index| units.get(index))
+        {
+            on_select(unit);
+        }
+    });
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    if data.is_empty() {
+        let label = Label::new(Some("No unit start times were recorded."));
+        label.add_css_class("dim-label");
+        scrolled.set_child(Some(&label));
+    } else {
+        scrolled.set_child(Some(&list));
+    }
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    let hint = Label::new(Some("Click a service to select it in the services list."));
+    hint.set_halign(gtk4::Align::Start);
+    hint.add_css_class("dim-label");
+    content_box.append(&hint);
+    content_box.append(&scrolled);
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.show();
+}
+
+/// Shows the boot timeline rendered from `systemd-analyze plot`, with the
+/// `systemd-analyze time` summary above it
+pub fn show_boot_performance_dialog(parent: &Window, summary: &str, svg: &str) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Startup Analysis"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Save SVG…", ResponseType::Apply);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(900, 600);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    let summary_label = Label::new(Some(summary.trim()));
+    summary_label.set_halign(gtk4::Align::Start);
+    summary_label.set_wrap(true);
+    summary_label.set_selectable(true);
+    content_box.append(&summary_label);
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+
+    // Rasterise through gdk-pixbuf, which picks up the librsvg loader
+    let stream = gio::MemoryInputStream::from_bytes(&glib::Bytes::from(svg.as_bytes()));
+    match gtk4::gdk_pixbuf::Pixbuf::from_stream(&stream, None::<&gio::Cancellable>) {
+        Ok(pixbuf) => {
+            let picture = gtk4::Picture::for_pixbuf(&pixbuf);
+            picture.set_can_shrink(false);
+            picture.set_halign(gtk4::Align::Start);
+            picture.set_valign(gtk4::Align::Start);
+            scrolled.set_child(Some(&picture));
+        }
+        Err(e) => {
+            warn!("Failed to render boot plot: {}", e);
+            let label = Label::new(Some("The boot timeline could not be rendered."));
+            label.add_css_class("dim-label");
+            scrolled.set_child(Some(&label));
+        }
+    }
+    content_box.append(&scrolled);
+
+    dialog.set_child(Some(&content_box));
+
+    let svg = svg.to_string();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Apply {
+            let file_dialog = gtk4::FileChooserDialog::new(
+                Some("Save SVG"),
+                Some(dialog),
+                gtk4::FileChooserAction::Save,
+                &[
+                    ("Cancel", ResponseType::Cancel),
+                    ("Save", ResponseType::Accept),
+                ],
+            );
+            file_dialog.set_modal(true);
+            file_dialog.set_current_name("boot.svg");
+
+            let parent = dialog.clone();
+            let svg = svg.clone();
+            file_dialog.connect_response(move |file_dialog, response| {
+                if response == ResponseType::Accept {
+                    if let Some(path) = file_dialog.file().and_then(|file| file.path()) {
+                        if let Err(e) = std::fs::write(&path, &svg) {
+                            error!("Failed to save {}: {}", path.display(), e);
+                            show_error_dialog(
+                                parent.upcast_ref(),
+                                "Save Failed",
+                                &format!("Could not write {}: {}", path.display(), e),
+                            );
+                        }
+                    }
+                }
+                file_dialog.close();
+            });
+
+            file_dialog.show();
+            return;
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Plots a service's CPU and memory usage. The caller keeps `series` filled
+/// while the returned dialog is open; the graphs redraw every second.
+pub fn show_service_graph_dialog(
+    parent: &Window,
+    service_name: &str,
+    series: Rc<RefCell<ResourceTimeSeries>>,
+) -> Dialog {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Resource Usage of {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(700, 500);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    let cpu_label = Label::new(None);
+    cpu_label.set_markup("<b>CPU (%)</b>");
+    cpu_label.set_halign(gtk4::Align::Start);
+
+    let cpu_area = gtk4::DrawingArea::new();
+    cpu_area.set_vexpand(true);
+    let cpu_series = series.clone();
+    cpu_area.set_draw_func(move |_, cr, width, height| {
+        let series = cpu_series.borrow();
+        draw_line_graph(
+            cr,
+            width,
+            height,
+            &series.cpu_values,
+            (0.16, 0.50, 0.73),
+            |v| format!("{:.1}%", v),
+        );
+    });
+
+    let memory_label = Label::new(None);
+    memory_label.set_markup("<b>Memory</b>");
+    memory_label.set_halign(gtk4::Align::Start);
+
+    let memory_area = gtk4::DrawingArea::new();
+    memory_area.set_vexpand(true);
+    let memory_series = series;
+    memory_area.set_draw_func(move |_, cr, width, height| {
+        let series = memory_series.borrow();
+        let values: Vec<f64> = series.memory_values.iter().map(|v| *v as f64).collect();
+        draw_line_graph(cr, width, height, &values, (0.15, 0.68, 0.38), |v| {
+            format!("{:.1} MiB", v / (1024.0 * 1024.0))
+        });
+    });
+
+    content_box.append(&cpu_label);
+    content_box.append(&cpu_area);
+    content_box.append(&memory_label);
+    content_box.append(&memory_area);
+
+    dialog.set_child(Some(&content_box));
+
+    let weak_dialog = dialog.downgrade();
+    glib::timeout_add_local(std::time::Duration::from_secs(1), move || {
+        if weak_dialog.upgrade().is_none() {
+            return glib::ControlFlow::Break;
+        }
+        cpu_area.queue_draw();
+        memory_area.queue_draw();
+        glib::ControlFlow::Continue
+    });
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+    dialog
+}
+
+/// Draws `values` as a line graph whose Y axis scales to the largest value
+fn draw_line_graph(
+    cr: &gtk4::cairo::Context,
+    width: i32,
+    height: i32,
+    values: &[f64],
+    (r, g, b): (f64, f64, f64),
+    format_value: impl Fn(f64) -> String,
+) {
+    const MARGIN: f64 = 8.0;
+    let width = width as f64;
+    let height = height as f64;
+    let plot_width = width - 2.0 * MARGIN;
+    let plot_height = height - 2.0 * MARGIN;
+
+    // Frame
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.4);
+    cr.set_line_width(1.0);
+    cr.rectangle(MARGIN, MARGIN, plot_width, plot_height);
+    let _ = cr.stroke();
+
+    let max = values.iter().cloned().fold(0.0_f64, f64::max);
+    let scale = if max > 0.0 { max * 1.1 } else { 1.0 };
+
+    cr.set_source_rgba(0.5, 0.5, 0.5, 0.9);
+    cr.move_to(MARGIN + 4.0, MARGIN + 14.0);
+    let _ = cr.show_text(&format!("max {}", format_value(max)));
+
+    if values.len() < 2 {
+        return;
+    }
+
+    let step = plot_width / (values.len() - 1) as f64;
+    cr.set_source_rgb(r, g, b);
+    cr.set_line_width(2.0);
+    for (i, value) in values.iter().enumerate() {
+        let x = MARGIN + i as f64 * step;
+        let y = MARGIN + plot_height - value / scale * plot_height;
+        if i == 0 {
+            cr.move_to(x, y);
+        } else {
+            cr.line_to(x, y);
+        }
+    }
+    let _ = cr.stroke();
+}
+
+/// Lists what a service depends on next to the units that depend on it.
+/// Activating a unit passes it to `on_select`.
+pub fn show_dependencies_dialog(
+    parent: &Window,
+    service_name: &str,
+    dependencies: &[String],
+    reverse_dependencies: &[String],
+    on_select: impl Fn(&str) + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Dependencies of {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(800, 500);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    let hint = Label::new(Some("Click a service to select it in the services list."));
+    hint.set_halign(gtk4::Align::Start);
+    hint.add_css_class("dim-label");
+    content_box.append(&hint);
+
+    let columns = gtk4::Box::new(gtk4::Orientation::Horizontal, 12);
+    columns.set_homogeneous(true);
+    columns.set_vexpand(true);
+
+    let on_select = Rc::new(on_select);
+    for (title, units) in [
+        ("Requires / Wants", dependencies),
+        ("Required by", reverse_dependencies),
+    ] {
+        let column = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+
+        let caption = Label::new(None);
+        caption.set_markup(&format!("<b>{}</b> ({})", title, units.len()));
+        caption.set_halign(gtk4::Align::Start);
+        column.append(&caption);
+
+        let list_box = gtk4::ListBox::new();
+        list_box.set_selection_mode(gtk4::SelectionMode::None);
+        for unit in units {
+            let label = Label::new(Some(unit));
+            label.set_halign(gtk4::Align::Start);
+            label.set_margin_start(6);
+            label.set_margin_top(3);
+            label.set_margin_bottom(3);
+            list_box.append(&label);
+        }
+
+        let row_units = units.to_vec();
+        let on_select = on_select.clone();
+        list_box.connect_row_activated(move |_, row| {
+            if let Some(unit) = usize::try_from(row.index())
+                .ok()
+                .and_then(|index| row_units.get(index))
+            {
+                on_select(unit);
+            }
+        });
+
+        let scrolled = ScrolledWindow::new();
+        scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+        scrolled.set_vexpand(true);
+        if units.is_empty() {
+            let label = Label::new(Some(&format!("No units found for {}", service_name)));
+            label.add_css_class("dim-label");
+            scrolled.set_child(Some(&label));
+        } else {
+            scrolled.set_child(Some(&list_box));
+        }
+        column.append(&scrolled);
+
+        columns.append(&column);
+    }
+
+    content_box.append(&columns);
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Lists the variables a service is started with, followed by the files
+/// it reads more variables from
+pub fn show_environment_dialog(
+    parent: &Window,
+    service_name: &str,
+    environment: &ServiceEnvironment,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Environment - {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+    dialog.set_default_size(700, 450);
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 12);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+
+    let store = gtk4::ListStore::new(&[glib::Type::STRING, glib::Type::STRING]);
+    for (key, value) in &environment.variables {
+        store.insert_with_values(None, &[(0, key), (1, value)]);
+    }
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+    for (column_id, title) in [(0, "Variable"), (1, "Value")] {
+        // Editable cells let the text be selected and copied; edits are
+        // never written back
+        let renderer = gtk4::CellRendererText::new();
+        renderer.set_editable(true);
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", column_id);
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    if environment.variables.is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No environment variables",
+            "The unit sets no Environment= variables",
+        )));
+    } else {
+        scrolled.set_child(Some(&tree_view));
+    }
+    content_box.append(&scrolled);
+
+    let copy_button = gtk4::Button::with_label("Copy All");
+    copy_button.set_tooltip_text(Some("Copy the variables as KEY=VALUE lines"));
+    copy_button.set_halign(gtk4::Align::Start);
+    copy_button.set_sensitive(!environment.variables.is_empty());
+    let assignments: String = environment
+        .variables
+        .iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect();
+    copy_button.connect_clicked(move |button| button.clipboard().set_text(&assignments));
+    content_box.append(&copy_button);
+
+    if !environment.files.is_empty() {
+        let files_label = Label::new(Some("Environment Files"));
+        files_label.add_css_class("heading");
+        files_label.set_halign(gtk4::Align::Start);
+        content_box.append(&files_label);
+
+        for file in &environment.files {
+            let file_label = Label::new(Some(file));
+            file_label.set_halign(gtk4::Align::Start);
+            file_label.set_selectable(true);
+            content_box.append(&file_label);
+        }
+    }
+
+    dialog.set_child(Some(&content_box));
+    dialog.connect_response(|dialog, _| dialog.close());
+    dialog.show();
+}
+
+/// Lists systemd environment generators, marking symlinks and disabled ones
+pub fn show_environment_generators_dialog(parent: &Window, generators: &[GeneratorInfo]) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Environment Generators"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
 
-    scrolled.set_child(Some(&text_view));
+    dialog.set_default_size(600, 400);
+
+    let list_box = gtk4::ListBox::new();
+    list_box.set_selection_mode(gtk4::SelectionMode::None);
+    for generator in generators {
+        let kind = if generator.is_symlink {
+            "symlink"
+        } else {
+            "executable"
+        };
+        let state = if generator.enabled {
+            "enabled"
+        } else {
+            "disabled"
+        };
+
+        let row_label = Label::new(None);
+        row_label.set_markup(&format!(
+            "<b>{}</b>  <small>{}, {}</small>\n<small>{}</small>{}",
+            glib::markup_escape_text(&generator.name),
+            kind,
+            state,
+            glib::markup_escape_text(&generator.path.to_string_lossy()),
+            if generator.description.is_empty() {
+                String::new()
+            } else {
+                format!("\n{}", glib::markup_escape_text(&generator.description))
+            }
+        ));
+        row_label.set_halign(gtk4::Align::Start);
+        row_label.set_margin_start(6);
+        row_label.set_margin_end(6);
+        row_label.set_margin_top(6);
+        row_label.set_margin_bottom(6);
+        if generator.is_symlink {
+            row_label.add_css_class("generator-symlink");
+        }
+        if !generator.enabled {
+            row_label.add_css_class("dim-label");
+        }
+        list_box.append(&row_label);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+
+    if generators.is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No environment generators",
+            "None of the system-environment-generators directories contain generators",
+        )));
+    } else {
+        scrolled.set_child(Some(&list_box));
+    }
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Lets the user pick which of the hosts in `~/.ssh/config` to add to
+/// `remote_hosts`. Hosts that are already saved start unchecked, and
+/// `on_import` is called with the number of hosts added.
+pub fn show_import_ssh_config_dialog(
+    parent: &Window,
+    remote_hosts: &Rc<RefCell<HashMap<String, RemoteHost>>>,
+    on_import: impl Fn(usize) + 'static,
+) {
+    let hosts = match parse_user_ssh_config() {
+        Ok(hosts) if !hosts.is_empty() => hosts,
+        Ok(_) => {
+            show_info_dialog(
+                parent,
+                "Import from SSH Config",
+                "~/.ssh/config defines no hosts without wildcards",
+            );
+            return;
+        }
+        Err(e) => {
+            show_error_dialog(
+                parent,
+                "Import from SSH Config",
+                &format!("Could not read ~/.ssh/config: {}", e),
+            );
+            return;
+        }
+    };
+
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Import from SSH Config"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Import Selected", ResponseType::Ok);
+    dialog.set_default_size(600, 400);
+
+    let store = gtk4::ListStore::new(&[
+        glib::Type::BOOL,   // Import
+        glib::Type::STRING, // Name
+        glib::Type::STRING, // Hostname
+        glib::Type::STRING, // User
+        glib::Type::STRING, // Port
+    ]);
+    for host in &hosts {
+        let exists = remote_hosts.borrow().contains_key(&host.name);
+        store.insert_with_values(
+            None,
+            &[
+                (0, &!exists),
+                (1, &host.name),
+                (2, &host.hostname),
+                (3, &host.username),
+                (4, &host.port.to_string()),
+            ],
+        );
+    }
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+
+    let toggle = gtk4::CellRendererToggle::new();
+    toggle.connect_toggled(clone!(
+        #[weak]
+        store,
+        move |_, path| {
+            if let Some(iter) = store.iter(&path) {
+                let checked = store.get::<bool>(&iter, 0);
+                store.set_value(&iter, 0, &(!checked).to_value());
+            }
+        }
+    ));
+    let import_column = gtk4::TreeViewColumn::new();
+    import_column.pack_start(&toggle, false);
+    import_column.add_attribute(&toggle, "active", 0);
+    tree_view.append_column(&import_column);
+
+    for (i, title) in ["Name", "Hostname", "User", "Port"].iter().enumerate() {
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+
+        let renderer = gtk4::CellRendererText::new();
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", i as i32 + 1);
+
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&tree_view));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    let hint = Label::new(Some(
+        "Checked hosts are added. Hosts that are already saved are replaced.",
+    ));
+    hint.set_xalign(0.0);
+    content_box.append(&hint);
+    content_box.append(&scrolled);
+
+    dialog.set_child(Some(&content_box));
+
+    let remote_hosts = remote_hosts.clone();
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            let mut imported = 0;
+            let mut index = 0;
+            if let Some(iter) = store.iter_first() {
+                loop {
+                    if store.get::<bool>(&iter, 0) {
+                        let host = hosts[index].clone();
+                        remote_hosts.borrow_mut().insert(host.name.clone(), host);
+                        imported += 1;
+                    }
+                    index += 1;
+                    if !store.iter_next(&iter) {
+                        break;
+                    }
+                }
+            }
+            on_import(imported);
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Lists SSH connection events, newest first
+pub fn show_remote_host_connection_log(parent: &Window, events: &[ConnectionEvent]) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Connection Log"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(700, 400);
+
+    let store = gtk4::ListStore::new(&[
+        glib::Type::STRING, // Timestamp
+        glib::Type::STRING, // Host
+        glib::Type::STRING, // Event
+    ]);
+
+    let mut events: Vec<&ConnectionEvent> = events.iter().collect();
+    events.sort_by_key(|event| std::cmp::Reverse(event.timestamp));
+
+    for event in &events {
+        let timestamp = event
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+
+        store.insert_with_values(
+            None,
+            &[
+                (0, &timestamp),
+                (1, &event.host_name),
+                (2, &event.event_type.to_string()),
+            ],
+        );
+    }
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+    for (i, title) in ["Time", "Host", "Event"].iter().enumerate() {
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+
+        let renderer = gtk4::CellRendererText::new();
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", i as i32);
+
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+
+    if events.is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No connection events",
+            "Connecting to or disconnecting from a host is recorded here",
+        )));
+    } else {
+        scrolled.set_child(Some(&tree_view));
+    }
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+pub fn show_service_audit_dialog(parent: &Window, service_name: &str, entries: &[AuditEntry]) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Audit Log for {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(800, 500);
+
+    let store = gtk4::ListStore::new(&[
+        glib::Type::STRING, // Timestamp
+        glib::Type::STRING, // User
+        glib::Type::STRING, // Action
+    ]);
+
+    for entry in entries {
+        let timestamp = entry
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string();
+        let user = entry
+            .user_uid
+            .map(|uid| uid.to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        store.insert_with_values(None, &[(0, &timestamp), (1, &user), (2, &entry.action)]);
+    }
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+    for (i, title) in ["Time", "UID", "Action"].iter().enumerate() {
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+
+        let renderer = gtk4::CellRendererText::new();
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", i as i32);
+
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+
+    if entries.is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No operations recorded",
+            "The journal has no start, stop or reload requests for this service",
+        )));
+    } else {
+        scrolled.set_child(Some(&tree_view));
+    }
 
     let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 0);
     content_box.set_margin_start(12);
@@ -527,6 +3018,413 @@ pub fn show_service_details_dialog(
     dialog.show();
 }
 
+/// Entries shown per page of the audit log dialog
+const AUDIT_LOG_PAGE_SIZE: usize = 100;
+
+/// Shows every service operation run from the app, newest first, a page
+/// at a time. Failed operations are shown in red.
+pub fn show_audit_log_dialog(parent: &Window, audit_log: Rc<RefCell<AuditLog>>) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Audit Log"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(900, 500);
+
+    let store = gtk4::ListStore::new(&[
+        glib::Type::STRING, // Timestamp
+        glib::Type::STRING, // Host
+        glib::Type::STRING, // Service
+        glib::Type::STRING, // Action
+        glib::Type::STRING, // Result
+        glib::Type::STRING, // Text color
+    ]);
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+    for (i, title) in ["Timestamp", "Host", "Service", "Action", "Result"]
+        .iter()
+        .enumerate()
+    {
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+
+        let renderer = gtk4::CellRendererText::new();
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", i as i32);
+        column.add_attribute(&renderer, "foreground", 5);
+
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+
+    if audit_log.borrow().is_empty() {
+        scrolled.set_child(Some(&crate::ui::components::create_empty_state_widget(
+            "No operations recorded",
+            "Services started, stopped or changed from here are listed in the audit log",
+        )));
+    } else {
+        scrolled.set_child(Some(&tree_view));
+    }
+
+    let previous_button = gtk4::Button::with_label("◀ Newer");
+    let next_button = gtk4::Button::with_label("Older ▶");
+    let page_label = Label::new(None);
+    let pager = gtk4::Box::new(gtk4::Orientation::Horizontal, 6);
+    pager.set_halign(gtk4::Align::Center);
+    pager.append(&previous_button);
+    pager.append(&page_label);
+    pager.append(&next_button);
+
+    let page = Rc::new(Cell::new(0));
+    let show_page = Rc::new(clone!(
+        #[weak]
+        store,
+        #[weak]
+        page_label,
+        #[weak]
+        previous_button,
+        #[weak]
+        next_button,
+        #[strong]
+        page,
+        move || {
+            let audit_log = audit_log.borrow();
+            let page_count = audit_log.page_count(AUDIT_LOG_PAGE_SIZE);
+            let current = page.get().min(page_count - 1);
+            page.set(current);
+
+            store.clear();
+            for entry in audit_log.page(current, AUDIT_LOG_PAGE_SIZE) {
+                let timestamp = entry
+                    .timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string();
+                let result = match &entry.error {
+                    Some(error) => format!("Failed: {}", error),
+                    None if entry.success => "Succeeded".to_string(),
+                    None => "Failed".to_string(),
+                };
+                let color = (!entry.success).then_some("red");
+                store.insert_with_values(
+                    None,
+                    &[
+                        (0, &timestamp),
+                        (1, &entry.host.as_deref().unwrap_or("local")),
+                        (2, &entry.service),
+                        (3, &entry.action),
+                        (4, &result),
+                        (5, &color),
+                    ],
+                );
+            }
+
+            page_label.set_text(&format!("Page {} of {}", current + 1, page_count));
+            previous_button.set_sensitive(current > 0);
+            next_button.set_sensitive(current + 1 < page_count);
+        }
+    ));
+    show_page();
+
+    let previous_page = page.clone();
+    let show_previous = show_page.clone();
+    previous_button.connect_clicked(move |_| {
+        previous_page.set(previous_page.get().saturating_sub(1));
+        show_previous();
+    });
+    next_button.connect_clicked(move |_| {
+        page.set(page.get() + 1);
+        show_page();
+    });
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&scrolled);
+    content_box.append(&pager);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Color of an exposure score from `systemd-analyze security`
+fn exposure_color(score: f32) -> &'static str {
+    if score < 3.0 {
+        "green"
+    } else if score < 6.0 {
+        "#c88800"
+    } else {
+        "red"
+    }
+}
+
+/// Shows a service's `systemd-analyze security` exposure score and the
+/// sandboxing settings behind it, with the unsafe ones in red. When some of
+/// them can be fixed automatically, Fix Unsafe Settings calls `fix`, which
+/// adds `fixes` in a drop-in.
+pub fn show_security_dialog(
+    parent: &Window,
+    service_name: &str,
+    score: f32,
+    rows: &[SecuritySetting],
+    fixes: &[&str],
+    fix: impl Fn() + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some(&format!("Security of {}", service_name)));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Close", ResponseType::Close);
+
+    dialog.set_default_size(800, 550);
+
+    let score_label = Label::new(None);
+    score_label.set_halign(gtk4::Align::Start);
+    score_label.set_markup(&format!(
+        "Overall exposure: <span foreground=\"{}\" weight=\"bold\">{:.1}</span> / 10",
+        exposure_color(score),
+        score
+    ));
+
+    let store = gtk4::ListStore::new(&[
+        glib::Type::STRING, // Setting
+        glib::Type::STRING, // Value
+        glib::Type::STRING, // Description
+        glib::Type::STRING, // Text color
+    ]);
+
+    for (setting, value, description) in rows {
+        let color = (value == "unsafe").then_some("red");
+        store.insert_with_values(
+            None,
+            &[(0, setting), (1, value), (2, description), (3, &color)],
+        );
+    }
+
+    let tree_view = gtk4::TreeView::with_model(&store);
+    for (i, title) in ["Setting", "Value", "Description"].iter().enumerate() {
+        let column = gtk4::TreeViewColumn::new();
+        column.set_title(title);
+        column.set_resizable(true);
+
+        let renderer = gtk4::CellRendererText::new();
+        column.pack_start(&renderer, true);
+        column.add_attribute(&renderer, "text", i as i32);
+        column.add_attribute(&renderer, "foreground", 3);
+
+        tree_view.append_column(&column);
+    }
+
+    let scrolled = ScrolledWindow::new();
+    scrolled.set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Automatic);
+    scrolled.set_vexpand(true);
+    scrolled.set_child(Some(&tree_view));
+
+    let fix_button = gtk4::Button::with_label("Fix Unsafe Settings");
+    fix_button.set_halign(gtk4::Align::Start);
+    if fixes.is_empty() {
+        fix_button.set_sensitive(false);
+        fix_button.set_tooltip_text(Some(
+            "None of the unsafe settings can be fixed automatically",
+        ));
+    } else {
+        fix_button.set_tooltip_text(Some(&format!("Adds a drop-in with:\n{}", fixes.join("\n"))));
+    }
+    fix_button.connect_clicked(clone!(
+        #[weak]
+        dialog,
+        move |_| {
+            fix();
+            dialog.close();
+        }
+    ));
+
+    let content_box = gtk4::Box::new(gtk4::Orientation::Vertical, 6);
+    content_box.set_margin_start(12);
+    content_box.set_margin_end(12);
+    content_box.set_margin_top(12);
+    content_box.set_margin_bottom(12);
+    content_box.append(&score_label);
+    content_box.append(&scrolled);
+    content_box.append(&fix_button);
+
+    dialog.set_child(Some(&content_box));
+
+    dialog.connect_response(|dialog, _| {
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
+/// Edits the preferences in `config` in place. Save hands them to `save`
+/// to be stored and applied; Cancel restores what was there before.
+/// `clear_passwords` removes the SSH passwords saved in the keyring.
+pub fn show_preferences_dialog(
+    parent: &Window,
+    config: Rc<RefCell<PreferencesConfig>>,
+    save: impl Fn(&PreferencesConfig) + 'static,
+    clear_passwords: impl Fn() + 'static,
+) {
+    let dialog = Dialog::new();
+    dialog.set_title(Some("Preferences"));
+    dialog.set_transient_for(Some(parent));
+    dialog.set_modal(true);
+    dialog.add_button("Cancel", ResponseType::Cancel);
+    dialog.add_button("Save", ResponseType::Ok);
+    dialog.set_default_response(ResponseType::Ok);
+
+    let grid = Grid::new();
+    grid.set_row_spacing(12);
+    grid.set_column_spacing(12);
+    grid.set_margin_start(20);
+    grid.set_margin_end(20);
+    grid.set_margin_top(20);
+    grid.set_margin_bottom(20);
+
+    let auto_refresh_check = gtk4::CheckButton::with_label("Refresh the service lists");
+    grid.attach(&auto_refresh_check, 0, 0, 2, 1);
+
+    let interval_label = Label::new(Some("Refresh Interval (seconds):"));
+    interval_label.set_halign(gtk4::Align::Start);
+    let interval_spin = gtk4::SpinButton::with_range(
+        RefreshConfig::MIN_INTERVAL_SECS as f64,
+        RefreshConfig::MAX_INTERVAL_SECS as f64,
+        5.0,
+    );
+    grid.attach(&interval_label, 0, 1, 1, 1);
+    grid.attach(&interval_spin, 1, 1, 1, 1);
+
+    let show_inactive_check = gtk4::CheckButton::with_label("Show inactive units by default");
+    grid.attach(&show_inactive_check, 0, 2, 2, 1);
+
+    let log_lines_label = Label::new(Some("Log Lines:"));
+    log_lines_label.set_halign(gtk4::Align::Start);
+    let log_lines_spin = gtk4::SpinButton::with_range(
+        PreferencesConfig::MIN_LOG_LINES as f64,
+        PreferencesConfig::MAX_LOG_LINES as f64,
+        50.0,
+    );
+    log_lines_spin.set_tooltip_text(Some("Journal lines shown when a service's logs open"));
+    grid.attach(&log_lines_label, 0, 3, 1, 1);
+    grid.attach(&log_lines_spin, 1, 3, 1, 1);
+
+    let theme_label = Label::new(Some("Theme:"));
+    theme_label.set_halign(gtk4::Align::Start);
+    let theme_combo = ComboBoxText::new();
+    theme_combo.append(Some("system"), "Follow System");
+    theme_combo.append(Some("light"), "Light");
+    theme_combo.append(Some("dark"), "Dark");
+    grid.attach(&theme_label, 0, 4, 1, 1);
+    grid.attach(&theme_combo, 1, 4, 1, 1);
+
+    let passwords_label = Label::new(Some("Saved Passwords:"));
+    passwords_label.set_halign(gtk4::Align::Start);
+    let clear_passwords_button = gtk4::Button::with_label("Clear Saved Passwords");
+    clear_passwords_button.set_tooltip_text(Some("Forget the SSH passwords kept in the keyring"));
+    clear_passwords_button.connect_clicked(move |_| clear_passwords());
+    grid.attach(&passwords_label, 0, 5, 1, 1);
+    grid.attach(&clear_passwords_button, 1, 5, 1, 1);
+
+    let reset_button = gtk4::Button::with_label("Reset to Defaults");
+    reset_button.set_halign(gtk4::Align::Start);
+    grid.attach(&reset_button, 0, 6, 2, 1);
+
+    // Shows `preferences` in the controls, whose handlers below copy them
+    // back into `config`
+    let show_preferences = clone!(
+        #[weak]
+        auto_refresh_check,
+        #[weak]
+        interval_spin,
+        #[weak]
+        show_inactive_check,
+        #[weak]
+        log_lines_spin,
+        #[weak]
+        theme_combo,
+        move |preferences: &PreferencesConfig| {
+            auto_refresh_check.set_active(preferences.auto_refresh);
+            interval_spin.set_value(preferences.refresh().interval() as f64);
+            interval_spin.set_sensitive(preferences.auto_refresh);
+            show_inactive_check.set_active(preferences.show_inactive_default);
+            log_lines_spin.set_value(preferences.log_lines() as f64);
+            theme_combo.set_active_id(Some(match preferences.dark_mode_override {
+                None => "system",
+                Some(false) => "light",
+                Some(true) => "dark",
+            }));
+        }
+    );
+    let original = config.borrow().clone();
+    show_preferences(&original);
+
+    auto_refresh_check.connect_toggled(clone!(
+        #[strong]
+        config,
+        #[weak]
+        interval_spin,
+        move |check| {
+            config.borrow_mut().auto_refresh = check.is_active();
+            interval_spin.set_sensitive(check.is_active());
+        }
+    ));
+    interval_spin.connect_value_changed(clone!(
+        #[strong]
+        config,
+        move |spin| config.borrow_mut().refresh_interval_secs = spin.value_as_int() as u32
+    ));
+    show_inactive_check.connect_toggled(clone!(
+        #[strong]
+        config,
+        move |check| config.borrow_mut().show_inactive_default = check.is_active()
+    ));
+    log_lines_spin.connect_value_changed(clone!(
+        #[strong]
+        config,
+        move |spin| config.borrow_mut().log_lines_default = spin.value_as_int() as u32
+    ));
+    theme_combo.connect_changed(clone!(
+        #[strong]
+        config,
+        move |combo| {
+            config.borrow_mut().dark_mode_override = match combo.active_id().as_deref() {
+                Some("light") => Some(false),
+                Some("dark") => Some(true),
+                _ => None,
+            };
+        }
+    ));
+    reset_button.connect_clicked(move |_| show_preferences(&PreferencesConfig::default()));
+
+    dialog.set_child(Some(&grid));
+
+    dialog.connect_response(move |dialog, response| {
+        if response == ResponseType::Ok {
+            // `save` may update the preferences itself
+            let preferences = config.borrow().clone();
+            save(&preferences);
+        } else {
+            config.replace(original.clone());
+        }
+        dialog.close();
+    });
+
+    dialog.show();
+}
+
 pub fn show_about_dialog(parent: &Window) {
     let dialog = gtk4::AboutDialog::new();
     dialog.set_transient_for(Some(parent));
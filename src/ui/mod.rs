@@ -1,5 +1,6 @@
 pub mod components;
 pub mod dialogs;
+pub mod shortcuts;
 pub mod styles;
 
 pub use components::*;
@@ -0,0 +1,103 @@
+use gtk4::prelude::*;
+use gtk4::Application;
+
+/// Where a listed shortcut's accelerator comes from
+enum Accelerator {
+    /// Whatever is registered on the application for this action
+    Action(&'static str),
+    /// A key handled by a single widget, such as Delete in the hosts list
+    Key(&'static str),
+}
+
+/// Shortcuts listed in the keyboard shortcuts window, by group
+const SHORTCUT_GROUPS: &[(&str, &[(Accelerator, &str)])] = &[
+    (
+        "Service Operations",
+        &[
+            (Accelerator::Action("app.refresh"), "Refresh services"),
+            (Accelerator::Action("app.start"), "Start service"),
+            (Accelerator::Action("app.stop"), "Stop service"),
+            (Accelerator::Action("app.restart"), "Restart service"),
+            (Accelerator::Action("app.logs"), "View logs"),
+            (
+                Accelerator::Key("<Control>c"),
+                "Copy the selected service names",
+            ),
+        ],
+    ),
+    (
+        "Navigation",
+        &[
+            (Accelerator::Action("win.next-tab"), "Switch tabs"),
+            (Accelerator::Action("win.focus-search"), "Search services"),
+            (Accelerator::Key("Menu"), "Open the context menu"),
+            (
+                Accelerator::Action("win.show-help-overlay"),
+                "Keyboard shortcuts",
+            ),
+            (Accelerator::Action("win.preferences"), "Preferences"),
+        ],
+    ),
+    (
+        "Hosts",
+        &[
+            (Accelerator::Action("win.add-host"), "Add host"),
+            (Accelerator::Action("win.focus-host-search"), "Search hosts"),
+            (Accelerator::Key("Delete"), "Remove the selected host"),
+        ],
+    ),
+];
+
+/// Builds the keyboard shortcuts window from the accelerators registered
+/// on `app`, so it always matches the actual bindings. Actions without an
+/// accelerator are left out.
+pub fn build_shortcuts_window(app: &Application) -> gtk4::ShortcutsWindow {
+    let mut groups = String::new();
+    for (group_title, shortcuts) in SHORTCUT_GROUPS {
+        let mut entries = String::new();
+        for (accelerator, title) in *shortcuts {
+            let accelerator = match accelerator {
+                Accelerator::Action(action) => app
+                    .accels_for_action(action)
+                    .iter()
+                    .map(|accel| accel.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                Accelerator::Key(key) => key.to_string(),
+            };
+            if accelerator.is_empty() {
+                continue;
+            }
+            entries.push_str(&format!(
+                "<child><object class=\"GtkShortcutsShortcut\">\
+                 <property name=\"title\">{}</property>\
+                 <property name=\"accelerator\">{}</property>\
+                 </object></child>",
+                glib::markup_escape_text(title),
+                glib::markup_escape_text(&accelerator)
+            ));
+        }
+
+        if !entries.is_empty() {
+            groups.push_str(&format!(
+                "<child><object class=\"GtkShortcutsGroup\">\
+                 <property name=\"title\">{}</property>{}</object></child>",
+                glib::markup_escape_text(group_title),
+                entries
+            ));
+        }
+    }
+
+    // Sections and groups can only be added through GtkBuildable before GTK 4.14
+    let ui = format!(
+        "<interface><object class=\"GtkShortcutsWindow\" id=\"shortcuts\">\
+         <property name=\"modal\">1</property>\
+         <child><object class=\"GtkShortcutsSection\">\
+         <property name=\"section-name\">shortcuts</property>{}</object></child>\
+         </object></interface>",
+        groups
+    );
+    gtk4::Builder::from_string(&ui)
+        .object("shortcuts")
+        .expect("Shortcuts window is defined in the generated UI")
+}
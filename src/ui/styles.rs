@@ -2,6 +2,45 @@ use gtk4::prelude::*;
 use gtk4::{CssProvider, StyleContext, Widget, STYLE_PROVIDER_PRIORITY_APPLICATION};
 use log::{debug, error, warn};
 
+use crate::service_manager::ServiceStatus;
+
+/// Journal priority names and their highlight colours, indexed by syslog priority (0-7)
+pub const LOG_PRIORITY_COLORS: [(&str, &str); 8] = [
+    ("EMERG", "#c0392b"),
+    ("ALERT", "#d35400"),
+    ("CRIT", "#e74c3c"),
+    ("ERROR", "#e67e22"),
+    ("WARN", "#f39c12"),
+    ("NOTICE", "#2980b9"),
+    ("INFO", "#27ae60"),
+    ("DEBUG", "#7f8c8d"),
+];
+
+/// Syslog priority of a journal line, from a `<0>`–`<7>` prefix or the
+/// first level name it contains (`ERROR`, `WARNING`, …)
+pub fn log_line_priority(line: &str) -> Option<usize> {
+    let line = line.trim_start();
+    let mut chars = line.chars();
+    if let (Some('<'), Some(digit), Some('>')) = (chars.next(), chars.next(), chars.next()) {
+        if let Some(priority) = digit.to_digit(8) {
+            return Some(priority as usize);
+        }
+    }
+
+    line.split(|c: char| !c.is_ascii_alphanumeric())
+        .find_map(|word| match word {
+            "EMERG" => Some(0),
+            "ALERT" => Some(1),
+            "CRIT" => Some(2),
+            "ERR" | "ERROR" => Some(3),
+            "WARN" | "WARNING" => Some(4),
+            "NOTICE" => Some(5),
+            "INFO" => Some(6),
+            "DEBUG" => Some(7),
+            _ => None,
+        })
+}
+
 /// Additional CSS styles for specific components
 const COMPONENT_STYLES: &str = r#"
     /* Service list specific styles */
@@ -570,6 +609,32 @@ pub fn remove_success_state(widget: &impl IsA<Widget>) {
     remove_css_class(widget, "success-state");
 }
 
+/// CSS class for a service row combining its status and whether it is
+/// enabled, e.g. `service-active-disabled` for a unit that is running now but
+/// will not be started again after a reboot
+pub fn get_service_row_css_class(status: &ServiceStatus, enabled: bool) -> &'static str {
+    match (status, enabled) {
+        (ServiceStatus::Active, true) => "service-active-enabled",
+        (ServiceStatus::Active, false) => "service-active-disabled",
+        (ServiceStatus::Inactive, true) => "service-inactive-enabled",
+        (ServiceStatus::Inactive, false) => "service-inactive-disabled",
+        (ServiceStatus::Failed, true) => "service-failed-enabled",
+        (ServiceStatus::Failed, false) => "service-failed-disabled",
+        (ServiceStatus::ConditionFailed, true) => "service-condition-failed-enabled",
+        (ServiceStatus::ConditionFailed, false) => "service-condition-failed-disabled",
+        (ServiceStatus::Activating, true) => "service-activating-enabled",
+        (ServiceStatus::Activating, false) => "service-activating-disabled",
+        (ServiceStatus::Deactivating, true) => "service-deactivating-enabled",
+        (ServiceStatus::Deactivating, false) => "service-deactivating-disabled",
+        (ServiceStatus::Reloading, true) => "service-reloading-enabled",
+        (ServiceStatus::Reloading, false) => "service-reloading-disabled",
+        (ServiceStatus::Masked, true) => "service-masked-enabled",
+        (ServiceStatus::Masked, false) => "service-masked-disabled",
+        (ServiceStatus::Unknown, true) => "service-unknown-enabled",
+        (ServiceStatus::Unknown, false) => "service-unknown-disabled",
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,4 +661,56 @@ mod tests {
             assert!(COMPONENT_STYLES.contains(class_name));
         }
     }
+
+    #[test]
+    fn test_log_priority_colors() {
+        assert_eq!(LOG_PRIORITY_COLORS[0].0, "EMERG");
+        assert_eq!(LOG_PRIORITY_COLORS[7].0, "DEBUG");
+
+        for (_, color) in LOG_PRIORITY_COLORS {
+            assert!(color.starts_with('#') && color.len() == 7);
+        }
+    }
+
+    #[test]
+    fn test_log_line_priority() {
+        assert_eq!(log_line_priority("<3>nginx: bind() failed"), Some(3));
+        assert_eq!(
+            log_line_priority("Mar 09 14:05:07 web01 app[812]: ERROR connection refused"),
+            Some(3)
+        );
+        assert_eq!(log_line_priority("[WARNING] disk almost full"), Some(4));
+        assert_eq!(log_line_priority("level=DEBUG msg=tick"), Some(7));
+        // Level names only count as whole words
+        assert_eq!(log_line_priority("Started INFORMATION service"), None);
+        assert_eq!(log_line_priority("<9>not a priority"), None);
+    }
+
+    #[test]
+    fn test_service_row_css_class() {
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::Active, true),
+            "service-active-enabled"
+        );
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::Active, false),
+            "service-active-disabled"
+        );
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::Failed, true),
+            "service-failed-enabled"
+        );
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::Unknown, false),
+            "service-unknown-disabled"
+        );
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::ConditionFailed, true),
+            "service-condition-failed-enabled"
+        );
+        assert_eq!(
+            get_service_row_css_class(&ServiceStatus::Activating, false),
+            "service-activating-disabled"
+        );
+    }
 }
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use crate::utils::config::config_file;
+
+/// Name of the file the audit log is kept in
+const FILE_NAME: &str = "audit.log";
+
+/// A service operation run from the app
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub action: String,
+    pub service: String,
+    /// Remote host the service is on, or none for the local machine
+    pub host: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// Every service operation run from the app, kept in
+/// `~/.config/systemd-pilot/audit.log` with one JSON entry per line
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+    /// Number of entries already in the file, which `save` skips
+    saved: usize,
+}
+
+impl AuditLog {
+    pub fn load() -> Result<Self> {
+        let path = config_file(FILE_NAME)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(Self::parse(&std::fs::read_to_string(&path)?))
+    }
+
+    /// Reads newline-delimited entries, skipping lines that don't parse so
+    /// one damaged line doesn't hide the rest
+    fn parse(content: &str) -> Self {
+        let entries: Vec<AuditEntry> = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                serde_json::from_str(line)
+                    .inspect_err(|e| warn!("Skipping unreadable audit log entry: {}", e))
+                    .ok()
+            })
+            .collect();
+        Self {
+            saved: entries.len(),
+            entries,
+        }
+    }
+
+    /// Appends the entries recorded since the last save to the file
+    pub fn save(&mut self) -> Result<()> {
+        if self.saved == self.entries.len() {
+            return Ok(());
+        }
+
+        let path = config_file(FILE_NAME)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        file.write_all(self.unsaved_lines()?.as_bytes())?;
+        self.saved = self.entries.len();
+        Ok(())
+    }
+
+    fn unsaved_lines(&self) -> Result<String> {
+        let mut lines = String::new();
+        for entry in &self.entries[self.saved..] {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        Ok(lines)
+    }
+
+    /// Records the outcome of running `action` on `service` now
+    pub fn record(
+        &mut self,
+        action: &str,
+        service: &str,
+        host: Option<&str>,
+        result: Result<(), String>,
+    ) {
+        self.entries.push(AuditEntry {
+            timestamp: Utc::now(),
+            action: action.to_string(),
+            service: service.to_string(),
+            host: host.map(str::to_string),
+            success: result.is_ok(),
+            error: result.err(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of pages of `page_size` entries, at least one
+    pub fn page_count(&self, page_size: usize) -> usize {
+        self.entries.len().div_ceil(page_size).max(1)
+    }
+
+    /// The entries on page `page` (from 0), newest first
+    pub fn page(&self, page: usize, page_size: usize) -> Vec<&AuditEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .skip(page * page_size)
+            .take(page_size)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entries_round_trip_as_lines() {
+        let mut log = AuditLog::default();
+        log.record("start", "nginx.service", None, Ok(()));
+        log.record(
+            "restart",
+            "sshd.service",
+            Some("web1"),
+            Err("Access denied".to_string()),
+        );
+
+        let lines = log.unsaved_lines().unwrap();
+        assert_eq!(lines.lines().count(), 2);
+
+        let loaded = AuditLog::parse(&format!("{}not json\n\n", lines));
+        assert_eq!(loaded.entries.len(), 2);
+        assert_eq!(loaded.entries, log.entries);
+        assert!(loaded.unsaved_lines().unwrap().is_empty());
+
+        let failed = &loaded.entries[1];
+        assert_eq!(failed.host.as_deref(), Some("web1"));
+        assert!(!failed.success);
+        assert_eq!(failed.error.as_deref(), Some("Access denied"));
+    }
+
+    #[test]
+    fn test_pages_are_newest_first() {
+        let mut log = AuditLog::default();
+        assert_eq!(log.page_count(2), 1);
+        for service in ["a", "b", "c"] {
+            log.record("stop", service, None, Ok(()));
+        }
+
+        assert_eq!(log.page_count(2), 2);
+        let services = |page| -> Vec<String> {
+            log.page(page, 2)
+                .iter()
+                .map(|entry| entry.service.clone())
+                .collect()
+        };
+        assert_eq!(services(0), ["c", "b"]);
+        assert_eq!(services(1), ["a"]);
+        assert!(services(2).is_empty());
+    }
+}
@@ -0,0 +1,277 @@
+use anyhow::{anyhow, Result};
+use log::{info, warn};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::service_manager::ActionLog;
+
+/// Environment variable overriding `Config::refresh`
+pub const ENV_REFRESH_INTERVAL: &str = "SYSTEMD_PILOT_REFRESH_INTERVAL";
+/// Environment variable overriding `Config::dark_mode`
+pub const ENV_DARK_MODE: &str = "SYSTEMD_PILOT_DARK_MODE";
+/// Environment variable overriding `Config::default_host`
+pub const ENV_DEFAULT_HOST: &str = "SYSTEMD_PILOT_DEFAULT_HOST";
+
+/// Name of the file `Config` is saved in
+const FILE_NAME: &str = "config.json";
+
+/// The file `name` in the app's config directory, `~/.config/systemd-pilot`
+pub fn config_file(name: &str) -> Result<PathBuf> {
+    let config_dir =
+        dirs::config_dir().ok_or_else(|| anyhow!("Could not find config directory"))?;
+    Ok(config_dir.join("systemd-pilot").join(name))
+}
+
+/// Reads the JSON config file `name`, or `None` when it wasn't saved yet
+pub fn load_json<T: DeserializeOwned>(name: &str) -> Result<Option<T>> {
+    let path = config_file(name)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&std::fs::read_to_string(
+        &path,
+    )?)?))
+}
+
+/// Writes `value` as pretty-printed JSON to the config file `name`,
+/// creating the config directory when needed
+pub fn save_json<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let path = config_file(name)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    std::fs::write(&path, serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
+
+/// Application settings stored in `~/.config/systemd-pilot/config.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Periodic refresh of the service lists. Saved in the preferences; only
+    /// read here from config files written by older versions
+    #[serde(skip_serializing)]
+    pub refresh: RefreshConfig,
+    /// `refresh` as saved by older versions: seconds between refreshes, 0 to disable
+    #[serde(rename = "refresh_interval", skip_serializing)]
+    legacy_refresh_interval: Option<u32>,
+    /// Forces dark or light mode; the system theme is followed when unset.
+    /// Saved like `refresh`.
+    #[serde(skip_serializing)]
+    pub dark_mode: Option<bool>,
+    /// Saved host to connect to at startup
+    pub default_host: Option<String>,
+    /// Recent actions run on each local service
+    pub action_log: ActionLog,
+    /// Local services kept at the top of the list
+    pub pinned_services: Vec<String>,
+}
+
+impl Config {
+    /// Loads the saved config, falling back to defaults when there is none
+    pub fn load() -> Result<Self> {
+        let path = config_file(FILE_NAME)?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::from_json(&std::fs::read_to_string(&path)?)
+    }
+
+    fn from_json(content: &str) -> Result<Self> {
+        let mut config: Self = serde_json::from_str(content)?;
+        if let Some(interval) = config.legacy_refresh_interval.take() {
+            config.refresh = RefreshConfig::from_interval(interval);
+        }
+        Ok(config)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(FILE_NAME, self)
+    }
+
+    /// Applies `change` to the config saved on disk. The running config has
+    /// the environment overrides merged in, which must not be saved, so
+    /// changes are made to both rather than saving the running one.
+    pub fn update_saved(change: impl FnOnce(&mut Self)) -> Result<()> {
+        let mut saved = Self::load()?;
+        change(&mut saved);
+        saved.save()
+    }
+
+    /// Overrides settings from the environment, so containerised deployments
+    /// can configure the app without writing files:
+    ///
+    /// - `SYSTEMD_PILOT_REFRESH_INTERVAL`: seconds between refreshes, `0` to disable
+    /// - `SYSTEMD_PILOT_DARK_MODE`: `true`/`false` (also `1`/`0`, `yes`/`no`, `on`/`off`)
+    /// - `SYSTEMD_PILOT_DEFAULT_HOST`: name of a saved host to connect to at startup
+    ///
+    /// Invalid values are logged and ignored.
+    pub fn merge_from_env(&mut self) {
+        self.merge_from(|key| std::env::var(key).ok());
+    }
+
+    fn merge_from(&mut self, var: impl Fn(&str) -> Option<String>) {
+        if let Some(value) = var(ENV_REFRESH_INTERVAL) {
+            match value.trim().parse() {
+                Ok(interval) => self.refresh = RefreshConfig::from_interval(interval),
+                Err(_) => warn!("Ignoring invalid {}: {}", ENV_REFRESH_INTERVAL, value),
+            }
+        }
+
+        if let Some(value) = var(ENV_DARK_MODE) {
+            match parse_bool(&value) {
+                Some(dark_mode) => self.dark_mode = Some(dark_mode),
+                None => warn!("Ignoring invalid {}: {}", ENV_DARK_MODE, value),
+            }
+        }
+
+        if let Some(value) = var(ENV_DEFAULT_HOST) {
+            let host = value.trim();
+            if !host.is_empty() {
+                info!("Default host set from environment: {}", host);
+                self.default_host = Some(host.to_string());
+            }
+        }
+    }
+}
+
+/// Whether and how often the service lists refresh themselves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshConfig {
+    pub enabled: bool,
+    pub interval_secs: u32,
+}
+
+impl RefreshConfig {
+    pub const MIN_INTERVAL_SECS: u32 = 5;
+    pub const MAX_INTERVAL_SECS: u32 = 300;
+    pub const DEFAULT_INTERVAL_SECS: u32 = 30;
+
+    /// From a plain number of seconds, where 0 means disabled
+    pub fn from_interval(interval_secs: u32) -> Self {
+        if interval_secs == 0 {
+            Self::default()
+        } else {
+            Self {
+                enabled: true,
+                interval_secs,
+            }
+        }
+    }
+
+    /// The interval within the supported range
+    pub fn interval(&self) -> u32 {
+        self.interval_secs
+            .clamp(Self::MIN_INTERVAL_SECS, Self::MAX_INTERVAL_SECS)
+    }
+}
+
+impl Default for RefreshConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: Self::DEFAULT_INTERVAL_SECS,
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn merged(vars: &[(&str, &str)]) -> Config {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let mut config = Config::default();
+        config.merge_from(|key| vars.get(key).cloned());
+        config
+    }
+
+    #[test]
+    fn test_no_overrides() {
+        assert_eq!(merged(&[]), Config::default());
+    }
+
+    #[test]
+    fn test_refresh_interval_override() {
+        assert_eq!(
+            merged(&[(ENV_REFRESH_INTERVAL, "45")]).refresh,
+            RefreshConfig {
+                enabled: true,
+                interval_secs: 45
+            }
+        );
+        assert!(!merged(&[(ENV_REFRESH_INTERVAL, "0")]).refresh.enabled);
+        assert_eq!(
+            merged(&[(ENV_REFRESH_INTERVAL, "soon")]).refresh,
+            Config::default().refresh
+        );
+    }
+
+    #[test]
+    fn test_refresh_interval_is_clamped() {
+        assert_eq!(RefreshConfig::from_interval(2).interval(), 5);
+        assert_eq!(RefreshConfig::from_interval(3600).interval(), 300);
+        assert_eq!(RefreshConfig::default().interval(), 30);
+    }
+
+    #[test]
+    fn test_dark_mode_override() {
+        assert_eq!(merged(&[(ENV_DARK_MODE, "true")]).dark_mode, Some(true));
+        assert_eq!(merged(&[(ENV_DARK_MODE, "Off")]).dark_mode, Some(false));
+        assert_eq!(merged(&[(ENV_DARK_MODE, "maybe")]).dark_mode, None);
+    }
+
+    #[test]
+    fn test_default_host_override() {
+        assert_eq!(
+            merged(&[(ENV_DEFAULT_HOST, "web1")]).default_host,
+            Some("web1".to_string())
+        );
+        assert_eq!(merged(&[(ENV_DEFAULT_HOST, "  ")]).default_host, None);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let config: Config = serde_json::from_str(r#"{"dark_mode": true}"#).unwrap();
+        assert_eq!(config.dark_mode, Some(true));
+        assert!(!config.refresh.enabled);
+    }
+
+    #[test]
+    fn test_preference_settings_are_not_saved() {
+        let config = Config {
+            refresh: RefreshConfig::from_interval(60),
+            dark_mode: Some(true),
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("refresh"));
+        assert!(!json.contains("dark_mode"));
+    }
+
+    #[test]
+    fn test_legacy_refresh_interval() {
+        let config = Config::from_json(r#"{"refresh_interval": 60}"#).unwrap();
+        assert_eq!(config.refresh, RefreshConfig::from_interval(60));
+        assert!(!serde_json::to_string(&config)
+            .unwrap()
+            .contains("refresh_interval"));
+    }
+}
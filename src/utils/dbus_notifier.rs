@@ -0,0 +1,36 @@
+use gio::prelude::*;
+use log::debug;
+
+/// How prominently the desktop should present a notification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationUrgency {
+    Low,
+    Normal,
+    Urgent,
+}
+
+impl From<NotificationUrgency> for gio::NotificationPriority {
+    fn from(urgency: NotificationUrgency) -> Self {
+        match urgency {
+            NotificationUrgency::Low => gio::NotificationPriority::Low,
+            NotificationUrgency::Normal => gio::NotificationPriority::Normal,
+            NotificationUrgency::Urgent => gio::NotificationPriority::Urgent,
+        }
+    }
+}
+
+/// Sends a desktop notification through the application's D-Bus connection.
+/// `set_urgent` is deprecated in GIO, so urgency maps onto the notification priority.
+pub fn send_notification(
+    app: &gtk4::Application,
+    title: &str,
+    body: &str,
+    urgency: NotificationUrgency,
+) {
+    let notification = gio::Notification::new(title);
+    notification.set_body(Some(body));
+    notification.set_priority(urgency.into());
+
+    debug!("Sending {:?} notification: {}", urgency, title);
+    app.send_notification(None, &notification);
+}
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use std::io::{Seek, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Config keys containing any of these fragments are left out of exports
+const CREDENTIAL_KEY_FRAGMENTS: &[&str] = &["password", "passphrase", "secret", "token"];
+
+/// Default file name for a support bundle created at `now`
+pub fn bundle_file_name(now: DateTime<Local>) -> String {
+    format!(
+        "systemd-pilot-diagnostics-{}.zip",
+        now.format("%Y%m%d-%H%M%S")
+    )
+}
+
+/// Default file name for the logs of `service_name` exported at `now`
+pub fn log_export_file_name(service_name: &str, now: DateTime<Local>) -> String {
+    format!("{}_{}.log", service_name, now.format("%Y%m%d-%H%M%S"))
+}
+
+/// Removes every credential field from a serialized config, at any depth
+pub fn strip_credentials(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !is_credential_key(key));
+            map.values_mut().for_each(strip_credentials);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_credentials),
+        _ => {}
+    }
+}
+
+fn is_credential_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    CREDENTIAL_KEY_FRAGMENTS
+        .iter()
+        .any(|fragment| key.contains(fragment))
+}
+
+/// Writes `(file name, contents)` pairs into a zip archive
+pub fn write_bundle<W: Write + Seek>(writer: W, files: &[(&str, String)]) -> Result<()> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for (name, contents) in files {
+        zip.start_file(*name, options)?;
+        zip.write_all(contents.as_bytes())?;
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use serde_json::json;
+    use std::io::{Cursor, Read};
+
+    #[test]
+    fn test_bundle_file_name() {
+        let now = Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        assert_eq!(
+            bundle_file_name(now),
+            "systemd-pilot-diagnostics-20240309-140507.zip"
+        );
+    }
+
+    #[test]
+    fn test_log_export_file_name() {
+        let now = Local.with_ymd_and_hms(2024, 3, 9, 14, 5, 7).unwrap();
+        assert_eq!(
+            log_export_file_name("nginx.service", now),
+            "nginx.service_20240309-140507.log"
+        );
+    }
+
+    #[test]
+    fn test_strip_credentials() {
+        let mut config = json!({
+            "refresh_interval": 30,
+            "password": "hunter2",
+            "hosts": [{"name": "web1", "ssh_passphrase": "x", "api_token": "y"}],
+        });
+        strip_credentials(&mut config);
+
+        assert_eq!(
+            config,
+            json!({"refresh_interval": 30, "hosts": [{"name": "web1"}]})
+        );
+    }
+
+    #[test]
+    fn test_write_bundle() {
+        let mut buffer = Cursor::new(Vec::new());
+        write_bundle(&mut buffer, &[("version.txt", "3.0.0".to_string())]).unwrap();
+
+        let mut archive = zip::ZipArchive::new(buffer).unwrap();
+        let mut contents = String::new();
+        archive
+            .by_name("version.txt")
+            .unwrap()
+            .read_to_string(&mut contents)
+            .unwrap();
+        assert_eq!(contents, "3.0.0");
+    }
+}
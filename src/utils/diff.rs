@@ -0,0 +1,46 @@
+use similar::TextDiff;
+
+/// Lines of unchanged context kept around each change
+const CONTEXT_LINES: usize = 3;
+
+/// A unified diff of `old` against `new`, like `diff -u` prints, with
+/// `old_label` and `new_label` naming the two sides. Identical texts give
+/// an empty diff.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "[Unit]\nDescription=Web\n\n[Service]\nExecStart=/usr/bin/web\nUser=web\n\
+                   Restart=always\nRestartSec=5\n\n[Install]\nWantedBy=multi-user.target\n";
+        let new = "[Unit]\nDescription=Web\n\n[Service]\nExecStart=/usr/bin/web --port 8080\n\
+                   User=web\nRestart=always\nRestartSec=5\n\n[Install]\nWantedBy=multi-user.target\n\
+                   Alias=www.service\n";
+
+        assert_eq!(
+            unified_diff(old, new, "local", "web1"),
+            "--- local\n+++ web1\n\
+             @@ -2,10 +2,11 @@\n Description=Web\n \n [Service]\n-ExecStart=/usr/bin/web\n\
+             +ExecStart=/usr/bin/web --port 8080\n User=web\n Restart=always\n RestartSec=5\n\
+             \x20\n [Install]\n WantedBy=multi-user.target\n+Alias=www.service\n"
+        );
+        assert_eq!(unified_diff(old, old, "local", "web1"), "");
+    }
+
+    #[test]
+    fn test_unified_diff_of_empty_side() {
+        assert_eq!(
+            unified_diff("", "a\nb\n", "local", "web1"),
+            "--- local\n+++ web1\n@@ -0,0 +1,2 @@\n+a\n+b\n"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use log::{debug, warn};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+
+/// Keeps a directory watch alive; watching stops when the handle is dropped
+pub struct WatcherHandle {
+    _watcher: RecommendedWatcher,
+}
+
+/// Watches `path` (non-recursively) and invokes `callback` with the path of
+/// every file whose contents were modified
+///
+/// The callback runs on the watcher's background thread, not the GTK main loop.
+pub fn watch_directory(
+    path: &Path,
+    callback: impl Fn(PathBuf) + Send + 'static,
+) -> Result<WatcherHandle> {
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) => {
+                if matches!(event.kind, EventKind::Modify(ModifyKind::Data(_))) {
+                    for path in event.paths {
+                        callback(path);
+                    }
+                }
+            }
+            Err(e) => warn!("File watcher error: {}", e),
+        })?;
+
+    watcher.watch(path, RecursiveMode::NonRecursive)?;
+    debug!("Watching {} for changes", path.display());
+
+    Ok(WatcherHandle { _watcher: watcher })
+}
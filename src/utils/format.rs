@@ -0,0 +1,227 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+
+/// Parses a timestamp as printed by `systemctl show`, either formatted
+/// (`Tue 2024-03-12 14:22:03 UTC`) or as microseconds since the epoch
+/// (`ActiveEnterTimestampMonotonic`-style `...USec` properties). Unset values
+/// such as `0`, `n/a` or an empty string yield `None`.
+pub fn parse_systemd_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+
+    if let Ok(micros) = s.parse::<i64>() {
+        return match micros {
+            0 => None,
+            micros => DateTime::from_timestamp_micros(micros),
+        };
+    }
+
+    let mut parts: Vec<&str> = s.split_whitespace().collect();
+
+    // The weekday is informational only
+    if parts
+        .first()
+        .is_some_and(|part| part.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        parts.remove(0);
+    }
+
+    let (date, time, zone) = match parts.as_slice() {
+        [date, time] => (*date, *time, None),
+        [date, time, zone] => (*date, *time, Some(*zone)),
+        _ => return None,
+    };
+
+    let naive =
+        NaiveDateTime::parse_from_str(&format!("{} {}", date, time), "%Y-%m-%d %H:%M:%S").ok()?;
+
+    match zone {
+        Some("UTC" | "GMT") => Some(Utc.from_utc_datetime(&naive)),
+        // systemd prints other zones by abbreviation, which chrono can't
+        // resolve; they are the system's local zone in practice
+        _ => Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .map(|dt| dt.with_timezone(&Utc)),
+    }
+}
+
+/// Describes `dt` relative to now, e.g. "3 minutes ago", "in 2 hours" or "just now"
+pub fn format_relative(dt: &DateTime<Utc>) -> String {
+    format_relative_to(dt, &Utc::now())
+}
+
+/// A systemd timestamp relative to now, falling back to the text as systemd
+/// printed it when it can't be parsed
+pub fn format_systemd_timestamp(s: &str) -> String {
+    parse_systemd_timestamp(s)
+        .map(|dt| format_relative(&dt))
+        .unwrap_or_else(|| s.trim().to_string())
+}
+
+fn format_relative_to(dt: &DateTime<Utc>, now: &DateTime<Utc>) -> String {
+    let seconds = (*dt - *now).num_seconds();
+    let distance = seconds.unsigned_abs();
+
+    if distance < 60 {
+        return "just now".to_string();
+    }
+
+    let (amount, unit) = match distance {
+        d if d < 3600 => (d / 60, "minute"),
+        d if d < 86_400 => (d / 3600, "hour"),
+        d if d < 30 * 86_400 => (d / 86_400, "day"),
+        d if d < 365 * 86_400 => (d / (30 * 86_400), "month"),
+        d => (d / (365 * 86_400), "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if seconds < 0 {
+        format!("{} {}{} ago", amount, unit, plural)
+    } else {
+        format!("in {} {}{}", amount, unit, plural)
+    }
+}
+
+/// A short duration the way systemd prints it, e.g. "350ms", "1.234s" or
+/// "1min 2.345s"
+pub fn format_timespan(duration: std::time::Duration) -> String {
+    let millis = duration.as_millis();
+    match millis {
+        m if m < 1000 => format!("{}ms", m),
+        m if m < 60_000 => format!("{:.3}s", duration.as_secs_f64()),
+        m => format!("{}min {:.3}s", m / 60_000, (m % 60_000) as f64 / 1000.0),
+    }
+}
+
+/// A byte count in decimal units, e.g. "512 B" or "45.2 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["kB", "MB", "GB", "TB"];
+
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if value < 1000.0 {
+            break;
+        }
+        value /= 1000.0;
+        unit = next;
+    }
+    format!("{:.1} {}", value, unit)
+}
+
+/// CPU time in nanoseconds, rounded for a list column, e.g. "1.3s" or
+/// "2min 5s"
+pub fn format_cpu_time(nanoseconds: u64) -> String {
+    let seconds = nanoseconds as f64 / 1e9;
+    if seconds < 60.0 {
+        format!("{:.1}s", seconds)
+    } else {
+        let seconds = seconds.round() as u64;
+        format!("{}min {}s", seconds / 60, seconds % 60)
+    }
+}
+
+/// Position of the selected search match, e.g. "(3 of 17)", or
+/// "(17 matches)" before one is selected
+pub fn format_match_count(current: Option<usize>, total: usize) -> String {
+    match (current, total) {
+        (_, 0) => "No matches".to_string(),
+        (Some(index), total) => format!("({} of {})", index + 1, total),
+        (None, 1) => "(1 match)".to_string(),
+        (None, total) => format!("({} matches)", total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_parse_formatted_timestamp() {
+        let expected = Utc.with_ymd_and_hms(2024, 3, 12, 14, 22, 3).unwrap();
+        assert_eq!(
+            parse_systemd_timestamp("Tue 2024-03-12 14:22:03 UTC"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_systemd_timestamp("2024-03-12 14:22:03 GMT"),
+            Some(expected)
+        );
+    }
+
+    #[test]
+    fn test_parse_microsecond_timestamp() {
+        assert_eq!(
+            parse_systemd_timestamp("1710253323000000"),
+            Some(Utc.with_ymd_and_hms(2024, 3, 12, 14, 22, 3).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_unset_timestamp() {
+        assert_eq!(parse_systemd_timestamp(""), None);
+        assert_eq!(parse_systemd_timestamp("0"), None);
+        assert_eq!(parse_systemd_timestamp("n/a"), None);
+    }
+
+    #[test]
+    fn test_format_systemd_timestamp_fallback() {
+        assert_eq!(format_systemd_timestamp(" soon "), "soon");
+        assert_ne!(
+            format_systemd_timestamp("Tue 2024-03-12 14:22:03 UTC"),
+            "Tue 2024-03-12 14:22:03 UTC"
+        );
+    }
+
+    #[test]
+    fn test_format_match_count() {
+        assert_eq!(format_match_count(Some(2), 17), "(3 of 17)");
+        assert_eq!(format_match_count(None, 17), "(17 matches)");
+        assert_eq!(format_match_count(None, 1), "(1 match)");
+        assert_eq!(format_match_count(None, 0), "No matches");
+    }
+
+    #[test]
+    fn test_format_timespan() {
+        use std::time::Duration;
+
+        assert_eq!(format_timespan(Duration::from_micros(812)), "0ms");
+        assert_eq!(format_timespan(Duration::from_millis(350)), "350ms");
+        assert_eq!(format_timespan(Duration::from_millis(1234)), "1.234s");
+        assert_eq!(
+            format_timespan(Duration::from_millis(62_345)),
+            "1min 2.345s"
+        );
+    }
+
+    #[test]
+    fn test_format_resource_usage() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(45_200_000), "45.2 MB");
+        assert_eq!(format_bytes(3_500_000_000), "3.5 GB");
+        assert_eq!(format_cpu_time(1_300_000_000), "1.3s");
+        assert_eq!(format_cpu_time(125_000_000_000), "2min 5s");
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 12, 14, 0, 0).unwrap();
+
+        assert_eq!(format_relative_to(&now, &now), "just now");
+        assert_eq!(
+            format_relative_to(&(now - Duration::minutes(3)), &now),
+            "3 minutes ago"
+        );
+        assert_eq!(
+            format_relative_to(&(now + Duration::hours(2)), &now),
+            "in 2 hours"
+        );
+        assert_eq!(
+            format_relative_to(&(now - Duration::days(1)), &now),
+            "1 day ago"
+        );
+    }
+}
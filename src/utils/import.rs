@@ -0,0 +1,315 @@
+use anyhow::{anyhow, Result};
+use log::warn;
+use serde_yaml::{Mapping, Value};
+use std::path::{Path, PathBuf};
+
+use crate::remote_host::{AuthType, RemoteHost, DEFAULT_SSH_PORT};
+
+/// Reads hosts from an Ansible YAML inventory file
+pub fn parse_ansible_inventory(path: &Path) -> Result<Vec<RemoteHost>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_ansible_inventory_str(&content)
+}
+
+/// Reads hosts from the `all` group of an Ansible YAML inventory, including
+/// hosts declared in nested `children` groups
+pub fn parse_ansible_inventory_str(content: &str) -> Result<Vec<RemoteHost>> {
+    let inventory: Value = serde_yaml::from_str(content)?;
+    let all = inventory
+        .get("all")
+        .ok_or_else(|| anyhow!("Inventory has no 'all' group"))?;
+
+    let mut hosts = Vec::new();
+    collect_group_hosts(all, &mut hosts);
+    Ok(hosts)
+}
+
+fn collect_group_hosts(group: &Value, hosts: &mut Vec<RemoteHost>) {
+    if let Some(group_hosts) = group.get("hosts").and_then(Value::as_mapping) {
+        for (name, vars) in group_hosts {
+            let Some(name) = name.as_str() else {
+                continue;
+            };
+            if hosts.iter().any(|host| host.name == name) {
+                continue;
+            }
+            hosts.push(host_from_vars(name, vars.as_mapping()));
+        }
+    }
+
+    if let Some(children) = group.get("children").and_then(Value::as_mapping) {
+        for child in children.values() {
+            collect_group_hosts(child, hosts);
+        }
+    }
+}
+
+/// Writes `hosts` to `path` as an Ansible YAML inventory
+pub fn export_hosts_yaml<'a>(
+    hosts: impl IntoIterator<Item = &'a RemoteHost>,
+    path: &Path,
+) -> Result<()> {
+    std::fs::write(path, hosts_to_inventory_yaml(hosts)?)?;
+    Ok(())
+}
+
+/// Serialises `hosts` as an Ansible YAML inventory with every host in the
+/// `all` group, sorted by name. Passwords are never written.
+pub fn hosts_to_inventory_yaml<'a>(
+    hosts: impl IntoIterator<Item = &'a RemoteHost>,
+) -> Result<String> {
+    let mut hosts: Vec<&RemoteHost> = hosts.into_iter().collect();
+    hosts.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut inventory_hosts = Mapping::new();
+    for host in hosts {
+        let mut vars = Mapping::new();
+        vars.insert("ansible_host".into(), host.hostname.clone().into());
+        vars.insert("ansible_user".into(), host.username.clone().into());
+        vars.insert("ansible_port".into(), host.port.into());
+        if let Some(key_path) = host.key_path() {
+            vars.insert(
+                "ansible_ssh_private_key_file".into(),
+                key_path.to_string_lossy().into_owned().into(),
+            );
+        }
+        inventory_hosts.insert(host.name.clone().into(), vars.into());
+    }
+
+    let mut all = Mapping::new();
+    all.insert("hosts".into(), inventory_hosts.into());
+    let mut inventory = Mapping::new();
+    inventory.insert("all".into(), all.into());
+
+    Ok(serde_yaml::to_string(&inventory)?)
+}
+
+/// Reads the hosts defined in `~/.ssh/config`
+pub fn parse_user_ssh_config() -> Result<Vec<RemoteHost>> {
+    let path = dirs::home_dir()
+        .ok_or_else(|| anyhow!("Could not find home directory"))?
+        .join(".ssh")
+        .join("config");
+    Ok(parse_ssh_config_str(&std::fs::read_to_string(path)?))
+}
+
+/// Reads one host per alias of each `Host` stanza of an OpenSSH client
+/// config, taking its `HostName`, `User`, `Port` and `IdentityFile`.
+/// Wildcard patterns such as `Host *` and `Match` blocks are skipped, and
+/// like `ssh`, the first value given for an option wins.
+pub fn parse_ssh_config_str(content: &str) -> Vec<RemoteHost> {
+    let mut hosts: Vec<RemoteHost> = Vec::new();
+    // Hosts of the current stanza, as indices into `hosts`
+    let mut stanza: Vec<usize> = Vec::new();
+    // Options already set by the current stanza
+    let mut seen: Vec<String> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, value) = match line.split_once(|c: char| c.is_whitespace() || c == '=') {
+            Some((keyword, value)) => (
+                keyword.to_lowercase(),
+                value
+                    .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+                    .trim(),
+            ),
+            None => (line.to_lowercase(), ""),
+        };
+        let value = value.trim_matches('"');
+
+        match keyword.as_str() {
+            "host" => {
+                stanza.clear();
+                seen.clear();
+                for alias in value.split_whitespace() {
+                    if alias.contains(['*', '?', '!'])
+                        || hosts.iter().any(|host| host.name == alias)
+                    {
+                        continue;
+                    }
+                    stanza.push(hosts.len());
+                    hosts.push(RemoteHost::new(
+                        alias.to_string(),
+                        alias.to_string(),
+                        std::env::var("USER").unwrap_or_default(),
+                        AuthType::Agent,
+                    ));
+                }
+            }
+            "match" => {
+                stanza.clear();
+                seen.clear();
+            }
+            "hostname" | "user" | "port" | "identityfile" => {
+                if seen.contains(&keyword) {
+                    continue;
+                }
+                seen.push(keyword.clone());
+
+                for &index in &stanza {
+                    let host = &mut hosts[index];
+                    match keyword.as_str() {
+                        "hostname" => host.hostname = value.to_string(),
+                        "user" => host.username = value.to_string(),
+                        "port" => match value.parse() {
+                            Ok(port) => host.port = port,
+                            Err(_) => warn!("Ignoring invalid Port for {}", host.name),
+                        },
+                        _ => {
+                            host.auth_type = AuthType::Key {
+                                path: Some(PathBuf::from(value)),
+                            }
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    hosts
+}
+
+fn host_from_vars(name: &str, vars: Option<&Mapping>) -> RemoteHost {
+    let var = |key: &str| vars.and_then(|vars| vars.get(key));
+    let var_str = |key: &str| var(key).and_then(Value::as_str).map(str::to_string);
+
+    let hostname = var_str("ansible_host").unwrap_or_else(|| name.to_string());
+    let username = var_str("ansible_user")
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default();
+
+    let port = match var("ansible_port") {
+        Some(Value::Number(port)) => port.as_u64().and_then(|p| u16::try_from(p).ok()),
+        Some(Value::String(port)) => port.parse().ok(),
+        _ => Some(DEFAULT_SSH_PORT),
+    }
+    .unwrap_or_else(|| {
+        warn!("Ignoring invalid ansible_port for {}", name);
+        DEFAULT_SSH_PORT
+    });
+
+    let auth_type = match var_str("ansible_ssh_private_key_file") {
+        Some(key_file) => AuthType::Key {
+            path: Some(PathBuf::from(key_file)),
+        },
+        None => AuthType::Password,
+    };
+
+    RemoteHost::new(name.to_string(), hostname, username, auth_type).with_port(port)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INVENTORY: &str = r#"
+all:
+  hosts:
+    web1:
+      ansible_host: 192.168.1.10
+      ansible_user: deploy
+      ansible_port: 2222
+      ansible_ssh_private_key_file: ~/.ssh/web.pem
+    db1:
+      ansible_host: db.example.com
+      ansible_user: admin
+  children:
+    workers:
+      hosts:
+        worker1:
+          ansible_user: ops
+          ansible_port: "2200"
+"#;
+
+    #[test]
+    fn test_parse_ansible_inventory() {
+        let hosts = parse_ansible_inventory_str(INVENTORY).unwrap();
+        assert_eq!(hosts.len(), 3);
+
+        let web = hosts.iter().find(|h| h.name == "web1").unwrap();
+        assert_eq!(web.hostname, "192.168.1.10");
+        assert_eq!(web.username, "deploy");
+        assert_eq!(web.port, 2222);
+        assert_eq!(web.key_path(), Some(&PathBuf::from("~/.ssh/web.pem")));
+
+        let db = hosts.iter().find(|h| h.name == "db1").unwrap();
+        assert_eq!(db.port, DEFAULT_SSH_PORT);
+        assert!(db.is_password_auth());
+
+        let worker = hosts.iter().find(|h| h.name == "worker1").unwrap();
+        assert_eq!(worker.hostname, "worker1");
+        assert_eq!(worker.port, 2200);
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let hosts = parse_ansible_inventory_str(INVENTORY).unwrap();
+        let yaml = hosts_to_inventory_yaml(&hosts).unwrap();
+
+        assert!(yaml.starts_with("all:\n  hosts:\n    db1:\n"));
+        assert!(!yaml.contains("children"));
+
+        let mut reimported = parse_ansible_inventory_str(&yaml).unwrap();
+        reimported.sort_by(|a, b| a.name.cmp(&b.name));
+        let web = reimported.iter().find(|h| h.name == "web1").unwrap();
+        assert_eq!(web.hostname, "192.168.1.10");
+        assert_eq!(web.port, 2222);
+        assert_eq!(web.key_path(), Some(&PathBuf::from("~/.ssh/web.pem")));
+        assert!(reimported
+            .iter()
+            .find(|h| h.name == "db1")
+            .unwrap()
+            .is_password_auth());
+        assert_eq!(reimported.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_ssh_config() {
+        let config = r#"
+# Personal servers
+Host web1 web1-alias
+    HostName 192.168.1.10
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/web.pem
+    Port 22
+
+Host *.internal bastion
+  hostname=bastion.example.com
+  User "ops"
+
+Match host db*
+    User root
+
+Host *
+    User nobody
+    ServerAliveInterval 30
+"#;
+        let hosts = parse_ssh_config_str(config);
+        let names: Vec<&str> = hosts.iter().map(|host| host.name.as_str()).collect();
+        assert_eq!(names, ["web1", "web1-alias", "bastion"]);
+
+        let web = &hosts[0];
+        assert_eq!(web.hostname, "192.168.1.10");
+        assert_eq!(web.username, "deploy");
+        assert_eq!(web.port, 2222);
+        assert_eq!(web.key_path(), Some(&PathBuf::from("~/.ssh/web.pem")));
+        assert_eq!(hosts[1].hostname, "192.168.1.10");
+
+        let bastion = &hosts[2];
+        assert_eq!(bastion.hostname, "bastion.example.com");
+        assert_eq!(bastion.username, "ops");
+        assert_eq!(bastion.port, DEFAULT_SSH_PORT);
+        assert_eq!(bastion.auth_type, AuthType::Agent);
+    }
+
+    #[test]
+    fn test_inventory_without_all_group() {
+        assert!(parse_ansible_inventory_str("webservers:\n  hosts: {}\n").is_err());
+    }
+}
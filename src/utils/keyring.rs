@@ -0,0 +1,76 @@
+use anyhow::Result;
+use secret_service::blocking::SecretService;
+use secret_service::EncryptionType;
+use std::collections::HashMap;
+
+/// Attribute marking the secrets stored by this app, so they can be found
+/// and cleared together
+const APPLICATION_ATTRIBUTE: (&str, &str) = ("application", "systemd-pilot");
+
+/// Key a host's password is stored under, `host:port:username` where
+/// `host` is the address from `KnownHosts::address`
+fn account(host: &str, username: &str) -> String {
+    format!("{}:{}", host, username)
+}
+
+fn attributes(account: &str) -> HashMap<&str, &str> {
+    HashMap::from([APPLICATION_ATTRIBUTE, ("account", account)])
+}
+
+/// Saves an SSH password in the desktop keyring (GNOME Keyring, KWallet
+/// or another Secret Service provider), replacing any saved before
+pub fn store_password(host: &str, username: &str, password: &str) -> Result<()> {
+    let service = SecretService::connect(EncryptionType::Dh)?;
+    let collection = service.get_default_collection()?;
+    collection.ensure_unlocked()?;
+
+    let account = account(host, username);
+    collection.create_item(
+        &format!("systemd Pilot SSH password for {}", account),
+        attributes(&account),
+        password.as_bytes(),
+        true,
+        "text/plain",
+    )?;
+    Ok(())
+}
+
+/// The saved SSH password for `username` on `host`, if there is one. The
+/// keyring may ask the user to unlock it first.
+pub fn get_password(host: &str, username: &str) -> Result<Option<String>> {
+    let service = SecretService::connect(EncryptionType::Dh)?;
+    let account = account(host, username);
+    let items = service.search_items(attributes(&account))?;
+    let Some(item) = items.unlocked.first().or(items.locked.first()) else {
+        return Ok(None);
+    };
+
+    item.ensure_unlocked()?;
+    Ok(Some(String::from_utf8(item.get_secret()?)?))
+}
+
+/// Removes every password saved by the app, returning how many there were
+pub fn clear_passwords() -> Result<usize> {
+    let service = SecretService::connect(EncryptionType::Dh)?;
+    let items = service.search_items(HashMap::from([APPLICATION_ATTRIBUTE]))?;
+
+    let mut cleared = 0;
+    for item in items.unlocked.iter().chain(&items.locked) {
+        item.delete()?;
+        cleared += 1;
+    }
+    Ok(cleared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyed_by_host_port_and_user() {
+        let account = account("web01:2222", "deploy");
+        let attributes = attributes(&account);
+        assert_eq!(attributes["account"], "web01:2222:deploy");
+        assert_eq!(attributes["application"], "systemd-pilot");
+    }
+}
@@ -0,0 +1,105 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::utils::config::{load_json, save_json};
+
+/// Name of the file the accepted keys are saved in
+const FILE_NAME: &str = "known_hosts.json";
+
+/// Host key fingerprints the user has accepted, stored in
+/// `~/.config/systemd-pilot/known_hosts.json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KnownHosts {
+    /// SHA-256 hex fingerprint of each host's key, keyed by `host:port`
+    pub known_hosts: HashMap<String, String>,
+}
+
+/// How a server's host key compares with the stored one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostKeyStatus {
+    Trusted,
+    /// Nothing is stored for the host yet
+    Unknown,
+    /// The key differs from the one accepted before, which was this
+    Changed(String),
+}
+
+impl KnownHosts {
+    /// Loads the accepted keys, or none when nothing was saved yet
+    pub fn load() -> Result<Self> {
+        Ok(load_json(FILE_NAME)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(FILE_NAME, self)
+    }
+
+    /// Key under which a host's fingerprint is stored
+    pub fn address(hostname: &str, port: u16) -> String {
+        format!("{}:{}", hostname, port)
+    }
+
+    pub fn fingerprint(&self, hostname: &str, port: u16) -> Option<&str> {
+        self.known_hosts
+            .get(&Self::address(hostname, port))
+            .map(String::as_str)
+    }
+
+    pub fn check(&self, hostname: &str, port: u16, fingerprint: &str) -> HostKeyStatus {
+        match self.fingerprint(hostname, port) {
+            None => HostKeyStatus::Unknown,
+            Some(stored) if stored.eq_ignore_ascii_case(fingerprint) => HostKeyStatus::Trusted,
+            Some(stored) => HostKeyStatus::Changed(stored.to_string()),
+        }
+    }
+
+    /// Accepts `fingerprint` for the host, replacing any earlier key
+    pub fn trust(&mut self, hostname: &str, port: u16, fingerprint: &str) {
+        self.known_hosts
+            .insert(Self::address(hostname, port), fingerprint.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_host_key() {
+        let mut known_hosts = KnownHosts::default();
+        assert_eq!(
+            known_hosts.check("web01", 22, "3f:a2"),
+            HostKeyStatus::Unknown
+        );
+
+        known_hosts.trust("web01", 22, "3f:a2");
+        assert_eq!(
+            known_hosts.check("web01", 22, "3F:A2"),
+            HostKeyStatus::Trusted
+        );
+        assert_eq!(
+            known_hosts.check("web01", 22, "00:11"),
+            HostKeyStatus::Changed("3f:a2".to_string())
+        );
+        // Each port is a separate entry
+        assert_eq!(
+            known_hosts.check("web01", 2222, "3f:a2"),
+            HostKeyStatus::Unknown
+        );
+    }
+
+    #[test]
+    fn test_stored_by_host_and_port() {
+        let mut known_hosts = KnownHosts::default();
+        known_hosts.trust("web01", 2222, "3f:a2");
+
+        let json = serde_json::to_string(&known_hosts).unwrap();
+        assert!(json.contains("\"web01:2222\":\"3f:a2\""));
+        assert_eq!(
+            serde_json::from_str::<KnownHosts>(&json).unwrap(),
+            known_hosts
+        );
+    }
+}
@@ -1,3 +1,17 @@
+pub mod audit;
+pub mod config;
+pub mod dbus_notifier;
+pub mod diagnostics;
+pub mod diff;
+pub mod file_watcher;
+pub mod format;
+pub mod import;
+pub mod keyring;
+pub mod known_hosts;
+pub mod preferences;
+pub mod ssh;
+pub mod terminal;
 pub mod theme;
+pub mod window_state;
 
 pub use theme::*;
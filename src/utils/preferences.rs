@@ -0,0 +1,130 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::utils::config::{load_json, save_json, Config, RefreshConfig};
+
+/// Name of the file the preferences are saved in
+const FILE_NAME: &str = "preferences.json";
+
+/// Settings edited in the Preferences dialog, stored in
+/// `~/.config/systemd-pilot/preferences.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PreferencesConfig {
+    /// Refresh the service lists periodically
+    pub auto_refresh: bool,
+    pub refresh_interval_secs: u32,
+    /// Whether the unit lists start with inactive units shown
+    pub show_inactive_default: bool,
+    /// Number of journal lines shown when a service's logs are opened
+    pub log_lines_default: u32,
+    /// Forces dark or light mode; the system theme is followed when unset
+    pub dark_mode_override: Option<bool>,
+}
+
+impl PreferencesConfig {
+    pub const MIN_LOG_LINES: u32 = 50;
+    pub const MAX_LOG_LINES: u32 = 10_000;
+    pub const DEFAULT_LOG_LINES: u32 = 500;
+
+    /// Loads the saved preferences. Before any were saved, the settings
+    /// older versions kept in `config` are taken from there and saved, as
+    /// config.json no longer keeps them.
+    pub fn load(config: &Config) -> Result<Self> {
+        if let Some(preferences) = load_json(FILE_NAME)? {
+            return Ok(preferences);
+        }
+
+        let preferences = Self::from_config(config);
+        preferences.save()?;
+        Ok(preferences)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(FILE_NAME, self)
+    }
+
+    fn from_config(config: &Config) -> Self {
+        Self {
+            auto_refresh: config.refresh.enabled,
+            refresh_interval_secs: config.refresh.interval_secs,
+            dark_mode_override: config.dark_mode,
+            ..Default::default()
+        }
+    }
+
+    pub fn refresh(&self) -> RefreshConfig {
+        RefreshConfig {
+            enabled: self.auto_refresh,
+            interval_secs: self.refresh_interval_secs,
+        }
+    }
+
+    pub fn set_refresh(&mut self, refresh: RefreshConfig) {
+        self.auto_refresh = refresh.enabled;
+        self.refresh_interval_secs = refresh.interval_secs;
+    }
+
+    /// Gives `config` the settings it reads from the preferences at startup,
+    /// before the environment overrides are merged in
+    pub fn apply_to(&self, config: &mut Config) {
+        config.refresh = self.refresh();
+        config.dark_mode = self.dark_mode_override;
+    }
+
+    /// The log line count within the supported range
+    pub fn log_lines(&self) -> u32 {
+        self.log_lines_default
+            .clamp(Self::MIN_LOG_LINES, Self::MAX_LOG_LINES)
+    }
+}
+
+impl Default for PreferencesConfig {
+    fn default() -> Self {
+        let refresh = RefreshConfig::default();
+        Self {
+            auto_refresh: refresh.enabled,
+            refresh_interval_secs: refresh.interval_secs,
+            show_inactive_default: false,
+            log_lines_default: Self::DEFAULT_LOG_LINES,
+            dark_mode_override: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_settings_round_trip() {
+        let mut config = Config::default();
+        config.refresh = RefreshConfig::from_interval(60);
+        config.dark_mode = Some(true);
+        let preferences = PreferencesConfig::from_config(&config);
+        assert!(preferences.auto_refresh);
+        assert_eq!(preferences.refresh_interval_secs, 60);
+        assert_eq!(preferences.dark_mode_override, Some(true));
+        assert_eq!(
+            preferences.log_lines(),
+            PreferencesConfig::DEFAULT_LOG_LINES
+        );
+
+        let preferences = PreferencesConfig::default();
+        preferences.apply_to(&mut config);
+        assert_eq!(config.refresh, RefreshConfig::default());
+        assert_eq!(config.dark_mode, None);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let preferences: PreferencesConfig =
+            serde_json::from_str(r#"{"log_lines_default": 20000}"#).unwrap();
+        assert_eq!(preferences.log_lines(), PreferencesConfig::MAX_LOG_LINES);
+        assert_eq!(
+            preferences.refresh_interval_secs,
+            RefreshConfig::DEFAULT_INTERVAL_SECS
+        );
+        assert!(!preferences.show_inactive_default);
+    }
+}
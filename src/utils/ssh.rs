@@ -0,0 +1,496 @@
+use anyhow::{anyhow, Result};
+use log::{debug, info, warn};
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+use crate::remote_host::{AuthType, RemoteHost};
+
+/// Returned by `open_session` when the server's host key is not the one the
+/// caller trusts, before any credentials are sent
+#[derive(Debug, thiserror::Error)]
+#[error("The host key of {host} is not trusted ({fingerprint})")]
+pub struct UntrustedHostKey {
+    pub host: String,
+    /// SHA-256 hash of the key as hex, as stored in the known hosts
+    pub fingerprint_hex: String,
+    /// The same hash in the `SHA256:…` form printed by OpenSSH
+    pub fingerprint: String,
+}
+
+/// Opens and authenticates an SSH session to `host`. This blocks, so run it
+/// on a blocking thread. `secret` is the password, or the key passphrase for
+/// key authentication. The server's key must match `trusted_host_key`, a hex
+/// fingerprint, or `UntrustedHostKey` is returned instead.
+pub fn open_session(
+    host: &RemoteHost,
+    secret: Option<&str>,
+    trusted_host_key: Option<&str>,
+) -> Result<ssh2::Session> {
+    let tcp = TcpStream::connect((host.hostname.as_str(), host.port))?;
+
+    let mut session = ssh2::Session::new()?;
+    session.set_tcp_stream(tcp);
+    session.handshake()?;
+
+    let (sha256, hex) = host_key_fingerprints(&session)
+        .ok_or_else(|| anyhow!("{} sent no host key", host.connection_string()))?;
+    debug!(
+        "Host key for {} is {} ({})",
+        host.connection_string(),
+        sha256,
+        hex
+    );
+    if trusted_host_key.is_none_or(|trusted| !trusted.eq_ignore_ascii_case(&hex)) {
+        return Err(UntrustedHostKey {
+            host: host.connection_string(),
+            fingerprint_hex: hex,
+            fingerprint: sha256,
+        }
+        .into());
+    }
+
+    match &host.auth_type {
+        AuthType::Password => {
+            session.userauth_password(&host.username, secret.unwrap_or_default())?
+        }
+        AuthType::Key { .. } => {
+            let key_path = host
+                .resolved_key_path()
+                .or_else(|| dirs::home_dir().map(|home| home.join(".ssh").join("id_rsa")))
+                .ok_or_else(|| anyhow!("Could not locate an SSH key"))?;
+            session.userauth_pubkey_file(&host.username, None, &key_path, secret)?
+        }
+        AuthType::Agent => authenticate_with_agent(&session, &host.username)?,
+    }
+
+    if !session.authenticated() {
+        return Err(anyhow!(
+            "Authentication failed for {}",
+            host.connection_string()
+        ));
+    }
+
+    info!("Connected to {}", host.connection_string());
+    Ok(session)
+}
+
+/// Whether an SSH agent is advertised through `SSH_AUTH_SOCK`
+pub fn agent_available() -> bool {
+    std::env::var_os("SSH_AUTH_SOCK").is_some()
+}
+
+/// Offers each identity of the SSH agent at `SSH_AUTH_SOCK` until the
+/// server accepts one
+fn authenticate_with_agent(session: &ssh2::Session, username: &str) -> Result<()> {
+    if !agent_available() {
+        return Err(anyhow!(
+            "No SSH agent is running (SSH_AUTH_SOCK is not set)"
+        ));
+    }
+
+    let mut agent = session.agent()?;
+    agent
+        .connect()
+        .map_err(|e| anyhow!("Could not connect to the SSH agent: {}", e))?;
+    agent.list_identities()?;
+
+    let identities = agent.identities()?;
+    let accepted = identities
+        .iter()
+        .any(|identity| match agent.userauth(username, identity) {
+            Ok(()) => true,
+            Err(e) => {
+                debug!("Agent key {} was rejected: {}", identity.comment(), e);
+                false
+            }
+        });
+    if let Err(e) = agent.disconnect() {
+        debug!("Failed to disconnect from the SSH agent: {}", e);
+    }
+
+    match (identities.is_empty(), accepted) {
+        (_, true) => Ok(()),
+        (true, false) => Err(anyhow!("The SSH agent has no keys loaded")),
+        (false, false) => Err(anyhow!(
+            "None of the SSH agent's {} keys were accepted",
+            identities.len()
+        )),
+    }
+}
+
+/// The server's host key fingerprint in both the SHA256 and hex forms
+/// printed by OpenSSH
+pub fn host_key_fingerprints(session: &ssh2::Session) -> Option<(String, String)> {
+    let (_, key_type) = session.host_key()?;
+    let hash = session.host_key_hash(ssh2::HashType::Sha256)?;
+
+    let key_type = match key_type {
+        ssh2::HostKeyType::Rsa => "RSA",
+        ssh2::HostKeyType::Dss => "DSA",
+        ssh2::HostKeyType::Ecdsa256 | ssh2::HostKeyType::Ecdsa384 | ssh2::HostKeyType::Ecdsa521 => {
+            "ECDSA"
+        }
+        ssh2::HostKeyType::Ed25519 => "ED25519",
+        ssh2::HostKeyType::Unknown => "UNKNOWN",
+    };
+
+    Some((
+        format_fingerprint(key_type, hash),
+        format_fingerprint_hex(hash),
+    ))
+}
+
+/// Formats a SHA256 host key hash like `ssh-keygen -l`, e.g.
+/// `SHA256:uNiVz…Zd0 (ED25519)`: unpadded base64 followed by the key type
+pub fn format_fingerprint(key_type: &str, hash_bytes: &[u8]) -> String {
+    format!(
+        "SHA256:{} ({})",
+        encode_base64_unpadded(hash_bytes),
+        key_type
+    )
+}
+
+/// Formats a host key hash as colon-separated hex bytes, e.g. `3f:a2:…`
+pub fn format_fingerprint_hex(hash_bytes: &[u8]) -> String {
+    hash_bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+fn encode_base64_unpadded(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | (*byte as u32) << (16 - 8 * i)
+        });
+
+        // A chunk of n bytes fills n + 1 base64 digits
+        for i in 0..=chunk.len() {
+            let index = (group >> (18 - 6 * i)) & 0x3f;
+            encoded.push(ALPHABET[index as usize] as char);
+        }
+    }
+    encoded
+}
+
+/// An established connection to a remote host
+#[derive(Clone)]
+pub enum HostConnection {
+    /// A session opened and authenticated by the app. Commands, file reads
+    /// and port forwards share it, so each takes the lock while using it.
+    Session(Arc<Mutex<ssh2::Session>>),
+    /// Commands are run through the system `ssh` client over an existing
+    /// control socket
+    ControlMaster(ControlMaster),
+}
+
+impl HostConnection {
+    /// The ssh2 session, for operations that need one such as port forwarding
+    pub fn session(&self) -> Option<&Arc<Mutex<ssh2::Session>>> {
+        match self {
+            HostConnection::Session(session) => Some(session),
+            HostConnection::ControlMaster(_) => None,
+        }
+    }
+}
+
+/// Connects to `host`, reusing its OpenSSH control socket when one is
+/// configured and alive, and opening a new session otherwise. This blocks,
+/// so run it on a blocking thread. The system client checks the host key of
+/// a control socket itself; a new session needs `trusted_host_key`.
+pub fn connect(
+    host: &RemoteHost,
+    secret: Option<&str>,
+    trusted_host_key: Option<&str>,
+) -> Result<HostConnection> {
+    if let Some(control_master) = ControlMaster::for_host(host) {
+        match control_master.check() {
+            Ok(()) => {
+                info!(
+                    "Reusing control socket {} for {}",
+                    control_master.control_path.display(),
+                    host.connection_string()
+                );
+                return Ok(HostConnection::ControlMaster(control_master));
+            }
+            Err(e) => warn!("Control socket for {} is unusable: {}", host.name, e),
+        }
+    }
+
+    open_session(host, secret, trusted_host_key)
+        .map(|session| HostConnection::Session(Arc::new(Mutex::new(session))))
+}
+
+/// A control socket created by the system SSH client (`ControlMaster`),
+/// which lets commands skip the SSH handshake
+#[derive(Debug, Clone)]
+pub struct ControlMaster {
+    pub control_path: PathBuf,
+    destination: String,
+    port: u16,
+}
+
+impl ControlMaster {
+    /// The host's control socket, if it has one configured and it exists
+    pub fn for_host(host: &RemoteHost) -> Option<Self> {
+        let control_path = host.resolved_control_path()?;
+        if !control_path.exists() {
+            debug!("No control socket at {}", control_path.display());
+            return None;
+        }
+
+        Some(Self {
+            control_path,
+            destination: host.ssh_destination(),
+            port: host.port,
+        })
+    }
+
+    /// Arguments for `ssh` that route through the control socket, keeping the
+    /// master connection alive after the command finishes
+    fn ssh_args(&self) -> Vec<String> {
+        vec![
+            "-o".to_string(),
+            format!("ControlPath={}", self.control_path.display()),
+            "-o".to_string(),
+            "ControlMaster=auto".to_string(),
+            "-o".to_string(),
+            "ControlPersist=yes".to_string(),
+            "-o".to_string(),
+            "BatchMode=yes".to_string(),
+            "-p".to_string(),
+            self.port.to_string(),
+            self.destination.clone(),
+        ]
+    }
+
+    /// Asks the master process whether it is still running
+    pub fn check(&self) -> Result<()> {
+        let output = std::process::Command::new("ssh")
+            .args(["-O", "check"])
+            .args(self.ssh_args())
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("ssh -O check failed: {}", stderr.trim()));
+        }
+        Ok(())
+    }
+
+    /// Runs `command` on the remote host and returns its stdout
+    pub async fn execute(&self, command: &str) -> Result<String> {
+        let output = tokio::process::Command::new("ssh")
+            .args(self.ssh_args())
+            .arg(command)
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(anyhow!("Remote command failed: {}", stderr.trim()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Forwards a local TCP port to a port reachable from the remote host,
+/// like `ssh -L local_port:remote_host:remote_port`
+pub struct SshTunnel {
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+    pub session: Arc<Mutex<ssh2::Session>>,
+}
+
+/// A running tunnel; forwarding stops when the handle is dropped
+pub struct TunnelHandle {
+    pub local_port: u16,
+    pub remote_host: String,
+    pub remote_port: u16,
+    task: JoinHandle<()>,
+}
+
+impl Drop for TunnelHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl SshTunnel {
+    pub fn new(
+        local_port: u16,
+        remote_host: String,
+        remote_port: u16,
+        session: Arc<Mutex<ssh2::Session>>,
+    ) -> Self {
+        Self {
+            local_port,
+            remote_host,
+            remote_port,
+            session,
+        }
+    }
+
+    /// Binds the local port and starts forwarding accepted connections.
+    /// Must be called from within the Tokio runtime.
+    pub async fn establish(&self) -> Result<TunnelHandle> {
+        let listener = TcpListener::bind(("127.0.0.1", self.local_port)).await?;
+        let local_port = listener.local_addr()?.port();
+
+        let session = self.session.clone();
+        let remote_host = self.remote_host.clone();
+        let remote_port = self.remote_port;
+
+        let task = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        warn!("Tunnel on port {} stopped accepting: {}", local_port, e);
+                        break;
+                    }
+                };
+                debug!("Forwarding connection from {}", peer);
+
+                let stream = match stream.into_std() {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        warn!("Failed to prepare forwarded connection: {}", e);
+                        continue;
+                    }
+                };
+
+                let session = session.clone();
+                let remote_host = remote_host.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(e) = forward_connection(&session, stream, &remote_host, remote_port)
+                    {
+                        warn!("Forwarded connection closed with error: {}", e);
+                    }
+                });
+            }
+        });
+
+        info!(
+            "Forwarding 127.0.0.1:{} to {}:{}",
+            local_port, self.remote_host, self.remote_port
+        );
+
+        Ok(TunnelHandle {
+            local_port,
+            remote_host: self.remote_host.clone(),
+            remote_port: self.remote_port,
+            task,
+        })
+    }
+}
+
+/// Pumps data between a local socket and a direct-tcpip channel until either side closes.
+///
+/// The session is shared with other operations, so it is only switched to
+/// non-blocking mode while the lock is held for a channel read.
+fn forward_connection(
+    session: &Mutex<ssh2::Session>,
+    mut stream: TcpStream,
+    remote_host: &str,
+    remote_port: u16,
+) -> Result<()> {
+    let mut channel = session
+        .lock()
+        .map_err(|_| anyhow!("SSH session lock poisoned"))?
+        .channel_direct_tcpip(remote_host, remote_port, None)?;
+
+    stream.set_nonblocking(true)?;
+    let mut buffer = [0u8; 16 * 1024];
+
+    loop {
+        let mut idle = true;
+
+        // Local -> remote
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                let _guard = session
+                    .lock()
+                    .map_err(|_| anyhow!("SSH session lock poisoned"))?;
+                channel.write_all(&buffer[..n])?;
+                idle = false;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        // Remote -> local
+        let read = {
+            let guard = session
+                .lock()
+                .map_err(|_| anyhow!("SSH session lock poisoned"))?;
+            guard.set_blocking(false);
+            let read = channel.read(&mut buffer);
+            guard.set_blocking(true);
+            read
+        };
+
+        match read {
+            Ok(0) => {
+                if channel.eof() {
+                    break;
+                }
+            }
+            Ok(n) => {
+                stream.set_nonblocking(false)?;
+                stream.write_all(&buffer[..n])?;
+                stream.set_nonblocking(true)?;
+                idle = false;
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        if idle {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    let _guard = session
+        .lock()
+        .map_err(|_| anyhow!("SSH session lock poisoned"))?;
+    channel.close()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_fingerprint() {
+        let hash: Vec<u8> = (0..32).collect();
+        assert_eq!(
+            format_fingerprint("ED25519", &hash),
+            "SHA256:AAECAwQFBgcICQoLDA0ODxAREhMUFRYXGBkaGxwdHh8 (ED25519)"
+        );
+        assert_eq!(format_fingerprint("RSA", &[0xff, 0xfe]), "SHA256://4 (RSA)");
+    }
+
+    #[test]
+    fn test_format_fingerprint_hex() {
+        assert_eq!(
+            format_fingerprint_hex(&[0x00, 0x01, 0xab, 0xff]),
+            "00:01:ab:ff"
+        );
+        assert_eq!(format_fingerprint_hex(&[]), "");
+    }
+}
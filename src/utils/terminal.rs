@@ -0,0 +1,109 @@
+use anyhow::{anyhow, Result};
+use gio::prelude::*;
+use log::{debug, info};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::service_manager::shell_quote;
+
+/// Terminal used when nothing better is configured
+const FALLBACK_TERMINAL: &str = "/usr/bin/xterm";
+
+/// Opens the user's terminal following the service's journal. Terminals
+/// split the `-e` command like a shell, so the name is quoted.
+pub fn open_terminal_for_service(service: &str) -> Result<()> {
+    let terminal = detect_terminal().ok_or_else(|| anyhow!("No terminal emulator found"))?;
+    info!("Opening {} for {}", terminal.display(), service);
+
+    Command::new(&terminal)
+        .arg("-e")
+        .arg(format!("journalctl -f -u {}", shell_quote(service)))
+        .spawn()?;
+    Ok(())
+}
+
+/// Finds a terminal from, in order: `$TERMINAL`, `$TERM`, the GNOME default
+/// terminal setting, xterm, and the handler for `terminal://` URIs
+fn detect_terminal() -> Option<PathBuf> {
+    let candidates = [
+        std::env::var("TERMINAL").ok(),
+        std::env::var("TERM").ok(),
+        gsettings_terminal(),
+        Some(FALLBACK_TERMINAL.to_string()),
+    ];
+
+    candidates
+        .into_iter()
+        .flatten()
+        .find_map(|candidate| {
+            let found = find_program(&candidate);
+            if found.is_none() {
+                debug!("Terminal candidate {} not found", candidate);
+            }
+            found
+        })
+        .or_else(|| gio::AppInfo::default_for_uri_scheme("terminal").map(|app| app.executable()))
+}
+
+fn gsettings_terminal() -> Option<String> {
+    let output = Command::new("gsettings")
+        .args([
+            "get",
+            "org.gnome.desktop.default-applications.terminal",
+            "exec",
+        ])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+    parse_gsettings_string(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Unquotes a string printed by `gsettings get`, e.g. `'gnome-terminal'`
+fn parse_gsettings_string(output: &str) -> Option<String> {
+    let value = output.trim().trim_matches('\'');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Resolves `program` to an executable path, searching `$PATH` for bare names
+fn find_program(program: &str) -> Option<PathBuf> {
+    let program = program.trim();
+    if program.is_empty() {
+        return None;
+    }
+
+    if program.contains('/') {
+        let path = Path::new(program);
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    std::env::var_os("PATH").and_then(|paths| {
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(program))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_gsettings_string() {
+        assert_eq!(
+            parse_gsettings_string("'gnome-terminal'\n"),
+            Some("gnome-terminal".to_string())
+        );
+        assert_eq!(parse_gsettings_string("''\n"), None);
+    }
+
+    #[test]
+    fn test_find_program() {
+        assert_eq!(find_program("/bin/sh"), Some(PathBuf::from("/bin/sh")));
+        assert!(find_program("sh").is_some());
+        assert_eq!(find_program("systemd-pilot-no-such-terminal"), None);
+        assert_eq!(find_program(""), None);
+    }
+}
@@ -6,6 +6,79 @@ use log::{debug, error, info, warn};
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use crate::service_manager::ServiceStatus;
+use crate::ui::styles::get_service_row_css_class;
+
+/// Minimum contrast ratio for normal text under WCAG 2.1 level AA
+const WCAG_AA_CONTRAST: f64 = 4.5;
+
+/// Base view backgrounds the status colours are drawn on
+const LIGHT_BACKGROUND: &str = "#ffffff";
+const DARK_BACKGROUND: &str = "#2d2d2d";
+
+/// Preferred status colours for light and dark backgrounds, before contrast
+/// adjustment. A status colour also covers its `-enabled` and `-disabled` rows
+/// unless one of those has its own entry.
+const LIGHT_STATUS_COLORS: [(&str, &str); 10] = [
+    ("service-active", "#27ae60"),
+    ("service-active-disabled", "#d68910"),
+    ("service-inactive", "#7f8c8d"),
+    ("service-failed", "#e74c3c"),
+    ("service-condition-failed", "#8e44ad"),
+    ("service-activating", "#2980b9"),
+    ("service-deactivating", "#2980b9"),
+    ("service-reloading", "#2980b9"),
+    ("service-masked", "#7f8c8d"),
+    ("service-unknown", "#f39c12"),
+];
+const DARK_STATUS_COLORS: [(&str, &str); 10] = [
+    ("service-active", "#4ade80"),
+    ("service-active-disabled", "#fb923c"),
+    ("service-inactive", "#9ca3af"),
+    ("service-failed", "#f87171"),
+    ("service-condition-failed", "#c084fc"),
+    ("service-activating", "#60a5fa"),
+    ("service-deactivating", "#60a5fa"),
+    ("service-reloading", "#60a5fa"),
+    ("service-masked", "#9ca3af"),
+    ("service-unknown", "#fbbf24"),
+];
+
+/// How a service row stands out besides its colour
+#[derive(Clone, Copy)]
+enum RowEmphasis {
+    Italic,
+    Dimmed,
+    Struck,
+}
+
+impl RowEmphasis {
+    fn css(self) -> &'static str {
+        match self {
+            RowEmphasis::Italic => "font-style: italic;",
+            RowEmphasis::Dimmed => "opacity: 0.7;",
+            RowEmphasis::Struck => "text-decoration: line-through;",
+        }
+    }
+
+    fn span_attribute(self) -> &'static str {
+        match self {
+            RowEmphasis::Italic => "style=\"italic\"",
+            RowEmphasis::Dimmed => "alpha=\"70%\"",
+            RowEmphasis::Struck => "strikethrough=\"true\"",
+        }
+    }
+}
+
+/// Emphasis by status or row class, looked up like the status colours
+const SERVICE_ROW_EMPHASIS: [(&str, RowEmphasis); 5] = [
+    ("service-active-disabled", RowEmphasis::Italic),
+    ("service-failed-disabled", RowEmphasis::Italic),
+    ("service-inactive-disabled", RowEmphasis::Dimmed),
+    ("service-unknown-disabled", RowEmphasis::Dimmed),
+    ("service-masked", RowEmphasis::Struck),
+];
+
 pub struct ThemeManager {
     is_dark_mode: RefCell<bool>,
     css_provider: CssProvider,
@@ -81,6 +154,39 @@ impl ThemeManager {
         debug!("Applied {} theme", if is_dark { "dark" } else { "light" });
     }
 
+    /// Status and service row rules, with colours adjusted to meet WCAG AA
+    /// contrast against `background_hex`
+    pub fn generate_accessible_css(&self, background_hex: &str) -> String {
+        let colors = accessible_status_colors(background_hex);
+        service_row_classes()
+            .filter_map(|class| {
+                let declarations: Vec<String> = row_color(&colors, class)
+                    .map(|color| format!("color: {};", color))
+                    .into_iter()
+                    .chain(row_emphasis(class).map(|emphasis| emphasis.css().to_string()))
+                    .collect();
+                (!declarations.is_empty())
+                    .then(|| format!(".{} {{\n    {}\n}}\n", class, declarations.join("\n    ")))
+            })
+            .collect()
+    }
+
+    /// Pango `<span>` attributes that give a tree view cell, which CSS can't
+    /// reach, the current theme's rule for the service row `class`
+    pub fn service_row_span_attributes(&self, class: &str) -> String {
+        let colors = accessible_status_colors(if self.is_dark_mode() {
+            DARK_BACKGROUND
+        } else {
+            LIGHT_BACKGROUND
+        });
+        row_color(&colors, class)
+            .map(|color| format!("foreground=\"{}\"", color))
+            .into_iter()
+            .chain(row_emphasis(class).map(|emphasis| emphasis.span_attribute().to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
     fn get_custom_css(&self, is_dark: bool) -> String {
         let base_css = r#"
             /* Base styling for systemd Pilot */
@@ -136,23 +242,38 @@ impl ThemeManager {
                 color: @theme_selected_fg_color;
             }
 
-            /* Service status colors */
+            /* Service status emphasis; colours are generated per theme */
             .service-active {
-                color: #27ae60;
                 font-weight: bold;
             }
 
-            .service-inactive {
-                color: #7f8c8d;
-            }
-
             .service-failed {
-                color: #e74c3c;
                 font-weight: bold;
             }
 
-            .service-unknown {
-                color: #f39c12;
+            /* Services starting up; row colours and emphasis are generated */
+            .service-activating,
+            .service-activating-enabled,
+            .service-activating-disabled {
+                animation: pulse 2s infinite;
+            }
+
+            @keyframes pulse {
+                0% { opacity: 1; }
+                50% { opacity: 0.5; }
+                100% { opacity: 1; }
+            }
+
+            /* Resource limits below the typical default */
+            .limit-low {
+                background: alpha(#f1c40f, 0.35);
+                border-radius: 3px;
+                padding: 0 4px;
+            }
+
+            /* Environment generators */
+            .generator-symlink {
+                font-style: italic;
             }
 
             /* Button styling */
@@ -247,6 +368,32 @@ impl ThemeManager {
                 font-family: monospace;
                 font-size: 0.9em;
             }
+
+            .log-level-legend {
+                font-size: 0.85em;
+                padding: 4px 0;
+            }
+
+            /* In-app notifications */
+            .notification {
+                margin: 12px;
+                padding: 6px 6px 6px 16px;
+                border-radius: 6px;
+                color: white;
+            }
+
+            .notification-success {
+                background: #27ae60;
+            }
+
+            .notification-error {
+                background: #e74c3c;
+            }
+
+            .notification-warning {
+                background: #f1c40f;
+                color: #212529;
+            }
         "#;
 
         let theme_specific_css = if is_dark {
@@ -259,23 +406,6 @@ impl ThemeManager {
                     color: #d4d4d4;
                 }
 
-                /* Darker service status colors for better contrast */
-                .service-active {
-                    color: #4ade80;
-                }
-
-                .service-inactive {
-                    color: #9ca3af;
-                }
-
-                .service-failed {
-                    color: #f87171;
-                }
-
-                .service-unknown {
-                    color: #fbbf24;
-                }
-
                 /* Dark scrollbars */
                 scrollbar {
                     background: #2d2d2d;
@@ -316,10 +446,187 @@ impl ThemeManager {
             "#
         };
 
-        format!("{}\n{}", base_css, theme_specific_css)
+        let status_css = self.generate_accessible_css(if is_dark {
+            DARK_BACKGROUND
+        } else {
+            LIGHT_BACKGROUND
+        });
+
+        format!("{}\n{}\n{}", base_css, theme_specific_css, status_css)
     }
 }
 
+/// Every status class and the row classes `get_service_row_css_class` builds
+/// from it, each status before its rows so the more specific rules win
+fn service_row_classes() -> impl Iterator<Item = &'static str> {
+    ServiceStatus::ALL.iter().flat_map(|status| {
+        let enabled = get_service_row_css_class(status, true);
+        [
+            enabled.trim_end_matches("-enabled"),
+            enabled,
+            get_service_row_css_class(status, false),
+        ]
+    })
+}
+
+/// `class`, then the status class of a service row class
+fn class_and_status(class: &str) -> impl Iterator<Item = &str> {
+    let status = class
+        .strip_suffix("-enabled")
+        .or_else(|| class.strip_suffix("-disabled"));
+    std::iter::once(class).chain(status)
+}
+
+fn row_color<'a>(colors: &'a [(&str, String)], class: &str) -> Option<&'a str> {
+    class_and_status(class).find_map(|candidate| {
+        colors
+            .iter()
+            .find(|(palette_class, _)| *palette_class == candidate)
+            .map(|(_, color)| color.as_str())
+    })
+}
+
+fn row_emphasis(class: &str) -> Option<RowEmphasis> {
+    class_and_status(class).find_map(|candidate| {
+        SERVICE_ROW_EMPHASIS
+            .iter()
+            .find(|(emphasis_class, _)| *emphasis_class == candidate)
+            .map(|(_, emphasis)| *emphasis)
+    })
+}
+
+/// Picks the status palette suited to `background_hex` and darkens or lightens
+/// each colour until it reaches the WCAG AA contrast ratio
+fn accessible_status_colors(background_hex: &str) -> Vec<(&'static str, String)> {
+    let background = parse_hex_color(background_hex).unwrap_or((1.0, 1.0, 1.0));
+    let background_luminance = relative_luminance(background);
+    let dark_background = background_luminance < 0.5;
+
+    let palette = if dark_background {
+        &DARK_STATUS_COLORS
+    } else {
+        &LIGHT_STATUS_COLORS
+    };
+
+    palette
+        .iter()
+        .map(|(class, hex)| {
+            let (h, s, mut l) = rgb_to_hsl(parse_hex_color(hex).unwrap_or((0.5, 0.5, 0.5)));
+            let mut rgb = hsl_to_rgb(h, s, l);
+
+            while contrast_ratio(relative_luminance(rgb), background_luminance) < WCAG_AA_CONTRAST {
+                l = if dark_background { l + 0.01 } else { l - 0.01 };
+                if !(0.0..=1.0).contains(&l) {
+                    break;
+                }
+                rgb = hsl_to_rgb(h, s, l);
+            }
+
+            (*class, to_hex_color(rgb))
+        })
+        .collect()
+}
+
+fn parse_hex_color(hex: &str) -> Option<(f64, f64, f64)> {
+    let hex = hex.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let channel = |i: usize| {
+        u8::from_str_radix(&hex[i..i + 2], 16)
+            .ok()
+            .map(|v| v as f64 / 255.0)
+    };
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+fn to_hex_color((r, g, b): (f64, f64, f64)) -> String {
+    let channel = |v: f64| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(r), channel(g), channel(b))
+}
+
+/// Relative luminance as defined by WCAG 2.1
+fn relative_luminance((r, g, b): (f64, f64, f64)) -> f64 {
+    let linear = |c: f64| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+fn contrast_ratio(a: f64, b: f64) -> f64 {
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+    (lighter + 0.05) / (darker + 0.05)
+}
+
+fn rgb_to_hsl((r, g, b): (f64, f64, f64)) -> (f64, f64, f64) {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+
+    if max == min {
+        return (0.0, 0.0, l);
+    }
+
+    let d = max - min;
+    let s = if l > 0.5 {
+        d / (2.0 - max - min)
+    } else {
+        d / (max + min)
+    };
+    let h = if max == r {
+        (g - b) / d + if g < b { 6.0 } else { 0.0 }
+    } else if max == g {
+        (b - r) / d + 2.0
+    } else {
+        (r - g) / d + 4.0
+    };
+
+    (h / 6.0, s, l)
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (f64, f64, f64) {
+    let l = l.clamp(0.0, 1.0);
+    if s == 0.0 {
+        return (l, l, l);
+    }
+
+    let hue_to_rgb = |p: f64, q: f64, mut t: f64| {
+        if t < 0.0 {
+            t += 1.0;
+        }
+        if t > 1.0 {
+            t -= 1.0;
+        }
+        if t < 1.0 / 6.0 {
+            p + (q - p) * 6.0 * t
+        } else if t < 1.0 / 2.0 {
+            q
+        } else if t < 2.0 / 3.0 {
+            p + (q - p) * (2.0 / 3.0 - t) * 6.0
+        } else {
+            p
+        }
+    };
+
+    let q = if l < 0.5 {
+        l * (1.0 + s)
+    } else {
+        l + s - l * s
+    };
+    let p = 2.0 * l - q;
+
+    (
+        hue_to_rgb(p, q, h + 1.0 / 3.0),
+        hue_to_rgb(p, q, h),
+        hue_to_rgb(p, q, h - 1.0 / 3.0),
+    )
+}
+
 impl Default for ThemeManager {
     fn default() -> Self {
         Self::new()
@@ -365,4 +672,97 @@ mod tests {
         assert!(dark_css.len() > 0);
         assert!(light_css.len() > 0);
     }
+
+    #[test]
+    fn test_service_row_classes_are_styled() {
+        let css = ThemeManager::new().get_custom_css(false);
+        assert!(css.contains(".service-active-disabled {"));
+        assert!(css.contains(".service-unknown-disabled {"));
+        assert!(css.contains(".service-masked-disabled {"));
+        assert!(css.contains("@keyframes pulse"));
+        assert!(css.contains(".service-condition-failed-enabled"));
+    }
+
+    #[test]
+    fn test_service_row_span_attributes() {
+        let theme_manager = ThemeManager::new();
+        theme_manager.set_dark_mode(false);
+        let colors = accessible_status_colors(LIGHT_BACKGROUND);
+
+        for status in &ServiceStatus::ALL {
+            for enabled in [true, false] {
+                let class = get_service_row_css_class(status, enabled);
+                let markup = format!(
+                    "<span {}>{}</span>",
+                    theme_manager.service_row_span_attributes(class),
+                    status
+                );
+                assert!(
+                    gtk4::pango::parse_markup(&markup, '\0').is_ok(),
+                    "{}",
+                    markup
+                );
+            }
+        }
+        assert_eq!(
+            theme_manager.service_row_span_attributes("service-active-disabled"),
+            format!(
+                "foreground=\"{}\" style=\"italic\"",
+                row_color(&colors, "service-active-disabled").unwrap()
+            )
+        );
+        assert_eq!(
+            row_color(&colors, "service-failed-enabled"),
+            row_color(&colors, "service-failed")
+        );
+    }
+
+    #[test]
+    fn test_notification_kinds_are_styled() {
+        let css = ThemeManager::new().get_custom_css(false);
+        for kind in crate::ui::components::NotificationKind::ALL {
+            assert!(css.contains(&format!(".{} {{", kind.css_class())));
+        }
+    }
+
+    #[test]
+    fn test_status_colors_meet_wcag_aa() {
+        for background in [LIGHT_BACKGROUND, DARK_BACKGROUND, "#000000", "#f8f9fa"] {
+            let background_luminance = relative_luminance(parse_hex_color(background).unwrap());
+            for (class, color) in accessible_status_colors(background) {
+                let luminance = relative_luminance(parse_hex_color(&color).unwrap());
+                let ratio = contrast_ratio(luminance, background_luminance);
+                assert!(
+                    ratio >= WCAG_AA_CONTRAST,
+                    "{} {} on {} has contrast {:.2}",
+                    class,
+                    color,
+                    background,
+                    ratio
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_accessible_css_contains_status_classes() {
+        let theme_manager = ThemeManager::new();
+        let css = theme_manager.generate_accessible_css(LIGHT_BACKGROUND);
+
+        for class in [
+            "service-active",
+            "service-inactive",
+            "service-failed",
+            "service-unknown",
+        ] {
+            assert!(css.contains(&format!(".{} {{", class)));
+        }
+    }
+
+    #[test]
+    fn test_color_round_trip() {
+        let (h, s, l) = rgb_to_hsl(parse_hex_color("#27ae60").unwrap());
+        assert_eq!(to_hex_color(hsl_to_rgb(h, s, l)), "#27ae60");
+        assert_eq!(parse_hex_color("nope"), None);
+    }
 }
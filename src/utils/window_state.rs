@@ -0,0 +1,117 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::utils::config::{load_json, save_json};
+
+/// Name of the file the window state is saved in
+const FILE_NAME: &str = "window_state.json";
+
+/// Window geometry stored in `~/.config/systemd-pilot/window_state.json`,
+/// so the window reopens the way it was left
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowState {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Divider position of the split panes, keyed by orientation
+    /// (`"horizontal"` or `"vertical"`)
+    pub paned_positions: HashMap<String, i32>,
+    /// Width of the service list columns, keyed by `column_key`
+    pub column_widths: HashMap<String, i32>,
+    /// Column each service list is sorted by, keyed by list
+    /// (`"local"` or `"remote"`)
+    pub sort_columns: HashMap<String, ColumnSort>,
+}
+
+/// The column a list is sorted by, identified by its title
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnSort {
+    pub column: String,
+    pub descending: bool,
+}
+
+impl WindowState {
+    /// Loads the saved state, or an empty one when nothing was saved yet
+    pub fn load() -> Result<Self> {
+        Ok(load_json(FILE_NAME)?.unwrap_or_default())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(FILE_NAME, self)
+    }
+
+    /// The saved window size, shrunk to fit within `bounds` (the monitor's
+    /// width and height) when the window was left on a larger screen
+    pub fn size_within(&self, bounds: Option<(i32, i32)>) -> Option<(i32, i32)> {
+        let (width, height) = (self.width?, self.height?);
+        if width <= 0 || height <= 0 {
+            return None;
+        }
+
+        Some(match bounds {
+            Some((max_width, max_height)) => (width.min(max_width), height.min(max_height)),
+            None => (width, height),
+        })
+    }
+}
+
+/// Key under which the width of the column titled `title` in `list` is
+/// stored, such as `local:Service`
+pub fn column_key(list: &str, title: &str) -> String {
+    format!("{}:{}", list, title)
+}
+
+/// Key under which a pane's divider position is stored
+pub fn orientation_key(orientation: gtk4::Orientation) -> &'static str {
+    match orientation {
+        gtk4::Orientation::Vertical => "vertical",
+        _ => "horizontal",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_within_bounds() {
+        let state = WindowState {
+            width: Some(2400),
+            height: Some(900),
+            ..Default::default()
+        };
+
+        assert_eq!(state.size_within(None), Some((2400, 900)));
+        assert_eq!(state.size_within(Some((1920, 1080))), Some((1920, 900)));
+        assert_eq!(WindowState::default().size_within(Some((1920, 1080))), None);
+    }
+
+    #[test]
+    fn test_missing_fields_use_defaults() {
+        let state: WindowState = serde_json::from_str(r#"{"width": 1200}"#).unwrap();
+        assert_eq!(state.width, Some(1200));
+        assert_eq!(state.height, None);
+        assert!(state.paned_positions.is_empty());
+        assert!(state.column_widths.is_empty());
+    }
+
+    #[test]
+    fn test_column_state_round_trip() {
+        let mut state = WindowState::default();
+        state
+            .column_widths
+            .insert(column_key("remote", "Service"), 240);
+        state.sort_columns.insert(
+            "local".to_string(),
+            ColumnSort {
+                column: "Status".to_string(),
+                descending: true,
+            },
+        );
+
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(json.contains("\"remote:Service\":240"));
+        assert_eq!(serde_json::from_str::<WindowState>(&json).unwrap(), state);
+    }
+}